@@ -326,6 +326,66 @@ fn benchmark_eviction_strategies(c: &mut Criterion) {
     group.finish();
 }
 
+// Warm-up cost: 10k puts one by one against a single bulk_load
+fn benchmark_bulk_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_load");
+    group.measurement_time(Duration::from_secs(10));
+    let entries = || (0..10_000).map(|i| (i, format!("value_{}", i), None, 5));
+    
+    group.bench_function("qwen30b_put_10k", |b| {
+        b.iter(|| {
+            let cache = Cache30B::new(5_000);
+            for (key, value, ttl, priority) in entries() {
+                cache.put(key, value, ttl, priority);
+            }
+            black_box(cache.size());
+        });
+    });
+    
+    group.bench_function("qwen30b_bulk_load_10k", |b| {
+        b.iter(|| {
+            let cache = Cache30B::new(5_000);
+            black_box(cache.bulk_load(entries()));
+        });
+    });
+    
+    group.bench_function("qwen235b_put_10k", |b| {
+        b.iter(|| {
+            let cache = Cache235B::new(5_000);
+            for (key, value, ttl, priority) in entries() {
+                cache.put(key, value, ttl, priority);
+            }
+            black_box(cache.size());
+        });
+    });
+    
+    group.bench_function("qwen235b_bulk_load_10k", |b| {
+        b.iter(|| {
+            let cache = Cache235B::new(5_000);
+            black_box(cache.bulk_load(entries()));
+        });
+    });
+    
+    group.bench_function("qwen435b_put_10k", |b| {
+        b.iter(|| {
+            let cache = Cache435B::new(5_000);
+            for (key, value, ttl, priority) in entries() {
+                cache.put(key, value, ttl, priority);
+            }
+            black_box(cache.get_stats().size);
+        });
+    });
+    
+    group.bench_function("qwen435b_bulk_load_10k", |b| {
+        b.iter(|| {
+            let cache = Cache435B::new(5_000);
+            black_box(cache.bulk_load(entries()));
+        });
+    });
+    
+    group.finish();
+}
+
 fn benchmark_ttl_operations(c: &mut Criterion) {
     let mut group = c.benchmark_group("ttl");
     group.measurement_time(Duration::from_secs(10));
@@ -490,6 +550,7 @@ criterion_group!(
     benchmark_single_thread_operations,
     benchmark_concurrent_operations,
     benchmark_eviction_strategies,
+    benchmark_bulk_load,
     benchmark_ttl_operations
 );
 criterion_main!(benches);
\ No newline at end of file
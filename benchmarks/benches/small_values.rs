@@ -0,0 +1,121 @@
+use cache_core::CompactString;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use qwen30b_cache::SmartCache as Cache30B;
+use qwen235b_cache::SmartCache as Cache235B;
+use qwen435b_cache::SmartCache as Cache435B;
+
+// Counts heap allocations so the report can show what inline values save
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ENTRIES: u64 = 10_000;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn values() -> Vec<String> {
+    (0..ENTRIES).map(|i| format!("session:{:08x}", i)).collect()
+}
+
+// Allocations per put and per get once the cache is warm, for String values and
+// for the same values as CompactString
+macro_rules! report_allocations {
+    ($name:expr, $cache:ty) => {{
+        let values = values();
+        let strings: $cache = <$cache>::new(ENTRIES as usize * 2);
+        let compact = <$cache>::new(ENTRIES as usize * 2);
+        let string_puts = allocations_during(|| {
+            for (i, value) in values.iter().enumerate() {
+                strings.put(i as u64, value.clone(), None, 5);
+            }
+        });
+        let compact_puts = allocations_during(|| {
+            for (i, value) in values.iter().enumerate() {
+                compact.put(i as u64, CompactString::<64>::from(value.as_str()), None, 5);
+            }
+        });
+        let string_gets = allocations_during(|| {
+            for i in 0..ENTRIES {
+                black_box(strings.get(&i));
+            }
+        });
+        let compact_gets = allocations_during(|| {
+            for i in 0..ENTRIES {
+                black_box(compact.get(&i));
+            }
+        });
+        let per_op = |count: usize| count as f64 / ENTRIES as f64;
+        println!(
+            "{:<10} allocations per put: String {:.2}, CompactString {:.2}; per get: String {:.2}, CompactString {:.2}",
+            $name,
+            per_op(string_puts),
+            per_op(compact_puts),
+            per_op(string_gets),
+            per_op(compact_gets)
+        );
+    }};
+}
+
+macro_rules! bench_values {
+    ($group:expr, $name:expr, $cache:ty) => {{
+        let values = values();
+        $group.bench_function(concat!($name, "_string_put_get"), |b| {
+            let cache: $cache = <$cache>::new(ENTRIES as usize * 2);
+            let mut i = 0;
+            b.iter(|| {
+                let key = i % ENTRIES;
+                cache.put(key, values[key as usize].clone(), None, 5);
+                black_box(cache.get(&key));
+                i += 1;
+            });
+        });
+        $group.bench_function(concat!($name, "_compact_put_get"), |b| {
+            let cache: $cache = <$cache>::new(ENTRIES as usize * 2);
+            let mut i = 0;
+            b.iter(|| {
+                let key = i % ENTRIES;
+                cache.put(key, CompactString::<64>::from(values[key as usize].as_str()), None, 5);
+                black_box(cache.get(&key));
+                i += 1;
+            });
+        });
+    }};
+}
+
+fn benchmark_small_values(c: &mut Criterion) {
+    report_allocations!("qwen30b", Cache30B<u64, _>);
+    report_allocations!("qwen235b", Cache235B<u64, _>);
+    report_allocations!("qwen435b", Cache435B<u64, _>);
+
+    let mut group = c.benchmark_group("small_values");
+    group.measurement_time(Duration::from_secs(5));
+    bench_values!(group, "qwen30b", Cache30B<u64, _>);
+    bench_values!(group, "qwen235b", Cache235B<u64, _>);
+    bench_values!(group, "qwen435b", Cache435B<u64, _>);
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_small_values);
+criterion_main!(benches);
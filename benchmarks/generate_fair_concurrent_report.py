@@ -21,6 +21,18 @@ def load_json_results(pattern):
             print(f"Warning: Could not load {file}")
     return results
 
+def format_metric(result, key):
+    """A metric for display; typed reports store numbers, percentages under a _pct key
+    and --runs summaries as objects with a mean"""
+    if key not in result and f"{key}_pct" in result:
+        return f"{format_metric(result, f'{key}_pct')}%"
+    value = result.get(key, 'N/A')
+    if isinstance(value, dict) and 'mean' in value:
+        value = value['mean']
+    if isinstance(value, float):
+        return f"{value:.2f}"
+    return str(value)
+
 def generate_html_report():
     """Generate comprehensive HTML report from all fair concurrent benchmarks"""
     
@@ -291,8 +303,7 @@ def generate_html_report():
             values = []
             for data in [python_data, java_data, rust_30b_data, rust_235b_data, rust_435b_data, rust_glm45_data]:
                 if data and 'benchmarks' in data and bench_key in data['benchmarks']:
-                    value = data['benchmarks'][bench_key].get(metric_key, 'N/A')
-                    values.append(str(value))
+                    values.append(format_metric(data['benchmarks'][bench_key], metric_key))
                 else:
                     values.append('N/A')
             
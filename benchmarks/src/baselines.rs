@@ -0,0 +1,86 @@
+// Named result sets for the bench binary, like criterion's saved baselines but over
+// the reports the benchmark binaries write to results/. `bench save-baseline NAME`
+// copies the newest report of every series (implementation and benchmark binary,
+// e.g. rust_qwen30b_fair_concurrent) to results/baselines/NAME/, replacing what
+// was saved under that name; `bench compare NAME` sets the newest reports against
+// them.
+
+use crate::report::BenchmarkReport;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const RESULTS_DIR: &str = "results";
+
+// The series of a saved report: rust_qwen30b_fair_concurrent_20250101_120000.json
+// is rust_qwen30b_fair_concurrent. Timestamps sort in time order.
+fn series(file_name: &str) -> Option<(&str, &str)> {
+    let stem = file_name.strip_suffix(".json")?;
+    let (rest, time) = stem.rsplit_once('_')?;
+    let (series, date) = rest.rsplit_once('_')?;
+    let timestamp = &stem[series.len() + 1..];
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    (is_digits(date) && is_digits(time)).then_some((series, timestamp))
+}
+
+// The newest report file of every series in `dir`
+pub fn latest_reports(dir: &Path) -> Result<BTreeMap<String, PathBuf>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|err| format!("cannot read {}: {}", dir.display(), err))?;
+    let mut latest: BTreeMap<String, (String, PathBuf)> = BTreeMap::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let Some((series, timestamp)) = series(&file_name) else {
+            continue;
+        };
+        if latest.get(series).is_none_or(|(newest, _)| timestamp > newest.as_str()) {
+            latest.insert(series.to_string(), (timestamp.to_string(), entry.path()));
+        }
+    }
+    Ok(latest.into_iter().map(|(series, (_, path))| (series, path)).collect())
+}
+
+fn baseline_dir(name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!("invalid baseline name {:?}", name));
+    }
+    Ok(Path::new(RESULTS_DIR).join("baselines").join(name))
+}
+
+// Save the newest report of every series under `name`; returns the series saved
+pub fn save(name: &str) -> Result<Vec<String>, String> {
+    let dir = baseline_dir(name)?;
+    let latest = latest_reports(Path::new(RESULTS_DIR))?;
+    if latest.is_empty() {
+        return Err(format!("no results in {}/ to save; run a benchmark first", RESULTS_DIR));
+    }
+    // A report that no longer loads would only fail the comparison later
+    for path in latest.values() {
+        BenchmarkReport::load(&path.to_string_lossy())?;
+    }
+
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|err| format!("cannot replace {}: {}", dir.display(), err))?;
+    }
+    std::fs::create_dir_all(&dir).map_err(|err| format!("cannot create {}: {}", dir.display(), err))?;
+    for (series, path) in &latest {
+        let target = dir.join(format!("{}.json", series));
+        std::fs::copy(path, &target).map_err(|err| format!("cannot write {}: {}", target.display(), err))?;
+    }
+    Ok(latest.into_keys().collect())
+}
+
+// The reports saved under `name`, by series
+pub fn load(name: &str) -> Result<BTreeMap<String, BenchmarkReport>, String> {
+    let dir = baseline_dir(name)?;
+    if !dir.is_dir() {
+        return Err(format!("no baseline named {:?} (looked in {})", name, dir.display()));
+    }
+    let entries = std::fs::read_dir(&dir).map_err(|err| format!("cannot read {}: {}", dir.display(), err))?;
+    let mut reports = BTreeMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if let Some(series) = path.file_stem().filter(|_| path.extension().is_some_and(|ext| ext == "json")) {
+            reports.insert(series.to_string_lossy().into_owned(), BenchmarkReport::load(&path.to_string_lossy())?);
+        }
+    }
+    Ok(reports)
+}
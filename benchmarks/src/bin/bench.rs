@@ -0,0 +1,126 @@
+// Named baselines over the saved benchmark results (see baselines.rs):
+//
+//     cargo run --bin bench -- save-baseline before
+//     ... change an implementation, rerun fair_concurrent_all ...
+//     cargo run --bin bench -- compare before [--threshold PCT]
+//
+// compare prints every metric of the newest results next to the baseline, marking
+// changes beyond the threshold (10% by default, as with --baseline) as better or
+// worse where the metric has a direction, and exits with the regression exit code
+// if anything got worse.
+
+use cache_benchmarks::baselines;
+use cache_benchmarks::regression::{self, REGRESSION_EXIT_CODE};
+use cache_benchmarks::report::BenchmarkReport;
+use std::path::Path;
+
+const USAGE: &str = "usage: bench save-baseline NAME | bench compare NAME [--threshold PCT]";
+
+enum Command {
+    SaveBaseline(String),
+    Compare { name: String, threshold_pct: f64 },
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let command = args.next().ok_or_else(|| USAGE.to_string())?;
+    let name = args.next().ok_or_else(|| format!("{} needs a baseline name\n{}", command, USAGE))?;
+    match command.as_str() {
+        "save-baseline" => match args.next() {
+            None => Ok(Command::SaveBaseline(name)),
+            Some(other) => Err(format!("unknown argument {:?}\n{}", other, USAGE)),
+        },
+        "compare" => {
+            let mut threshold_pct = 10.0;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--threshold" => {
+                        threshold_pct = args
+                            .next()
+                            .and_then(|value| value.parse::<f64>().ok())
+                            .filter(|pct| *pct >= 0.0)
+                            .ok_or_else(|| format!("--threshold needs a percentage\n{}", USAGE))?;
+                    }
+                    other => return Err(format!("unknown argument {:?}\n{}", other, USAGE)),
+                }
+            }
+            Ok(Command::Compare { name, threshold_pct })
+        }
+        other => Err(format!("unknown command {:?}\n{}", other, USAGE)),
+    }
+}
+
+fn save_baseline(name: &str) -> Result<(), String> {
+    let saved = baselines::save(name)?;
+    println!("Saved baseline {:?} ({} series):", name, saved.len());
+    for series in saved {
+        println!("  {}", series);
+    }
+    Ok(())
+}
+
+// Returns the number of regressed metrics
+fn compare(name: &str, threshold_pct: f64) -> Result<usize, String> {
+    let baseline = baselines::load(name)?;
+    let latest = baselines::latest_reports(Path::new(baselines::RESULTS_DIR))?;
+
+    let mut regressed = 0;
+    for (series, old) in &baseline {
+        let Some(path) = latest.get(series) else {
+            println!("\n{}: no current results", series);
+            continue;
+        };
+        let new = BenchmarkReport::load(&path.to_string_lossy())?;
+        println!("\n{} ({} -> {})", series, old.timestamp, new.timestamp);
+        println!("{}", "-".repeat(60));
+
+        let deltas = regression::deltas(old, &new);
+        let width = deltas.iter().map(|delta| delta.metric.len()).max().unwrap_or(0);
+        for delta in deltas {
+            let verdict = if delta.regressed(threshold_pct) {
+                regressed += 1;
+                "worse"
+            } else if delta.improved(threshold_pct) {
+                "better"
+            } else {
+                ""
+            };
+            let change = match delta.change_pct {
+                Some(pct) => format!("{:+.1}%", pct),
+                None => "-".to_string(),
+            };
+            let line = format!(
+                "  {:<width$}  {:>14.2} -> {:>14.2}  {:>8}  {}",
+                delta.metric, delta.baseline, delta.current, change, verdict,
+                width = width
+            );
+            println!("{}", line.trim_end());
+        }
+    }
+    for series in latest.keys().filter(|series| !baseline.contains_key(*series)) {
+        println!("\n{}: not in baseline {:?}", series, name);
+    }
+    Ok(regressed)
+}
+
+fn main() {
+    let command = parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+    let outcome = match command {
+        Command::SaveBaseline(name) => save_baseline(&name).map(|()| 0),
+        Command::Compare { name, threshold_pct } => compare(&name, threshold_pct).inspect(|&regressed| {
+            if regressed > 0 {
+                println!("\n{} metric(s) worse than baseline {:?} by more than {}%", regressed, name, threshold_pct);
+            }
+        }),
+    };
+    match outcome {
+        Ok(0) => {}
+        Ok(_) => std::process::exit(REGRESSION_EXIT_CODE),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
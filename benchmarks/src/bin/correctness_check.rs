@@ -0,0 +1,274 @@
+use std::sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::collections::HashMap;
+use rand::Rng;
+
+// Concurrent correctness checker: threads run random PUT/GET/DELETE against one cache
+// while every call is recorded with its invoke and response time. Afterwards an
+// oracle walks that history and checks each GET against what some linearization of
+// the recorded operations allows. Evictions are legal at any time, so a miss is never
+// a violation; returning a value that cannot be current is.
+
+type Cache30B = qwen30b_cache::SmartCache<String, String>;
+type Cache235B = qwen235b_cache::SmartCache<String, String>;
+type Cache435B = qwen435b_cache::SmartCache<String, String>;
+
+const CAPACITY: usize = 64;
+const KEY_SPACE: usize = 256;
+const NUM_THREADS: usize = 8;
+const OPS_PER_THREAD: usize = 20_000;
+// (TTL, share of puts); None takes the cache's default TTL
+const TTLS: [(Option<u64>, f64); 3] = [(None, 0.4), (Some(5), 0.3), (Some(50), 0.3)];
+// Violations printed per kind
+const EXAMPLES: usize = 3;
+
+// The operations the checker needs from each implementation
+trait CacheUnderTest: Send + Sync {
+    fn put(&self, key: String, value: String, ttl: Option<Duration>) -> bool;
+    fn get(&self, key: &str) -> Option<String>;
+    fn delete(&self, key: &str) -> bool;
+    // Entries held, expired ones awaiting cleanup included
+    fn stored_entries(&self) -> usize;
+}
+
+macro_rules! impl_cache_under_test {
+    ($cache_type:ty, |$cache:ident| $stored:expr) => {
+        impl CacheUnderTest for $cache_type {
+            fn put(&self, key: String, value: String, ttl: Option<Duration>) -> bool {
+                <$cache_type>::put(self, key, value, ttl, 5)
+            }
+            
+            fn get(&self, key: &str) -> Option<String> {
+                <$cache_type>::get(self, key)
+            }
+            
+            fn delete(&self, key: &str) -> bool {
+                <$cache_type>::delete(self, key)
+            }
+            
+            fn stored_entries(&self) -> usize {
+                let $cache = self;
+                $stored
+            }
+        }
+    };
+}
+
+impl_cache_under_test!(Cache30B, |cache| cache.size());
+impl_cache_under_test!(Cache235B, |cache| cache.size());
+impl_cache_under_test!(Cache435B, |cache| cache.get_stats().size);
+
+#[derive(Debug, Clone)]
+enum Op {
+    // Values are unique across the run, so a GET result names the PUT it came from
+    Put { value: String, ttl: Option<Duration>, stored: bool },
+    Get { result: Option<String> },
+    Delete,
+}
+
+#[derive(Debug, Clone)]
+struct Record {
+    thread: usize,
+    key: String,
+    op: Op,
+    // Nanoseconds since the run started
+    invoked: u64,
+    returned: u64,
+}
+
+fn run_history(cache: Arc<dyn CacheUnderTest>) -> (Vec<Record>, usize) {
+    let start = Instant::now();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let max_stored = Arc::new(AtomicUsize::new(0));
+    
+    // Sample the stored size for the whole run
+    let sampler = {
+        let cache = Arc::clone(&cache);
+        let stop = Arc::clone(&stop_flag);
+        let max_stored = Arc::clone(&max_stored);
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                max_stored.fetch_max(cache.stored_entries(), Ordering::Relaxed);
+                thread::sleep(Duration::from_micros(100));
+            }
+        })
+    };
+    
+    let workers: Vec<_> = (0..NUM_THREADS).map(|thread| {
+        let cache = Arc::clone(&cache);
+        thread::spawn(move || {
+            let mut rng = rand::thread_rng();
+            let mut records = Vec::with_capacity(OPS_PER_THREAD);
+            let now = || start.elapsed().as_nanos() as u64;
+            
+            for seq in 0..OPS_PER_THREAD {
+                let key = format!("key_{}", rng.gen_range(0..KEY_SPACE));
+                let roll = rng.gen::<f64>();
+                let (op, invoked, returned) = if roll < 0.45 {
+                    let value = format!("t{}_{}", thread, seq);
+                    let pick = rng.gen::<f64>();
+                    let mut cumulative = 0.0;
+                    let ttl = TTLS.iter()
+                        .find(|(_, share)| { cumulative += share; pick < cumulative })
+                        .and_then(|(ttl, _)| ttl.map(Duration::from_millis));
+                    
+                    let invoked = now();
+                    let stored = cache.put(key.clone(), value.clone(), ttl);
+                    (Op::Put { value, ttl, stored }, invoked, now())
+                } else if roll < 0.9 {
+                    let invoked = now();
+                    let result = cache.get(&key);
+                    (Op::Get { result }, invoked, now())
+                } else {
+                    let invoked = now();
+                    cache.delete(&key);
+                    (Op::Delete, invoked, now())
+                };
+                records.push(Record { thread, key, op, invoked, returned });
+            }
+            records
+        })
+    }).collect();
+    
+    let mut history = Vec::new();
+    for worker in workers {
+        history.extend(worker.join().unwrap());
+    }
+    stop_flag.store(true, Ordering::Relaxed);
+    sampler.join().unwrap();
+    max_stored.fetch_max(cache.stored_entries(), Ordering::Relaxed);
+    
+    (history, max_stored.load(Ordering::Relaxed))
+}
+
+// Reference model of one key: the writes that completed, in a linearization
+// consistent with real time. A GET may return the value of a PUT only if no
+// other write on the key both started after that PUT returned and returned before
+// the GET started - such a write must take effect between the two.
+fn check_history(history: &[Record]) -> HashMap<&'static str, Vec<String>> {
+    let mut violations: HashMap<&'static str, Vec<String>> = HashMap::new();
+    
+    let mut puts_by_value = HashMap::new();
+    let mut writes_by_key: HashMap<&str, Vec<&Record>> = HashMap::new();
+    for record in history {
+        match &record.op {
+            Op::Put { value, stored, .. } => {
+                puts_by_value.insert(value.as_str(), record);
+                if *stored {
+                    writes_by_key.entry(record.key.as_str()).or_default().push(record);
+                }
+            }
+            Op::Delete => writes_by_key.entry(record.key.as_str()).or_default().push(record),
+            Op::Get { .. } => {}
+        }
+    }
+    
+    for get in history {
+        let Op::Get { result: Some(value) } = &get.op else {
+            continue;
+        };
+        let Some(put) = puts_by_value.get(value.as_str()).filter(|put| put.key == get.key) else {
+            violations.entry("phantom value").or_default()
+                .push(format!("thread {} got {:?} for {}, which was never put there", get.thread, value, get.key));
+            continue;
+        };
+        let Op::Put { ttl, stored, .. } = &put.op else {
+            unreachable!()
+        };
+        
+        if !stored {
+            violations.entry("rejected put visible").or_default()
+                .push(format!("thread {} got {:?} for {}, but that put returned false", get.thread, value, get.key));
+        }
+        if put.invoked > get.returned {
+            violations.entry("read from the future").or_default()
+                .push(format!("thread {} got {:?} for {} before it was put", get.thread, value, get.key));
+        }
+        // The entry's deadline is fixed at the latest when its put returns
+        if let Some(ttl) = ttl {
+            let expired_by = put.returned + ttl.as_nanos() as u64;
+            if get.invoked > expired_by {
+                violations.entry("expired value returned").or_default().push(format!(
+                    "thread {} got {:?} for {} {:.1}ms after it expired",
+                    get.thread, value, get.key, (get.invoked - expired_by) as f64 / 1e6
+                ));
+            }
+        }
+        
+        let superseded = writes_by_key[get.key.as_str()].iter()
+            .find(|write| write.invoked > put.returned && write.returned < get.invoked);
+        match superseded.map(|write| &write.op) {
+            Some(Op::Delete) => violations.entry("deleted key returned").or_default().push(format!(
+                "thread {} got {:?} for {} after thread {} deleted it",
+                get.thread, value, get.key, superseded.unwrap().thread
+            )),
+            Some(_) => violations.entry("overwritten value returned").or_default().push(format!(
+                "thread {} got {:?} for {} after thread {} overwrote it",
+                get.thread, value, get.key, superseded.unwrap().thread
+            )),
+            None => {}
+        }
+    }
+    
+    violations
+}
+
+fn check(name: &str, cache: Arc<dyn CacheUnderTest>) -> bool {
+    println!("\n{}", "=".repeat(60));
+    println!("Checking: {} Rust Implementation", name);
+    println!("{}", "=".repeat(60));
+    println!("{} threads x {} ops over {} keys, capacity {}", NUM_THREADS, OPS_PER_THREAD, KEY_SPACE, CAPACITY);
+    
+    let (history, max_stored) = run_history(cache);
+    let mut violations = check_history(&history);
+    if max_stored > CAPACITY {
+        violations.entry("size over capacity").or_default()
+            .push(format!("held {} entries with capacity {}", max_stored, CAPACITY));
+    }
+    
+    let gets = history.iter().filter(|record| matches!(record.op, Op::Get { .. })).count();
+    let hits = history.iter().filter(|record| matches!(record.op, Op::Get { result: Some(_) })).count();
+    println!("Operations: {}, GETs: {}, hits: {}, max stored: {}", history.len(), gets, hits, max_stored);
+    
+    if violations.is_empty() {
+        println!("No violations");
+        return true;
+    }
+    let mut kinds: Vec<_> = violations.into_iter().collect();
+    kinds.sort_by_key(|(kind, _)| *kind);
+    for (kind, examples) in kinds {
+        println!("VIOLATION {}: {} occurrence(s)", kind, examples.len());
+        for example in examples.iter().take(EXAMPLES) {
+            println!("  {}", example);
+        }
+    }
+    false
+}
+
+fn main() {
+    println!("{}", "=".repeat(60));
+    println!("Concurrent Correctness Check");
+    println!("{}", "=".repeat(60));
+    
+    let implementations: Vec<(&str, Arc<dyn CacheUnderTest>)> = vec![
+        ("Qwen30B", Arc::new(Cache30B::new(CAPACITY))),
+        ("Qwen235B", Arc::new(Cache235B::new(CAPACITY))),
+        ("Qwen435B", Arc::new(Cache435B::new(CAPACITY))),
+    ];
+    
+    let mut failed = Vec::new();
+    for (name, cache) in implementations {
+        if !check(name, cache) {
+            failed.push(name);
+        }
+    }
+    
+    println!("\n{}", "=".repeat(60));
+    if failed.is_empty() {
+        println!("All implementations passed");
+    } else {
+        println!("Violations found in: {}", failed.join(", "));
+        std::process::exit(1);
+    }
+}
@@ -1,18 +1,17 @@
-use std::sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}};
-use std::thread;
-use std::time::{Duration, Instant};
-use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
 use threadpool::ThreadPool;
 use crossbeam::channel::{unbounded};
 use rand::Rng;
-use serde_json;
+use cache_benchmarks::{Fairness, WorkloadMix, args_from_env, baseline_from_args, regression, stats, watchdog};
+use cache_benchmarks::report::{BenchmarkReport, BenchmarkResult, Metric};
 
 // Import the cache implementations with concrete types
 type Cache30B = qwen30b_cache::SmartCache<String, String>;
 type Cache235B = qwen235b_cache::SmartCache<String, String>;
 type Cache435B = qwen435b_cache::SmartCache<String, String>;
 
-fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> HashMap<String, serde_json::Value> {
+fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize, mix: WorkloadMix) -> BenchmarkResult {
     let cache = Arc::new(Cache30B::new(100000));
     
     // Create work queue
@@ -21,15 +20,15 @@ fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> H
     
     // Fill with mixed operations
     for i in 0..num_operations {
-        if rng.gen::<f64>() < 0.7 { // 70% writes
+        if rng.gen::<f64>() < mix.write_ratio { // writes
             tx.send(("PUT", 
-                    format!("key_{}", i % 1000),
+                    format!("key_{}", i % mix.key_space),
                     format!("value_{}", i),
                     rng.gen_range(1..=10)))
                 .unwrap();
-        } else { // 30% reads
+        } else { // reads
             tx.send(("GET", 
-                    format!("key_{}", rng.gen_range(0..1000)),
+                    format!("key_{}", rng.gen_range(0..mix.key_space)),
                     String::new(),
                     0))
                 .unwrap();
@@ -37,21 +36,26 @@ fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> H
     }
     drop(tx); // Close sender
     
-    println!("\nRunning Shared Workload benchmark ({} workers, {} operations)...", 
-            num_workers, num_operations);
+    println!("\nRunning Shared Workload benchmark ({} workers, {} operations, {})...", 
+            num_workers, num_operations, mix);
     
     let start = Instant::now();
     let pool = ThreadPool::new(num_workers);
     let (done_tx, done_rx) = unbounded();
     
     // Start workers
-    for _ in 0..num_workers {
+    for i in 0..num_workers {
         let cache = Arc::clone(&cache);
         let rx = rx.clone();
         let done = done_tx.clone();
         
         pool.execute(move || {
+            let progress = watchdog::worker(format!("shared worker {}", i));
+            let mut local_ops = 0;
+            
             while let Ok((op, key, value, priority)) = rx.recv() {
+                local_ops += 1;
+                let tracked = progress.enter(op, &key);
                 match op {
                     "PUT" => {
                         cache.put(key, value, None, priority);
@@ -61,29 +65,31 @@ fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> H
                     },
                     _ => {}
                 }
+                tracked.finish();
             }
-            done.send(()).unwrap();
+            done.send(local_ops).unwrap();
         });
     }
     
     drop(done_tx);
     // Wait for all workers to complete
-    for _ in 0..num_workers {
-        done_rx.recv().unwrap();
-    }
+    let worker_ops: Vec<usize> = (0..num_workers).map(|_| done_rx.recv().unwrap()).collect();
     
     let elapsed = start.elapsed();
     
-    let mut result = HashMap::new();
-    result.insert("duration".to_string(), serde_json::json!(format!("{:.3}", elapsed.as_secs_f64())));
-    result.insert("num_workers".to_string(), serde_json::json!(num_workers));
-    result.insert("total_operations".to_string(), serde_json::json!(num_operations));
-    result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", num_operations as f64 / elapsed.as_secs_f64())));
+    let mut result = BenchmarkResult::new();
+    result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+    result.insert("num_workers".to_string(), Metric::from(num_workers));
+    result.insert("total_operations".to_string(), Metric::from(num_operations));
+    result.insert("write_ratio".to_string(), Metric::from(mix.write_ratio));
+    result.insert("key_space".to_string(), Metric::from(mix.key_space));
+    result.insert("worker_fairness".to_string(), Fairness::from_counts(&worker_ops, elapsed).to_metric());
+    result.insert("ops_per_second".to_string(), Metric::from(num_operations as f64 / elapsed.as_secs_f64()));
     
     result
 }
 
-fn benchmark_shared_workload_235b(num_workers: usize, num_operations: usize) -> HashMap<String, serde_json::Value> {
+fn benchmark_shared_workload_235b(num_workers: usize, num_operations: usize, mix: WorkloadMix) -> BenchmarkResult {
     let cache = Arc::new(Cache235B::new(100000));
     
     // Create work queue
@@ -92,15 +98,15 @@ fn benchmark_shared_workload_235b(num_workers: usize, num_operations: usize) ->
     
     // Fill with mixed operations
     for i in 0..num_operations {
-        if rng.gen::<f64>() < 0.7 { // 70% writes
+        if rng.gen::<f64>() < mix.write_ratio { // writes
             tx.send(("PUT", 
-                    format!("key_{}", i % 1000),
+                    format!("key_{}", i % mix.key_space),
                     format!("value_{}", i),
                     rng.gen_range(1..=10)))
                 .unwrap();
-        } else { // 30% reads
+        } else { // reads
             tx.send(("GET", 
-                    format!("key_{}", rng.gen_range(0..1000)),
+                    format!("key_{}", rng.gen_range(0..mix.key_space)),
                     String::new(),
                     0))
                 .unwrap();
@@ -108,21 +114,26 @@ fn benchmark_shared_workload_235b(num_workers: usize, num_operations: usize) ->
     }
     drop(tx); // Close sender
     
-    println!("\nRunning Shared Workload benchmark ({} workers, {} operations)...", 
-            num_workers, num_operations);
+    println!("\nRunning Shared Workload benchmark ({} workers, {} operations, {})...", 
+            num_workers, num_operations, mix);
     
     let start = Instant::now();
     let pool = ThreadPool::new(num_workers);
     let (done_tx, done_rx) = unbounded();
     
     // Start workers
-    for _ in 0..num_workers {
+    for i in 0..num_workers {
         let cache = Arc::clone(&cache);
         let rx = rx.clone();
         let done = done_tx.clone();
         
         pool.execute(move || {
+            let progress = watchdog::worker(format!("shared worker {}", i));
+            let mut local_ops = 0;
+            
             while let Ok((op, key, value, priority)) = rx.recv() {
+                local_ops += 1;
+                let tracked = progress.enter(op, &key);
                 match op {
                     "PUT" => {
                         cache.put(key, value, None, priority);
@@ -132,29 +143,31 @@ fn benchmark_shared_workload_235b(num_workers: usize, num_operations: usize) ->
                     },
                     _ => {}
                 }
+                tracked.finish();
             }
-            done.send(()).unwrap();
+            done.send(local_ops).unwrap();
         });
     }
     
     drop(done_tx);
     // Wait for all workers to complete
-    for _ in 0..num_workers {
-        done_rx.recv().unwrap();
-    }
+    let worker_ops: Vec<usize> = (0..num_workers).map(|_| done_rx.recv().unwrap()).collect();
     
     let elapsed = start.elapsed();
     
-    let mut result = HashMap::new();
-    result.insert("duration".to_string(), serde_json::json!(format!("{:.3}", elapsed.as_secs_f64())));
-    result.insert("num_workers".to_string(), serde_json::json!(num_workers));
-    result.insert("total_operations".to_string(), serde_json::json!(num_operations));
-    result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", num_operations as f64 / elapsed.as_secs_f64())));
+    let mut result = BenchmarkResult::new();
+    result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+    result.insert("num_workers".to_string(), Metric::from(num_workers));
+    result.insert("total_operations".to_string(), Metric::from(num_operations));
+    result.insert("write_ratio".to_string(), Metric::from(mix.write_ratio));
+    result.insert("key_space".to_string(), Metric::from(mix.key_space));
+    result.insert("worker_fairness".to_string(), Fairness::from_counts(&worker_ops, elapsed).to_metric());
+    result.insert("ops_per_second".to_string(), Metric::from(num_operations as f64 / elapsed.as_secs_f64()));
     
     result
 }
 
-fn benchmark_shared_workload_435b(num_workers: usize, num_operations: usize) -> HashMap<String, serde_json::Value> {
+fn benchmark_shared_workload_435b(num_workers: usize, num_operations: usize, mix: WorkloadMix) -> BenchmarkResult {
     let cache = Arc::new(Cache435B::new(100000));
     
     // Create work queue
@@ -163,15 +176,15 @@ fn benchmark_shared_workload_435b(num_workers: usize, num_operations: usize) ->
     
     // Fill with mixed operations
     for i in 0..num_operations {
-        if rng.gen::<f64>() < 0.7 { // 70% writes
+        if rng.gen::<f64>() < mix.write_ratio { // writes
             tx.send(("PUT", 
-                    format!("key_{}", i % 1000),
+                    format!("key_{}", i % mix.key_space),
                     format!("value_{}", i),
                     rng.gen_range(1..=10)))
                 .unwrap();
-        } else { // 30% reads
+        } else { // reads
             tx.send(("GET", 
-                    format!("key_{}", rng.gen_range(0..1000)),
+                    format!("key_{}", rng.gen_range(0..mix.key_space)),
                     String::new(),
                     0))
                 .unwrap();
@@ -179,21 +192,26 @@ fn benchmark_shared_workload_435b(num_workers: usize, num_operations: usize) ->
     }
     drop(tx); // Close sender
     
-    println!("\nRunning Shared Workload benchmark ({} workers, {} operations)...", 
-            num_workers, num_operations);
+    println!("\nRunning Shared Workload benchmark ({} workers, {} operations, {})...", 
+            num_workers, num_operations, mix);
     
     let start = Instant::now();
     let pool = ThreadPool::new(num_workers);
     let (done_tx, done_rx) = unbounded();
     
     // Start workers
-    for _ in 0..num_workers {
+    for i in 0..num_workers {
         let cache = Arc::clone(&cache);
         let rx = rx.clone();
         let done = done_tx.clone();
         
         pool.execute(move || {
+            let progress = watchdog::worker(format!("shared worker {}", i));
+            let mut local_ops = 0;
+            
             while let Ok((op, key, value, priority)) = rx.recv() {
+                local_ops += 1;
+                let tracked = progress.enter(op, &key);
                 match op {
                     "PUT" => {
                         cache.put(key, value, None, priority);
@@ -203,24 +221,26 @@ fn benchmark_shared_workload_435b(num_workers: usize, num_operations: usize) ->
                     },
                     _ => {}
                 }
+                tracked.finish();
             }
-            done.send(()).unwrap();
+            done.send(local_ops).unwrap();
         });
     }
     
     drop(done_tx);
     // Wait for all workers to complete
-    for _ in 0..num_workers {
-        done_rx.recv().unwrap();
-    }
+    let worker_ops: Vec<usize> = (0..num_workers).map(|_| done_rx.recv().unwrap()).collect();
     
     let elapsed = start.elapsed();
     
-    let mut result = HashMap::new();
-    result.insert("duration".to_string(), serde_json::json!(format!("{:.3}", elapsed.as_secs_f64())));
-    result.insert("num_workers".to_string(), serde_json::json!(num_workers));
-    result.insert("total_operations".to_string(), serde_json::json!(num_operations));
-    result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", num_operations as f64 / elapsed.as_secs_f64())));
+    let mut result = BenchmarkResult::new();
+    result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+    result.insert("num_workers".to_string(), Metric::from(num_workers));
+    result.insert("total_operations".to_string(), Metric::from(num_operations));
+    result.insert("write_ratio".to_string(), Metric::from(mix.write_ratio));
+    result.insert("key_space".to_string(), Metric::from(mix.key_space));
+    result.insert("worker_fairness".to_string(), Fairness::from_counts(&worker_ops, elapsed).to_metric());
+    result.insert("ops_per_second".to_string(), Metric::from(num_operations as f64 / elapsed.as_secs_f64()));
     
     result
 }
@@ -231,8 +251,13 @@ fn main() {
     println!("Rust Implementation with True Parallelism");
     println!("{}", "=".repeat(60));
     
+    let args = args_from_env();
+    let baseline = baseline_from_args(&args);
+    let mut regressions = Vec::new();
+    let mixes = args.mixes;
+    
     // Test each implementation
-    type BenchmarkFn = fn(usize, usize) -> HashMap<String, serde_json::Value>;
+    type BenchmarkFn = fn(usize, usize, WorkloadMix) -> BenchmarkResult;
     let implementations: Vec<(&str, BenchmarkFn)> = vec![
         ("Qwen30B", benchmark_shared_workload_30b as BenchmarkFn),
         ("Qwen235B", benchmark_shared_workload_235b as BenchmarkFn),
@@ -244,39 +269,36 @@ fn main() {
         println!("Testing: {} Rust Implementation", name);
         println!("{}", "=".repeat(60));
         
-        println!("\nShared Workload (Fair Comparison)");
-        println!("{}", "-".repeat(40));
-        
-        let result = benchmark_fn(100, 10000);
-        
-        println!("\nResults:");
-        for (key, value) in &result {
-            println!("  {}: {}", key, value);
+        let mut report = BenchmarkReport::new(format!("Rust {} (Fair Concurrent)", name))
+            .note("parallelism", "Rust has true parallelism with lock contention")
+            .note("comparison", "These metrics are directly comparable across languages");
+        report.cache_size = Some(100000);
+        for mix in &mixes {
+            println!("\nShared Workload (Fair Comparison, {})", mix);
+            println!("{}", "-".repeat(40));
+            
+            let result = stats::repeated(args.runs, || benchmark_fn(100, 10000, *mix));
+            
+            println!("\nResults:");
+            for (key, value) in &result {
+                println!("  {}: {}", key, value);
+            }
+            report.benchmarks.insert(mix.result_key("shared_workload", mixes.len()), result);
         }
         
         // Save results
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-        let output = serde_json::json!({
-            "implementation": format!("Rust {} (Fair Concurrent)", name),
-            "timestamp": timestamp,
-            "cache_size": 100000,
-            "benchmarks": {
-                "shared_workload": result
-            },
-            "notes": {
-                "parallelism": "Rust has true parallelism with lock contention",
-                "comparison": "These metrics are directly comparable across languages"
-            }
-        });
-        
-        let filename = format!("results/rust_{}_fair_concurrent_{}.json", name.to_lowercase(), timestamp);
-        std::fs::create_dir_all("results").unwrap();
-        std::fs::write(&filename, serde_json::to_string_pretty(&output).unwrap()).unwrap();
+        let filename = report.save(name, "fair_concurrent");
         
         println!("\nResults saved to: {}", filename);
+        
+        if let Some(baseline) = &baseline {
+            regressions.extend(baseline.compare(&report));
+        }
     }
     
     println!("\n{}", "=".repeat(60));
     println!("All Rust benchmarks complete!");
     println!("{}", "=".repeat(60));
+    
+    regression::finish(&regressions);
 }
\ No newline at end of file
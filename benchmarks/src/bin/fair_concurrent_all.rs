@@ -1,25 +1,114 @@
-use std::sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}};
+use std::sync::{Arc, Barrier, atomic::{AtomicBool, AtomicUsize, Ordering}};
 use std::thread;
 use std::time::{Duration, Instant};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use threadpool::ThreadPool;
 use crossbeam::channel::{unbounded};
 use rand::Rng;
-use serde_json;
+use cache_benchmarks::{CacheState, Fairness, WorkloadMix, args_from_env, baseline_from_args, metrics, regression, stats, watchdog};
+use cache_benchmarks::cpu::CpuTimer;
+use cache_benchmarks::loader::{LoaderLatency, SingleFlight};
+use cache_benchmarks::report::{BenchmarkReport, BenchmarkResult, Metric, ScalingMatrix};
+use cache_benchmarks::scenario::{BenchmarkKind, Scenario, ScenarioBenchmark};
+use cache_benchmarks::workload::{self, OpKind, Workload};
 
 // Import the cache implementations with concrete types
 type Cache30B = qwen30b_cache::SmartCache<String, String>;
 type Cache235B = qwen235b_cache::SmartCache<String, String>;
 type Cache435B = qwen435b_cache::SmartCache<String, String>;
+type CacheGlm45 = glm45_cache::SmartCache<String, String>;
+type Config30B = qwen30b_cache::CacheConfig;
+type Config235B = qwen235b_cache::CacheConfig;
+type Config435B = qwen435b_cache::CacheConfig;
+type ConfigGlm45 = glm45_cache::CacheConfig;
+
+// Entries held by a cache, expired ones awaiting cleanup included
+trait StoredEntries {
+    fn stored_entries(&self) -> usize;
+}
+
+impl StoredEntries for Cache30B {
+    fn stored_entries(&self) -> usize {
+        self.size()
+    }
+}
+
+impl StoredEntries for Cache235B {
+    fn stored_entries(&self) -> usize {
+        self.size()
+    }
+}
+
+impl StoredEntries for Cache435B {
+    fn stored_entries(&self) -> usize {
+        self.get_stats().size
+    }
+}
+
+impl StoredEntries for CacheGlm45 {
+    fn stored_entries(&self) -> usize {
+        self.size()
+    }
+}
+
+// Burst traffic pattern: every worker fires back-to-back for BURST_MS, then trickles
+// one op per TRICKLE_INTERVAL_MS for QUIET_MS
+const BURST_MS: u64 = 200;
+const QUIET_MS: u64 = 300;
+const TRICKLE_INTERVAL_MS: u64 = 5;
+// Quiet-period latencies are bucketed this finely when looking for recovery
+const RECOVERY_WINDOW_MS: u64 = 10;
+
+// TTL churn: (TTL, share of inserts) for short, medium and long lived entries
+const CHURN_TTLS_MS: [(u64, f64); 3] = [(10, 0.5), (500, 0.3), (60_000, 0.2)];
+const CHURN_CLEANUP_INTERVAL_MS: u64 = 100;
+const CHURN_SAMPLE_MS: u64 = 100;
+// Each writer inserts this many entries per millisecond at most
+const CHURN_BATCH: usize = 50;
+// Puts slower than this count as cleanup-induced spikes
+const CHURN_SPIKE_US: u64 = 1000;
+
+// Loader latency: read-through traffic over LOADER_KEYS keys that expire after
+// LOADER_TTL_MS, so popular keys keep missing under many concurrent readers
+const LOADER_KEYS: usize = 1000;
+const LOADER_TTL_MS: u64 = 200;
+
+// Scaling sweep (--scaling): every thread count is run against every capacity
+const SCALING_THREADS: [usize; 8] = [1, 2, 4, 8, 16, 32, 64, 128];
+const SCALING_CAPACITIES: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+const SCALING_CELL_MS: u64 = 1000;
+// Filling a cache to capacity stops after this long; slow inserts are part of the result
+const SCALING_PREFILL_BUDGET_SECS: u64 = 5;
+
+// Latencies are kept as nanoseconds, sorted ascending
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    sorted[((sorted.len() - 1) as f64 * p).round() as usize]
+}
 
 // Macro to generate benchmark functions for each cache type
 macro_rules! impl_benchmarks {
-    ($cache_type:ty, $name:expr, $mod_name:ident) => {
+    ($cache_type:ty, $config_type:ty, $name:expr, $mod_name:ident) => {
         mod $mod_name {
             use super::*;
             
-            pub fn benchmark_producer_consumer(num_producers: usize, num_consumers: usize, duration_secs: u64) -> HashMap<String, serde_json::Value> {
-                let cache = Arc::new(<$cache_type>::new(100000));
+            // Fill to the state's occupancy with `key(i)` before timing starts; keys
+            // past the ones the workload touches just take up room
+            fn prefill(cache: &$cache_type, state: CacheState, capacity: usize, ttl: Option<Duration>, key: impl Fn(usize) -> String) {
+                let count = state.prefill(capacity);
+                if count > 0 {
+                    println!("Prefilling {} entries ({})...", count, state);
+                }
+                for i in 0..count {
+                    cache.put(key(i), format!("value_{}", i), ttl, (i % 10 + 1) as u8);
+                }
+            }
+            
+            pub fn benchmark_producer_consumer(num_producers: usize, num_consumers: usize, duration_secs: u64, capacity: usize, state: CacheState) -> BenchmarkResult {
+                let cache = Arc::new(<$cache_type>::new(capacity));
+                prefill(&cache, state, capacity, None, |i| format!("p{}_item_{}", i % num_producers, i / num_producers));
                 let stop_flag = Arc::new(AtomicBool::new(false));
                 
                 let mut producer_counts = Vec::new();
@@ -39,6 +128,7 @@ macro_rules! impl_benchmarks {
                 println!("Duration: {} seconds", duration_secs);
                 
                 let start = Instant::now();
+                let cpu = CpuTimer::start();
                 let pool = ThreadPool::new(num_producers + num_consumers);
                 
                 // Start producers
@@ -48,6 +138,7 @@ macro_rules! impl_benchmarks {
                     let count = Arc::clone(&producer_counts[i]);
                     
                     pool.execute(move || {
+                        let progress = watchdog::worker(format!("producer {}", i));
                         let mut local_count = 0;
                         let mut rng = rand::thread_rng();
                         
@@ -57,7 +148,9 @@ macro_rules! impl_benchmarks {
                                 std::time::SystemTime::now()
                                 .duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos());
                             
+                            let tracked = progress.enter("PUT", &key);
                             cache.put(key, value, None, rng.gen_range(1..=10));
+                            tracked.finish();
                             local_count += 1;
                             count.store(local_count, Ordering::Relaxed);
                             
@@ -74,6 +167,7 @@ macro_rules! impl_benchmarks {
                     let misses = Arc::clone(&consumer_misses[i]);
                     
                     pool.execute(move || {
+                        let progress = watchdog::worker(format!("consumer {}", i));
                         let mut rng = rand::thread_rng();
                         
                         while !stop.load(Ordering::Relaxed) {
@@ -81,7 +175,10 @@ macro_rules! impl_benchmarks {
                             let item_id = rng.gen_range(0..1000);
                             let key = format!("p{}_item_{}", producer_id, item_id);
                             
-                            if cache.get(&key).is_some() {
+                            let tracked = progress.enter("GET", &key);
+                            let hit = cache.get(&key).is_some();
+                            tracked.finish();
+                            if hit {
                                 hits.fetch_add(1, Ordering::Relaxed);
                             } else {
                                 misses.fetch_add(1, Ordering::Relaxed);
@@ -100,6 +197,7 @@ macro_rules! impl_benchmarks {
                 drop(pool);
                 
                 let elapsed = start.elapsed();
+                let cpu = cpu.stop();
                 
                 // Calculate statistics
                 let total_puts: usize = producer_counts.iter()
@@ -121,93 +219,106 @@ macro_rules! impl_benchmarks {
                     0.0 
                 };
                 
-                let mut result = HashMap::new();
-                result.insert("duration".to_string(), serde_json::json!(elapsed.as_secs_f64()));
-                result.insert("total_puts".to_string(), serde_json::json!(total_puts));
-                result.insert("total_gets".to_string(), serde_json::json!(total_gets));
-                result.insert("total_operations".to_string(), serde_json::json!(total_puts + total_gets));
-                result.insert("puts_per_second".to_string(), serde_json::json!(format!("{:.2}", total_puts as f64 / elapsed.as_secs_f64())));
-                result.insert("gets_per_second".to_string(), serde_json::json!(format!("{:.2}", total_gets as f64 / elapsed.as_secs_f64())));
-                result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", (total_puts + total_gets) as f64 / elapsed.as_secs_f64())));
-                result.insert("hit_rate".to_string(), serde_json::json!(format!("{:.1}%", hit_rate * 100.0)));
-                result.insert("total_hits".to_string(), serde_json::json!(total_hits));
-                result.insert("total_misses".to_string(), serde_json::json!(total_misses));
+                let mut result = BenchmarkResult::new();
+                result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+                result.insert("total_puts".to_string(), Metric::from(total_puts));
+                result.insert("total_gets".to_string(), Metric::from(total_gets));
+                result.insert("total_operations".to_string(), Metric::from(total_puts + total_gets));
+                result.insert("puts_per_second".to_string(), Metric::from(total_puts as f64 / elapsed.as_secs_f64()));
+                result.insert("gets_per_second".to_string(), Metric::from(total_gets as f64 / elapsed.as_secs_f64()));
+                result.insert("ops_per_second".to_string(), Metric::from((total_puts + total_gets) as f64 / elapsed.as_secs_f64()));
+                cpu.insert_metrics(&mut result, total_puts + total_gets);
+                result.insert("hit_rate_pct".to_string(), Metric::from(hit_rate * 100.0));
+                result.insert("total_hits".to_string(), Metric::from(total_hits));
+                result.insert("total_misses".to_string(), Metric::from(total_misses));
+                
+                // Per-thread throughput spread
+                let producer_ops: Vec<usize> = producer_counts.iter().map(|c| c.load(Ordering::Relaxed)).collect();
+                let consumer_ops: Vec<usize> = consumer_hits.iter().zip(&consumer_misses)
+                    .map(|(h, m)| h.load(Ordering::Relaxed) + m.load(Ordering::Relaxed))
+                    .collect();
+                result.insert("producer_fairness".to_string(), Fairness::from_counts(&producer_ops, elapsed).to_metric());
+                result.insert("consumer_fairness".to_string(), Fairness::from_counts(&consumer_ops, elapsed).to_metric());
                 
                 result
             }
             
-            pub fn benchmark_shared_workload(num_workers: usize, num_operations: usize) -> HashMap<String, serde_json::Value> {
-                let cache = Arc::new(<$cache_type>::new(100000));
+            // With `record`, the run is saved there as a recorded workload (see workload.rs)
+            pub fn benchmark_shared_workload(num_workers: usize, num_operations: usize, mix: WorkloadMix, capacity: usize, state: CacheState, record: Option<&str>) -> BenchmarkResult {
+                let cache = Arc::new(<$cache_type>::new(capacity));
+                prefill(&cache, state, capacity, None, |i| format!("key_{}", i));
                 
-                // Create work queue
+                // Create work queue, filled with mixed operations
                 let (tx, rx) = unbounded();
-                let mut rng = rand::thread_rng();
-                
-                // Fill with mixed operations
-                for i in 0..num_operations {
-                    if rng.gen::<f64>() < 0.7 { // 70% writes
-                        tx.send(("PUT", 
-                                format!("key_{}", i % 1000),
-                                format!("value_{}", i),
-                                rng.gen_range(1..=10)))
-                            .unwrap();
-                    } else { // 30% reads
-                        tx.send(("GET", 
-                                format!("key_{}", rng.gen_range(0..1000)),
-                                String::new(),
-                                0))
-                            .unwrap();
-                    }
+                for op in workload::generate(mix, num_operations, &mut rand::thread_rng()) {
+                    tx.send(op).unwrap();
                 }
                 drop(tx); // Close sender
+                let recording = record.is_some();
                 
                 let operation_times = Arc::new(parking_lot::Mutex::new(Vec::new()));
                 
-                println!("\nRunning Shared Workload benchmark ({} workers, {} operations)...", 
-                        num_workers, num_operations);
+                println!("\nRunning Shared Workload benchmark ({} workers, {} operations, {})...", 
+                        num_workers, num_operations, mix);
                 
                 let start = Instant::now();
+                let cpu = CpuTimer::start();
                 let pool = ThreadPool::new(num_workers);
                 let (done_tx, done_rx) = unbounded();
                 
                 // Start workers
-                for _ in 0..num_workers {
+                for i in 0..num_workers {
                     let cache = Arc::clone(&cache);
                     let rx = rx.clone();
                     let times = Arc::clone(&operation_times);
                     let done = done_tx.clone();
                     
                     pool.execute(move || {
+                        let progress = watchdog::worker(format!("shared worker {}", i));
                         let mut local_times = Vec::new();
+                        let mut local_ops = Vec::new();
                         
-                        while let Ok((op, key, value, priority)) = rx.recv() {
+                        while let Ok(mut op) = rx.recv() {
                             let op_start = Instant::now();
+                            let key = op.key();
                             
-                            match op {
-                                "PUT" => {
-                                    cache.put(key, value, None, priority);
+                            let tracked = progress.enter(op.kind.name(), &key);
+                            match op.kind {
+                                OpKind::Put => {
+                                    cache.put(key, op.value(), None, op.priority);
                                 },
-                                "GET" => {
+                                OpKind::Get => {
                                     let _ = cache.get(&key);
                                 },
-                                _ => {}
                             }
+                            tracked.finish();
                             
                             local_times.push(op_start.elapsed());
+                            if recording {
+                                op.at = op_start.duration_since(start);
+                            }
+                            local_ops.push(op);
                         }
                         
                         times.lock().extend(local_times);
-                        done.send(()).unwrap();
+                        done.send(local_ops).unwrap();
                     });
                 }
                 
                 drop(done_tx);
                 // Wait for all workers to complete
-                for _ in 0..num_workers {
-                    done_rx.recv().unwrap();
-                }
+                let ran: Vec<Vec<workload::Op>> = (0..num_workers).map(|_| done_rx.recv().unwrap()).collect();
+                let worker_ops: Vec<usize> = ran.iter().map(Vec::len).collect();
                 
                 let elapsed = start.elapsed();
+                let cpu = cpu.stop();
+                
+                if let Some(path) = record {
+                    match (Workload { capacity, workers: ran }).save(path) {
+                        Ok(()) => println!("Workload recorded to: {}", path),
+                        Err(err) => eprintln!("{}", err),
+                    }
+                }
                 
                 // Calculate statistics
                 let times = operation_times.lock();
@@ -224,18 +335,22 @@ macro_rules! impl_benchmarks {
                     1.0
                 };
                 
-                let mut result = HashMap::new();
-                result.insert("duration".to_string(), serde_json::json!(format!("{:.3}", elapsed.as_secs_f64())));
-                result.insert("num_workers".to_string(), serde_json::json!(num_workers));
-                result.insert("total_operations".to_string(), serde_json::json!(num_operations));
-                result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", num_operations as f64 / elapsed.as_secs_f64())));
-                result.insert("avg_operation_time_ms".to_string(), serde_json::json!(format!("{:.3}", avg_op_time)));
-                result.insert("parallelism_factor".to_string(), serde_json::json!(format!("{:.2}x", parallelism_factor)));
+                let mut result = BenchmarkResult::new();
+                result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+                result.insert("num_workers".to_string(), Metric::from(num_workers));
+                result.insert("total_operations".to_string(), Metric::from(num_operations));
+                result.insert("write_ratio".to_string(), Metric::from(mix.write_ratio));
+                result.insert("key_space".to_string(), Metric::from(mix.key_space));
+                result.insert("worker_fairness".to_string(), Fairness::from_counts(&worker_ops, elapsed).to_metric());
+                result.insert("ops_per_second".to_string(), Metric::from(num_operations as f64 / elapsed.as_secs_f64()));
+                cpu.insert_metrics(&mut result, num_operations);
+                result.insert("avg_operation_time_ms".to_string(), Metric::from(avg_op_time));
+                result.insert("parallelism_factor".to_string(), Metric::from(parallelism_factor));
                 
                 result
             }
             
-            pub fn benchmark_eviction_strategy(cache_size: usize, total_insertions: usize) -> HashMap<String, serde_json::Value> {
+            pub fn benchmark_eviction_strategy(cache_size: usize, total_insertions: usize) -> BenchmarkResult {
                 let cache = Arc::new(<$cache_type>::new(cache_size));
                 
                 println!("\nRunning Eviction Strategy benchmark (cache size: {}, insertions: {})...", 
@@ -281,20 +396,20 @@ macro_rules! impl_benchmarks {
                     0.0
                 };
                 
-                let mut result = HashMap::new();
-                result.insert("duration".to_string(), serde_json::json!(format!("{:.3}", elapsed.as_secs_f64())));
-                result.insert("cache_size".to_string(), serde_json::json!(cache_size));
-                result.insert("total_insertions".to_string(), serde_json::json!(total_insertions));
-                result.insert("evictions_forced".to_string(), serde_json::json!(evictions_forced));
-                result.insert("evicted_count".to_string(), serde_json::json!(evicted_count));
-                result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", total_insertions as f64 / elapsed.as_secs_f64())));
-                result.insert("eviction_efficiency".to_string(), serde_json::json!(format!("{:.1}%", eviction_efficiency)));
+                let mut result = BenchmarkResult::new();
+                result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+                result.insert("cache_size".to_string(), Metric::from(cache_size));
+                result.insert("total_insertions".to_string(), Metric::from(total_insertions));
+                result.insert("evictions_forced".to_string(), Metric::from(evictions_forced));
+                result.insert("evicted_count".to_string(), Metric::from(evicted_count));
+                result.insert("ops_per_second".to_string(), Metric::from(total_insertions as f64 / elapsed.as_secs_f64()));
+                result.insert("eviction_efficiency_pct".to_string(), Metric::from(eviction_efficiency));
                 
                 result
             }
             
-            pub fn benchmark_ttl_operations(num_items: usize, ttl_ms: u64) -> HashMap<String, serde_json::Value> {
-                let cache = Arc::new(<$cache_type>::new(10000));
+            pub fn benchmark_ttl_operations(num_items: usize, ttl_ms: u64, capacity: usize) -> BenchmarkResult {
+                let cache = Arc::new(<$cache_type>::new(capacity));
                 
                 println!("\nRunning TTL Operations benchmark ({} items with {}ms TTL)...", 
                         num_items, ttl_ms);
@@ -364,22 +479,309 @@ macro_rules! impl_benchmarks {
                     0.0
                 };
                 
-                let mut result = HashMap::new();
-                result.insert("ttl_expiry_duration".to_string(), serde_json::json!(format!("{:.3}", expiry_elapsed.as_secs_f64())));
-                result.insert("ttl_check_duration".to_string(), serde_json::json!(format!("{:.3}", check_elapsed.as_secs_f64())));
-                result.insert("num_items".to_string(), serde_json::json!(num_items));
-                result.insert("ttl_ms".to_string(), serde_json::json!(ttl_ms));
-                result.insert("expired_count".to_string(), serde_json::json!(expired_count));
-                result.insert("expiry_rate".to_string(), serde_json::json!(format!("{:.1}%", expiry_rate)));
-                result.insert("valid_count".to_string(), serde_json::json!(valid_count));
-                result.insert("check_ops_per_second".to_string(), serde_json::json!(format!("{:.2}", check_ops_per_second)));
-                result.insert("avg_check_time_us".to_string(), serde_json::json!(format!("{:.2}", avg_check_time_us)));
+                let mut result = BenchmarkResult::new();
+                result.insert("ttl_expiry_duration".to_string(), Metric::from(expiry_elapsed.as_secs_f64()));
+                result.insert("ttl_check_duration".to_string(), Metric::from(check_elapsed.as_secs_f64()));
+                result.insert("num_items".to_string(), Metric::from(num_items));
+                result.insert("ttl_ms".to_string(), Metric::from(ttl_ms));
+                result.insert("expired_count".to_string(), Metric::from(expired_count));
+                result.insert("expiry_rate_pct".to_string(), Metric::from(expiry_rate));
+                result.insert("valid_count".to_string(), Metric::from(valid_count));
+                result.insert("check_ops_per_second".to_string(), Metric::from(check_ops_per_second));
+                result.insert("avg_check_time_us".to_string(), Metric::from(avg_check_time_us));
+                
+                result
+            }
+            
+            pub fn benchmark_ttl_churn(num_writers: usize, duration_secs: u64, capacity: usize) -> BenchmarkResult {
+                // The suite passes a capacity large enough that every entry leaves by expiring
+                let mut config = <$config_type>::default();
+                config.max_capacity = capacity;
+                config.cleanup_interval = Duration::from_millis(CHURN_CLEANUP_INTERVAL_MS);
+                let cache = Arc::new(<$cache_type>::with_config(config));
+                let stop_flag = Arc::new(AtomicBool::new(false));
+                
+                println!("\nRunning TTL Churn benchmark ({} writers, TTLs {:?}, cleanup every {}ms)...", 
+                        num_writers, CHURN_TTLS_MS.iter().map(|(ttl, _)| Duration::from_millis(*ttl)).collect::<Vec<_>>(),
+                        CHURN_CLEANUP_INTERVAL_MS);
+                println!("Duration: {} seconds", duration_secs);
+                
+                let start = Instant::now();
+                let cpu = CpuTimer::start();
+                let pool = ThreadPool::new(num_writers + 1);
+                let (done_tx, done_rx) = unbounded();
+                
+                // Start writers; each reports (insert offset, expiry offset, latency) in ns
+                for i in 0..num_writers {
+                    let cache = Arc::clone(&cache);
+                    let stop = Arc::clone(&stop_flag);
+                    let done = done_tx.clone();
+                    
+                    pool.execute(move || {
+                        let progress = watchdog::worker(format!("churn writer {}", i));
+                        let mut rng = rand::thread_rng();
+                        let mut records = Vec::new();
+                        
+                        while !stop.load(Ordering::Relaxed) {
+                            for _ in 0..CHURN_BATCH {
+                                let roll = rng.gen::<f64>();
+                                let mut cumulative = 0.0;
+                                let ttl_ms = CHURN_TTLS_MS.iter()
+                                    .find(|(_, share)| { cumulative += share; roll < cumulative })
+                                    .map_or(CHURN_TTLS_MS[CHURN_TTLS_MS.len() - 1].0, |(ttl, _)| *ttl);
+                                
+                                let key = format!("churn_{}_{}", i, records.len());
+                                let inserted_at = start.elapsed().as_nanos() as u64;
+                                let tracked = progress.enter("PUT", &key);
+                                let op_start = Instant::now();
+                                cache.put(key, format!("value_{}", i), Some(Duration::from_millis(ttl_ms)), 5);
+                                let latency = op_start.elapsed().as_nanos() as u64;
+                                tracked.finish();
+                                records.push((inserted_at, inserted_at + ttl_ms * 1_000_000, latency));
+                            }
+                            thread::sleep(Duration::from_millis(1));
+                        }
+                        
+                        done.send(records).unwrap();
+                    });
+                }
+                
+                // Sample how many entries the cache is holding
+                let (sample_tx, sample_rx) = unbounded();
+                {
+                    let cache = Arc::clone(&cache);
+                    let stop = Arc::clone(&stop_flag);
+                    pool.execute(move || {
+                        while !stop.load(Ordering::Relaxed) {
+                            thread::sleep(Duration::from_millis(CHURN_SAMPLE_MS));
+                            let at = start.elapsed().as_nanos() as u64;
+                            sample_tx.send((at, cache.stored_entries())).unwrap();
+                        }
+                    });
+                }
+                
+                // Run for specified duration
+                thread::sleep(Duration::from_secs(duration_secs));
+                stop_flag.store(true, Ordering::Relaxed);
+                
+                drop(done_tx);
+                // Wait for all writers to complete
+                let mut records = Vec::new();
+                for _ in 0..num_writers {
+                    records.extend(done_rx.recv().unwrap());
+                }
+                drop(pool);
+                let samples: Vec<(u64, usize)> = sample_rx.try_iter().collect();
+                
+                let elapsed = start.elapsed();
+                let cpu = cpu.stop();
+                
+                // Calculate statistics
+                let mut latencies: Vec<u64> = records.iter().map(|(_, _, latency)| *latency).collect();
+                latencies.sort_unstable();
+                let spikes = latencies.iter().filter(|latency| **latency > CHURN_SPIKE_US * 1000).count();
+                
+                // Residual expired entries: what the cache holds beyond what should still be live
+                let mut timeline = Vec::new();
+                let mut max_residual = 0;
+                let mut residual_sum = 0;
+                let mut window_start = 0;
+                for (at, stored) in &samples {
+                    let live = records.iter()
+                        .filter(|(inserted_at, expires_at, _)| inserted_at <= at && at < expires_at)
+                        .count();
+                    let residual = stored.saturating_sub(live);
+                    max_residual = max_residual.max(residual);
+                    residual_sum += residual;
+                    
+                    let window_max = records.iter()
+                        .filter(|(inserted_at, _, _)| (window_start..*at).contains(inserted_at))
+                        .map(|(_, _, latency)| *latency)
+                        .max()
+                        .unwrap_or(0);
+                    window_start = *at;
+                    
+                    timeline.push(metrics! {
+                        "at_ms" => at / 1_000_000,
+                        "stored_entries" => *stored,
+                        "live_entries" => live,
+                        "residual_expired" => residual,
+                        "max_put_latency_us" => window_max as f64 / 1000.0,
+                    });
+                }
+                let avg_residual = if samples.is_empty() { 0.0 } else { residual_sum as f64 / samples.len() as f64 };
+                
+                let mut result = BenchmarkResult::new();
+                result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+                result.insert("num_writers".to_string(), Metric::from(num_writers));
+                result.insert("total_inserts".to_string(), Metric::from(records.len()));
+                result.insert("inserts_per_second".to_string(), Metric::from(records.len() as f64 / elapsed.as_secs_f64()));
+                cpu.insert_metrics(&mut result, records.len());
+                result.insert("cleanup_interval_ms".to_string(), Metric::from(CHURN_CLEANUP_INTERVAL_MS));
+                result.insert("p50_put_latency_us".to_string(), Metric::from(percentile(&latencies, 0.5) as f64 / 1000.0));
+                result.insert("p99_put_latency_us".to_string(), Metric::from(percentile(&latencies, 0.99) as f64 / 1000.0));
+                result.insert("p999_put_latency_us".to_string(), Metric::from(percentile(&latencies, 0.999) as f64 / 1000.0));
+                result.insert("max_put_latency_us".to_string(), Metric::from(latencies.last().copied().unwrap_or(0) as f64 / 1000.0));
+                result.insert(format!("puts_over_{}us", CHURN_SPIKE_US), Metric::from(spikes));
+                result.insert("avg_residual_expired".to_string(), Metric::from(avg_residual));
+                result.insert("max_residual_expired".to_string(), Metric::from(max_residual));
+                result.insert("timeline".to_string(), Metric::from(timeline));
+                
+                result
+            }
+            
+            pub fn benchmark_burst_traffic(num_workers: usize, num_bursts: usize, capacity: usize) -> BenchmarkResult {
+                let cache = Arc::new(<$cache_type>::new(capacity));
+                let barrier = Arc::new(Barrier::new(num_workers));
+                
+                // Warm the key space so the baseline runs against the same population as later phases
+                for k in 0..10000 {
+                    cache.put(format!("key_{}", k), format!("value_{}", k), None, 5);
+                }
+                
+                println!("\nRunning Burst Traffic benchmark ({} workers, {} bursts of {}ms)...", 
+                        num_workers, num_bursts, BURST_MS);
+                
+                let start = Instant::now();
+                let pool = ThreadPool::new(num_workers);
+                let (done_tx, done_rx) = unbounded();
+                
+                // Start workers
+                for i in 0..num_workers {
+                    let cache = Arc::clone(&cache);
+                    let barrier = Arc::clone(&barrier);
+                    let done = done_tx.clone();
+                    
+                    pool.execute(move || {
+                        let progress = watchdog::worker(format!("burst worker {}", i));
+                        let mut rng = rand::thread_rng();
+                        let op = |rng: &mut rand::rngs::ThreadRng| {
+                            let key = format!("key_{}", rng.gen_range(0..10000));
+                            let write = rng.gen::<f64>() < 0.7;
+                            let tracked = progress.enter(if write { "PUT" } else { "GET" }, &key);
+                            let op_start = Instant::now();
+                            if write {
+                                cache.put(key, format!("value_{}", i), None, rng.gen_range(1..=10));
+                            } else {
+                                let _ = cache.get(&key);
+                            }
+                            let latency = op_start.elapsed().as_nanos() as u64;
+                            tracked.finish();
+                            latency
+                        };
+                        
+                        // (phase, offset from phase start, latency); phase 0 is the
+                        // baseline, phase b + 1 the quiet period after burst b
+                        let mut quiet = Vec::new();
+                        let mut bursts = Vec::new();
+                        
+                        for phase in 0..=num_bursts {
+                            if phase > 0 {
+                                // All workers start each burst together
+                                barrier.wait();
+                                let burst_start = Instant::now();
+                                let mut latencies = Vec::new();
+                                while burst_start.elapsed() < Duration::from_millis(BURST_MS) {
+                                    latencies.push(op(&mut rng));
+                                }
+                                bursts.push(latencies);
+                            }
+                            
+                            let quiet_start = Instant::now();
+                            while quiet_start.elapsed() < Duration::from_millis(QUIET_MS) {
+                                let offset = quiet_start.elapsed();
+                                quiet.push((phase, offset, op(&mut rng)));
+                                thread::sleep(Duration::from_millis(TRICKLE_INTERVAL_MS));
+                            }
+                        }
+                        
+                        done.send((quiet, bursts)).unwrap();
+                    });
+                }
+                
+                drop(done_tx);
+                // Wait for all workers to complete
+                let mut quiet = Vec::new();
+                let mut bursts = vec![Vec::new(); num_bursts];
+                for _ in 0..num_workers {
+                    let (worker_quiet, worker_bursts) = done_rx.recv().unwrap();
+                    quiet.extend(worker_quiet);
+                    for (burst, latencies) in bursts.iter_mut().zip(worker_bursts) {
+                        burst.extend(latencies);
+                    }
+                }
+                
+                let elapsed = start.elapsed();
+                
+                // Calculate statistics
+                let mut baseline: Vec<u64> = quiet.iter()
+                    .filter(|(phase, _, _)| *phase == 0)
+                    .map(|(_, _, latency)| *latency)
+                    .collect();
+                baseline.sort_unstable();
+                let baseline_p50 = percentile(&baseline, 0.5);
+                // Recovered once a whole window's median is back within 2x of baseline
+                let threshold = (baseline_p50 * 2).max(1000);
+                
+                let mut per_burst = Vec::new();
+                let mut worst_p99 = 0;
+                let mut worst_max = 0;
+                let mut recovery_times = Vec::new();
+                for (b, latencies) in bursts.iter_mut().enumerate() {
+                    latencies.sort_unstable();
+                    let p99 = percentile(latencies, 0.99);
+                    let max = latencies.last().copied().unwrap_or(0);
+                    worst_p99 = worst_p99.max(p99);
+                    worst_max = worst_max.max(max);
+                    
+                    let windows = (QUIET_MS / RECOVERY_WINDOW_MS) as usize;
+                    let mut by_window = vec![Vec::new(); windows];
+                    for (_, offset, latency) in quiet.iter().filter(|(phase, _, _)| *phase == b + 1) {
+                        let w = (offset.as_millis() as u64 / RECOVERY_WINDOW_MS) as usize;
+                        by_window[w.min(windows - 1)].push(*latency);
+                    }
+                    let recovered = by_window.iter_mut().position(|window| {
+                        window.sort_unstable();
+                        !window.is_empty() && percentile(window, 0.5) <= threshold
+                    });
+                    // Never recovering within the quiet period counts as the whole period
+                    let recovery_ms = recovered.map_or(QUIET_MS, |w| w as u64 * RECOVERY_WINDOW_MS);
+                    recovery_times.push(recovery_ms);
+                    
+                    per_burst.push(metrics! {
+                        "operations" => latencies.len(),
+                        "ops_per_second" => latencies.len() as f64 / (BURST_MS as f64 / 1000.0),
+                        "p99_latency_us" => p99 as f64 / 1000.0,
+                        "max_latency_us" => max as f64 / 1000.0,
+                        "recovery_ms" => recovery_ms,
+                    });
+                }
+                
+                let avg_recovery_ms = if recovery_times.is_empty() {
+                    0.0
+                } else {
+                    recovery_times.iter().sum::<u64>() as f64 / recovery_times.len() as f64
+                };
+                
+                let mut result = BenchmarkResult::new();
+                result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+                result.insert("num_workers".to_string(), Metric::from(num_workers));
+                result.insert("num_bursts".to_string(), Metric::from(num_bursts));
+                result.insert("burst_ms".to_string(), Metric::from(BURST_MS));
+                result.insert("quiet_ms".to_string(), Metric::from(QUIET_MS));
+                result.insert("baseline_p50_latency_us".to_string(), Metric::from(baseline_p50 as f64 / 1000.0));
+                result.insert("worst_burst_p99_latency_us".to_string(), Metric::from(worst_p99 as f64 / 1000.0));
+                result.insert("worst_burst_max_latency_us".to_string(), Metric::from(worst_max as f64 / 1000.0));
+                result.insert("avg_recovery_ms".to_string(), Metric::from(avg_recovery_ms));
+                result.insert("max_recovery_ms".to_string(), Metric::from(recovery_times.iter().max().copied().unwrap_or(0)));
+                result.insert("bursts".to_string(), Metric::from(per_burst));
                 
                 result
             }
             
-            pub fn benchmark_io_simulation(num_workers: usize, duration_secs: u64) -> HashMap<String, serde_json::Value> {
-                let cache = Arc::new(<$cache_type>::new(100000));
+            pub fn benchmark_io_simulation(num_workers: usize, duration_secs: u64, capacity: usize, state: CacheState) -> BenchmarkResult {
+                let cache = Arc::new(<$cache_type>::new(capacity));
+                prefill(&cache, state, capacity, None, |i| format!("worker_{}_item_{}", i % num_workers, i / num_workers));
                 let stop_flag = Arc::new(AtomicBool::new(false));
                 
                 let mut operation_counts = Vec::new();
@@ -391,6 +793,7 @@ macro_rules! impl_benchmarks {
                 println!("Simulating database/network delays where threading helps...");
                 
                 let start = Instant::now();
+                let cpu = CpuTimer::start();
                 let pool = ThreadPool::new(num_workers);
                 
                 // Start workers
@@ -400,6 +803,7 @@ macro_rules! impl_benchmarks {
                     let count = Arc::clone(&operation_counts[i]);
                     
                     pool.execute(move || {
+                        let progress = watchdog::worker(format!("io worker {}", i));
                         let mut local_count = 0;
                         let mut rng = rand::thread_rng();
                         
@@ -413,13 +817,18 @@ macro_rules! impl_benchmarks {
                                               .duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos());
                             
                             // Cache the result
+                            let tracked = progress.enter("PUT", &key);
                             cache.put(key, value, None, 5);
+                            tracked.finish();
                             
                             // Try to read some other worker's data
                             let other_worker = (i + rng.gen_range(1..num_workers)) % num_workers;
                             let other_key = format!("worker_{}_item_{}", other_worker, rng.gen_range(0..100));
                             
-                            if cache.get(&other_key).is_some() {
+                            let tracked = progress.enter("GET", &other_key);
+                            let hit = cache.get(&other_key).is_some();
+                            tracked.finish();
+                            if hit {
                                 // Simulate processing
                                 thread::sleep(Duration::from_millis(1));
                             }
@@ -438,6 +847,7 @@ macro_rules! impl_benchmarks {
                 drop(pool);
                 
                 let elapsed = start.elapsed();
+                let cpu = cpu.stop();
                 
                 // Calculate statistics
                 let total_operations: usize = operation_counts.iter()
@@ -447,84 +857,453 @@ macro_rules! impl_benchmarks {
                 let theoretical_sequential_time = total_operations as f64 * 0.006; // 6ms per op
                 let speedup = theoretical_sequential_time / elapsed.as_secs_f64();
                 
-                let mut result = HashMap::new();
-                result.insert("duration".to_string(), serde_json::json!(format!("{:.2}", elapsed.as_secs_f64())));
-                result.insert("num_workers".to_string(), serde_json::json!(num_workers));
-                result.insert("total_operations".to_string(), serde_json::json!(total_operations));
-                result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", total_operations as f64 / elapsed.as_secs_f64())));
-                result.insert("ops_per_worker".to_string(), serde_json::json!(total_operations / num_workers));
-                result.insert("theoretical_sequential_time".to_string(), serde_json::json!(format!("{:.2}", theoretical_sequential_time)));
-                result.insert("speedup".to_string(), serde_json::json!(format!("{:.2}x", speedup)));
+                let mut result = BenchmarkResult::new();
+                result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+                result.insert("num_workers".to_string(), Metric::from(num_workers));
+                result.insert("total_operations".to_string(), Metric::from(total_operations));
+                result.insert("ops_per_second".to_string(), Metric::from(total_operations as f64 / elapsed.as_secs_f64()));
+                cpu.insert_metrics(&mut result, total_operations);
+                result.insert("ops_per_worker".to_string(), Metric::from(total_operations / num_workers));
+                result.insert("theoretical_sequential_time".to_string(), Metric::from(theoretical_sequential_time));
+                result.insert("speedup".to_string(), Metric::from(speedup));
+                
+                result
+            }
+            
+            // Read-through gets: a miss runs the simulated loader and caches its value, so
+            // each get's latency includes any recomputation. Runs once with every miss
+            // loading and once with concurrent misses of a key sharing one load.
+            pub fn benchmark_loader_latency(num_workers: usize, duration_secs: u64, capacity: usize, key_space: usize, ttl_ms: u64, loader: LoaderLatency, state: CacheState) -> BenchmarkResult {
+                println!("\nRunning Loader Latency benchmark ({} workers, {} keys, TTL {}ms, loader {})...", 
+                        num_workers, key_space, ttl_ms, loader);
+                
+                let without = read_through(num_workers, duration_secs, capacity, key_space, ttl_ms, loader, state, false);
+                let with = read_through(num_workers, duration_secs, capacity, key_space, ttl_ms, loader, state, true);
+                let loads = |result: &BenchmarkResult| result["loader_calls"].as_f64().unwrap_or(0.0);
+                let loads_saved = if loads(&without) > 0.0 { (1.0 - loads(&with) / loads(&without)) * 100.0 } else { 0.0 };
                 
+                let mut result = BenchmarkResult::new();
+                result.insert("num_workers".to_string(), Metric::from(num_workers));
+                result.insert("key_space".to_string(), Metric::from(key_space));
+                result.insert("ttl_ms".to_string(), Metric::from(ttl_ms));
+                result.insert("loader_calls_saved_pct".to_string(), Metric::from(loads_saved));
+                result.insert("without_single_flight".to_string(), Metric::from(without));
+                result.insert("with_single_flight".to_string(), Metric::from(with));
                 result
             }
+            
+            #[allow(clippy::too_many_arguments)]
+            fn read_through(num_workers: usize, duration_secs: u64, capacity: usize, key_space: usize, ttl_ms: u64, loader: LoaderLatency, state: CacheState, single_flight: bool) -> BenchmarkResult {
+                let cache = Arc::new(<$cache_type>::new(capacity));
+                let ttl = Duration::from_millis(ttl_ms);
+                prefill(&cache, state, capacity, Some(ttl), |i| format!("key_{}", i));
+                let flights = Arc::new(SingleFlight::new());
+                let loader_calls = Arc::new(AtomicUsize::new(0));
+                let coalesced = Arc::new(AtomicUsize::new(0));
+                let stop_flag = Arc::new(AtomicBool::new(false));
+                
+                let start = Instant::now();
+                let cpu = CpuTimer::start();
+                let pool = ThreadPool::new(num_workers);
+                let (done_tx, done_rx) = unbounded();
+                
+                // Start workers; each reports (latency in ns, hit) per get
+                for i in 0..num_workers {
+                    let cache = Arc::clone(&cache);
+                    let flights = Arc::clone(&flights);
+                    let loader_calls = Arc::clone(&loader_calls);
+                    let coalesced = Arc::clone(&coalesced);
+                    let stop = Arc::clone(&stop_flag);
+                    let done = done_tx.clone();
+                    
+                    pool.execute(move || {
+                        let progress = watchdog::worker(format!("loader worker {}", i));
+                        let mut rng = rand::thread_rng();
+                        let mut records = Vec::new();
+                        
+                        while !stop.load(Ordering::Relaxed) {
+                            let key = format!("key_{}", rng.gen_range(0..key_space));
+                            let tracked = progress.enter("GET", &key);
+                            let op_start = Instant::now();
+                            let hit = cache.get(&key).is_some();
+                            if !hit {
+                                let load = || {
+                                    loader_calls.fetch_add(1, Ordering::Relaxed);
+                                    thread::sleep(loader.sample(&mut rand::thread_rng()));
+                                    let value = format!("loaded_{}", key);
+                                    cache.put(key.clone(), value.clone(), Some(ttl), 5);
+                                    value
+                                };
+                                if single_flight {
+                                    if !flights.load(&key, load).1 {
+                                        coalesced.fetch_add(1, Ordering::Relaxed);
+                                    }
+                                } else {
+                                    load();
+                                }
+                            }
+                            records.push((op_start.elapsed().as_nanos() as u64, hit));
+                            tracked.finish();
+                        }
+                        
+                        done.send(records).unwrap();
+                    });
+                }
+                
+                // Run for specified duration
+                thread::sleep(Duration::from_secs(duration_secs));
+                stop_flag.store(true, Ordering::Relaxed);
+                
+                drop(done_tx);
+                let mut records = Vec::new();
+                for _ in 0..num_workers {
+                    records.extend(done_rx.recv().unwrap());
+                }
+                drop(pool);
+                let elapsed = start.elapsed();
+                let cpu = cpu.stop();
+                
+                // Calculate statistics
+                let hits = records.iter().filter(|(_, hit)| *hit).count();
+                let mut latencies: Vec<u64> = records.iter().map(|(latency, _)| *latency).collect();
+                latencies.sort_unstable();
+                let hit_rate = if records.is_empty() { 0.0 } else { hits as f64 / records.len() as f64 };
+                
+                let mut result = BenchmarkResult::new();
+                result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+                result.insert("total_gets".to_string(), Metric::from(records.len()));
+                result.insert("gets_per_second".to_string(), Metric::from(records.len() as f64 / elapsed.as_secs_f64()));
+                cpu.insert_metrics(&mut result, records.len());
+                result.insert("hit_rate_pct".to_string(), Metric::from(hit_rate * 100.0));
+                result.insert("loader_calls".to_string(), Metric::from(loader_calls.load(Ordering::Relaxed)));
+                result.insert("coalesced_misses".to_string(), Metric::from(coalesced.load(Ordering::Relaxed)));
+                result.insert("p50_get_latency_us".to_string(), Metric::from(percentile(&latencies, 0.5) as f64 / 1000.0));
+                result.insert("p99_get_latency_us".to_string(), Metric::from(percentile(&latencies, 0.99) as f64 / 1000.0));
+                result.insert("p999_get_latency_us".to_string(), Metric::from(percentile(&latencies, 0.999) as f64 / 1000.0));
+                result.insert("max_get_latency_us".to_string(), Metric::from(latencies.last().copied().unwrap_or(0) as f64 / 1000.0));
+                
+                result
+            }
+            
+            // One cell of the scaling sweep: fill a cache to capacity, then run random
+            // operations over twice as many keys from `threads` threads for SCALING_CELL_MS
+            pub fn benchmark_scaling_cell(threads: usize, capacity: usize, write_ratio: f64) -> BenchmarkResult {
+                let cache = Arc::new(<$cache_type>::new(capacity));
+                let key_space = capacity * 2;
+                
+                let prefill_start = Instant::now();
+                let mut prefilled = 0;
+                while prefilled < capacity && prefill_start.elapsed() < Duration::from_secs(SCALING_PREFILL_BUDGET_SECS) {
+                    cache.put(format!("key_{}", prefilled), format!("value_{}", prefilled), None, 5);
+                    prefilled += 1;
+                }
+                let prefill_elapsed = prefill_start.elapsed();
+                
+                let stop_flag = Arc::new(AtomicBool::new(false));
+                let hits = Arc::new(AtomicUsize::new(0));
+                let gets = Arc::new(AtomicUsize::new(0));
+                let pool = ThreadPool::new(threads);
+                let (done_tx, done_rx) = unbounded();
+                
+                let start = Instant::now();
+                let cpu = CpuTimer::start();
+                for i in 0..threads {
+                    let cache = Arc::clone(&cache);
+                    let stop = Arc::clone(&stop_flag);
+                    let hits = Arc::clone(&hits);
+                    let gets = Arc::clone(&gets);
+                    let done = done_tx.clone();
+                    
+                    pool.execute(move || {
+                        let progress = watchdog::worker(format!("scaling worker {}", i));
+                        let mut rng = rand::thread_rng();
+                        let mut local_ops = 0;
+                        
+                        while !stop.load(Ordering::Relaxed) {
+                            let key = format!("key_{}", rng.gen_range(0..key_space));
+                            let tracked = progress.enter("OP", &key);
+                            if rng.gen::<f64>() < write_ratio {
+                                cache.put(key, format!("value_{}", local_ops), None, rng.gen_range(1..=10));
+                            } else {
+                                gets.fetch_add(1, Ordering::Relaxed);
+                                if cache.get(&key).is_some() {
+                                    hits.fetch_add(1, Ordering::Relaxed);
+                                }
+                            }
+                            tracked.finish();
+                            local_ops += 1;
+                        }
+                        
+                        done.send(local_ops).unwrap();
+                    });
+                }
+                
+                thread::sleep(Duration::from_millis(SCALING_CELL_MS));
+                stop_flag.store(true, Ordering::Relaxed);
+                
+                drop(done_tx);
+                let thread_ops: Vec<usize> = (0..threads).map(|_| done_rx.recv().unwrap()).collect();
+                let elapsed = start.elapsed();
+                let cpu = cpu.stop();
+                
+                let total_operations: usize = thread_ops.iter().sum();
+                let gets = gets.load(Ordering::Relaxed);
+                let hit_rate = if gets > 0 { hits.load(Ordering::Relaxed) as f64 / gets as f64 } else { 0.0 };
+                
+                let mut result = BenchmarkResult::new();
+                result.insert("threads".to_string(), Metric::from(threads));
+                result.insert("capacity".to_string(), Metric::from(capacity));
+                result.insert("prefilled".to_string(), Metric::from(prefilled));
+                result.insert("prefill_duration".to_string(), Metric::from(prefill_elapsed.as_secs_f64()));
+                result.insert("total_operations".to_string(), Metric::from(total_operations));
+                result.insert("ops_per_second".to_string(), Metric::from(total_operations as f64 / elapsed.as_secs_f64()));
+                cpu.insert_metrics(&mut result, total_operations);
+                result.insert("hit_rate_pct".to_string(), Metric::from(hit_rate * 100.0));
+                result.insert("thread_fairness".to_string(), Fairness::from_counts(&thread_ops, elapsed).to_metric());
+                
+                result
+            }
+            
+            // A recorded workload (--replay): every worker runs its operations in order,
+            // none before its recorded offset. Lag is how far behind that schedule an
+            // operation started, so a replay that cannot keep up shows it.
+            pub fn benchmark_replay(workload: Arc<Workload>) -> BenchmarkResult {
+                let cache = Arc::new(<$cache_type>::new(workload.capacity));
+                let num_workers = workload.workers.len();
+                let barrier = Arc::new(Barrier::new(num_workers + 1));
+                
+                println!("\nReplaying {} operations on {} workers...", workload.len(), num_workers);
+                
+                // Each worker reports (latency in ns, lag in ns) per operation
+                let handles: Vec<_> = (0..num_workers).map(|i| {
+                    let cache = Arc::clone(&cache);
+                    let workload = Arc::clone(&workload);
+                    let barrier = Arc::clone(&barrier);
+                    
+                    thread::spawn(move || {
+                        let progress = watchdog::worker(format!("replay worker {}", i));
+                        let mut records = Vec::with_capacity(workload.workers[i].len());
+                        barrier.wait();
+                        let start = Instant::now();
+                        
+                        for op in &workload.workers[i] {
+                            if let Some(wait) = op.at.checked_sub(start.elapsed()) {
+                                thread::sleep(wait);
+                            }
+                            let op_start = Instant::now();
+                            let lag = op_start.duration_since(start).saturating_sub(op.at);
+                            let key = op.key();
+                            
+                            let tracked = progress.enter(op.kind.name(), &key);
+                            match op.kind {
+                                OpKind::Put => {
+                                    cache.put(key, op.value(), None, op.priority);
+                                },
+                                OpKind::Get => {
+                                    let _ = cache.get(&key);
+                                },
+                            }
+                            tracked.finish();
+                            
+                            records.push((op_start.elapsed().as_nanos() as u64, lag.as_nanos() as u64));
+                        }
+                        records
+                    })
+                }).collect();
+                
+                barrier.wait();
+                let start = Instant::now();
+                let cpu = CpuTimer::start();
+                let records: Vec<Vec<(u64, u64)>> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+                let elapsed = start.elapsed();
+                let cpu = cpu.stop();
+                
+                // Calculate statistics
+                let worker_ops: Vec<usize> = records.iter().map(Vec::len).collect();
+                let mut latencies: Vec<u64> = records.iter().flatten().map(|(latency, _)| *latency).collect();
+                latencies.sort_unstable();
+                let max_lag = records.iter().flatten().map(|(_, lag)| *lag).max().unwrap_or(0);
+                
+                let mut result = BenchmarkResult::new();
+                result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+                result.insert("num_workers".to_string(), Metric::from(num_workers));
+                result.insert("total_operations".to_string(), Metric::from(latencies.len()));
+                result.insert("ops_per_second".to_string(), Metric::from(latencies.len() as f64 / elapsed.as_secs_f64()));
+                cpu.insert_metrics(&mut result, latencies.len());
+                result.insert("worker_fairness".to_string(), Fairness::from_counts(&worker_ops, elapsed).to_metric());
+                result.insert("p50_latency_us".to_string(), Metric::from(percentile(&latencies, 0.5) as f64 / 1000.0));
+                result.insert("p99_latency_us".to_string(), Metric::from(percentile(&latencies, 0.99) as f64 / 1000.0));
+                result.insert("p999_latency_us".to_string(), Metric::from(percentile(&latencies, 0.999) as f64 / 1000.0));
+                result.insert("max_latency_us".to_string(), Metric::from(latencies.last().copied().unwrap_or(0) as f64 / 1000.0));
+                result.insert("max_lag_ms".to_string(), Metric::from(max_lag as f64 / 1_000_000.0));
+                
+                result
+            }
+            
+            // One benchmark of a scenario file; settings left out take the suite's defaults
+            pub fn benchmark_scenario(benchmark: &ScenarioBenchmark, state: CacheState) -> BenchmarkResult {
+                match benchmark.kind {
+                    BenchmarkKind::ProducerConsumer => {
+                        let threads = benchmark.threads.unwrap_or(100);
+                        let producers = threads.div_ceil(2);
+                        benchmark_producer_consumer(
+                            producers,
+                            (threads - producers).max(1),
+                            benchmark.duration_secs.unwrap_or(5),
+                            benchmark.capacity.unwrap_or(100000),
+                            state,
+                        )
+                    }
+                    BenchmarkKind::SharedWorkload => benchmark_shared_workload(
+                        benchmark.threads.unwrap_or(100),
+                        benchmark.operations.unwrap_or(10000),
+                        benchmark.workload.unwrap_or_default(),
+                        benchmark.capacity.unwrap_or(100000),
+                        state,
+                        None,
+                    ),
+                    BenchmarkKind::IoSimulation => benchmark_io_simulation(
+                        benchmark.threads.unwrap_or(100),
+                        benchmark.duration_secs.unwrap_or(5),
+                        benchmark.capacity.unwrap_or(100000),
+                        state,
+                    ),
+                    BenchmarkKind::Eviction => benchmark_eviction_strategy(
+                        benchmark.capacity.unwrap_or(100),
+                        benchmark.operations.unwrap_or(200),
+                    ),
+                    BenchmarkKind::Ttl => benchmark_ttl_operations(
+                        benchmark.operations.unwrap_or(100),
+                        benchmark.ttl_ms.unwrap_or(100),
+                        benchmark.capacity.unwrap_or(10000),
+                    ),
+                    BenchmarkKind::BurstTraffic => benchmark_burst_traffic(
+                        benchmark.threads.unwrap_or(50),
+                        benchmark.bursts.unwrap_or(5),
+                        benchmark.capacity.unwrap_or(100000),
+                    ),
+                    BenchmarkKind::TtlChurn => benchmark_ttl_churn(
+                        benchmark.threads.unwrap_or(4),
+                        benchmark.duration_secs.unwrap_or(3),
+                        benchmark.capacity.unwrap_or(1_000_000),
+                    ),
+                    BenchmarkKind::LoaderLatency => benchmark_loader_latency(
+                        benchmark.threads.unwrap_or(32),
+                        benchmark.duration_secs.unwrap_or(3),
+                        benchmark.capacity.unwrap_or(100000),
+                        benchmark.keys.unwrap_or(LOADER_KEYS),
+                        benchmark.ttl_ms.unwrap_or(LOADER_TTL_MS),
+                        benchmark.loader.unwrap_or_default(),
+                        state,
+                    ),
+                }
+            }
         }
     };
 }
 
-// Generate benchmarks for all three cache implementations
-impl_benchmarks!(Cache30B, "Qwen30B", qwen30b);
-impl_benchmarks!(Cache235B, "Qwen235B", qwen235b);
-impl_benchmarks!(Cache435B, "Qwen435B", qwen435b);
+// Generate benchmarks for all four cache implementations
+impl_benchmarks!(Cache30B, Config30B, "Qwen30B", qwen30b);
+impl_benchmarks!(Cache235B, Config235B, "Qwen235B", qwen235b);
+impl_benchmarks!(Cache435B, Config435B, "Qwen435B", qwen435b);
+impl_benchmarks!(CacheGlm45, ConfigGlm45, "GLM45", glm45);
 
-fn run_all_benchmarks(name: &str, module: &str) -> HashMap<String, HashMap<String, serde_json::Value>> {
+fn run_all_benchmarks(
+    name: &str,
+    module: &str,
+    mixes: &[WorkloadMix],
+    states: &[CacheState],
+    runs: usize,
+    loader: LoaderLatency,
+    record: Option<&str>,
+) -> BTreeMap<String, BenchmarkResult> {
     println!("\n{}", "=".repeat(60));
     println!("Testing: {} Rust Implementation", name);
     println!("{}", "=".repeat(60));
     
-    let mut all_results = HashMap::new();
+    let mut all_results = BTreeMap::new();
+    let record = record.map(|path| workload::record_path(path, module));
     
-    // Run benchmarks based on module
-    let (pc_result, sw_result, io_result, evict_result, ttl_result) = match module {
+    // Run benchmarks based on module, each `runs` times; the throughput ones once per
+    // cache state, the shared workload once per mix and state
+    let sw_cases: Vec<(WorkloadMix, CacheState)> = mixes.iter()
+        .flat_map(|mix| states.iter().map(move |state| (*mix, *state)))
+        .collect();
+    let (pc_results, sw_results, io_results, evict_result, ttl_result, burst_result, churn_result, loader_results) = match module {
         "qwen30b" => (
-            qwen30b::benchmark_producer_consumer(50, 50, 5),
-            qwen30b::benchmark_shared_workload(100, 10000),
-            qwen30b::benchmark_io_simulation(100, 5),
-            qwen30b::benchmark_eviction_strategy(100, 200),
-            qwen30b::benchmark_ttl_operations(100, 100),
+            states.iter().map(|state| stats::repeated(runs, || qwen30b::benchmark_producer_consumer(50, 50, 5, 100000, *state))).collect::<Vec<_>>(),
+            sw_cases.iter().map(|(mix, state)| stats::repeated(runs, || qwen30b::benchmark_shared_workload(100, 10000, *mix, 100000, *state, record.as_deref()))).collect::<Vec<_>>(),
+            states.iter().map(|state| stats::repeated(runs, || qwen30b::benchmark_io_simulation(100, 5, 100000, *state))).collect::<Vec<_>>(),
+            stats::repeated(runs, || qwen30b::benchmark_eviction_strategy(100, 200)),
+            stats::repeated(runs, || qwen30b::benchmark_ttl_operations(100, 100, 10000)),
+            stats::repeated(runs, || qwen30b::benchmark_burst_traffic(50, 5, 100000)),
+            stats::repeated(runs, || qwen30b::benchmark_ttl_churn(4, 3, 1_000_000)),
+            states.iter().map(|state| stats::repeated(runs, || qwen30b::benchmark_loader_latency(32, 3, 100000, LOADER_KEYS, LOADER_TTL_MS, loader, *state))).collect::<Vec<_>>(),
         ),
         "qwen235b" => (
-            qwen235b::benchmark_producer_consumer(50, 50, 5),
-            qwen235b::benchmark_shared_workload(100, 10000),
-            qwen235b::benchmark_io_simulation(100, 5),
-            qwen235b::benchmark_eviction_strategy(100, 200),
-            qwen235b::benchmark_ttl_operations(100, 100),
+            states.iter().map(|state| stats::repeated(runs, || qwen235b::benchmark_producer_consumer(50, 50, 5, 100000, *state))).collect::<Vec<_>>(),
+            sw_cases.iter().map(|(mix, state)| stats::repeated(runs, || qwen235b::benchmark_shared_workload(100, 10000, *mix, 100000, *state, record.as_deref()))).collect::<Vec<_>>(),
+            states.iter().map(|state| stats::repeated(runs, || qwen235b::benchmark_io_simulation(100, 5, 100000, *state))).collect::<Vec<_>>(),
+            stats::repeated(runs, || qwen235b::benchmark_eviction_strategy(100, 200)),
+            stats::repeated(runs, || qwen235b::benchmark_ttl_operations(100, 100, 10000)),
+            stats::repeated(runs, || qwen235b::benchmark_burst_traffic(50, 5, 100000)),
+            stats::repeated(runs, || qwen235b::benchmark_ttl_churn(4, 3, 1_000_000)),
+            states.iter().map(|state| stats::repeated(runs, || qwen235b::benchmark_loader_latency(32, 3, 100000, LOADER_KEYS, LOADER_TTL_MS, loader, *state))).collect::<Vec<_>>(),
         ),
         "qwen435b" => (
-            qwen435b::benchmark_producer_consumer(50, 50, 5),
-            qwen435b::benchmark_shared_workload(100, 10000),
-            qwen435b::benchmark_io_simulation(100, 5),
-            qwen435b::benchmark_eviction_strategy(100, 200),
-            qwen435b::benchmark_ttl_operations(100, 100),
+            states.iter().map(|state| stats::repeated(runs, || qwen435b::benchmark_producer_consumer(50, 50, 5, 100000, *state))).collect::<Vec<_>>(),
+            sw_cases.iter().map(|(mix, state)| stats::repeated(runs, || qwen435b::benchmark_shared_workload(100, 10000, *mix, 100000, *state, record.as_deref()))).collect::<Vec<_>>(),
+            states.iter().map(|state| stats::repeated(runs, || qwen435b::benchmark_io_simulation(100, 5, 100000, *state))).collect::<Vec<_>>(),
+            stats::repeated(runs, || qwen435b::benchmark_eviction_strategy(100, 200)),
+            stats::repeated(runs, || qwen435b::benchmark_ttl_operations(100, 100, 10000)),
+            stats::repeated(runs, || qwen435b::benchmark_burst_traffic(50, 5, 100000)),
+            stats::repeated(runs, || qwen435b::benchmark_ttl_churn(4, 3, 1_000_000)),
+            states.iter().map(|state| stats::repeated(runs, || qwen435b::benchmark_loader_latency(32, 3, 100000, LOADER_KEYS, LOADER_TTL_MS, loader, *state))).collect::<Vec<_>>(),
+        ),
+        "glm45" => (
+            states.iter().map(|state| stats::repeated(runs, || glm45::benchmark_producer_consumer(50, 50, 5, 100000, *state))).collect::<Vec<_>>(),
+            sw_cases.iter().map(|(mix, state)| stats::repeated(runs, || glm45::benchmark_shared_workload(100, 10000, *mix, 100000, *state, record.as_deref()))).collect::<Vec<_>>(),
+            states.iter().map(|state| stats::repeated(runs, || glm45::benchmark_io_simulation(100, 5, 100000, *state))).collect::<Vec<_>>(),
+            stats::repeated(runs, || glm45::benchmark_eviction_strategy(100, 200)),
+            stats::repeated(runs, || glm45::benchmark_ttl_operations(100, 100, 10000)),
+            stats::repeated(runs, || glm45::benchmark_burst_traffic(50, 5, 100000)),
+            stats::repeated(runs, || glm45::benchmark_ttl_churn(4, 3, 1_000_000)),
+            states.iter().map(|state| stats::repeated(runs, || glm45::benchmark_loader_latency(32, 3, 100000, LOADER_KEYS, LOADER_TTL_MS, loader, *state))).collect::<Vec<_>>(),
         ),
         _ => panic!("Unknown module"),
     };
     
     // Test 1: Producer-Consumer Pattern
-    println!("\n1. Producer-Consumer Pattern");
-    println!("{}", "-".repeat(40));
-    all_results.insert("producer_consumer".to_string(), pc_result.clone());
-    println!("\nResults:");
-    for (key, value) in &pc_result {
-        println!("  {}: {}", key, value);
+    for (state, pc_result) in states.iter().zip(pc_results) {
+        println!("\n1. Producer-Consumer Pattern ({})", state);
+        println!("{}", "-".repeat(40));
+        println!("\nResults:");
+        for (key, value) in &pc_result {
+            println!("  {}: {}", key, value);
+        }
+        all_results.insert(state.result_key("producer_consumer", states.len()), pc_result);
     }
     
-    // Test 2: Shared Workload
-    println!("\n2. Shared Workload (Fair Comparison)");
-    println!("{}", "-".repeat(40));
-    all_results.insert("shared_workload".to_string(), sw_result.clone());
-    println!("\nResults:");
-    for (key, value) in &sw_result {
-        println!("  {}: {}", key, value);
+    // Test 2: Shared Workload, once per workload mix
+    for ((mix, state), sw_result) in sw_cases.iter().zip(sw_results) {
+        println!("\n2. Shared Workload (Fair Comparison, {}, {})", mix, state);
+        println!("{}", "-".repeat(40));
+        println!("\nResults:");
+        for (key, value) in &sw_result {
+            println!("  {}: {}", key, value);
+        }
+        all_results.insert(state.result_key(&mix.result_key("shared_workload", mixes.len()), states.len()), sw_result);
     }
     
     // Test 3: I/O Simulation
-    println!("\n3. I/O-Bound Simulation");
-    println!("{}", "-".repeat(40));
-    all_results.insert("io_simulation".to_string(), io_result.clone());
-    println!("\nResults:");
-    for (key, value) in &io_result {
-        println!("  {}: {}", key, value);
+    for (state, io_result) in states.iter().zip(io_results) {
+        println!("\n3. I/O-Bound Simulation ({})", state);
+        println!("{}", "-".repeat(40));
+        println!("\nResults:");
+        for (key, value) in &io_result {
+            println!("  {}: {}", key, value);
+        }
+        all_results.insert(state.result_key("io_simulation", states.len()), io_result);
     }
     
     // Test 4: Eviction Strategy
@@ -545,48 +1324,259 @@ fn run_all_benchmarks(name: &str, module: &str) -> HashMap<String, HashMap<Strin
         println!("  {}: {}", key, value);
     }
     
+    // Test 6: Burst Traffic
+    println!("\n6. Burst Traffic");
+    println!("{}", "-".repeat(40));
+    all_results.insert("burst_traffic".to_string(), burst_result.clone());
+    println!("\nResults:");
+    for (key, value) in &burst_result {
+        println!("  {}: {}", key, value);
+    }
+    
+    // Test 7: TTL Churn
+    println!("\n7. TTL Churn");
+    println!("{}", "-".repeat(40));
+    all_results.insert("ttl_churn".to_string(), churn_result.clone());
+    println!("\nResults:");
+    for (key, value) in &churn_result {
+        println!("  {}: {}", key, value);
+    }
+    
+    // Test 8: Loader Latency
+    for (state, loader_result) in states.iter().zip(loader_results) {
+        println!("\n8. Loader Latency ({})", state);
+        println!("{}", "-".repeat(40));
+        println!("\nResults:");
+        for (key, value) in &loader_result {
+            println!("  {}: {}", key, value);
+        }
+        all_results.insert(state.result_key("loader_latency", states.len()), loader_result);
+    }
+    
     all_results
 }
 
+// Throughput matrix over SCALING_CAPACITIES (rows) x SCALING_THREADS (columns)
+fn run_scaling_sweep(name: &str, module: &str, write_ratio: f64) -> ScalingMatrix {
+    println!("\n{}", "=".repeat(60));
+    println!("Scaling sweep: {} Rust Implementation ({:.0}% writes)", name, write_ratio * 100.0);
+    println!("{}", "=".repeat(60));
+    
+    type CellFn = fn(usize, usize, f64) -> BenchmarkResult;
+    let cell: CellFn = match module {
+        "qwen30b" => qwen30b::benchmark_scaling_cell,
+        "qwen235b" => qwen235b::benchmark_scaling_cell,
+        "qwen435b" => qwen435b::benchmark_scaling_cell,
+        "glm45" => glm45::benchmark_scaling_cell,
+        _ => panic!("Unknown module"),
+    };
+    
+    print!("{:>10}", "capacity");
+    for threads in SCALING_THREADS {
+        print!("{:>10}", format!("{}t", threads));
+    }
+    println!("   (kops/s)");
+    
+    let mut matrix = Vec::new();
+    let mut cells = Vec::new();
+    for capacity in SCALING_CAPACITIES {
+        print!("{:>10}", capacity);
+        let mut row = Vec::new();
+        for threads in SCALING_THREADS {
+            let result = cell(threads, capacity, write_ratio);
+            let ops_per_second = result["ops_per_second"].as_f64().unwrap_or(0.0);
+            print!("{:>10.1}", ops_per_second / 1000.0);
+            std::io::Write::flush(&mut std::io::stdout()).unwrap();
+            row.push(ops_per_second.round());
+            cells.push(result);
+        }
+        println!();
+        matrix.push(row);
+    }
+    
+    ScalingMatrix {
+        threads: SCALING_THREADS.to_vec(),
+        capacities: SCALING_CAPACITIES.to_vec(),
+        write_ratio,
+        cell_duration_ms: SCALING_CELL_MS,
+        ops_per_second: matrix,
+        cells,
+    }
+}
+
+// Replay a recorded workload against every implementation and save one report each
+fn run_replay(
+    workload: Workload,
+    path: &str,
+    implementations: &[(&str, &str)],
+    runs: usize,
+    baseline: Option<&regression::Baseline>,
+) -> Vec<regression::Regression> {
+    let workload = Arc::new(workload);
+    println!("\nReplay: {} ({} operations, {} workers, capacity {})", path, workload.len(), workload.workers.len(), workload.capacity);
+    
+    let mut regressions = Vec::new();
+    for (name, module) in implementations {
+        println!("\n{}", "=".repeat(60));
+        println!("Replaying on: {} Rust Implementation", name);
+        println!("{}", "=".repeat(60));
+        
+        let run: fn(Arc<Workload>) -> BenchmarkResult = match *module {
+            "qwen30b" => qwen30b::benchmark_replay,
+            "qwen235b" => qwen235b::benchmark_replay,
+            "qwen435b" => qwen435b::benchmark_replay,
+            "glm45" => glm45::benchmark_replay,
+            _ => panic!("Unknown module"),
+        };
+        let result = stats::repeated(runs, || run(Arc::clone(&workload)));
+        println!("\nResults:");
+        for (key, value) in &result {
+            println!("  {}: {}", key, value);
+        }
+        
+        let mut report = BenchmarkReport::new(format!("Rust {} (Replay)", name)).note("workload", path);
+        report.cache_size = Some(workload.capacity);
+        report.benchmarks.insert("replay".to_string(), result);
+        let filename = report.save(name, "replay");
+        println!("\nResults saved to: {}", filename);
+        if let Some(baseline) = baseline {
+            regressions.extend(baseline.compare(&report));
+        }
+    }
+    regressions
+}
+
+// Run a scenario file's benchmarks in order and save one report per implementation
+fn run_scenario(
+    scenario: &Scenario,
+    path: &str,
+    implementations: &[(&str, &str)],
+    states: &[CacheState],
+    runs: usize,
+    baseline: Option<&regression::Baseline>,
+) -> Vec<regression::Regression> {
+    let title = scenario.name.as_deref().unwrap_or(path);
+    let runs = scenario.runs.unwrap_or(runs);
+    println!("\nScenario: {} ({} benchmarks)", title, scenario.benchmarks.len());
+    
+    let mut reports: Vec<BenchmarkReport> = implementations.iter()
+        .map(|(name, _)| BenchmarkReport::new(format!("Rust {} (Scenario)", name)).note("scenario", title))
+        .collect();
+    
+    for (i, benchmark) in scenario.benchmarks.iter().enumerate() {
+        let states = if benchmark.kind.uses_cache_state() { states } else { &[CacheState::Cold][..] };
+        for ((name, module), report) in implementations.iter().zip(&mut reports) {
+            if !benchmark.runs_on(name) {
+                continue;
+            }
+            for state in states {
+                println!("\n{}. {}: {} ({})", i + 1, benchmark.result_key(), name, state);
+                println!("{}", "-".repeat(40));
+                
+                let run: fn(&ScenarioBenchmark, CacheState) -> BenchmarkResult = match *module {
+                    "qwen30b" => qwen30b::benchmark_scenario,
+                    "qwen235b" => qwen235b::benchmark_scenario,
+                    "qwen435b" => qwen435b::benchmark_scenario,
+                    "glm45" => glm45::benchmark_scenario,
+                    _ => panic!("Unknown module"),
+                };
+                let result = stats::repeated(runs, || run(benchmark, *state));
+                println!("\nResults:");
+                for (key, value) in &result {
+                    println!("  {}: {}", key, value);
+                }
+                report.benchmarks.insert(state.result_key(benchmark.result_key(), states.len()), result);
+            }
+        }
+    }
+    
+    let mut regressions = Vec::new();
+    for ((name, _), report) in implementations.iter().zip(&reports) {
+        if report.benchmarks.is_empty() {
+            continue;
+        }
+        let filename = report.save(name, "scenario");
+        println!("\nResults saved to: {}", filename);
+        if let Some(baseline) = baseline {
+            regressions.extend(baseline.compare(report));
+        }
+    }
+    regressions
+}
+
 fn main() {
     println!("{}", "=".repeat(60));
     println!("Fair Concurrent Benchmark Suite");
     println!("Rust Implementation with True Parallelism");
     println!("{}", "=".repeat(60));
     
-    // Test all three implementations
+    let args = args_from_env();
+    let baseline = baseline_from_args(&args);
+    let mut regressions = Vec::new();
+    let mixes = args.mixes;
+    
+    // Test all four implementations
     let implementations = vec![
         ("Qwen30B", "qwen30b"),
         ("Qwen235B", "qwen235b"),
         ("Qwen435B", "qwen435b"),
+        ("GLM45", "glm45"),
     ];
     
+    if let Some(path) = &args.scenario {
+        let names: Vec<&str> = implementations.iter().map(|(name, _)| *name).collect();
+        let scenario = Scenario::load(path, &names).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        });
+        let regressions = run_scenario(&scenario, path, &implementations, &args.cache_states, args.runs, baseline.as_ref());
+        regression::finish(&regressions);
+        return;
+    }
+    
+    if let Some(path) = &args.replay {
+        let workload = Workload::load(path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        });
+        let regressions = run_replay(workload, path, &implementations, args.runs, baseline.as_ref());
+        regression::finish(&regressions);
+        return;
+    }
+    
+    if args.scaling {
+        for (name, module) in implementations {
+            let mut report = BenchmarkReport::new(format!("Rust {} (Scaling Sweep)", name));
+            report.scaling = Some(run_scaling_sweep(name, module, mixes[0].write_ratio));
+            
+            let filename = report.save(name, "scaling");
+            println!("Results saved to: {}", filename);
+        }
+        return;
+    }
+    
     for (name, module) in implementations {
-        let all_results = run_all_benchmarks(name, module);
+        let mut report = BenchmarkReport::new(format!("Rust {} (Fair Concurrent)", name))
+            .note("parallelism", "Rust has true parallelism with lock contention")
+            .note("io_benefit", "Threading provides significant speedup for I/O operations")
+            .note("comparison", "These metrics are directly comparable across languages");
+        report.cache_size = Some(100000);
+        report.benchmarks = run_all_benchmarks(name, module, &mixes, &args.cache_states, args.runs, args.loader, args.record.as_deref());
         
         // Save results
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-        let output = serde_json::json!({
-            "implementation": format!("Rust {} (Fair Concurrent)", name),
-            "timestamp": timestamp,
-            "cache_size": 100000,
-            "benchmarks": all_results,
-            "notes": {
-                "parallelism": "Rust has true parallelism with lock contention",
-                "io_benefit": "Threading provides significant speedup for I/O operations",
-                "comparison": "These metrics are directly comparable across languages"
-            }
-        });
-        
-        let filename = format!("results/rust_{}_fair_concurrent_{}.json", name.to_lowercase(), timestamp);
-        std::fs::create_dir_all("results").unwrap();
-        std::fs::write(&filename, serde_json::to_string_pretty(&output).unwrap()).unwrap();
+        let filename = report.save(name, "fair_concurrent");
         
         println!("\n{}", "=".repeat(60));
         println!("Results saved to: {}", filename);
+        
+        if let Some(baseline) = &baseline {
+            regressions.extend(baseline.compare(&report));
+        }
     }
     
     println!("\n{}", "=".repeat(60));
     println!("All Rust benchmarks complete!");
     println!("{}", "=".repeat(60));
+    
+    regression::finish(&regressions);
 }
\ No newline at end of file
@@ -1,19 +1,17 @@
 use std::sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}};
 use std::thread;
 use std::time::{Duration, Instant};
-use std::collections::HashMap;
 use threadpool::ThreadPool;
 use crossbeam::channel::{unbounded};
 use rand::Rng;
-use serde_json;
+use cache_benchmarks::{Fairness, WorkloadMix, args_from_env, baseline_from_args, regression, stats, watchdog};
+use cache_benchmarks::report::{BenchmarkReport, BenchmarkResult, Metric};
 
 // Import the cache implementations with concrete types
 type Cache30B = qwen30b_cache::SmartCache<String, String>;
-type Cache235B = qwen235b_cache::SmartCache<String, String>;
-type Cache435B = qwen435b_cache::SmartCache<String, String>;
 
 // Producer-Consumer benchmark for each cache type
-fn benchmark_producer_consumer_30b(num_producers: usize, num_consumers: usize, duration_secs: u64) -> HashMap<String, serde_json::Value> {
+fn benchmark_producer_consumer_30b(num_producers: usize, num_consumers: usize, duration_secs: u64) -> BenchmarkResult {
     let cache = Arc::new(Cache30B::new(100000));
     let stop_flag = Arc::new(AtomicBool::new(false));
     
@@ -37,12 +35,13 @@ fn benchmark_producer_consumer_30b(num_producers: usize, num_consumers: usize, d
     let pool = ThreadPool::new(num_producers + num_consumers);
     
     // Start producers
-    for i in 0..num_producers {
+    for (i, count) in producer_counts.iter().enumerate() {
         let cache = Arc::clone(&cache);
         let stop = Arc::clone(&stop_flag);
-        let count = Arc::clone(&producer_counts[i]);
+        let count = Arc::clone(count);
         
         pool.execute(move || {
+            let progress = watchdog::worker(format!("producer {}", i));
             let mut local_count = 0;
             let mut rng = rand::thread_rng();
             
@@ -52,7 +51,9 @@ fn benchmark_producer_consumer_30b(num_producers: usize, num_consumers: usize, d
                     std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos());
                 
+                let tracked = progress.enter("PUT", &key);
                 cache.put(key, value, None, rng.gen_range(1..=10));
+                tracked.finish();
                 local_count += 1;
                 count.store(local_count, Ordering::Relaxed);
                 
@@ -69,6 +70,7 @@ fn benchmark_producer_consumer_30b(num_producers: usize, num_consumers: usize, d
         let misses = Arc::clone(&consumer_misses[i]);
         
         pool.execute(move || {
+            let progress = watchdog::worker(format!("consumer {}", i));
             let mut rng = rand::thread_rng();
             
             while !stop.load(Ordering::Relaxed) {
@@ -76,7 +78,10 @@ fn benchmark_producer_consumer_30b(num_producers: usize, num_consumers: usize, d
                 let item_id = rng.gen_range(0..1000);
                 let key = format!("p{}_item_{}", producer_id, item_id);
                 
-                if cache.get(&key).is_some() {
+                let tracked = progress.enter("GET", &key);
+                let hit = cache.get(&key).is_some();
+                tracked.finish();
+                if hit {
                     hits.fetch_add(1, Ordering::Relaxed);
                 } else {
                     misses.fetch_add(1, Ordering::Relaxed);
@@ -116,23 +121,31 @@ fn benchmark_producer_consumer_30b(num_producers: usize, num_consumers: usize, d
         0.0 
     };
     
-    let mut result = HashMap::new();
-    result.insert("duration".to_string(), serde_json::json!(elapsed.as_secs_f64()));
-    result.insert("total_puts".to_string(), serde_json::json!(total_puts));
-    result.insert("total_gets".to_string(), serde_json::json!(total_gets));
-    result.insert("total_operations".to_string(), serde_json::json!(total_puts + total_gets));
-    result.insert("puts_per_second".to_string(), serde_json::json!(format!("{:.2}", total_puts as f64 / elapsed.as_secs_f64())));
-    result.insert("gets_per_second".to_string(), serde_json::json!(format!("{:.2}", total_gets as f64 / elapsed.as_secs_f64())));
-    result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", (total_puts + total_gets) as f64 / elapsed.as_secs_f64())));
-    result.insert("hit_rate".to_string(), serde_json::json!(format!("{:.1}%", hit_rate * 100.0)));
-    result.insert("total_hits".to_string(), serde_json::json!(total_hits));
-    result.insert("total_misses".to_string(), serde_json::json!(total_misses));
+    let mut result = BenchmarkResult::new();
+    result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+    result.insert("total_puts".to_string(), Metric::from(total_puts));
+    result.insert("total_gets".to_string(), Metric::from(total_gets));
+    result.insert("total_operations".to_string(), Metric::from(total_puts + total_gets));
+    result.insert("puts_per_second".to_string(), Metric::from(total_puts as f64 / elapsed.as_secs_f64()));
+    result.insert("gets_per_second".to_string(), Metric::from(total_gets as f64 / elapsed.as_secs_f64()));
+    result.insert("ops_per_second".to_string(), Metric::from((total_puts + total_gets) as f64 / elapsed.as_secs_f64()));
+    result.insert("hit_rate_pct".to_string(), Metric::from(hit_rate * 100.0));
+    result.insert("total_hits".to_string(), Metric::from(total_hits));
+    result.insert("total_misses".to_string(), Metric::from(total_misses));
+    
+    // Per-thread throughput spread
+    let producer_ops: Vec<usize> = producer_counts.iter().map(|c| c.load(Ordering::Relaxed)).collect();
+    let consumer_ops: Vec<usize> = consumer_hits.iter().zip(&consumer_misses)
+        .map(|(h, m)| h.load(Ordering::Relaxed) + m.load(Ordering::Relaxed))
+        .collect();
+    result.insert("producer_fairness".to_string(), Fairness::from_counts(&producer_ops, elapsed).to_metric());
+    result.insert("consumer_fairness".to_string(), Fairness::from_counts(&consumer_ops, elapsed).to_metric());
     
     result
 }
 
 // Shared workload benchmark
-fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> HashMap<String, serde_json::Value> {
+fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize, mix: WorkloadMix) -> BenchmarkResult {
     let cache = Arc::new(Cache30B::new(100000));
     
     // Create work queue
@@ -141,15 +154,15 @@ fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> H
     
     // Fill with mixed operations
     for i in 0..num_operations {
-        if rng.gen::<f64>() < 0.7 { // 70% writes
+        if rng.gen::<f64>() < mix.write_ratio { // writes
             tx.send(("PUT", 
-                    format!("key_{}", i % 1000),
+                    format!("key_{}", i % mix.key_space),
                     format!("value_{}", i),
                     rng.gen_range(1..=10)))
                 .unwrap();
-        } else { // 30% reads
+        } else { // reads
             tx.send(("GET", 
-                    format!("key_{}", rng.gen_range(0..1000)),
+                    format!("key_{}", rng.gen_range(0..mix.key_space)),
                     String::new(),
                     0))
                 .unwrap();
@@ -159,26 +172,30 @@ fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> H
     
     let operation_times = Arc::new(parking_lot::Mutex::new(Vec::new()));
     
-    println!("\nRunning Shared Workload benchmark ({} workers, {} operations)...", 
-            num_workers, num_operations);
+    println!("\nRunning Shared Workload benchmark ({} workers, {} operations, {})...", 
+            num_workers, num_operations, mix);
     
     let start = Instant::now();
     let pool = ThreadPool::new(num_workers);
     let (done_tx, done_rx) = unbounded();
     
     // Start workers
-    for _ in 0..num_workers {
+    for i in 0..num_workers {
         let cache = Arc::clone(&cache);
         let rx = rx.clone();
         let times = Arc::clone(&operation_times);
         let done = done_tx.clone();
         
         pool.execute(move || {
+            let progress = watchdog::worker(format!("shared worker {}", i));
             let mut local_times = Vec::new();
+            let mut local_ops = 0;
             
             while let Ok((op, key, value, priority)) = rx.recv() {
+                local_ops += 1;
                 let op_start = Instant::now();
                 
+                let tracked = progress.enter(op, &key);
                 match op {
                     "PUT" => {
                         cache.put(key, value, None, priority);
@@ -188,20 +205,19 @@ fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> H
                     },
                     _ => {}
                 }
+                tracked.finish();
                 
                 local_times.push(op_start.elapsed());
             }
             
             times.lock().extend(local_times);
-            done.send(()).unwrap();
+            done.send(local_ops).unwrap();
         });
     }
     
     drop(done_tx);
     // Wait for all workers to complete
-    for _ in 0..num_workers {
-        done_rx.recv().unwrap();
-    }
+    let worker_ops: Vec<usize> = (0..num_workers).map(|_| done_rx.recv().unwrap()).collect();
     
     let elapsed = start.elapsed();
     
@@ -220,19 +236,22 @@ fn benchmark_shared_workload_30b(num_workers: usize, num_operations: usize) -> H
         1.0
     };
     
-    let mut result = HashMap::new();
-    result.insert("duration".to_string(), serde_json::json!(format!("{:.3}", elapsed.as_secs_f64())));
-    result.insert("num_workers".to_string(), serde_json::json!(num_workers));
-    result.insert("total_operations".to_string(), serde_json::json!(num_operations));
-    result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", num_operations as f64 / elapsed.as_secs_f64())));
-    result.insert("avg_operation_time_ms".to_string(), serde_json::json!(format!("{:.3}", avg_op_time)));
-    result.insert("parallelism_factor".to_string(), serde_json::json!(format!("{:.2}x", parallelism_factor)));
+    let mut result = BenchmarkResult::new();
+    result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+    result.insert("num_workers".to_string(), Metric::from(num_workers));
+    result.insert("total_operations".to_string(), Metric::from(num_operations));
+    result.insert("write_ratio".to_string(), Metric::from(mix.write_ratio));
+    result.insert("key_space".to_string(), Metric::from(mix.key_space));
+    result.insert("worker_fairness".to_string(), Fairness::from_counts(&worker_ops, elapsed).to_metric());
+    result.insert("ops_per_second".to_string(), Metric::from(num_operations as f64 / elapsed.as_secs_f64()));
+    result.insert("avg_operation_time_ms".to_string(), Metric::from(avg_op_time));
+    result.insert("parallelism_factor".to_string(), Metric::from(parallelism_factor));
     
     result
 }
 
 // I/O simulation benchmark
-fn benchmark_io_simulation_30b(num_workers: usize, duration_secs: u64) -> HashMap<String, serde_json::Value> {
+fn benchmark_io_simulation_30b(num_workers: usize, duration_secs: u64) -> BenchmarkResult {
     let cache = Arc::new(Cache30B::new(100000));
     let stop_flag = Arc::new(AtomicBool::new(false));
     
@@ -248,12 +267,13 @@ fn benchmark_io_simulation_30b(num_workers: usize, duration_secs: u64) -> HashMa
     let pool = ThreadPool::new(num_workers);
     
     // Start workers
-    for i in 0..num_workers {
+    for (i, count) in operation_counts.iter().enumerate() {
         let cache = Arc::clone(&cache);
         let stop = Arc::clone(&stop_flag);
-        let count = Arc::clone(&operation_counts[i]);
+        let count = Arc::clone(count);
         
         pool.execute(move || {
+            let progress = watchdog::worker(format!("io worker {}", i));
             let mut local_count = 0;
             let mut rng = rand::thread_rng();
             
@@ -267,13 +287,18 @@ fn benchmark_io_simulation_30b(num_workers: usize, duration_secs: u64) -> HashMa
                                   .duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos());
                 
                 // Cache the result
+                let tracked = progress.enter("PUT", &key);
                 cache.put(key, value, None, 5);
+                tracked.finish();
                 
                 // Try to read some other worker's data
                 let other_worker = (i + rng.gen_range(1..num_workers)) % num_workers;
                 let other_key = format!("worker_{}_item_{}", other_worker, rng.gen_range(0..100));
                 
-                if cache.get(&other_key).is_some() {
+                let tracked = progress.enter("GET", &other_key);
+                let hit = cache.get(&other_key).is_some();
+                tracked.finish();
+                if hit {
                     // Simulate processing
                     thread::sleep(Duration::from_millis(1));
                 }
@@ -301,14 +326,14 @@ fn benchmark_io_simulation_30b(num_workers: usize, duration_secs: u64) -> HashMa
     let theoretical_sequential_time = total_operations as f64 * 0.006; // 6ms per op
     let speedup = theoretical_sequential_time / elapsed.as_secs_f64();
     
-    let mut result = HashMap::new();
-    result.insert("duration".to_string(), serde_json::json!(format!("{:.2}", elapsed.as_secs_f64())));
-    result.insert("num_workers".to_string(), serde_json::json!(num_workers));
-    result.insert("total_operations".to_string(), serde_json::json!(total_operations));
-    result.insert("ops_per_second".to_string(), serde_json::json!(format!("{:.2}", total_operations as f64 / elapsed.as_secs_f64())));
-    result.insert("ops_per_worker".to_string(), serde_json::json!(total_operations / num_workers));
-    result.insert("theoretical_sequential_time".to_string(), serde_json::json!(format!("{:.2}", theoretical_sequential_time)));
-    result.insert("speedup".to_string(), serde_json::json!(format!("{:.2}x", speedup)));
+    let mut result = BenchmarkResult::new();
+    result.insert("duration".to_string(), Metric::from(elapsed.as_secs_f64()));
+    result.insert("num_workers".to_string(), Metric::from(num_workers));
+    result.insert("total_operations".to_string(), Metric::from(total_operations));
+    result.insert("ops_per_second".to_string(), Metric::from(total_operations as f64 / elapsed.as_secs_f64()));
+    result.insert("ops_per_worker".to_string(), Metric::from(total_operations / num_workers));
+    result.insert("theoretical_sequential_time".to_string(), Metric::from(theoretical_sequential_time));
+    result.insert("speedup".to_string(), Metric::from(speedup));
     
     result
 }
@@ -322,65 +347,64 @@ fn main() {
     println!("Rust Implementation with True Parallelism");
     println!("{}", "=".repeat(60));
     
+    let args = args_from_env();
+    let baseline = baseline_from_args(&args);
+    let mixes = args.mixes;
+    
     // Test Qwen30B implementation
     println!("\n{}", "=".repeat(60));
     println!("Testing: Qwen30B Rust Implementation");
     println!("{}", "=".repeat(60));
     
-    let mut all_results = HashMap::new();
+    let mut report = BenchmarkReport::new("Rust Qwen30B (Fair Concurrent)")
+        .note("parallelism", "Rust has true parallelism with lock contention")
+        .note("io_benefit", "Threading provides significant speedup for I/O operations")
+        .note("comparison", "These metrics are directly comparable across languages");
+    report.cache_size = Some(100000);
     
     // Test 1: Producer-Consumer Pattern
     println!("\n1. Producer-Consumer Pattern");
     println!("{}", "-".repeat(40));
-    let pc_result = benchmark_producer_consumer_30b(50, 50, 5);
-    all_results.insert("producer_consumer", pc_result.clone());
+    let pc_result = stats::repeated(args.runs, || benchmark_producer_consumer_30b(50, 50, 5));
+    report.benchmarks.insert("producer_consumer".to_string(), pc_result.clone());
     println!("\nResults:");
     for (key, value) in &pc_result {
         println!("  {}: {}", key, value);
     }
     
-    // Test 2: Shared Workload
-    println!("\n2. Shared Workload (Fair Comparison)");
-    println!("{}", "-".repeat(40));
-    let sw_result = benchmark_shared_workload_30b(100, 10000);
-    all_results.insert("shared_workload", sw_result.clone());
-    println!("\nResults:");
-    for (key, value) in &sw_result {
-        println!("  {}: {}", key, value);
+    // Test 2: Shared Workload, once per workload mix
+    for mix in &mixes {
+        println!("\n2. Shared Workload (Fair Comparison, {})", mix);
+        println!("{}", "-".repeat(40));
+        let sw_result = stats::repeated(args.runs, || benchmark_shared_workload_30b(100, 10000, *mix));
+        report.benchmarks.insert(mix.result_key("shared_workload", mixes.len()), sw_result.clone());
+        println!("\nResults:");
+        for (key, value) in &sw_result {
+            println!("  {}: {}", key, value);
+        }
     }
     
     // Test 3: I/O Simulation
     println!("\n3. I/O-Bound Simulation");
     println!("{}", "-".repeat(40));
-    let io_result = benchmark_io_simulation_30b(100, 5);
-    all_results.insert("io_simulation", io_result.clone());
+    let io_result = stats::repeated(args.runs, || benchmark_io_simulation_30b(100, 5));
+    report.benchmarks.insert("io_simulation".to_string(), io_result.clone());
     println!("\nResults:");
     for (key, value) in &io_result {
         println!("  {}: {}", key, value);
     }
     
     // Save results
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let output = serde_json::json!({
-        "implementation": "Rust Qwen30B (Fair Concurrent)",
-        "timestamp": timestamp,
-        "cache_size": 100000,
-        "benchmarks": all_results,
-        "notes": {
-            "parallelism": "Rust has true parallelism with lock contention",
-            "io_benefit": "Threading provides significant speedup for I/O operations",
-            "comparison": "These metrics are directly comparable across languages"
-        }
-    });
-    
-    let filename = format!("results/rust_qwen30b_fair_concurrent_{}.json", timestamp);
-    std::fs::create_dir_all("results").unwrap();
-    std::fs::write(&filename, serde_json::to_string_pretty(&output).unwrap()).unwrap();
+    let filename = report.save("Qwen30B", "fair_concurrent");
     
     println!("\n{}", "=".repeat(60));
     println!("Results saved to: {}", filename);
     println!("{}", "=".repeat(60));
     
+    if let Some(baseline) = &baseline {
+        regression::finish(&baseline.compare(&report));
+    }
+    
     // TODO: Add similar tests for Qwen235B and Qwen435B
     println!("\nNote: For complete results, run tests for Qwen235B and Qwen435B as well");
 }
\ No newline at end of file
@@ -0,0 +1,161 @@
+// What NUMA placement buys Qwen435B: with numa_placement on, worker threads bound
+// to one node run a read-mostly mix over keys whose shards live on a given node,
+// for every pair of nodes. The diagonal is node-local access, the rest cross-node.
+// On a single-node machine only the local case exists, and the report says so.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use rand::Rng;
+use cache_benchmarks::{args_from_env, baseline_from_args, regression, stats, watchdog};
+use cache_benchmarks::cpu::CpuTimer;
+use cache_benchmarks::report::{BenchmarkReport, BenchmarkResult, Metric};
+use qwen435b_cache::numa;
+
+type Cache435B = qwen435b_cache::SmartCache<String, String>;
+
+const KEYS_PER_NODE: usize = 100_000;
+const SHARDS: usize = 64;
+const DURATION_MS: u64 = 2000;
+const WRITE_RATIO: f64 = 0.1;
+// Workers per node, at most one per CPU of the node
+const MAX_THREADS_PER_NODE: usize = 8;
+
+// `keys_per_node` keys for each node, chosen by where their shard lives
+fn keys_by_node(cache: &Cache435B, nodes: usize, keys_per_node: usize) -> Vec<Vec<String>> {
+    let mut keys = vec![Vec::with_capacity(keys_per_node); nodes];
+    for i in 0.. {
+        if keys.iter().all(|node_keys| node_keys.len() >= keys_per_node) {
+            break;
+        }
+        let key = format!("key_{}", i);
+        let node = cache.node_of_key(&key).expect("numa_placement is on");
+        if keys[node].len() < keys_per_node {
+            keys[node].push(key);
+        }
+    }
+    keys
+}
+
+fn benchmark_pair(cache: &Arc<Cache435B>, thread_node: usize, keys: &Arc<Vec<String>>) -> BenchmarkResult {
+    let layout = cache.numa_layout().expect("numa_placement is on");
+    let node = layout.topology().nodes()[thread_node].clone();
+    let threads = node.cpus.len().clamp(1, MAX_THREADS_PER_NODE);
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let start = Instant::now();
+    let cpu = CpuTimer::start();
+    let handles: Vec<_> = (0..threads).map(|i| {
+        let cache = Arc::clone(cache);
+        let keys = Arc::clone(keys);
+        let stop = Arc::clone(&stop);
+        let node = node.clone();
+        thread::spawn(move || {
+            let bound = numa::bind_current_thread(&node);
+            let progress = watchdog::worker(format!("numa worker {}.{}", node.id, i));
+            let mut rng = rand::thread_rng();
+            let mut ops = 0usize;
+            while !stop.load(Ordering::Relaxed) {
+                let key = &keys[rng.gen_range(0..keys.len())];
+                if rng.gen::<f64>() < WRITE_RATIO {
+                    let tracked = progress.enter("PUT", key);
+                    cache.put(key.clone(), format!("value_{}", ops), None, 5);
+                    tracked.finish();
+                } else {
+                    let tracked = progress.enter("GET", key);
+                    let _ = cache.get(key);
+                    tracked.finish();
+                }
+                ops += 1;
+            }
+            (ops, bound)
+        })
+    }).collect();
+
+    thread::sleep(Duration::from_millis(DURATION_MS));
+    stop.store(true, Ordering::Relaxed);
+    let outcomes: Vec<(usize, bool)> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    let elapsed = start.elapsed();
+    let cpu = cpu.stop();
+
+    let total_operations: usize = outcomes.iter().map(|(ops, _)| ops).sum();
+    let mut result = BenchmarkResult::new();
+    result.insert("threads".to_string(), Metric::from(threads));
+    result.insert("threads_bound".to_string(), Metric::from(outcomes.iter().filter(|(_, bound)| *bound).count()));
+    result.insert("total_operations".to_string(), Metric::from(total_operations));
+    result.insert("ops_per_second".to_string(), Metric::from(total_operations as f64 / elapsed.as_secs_f64()));
+    result.insert("avg_operation_time_ns".to_string(), Metric::from(elapsed.as_nanos() as f64 * threads as f64 / total_operations.max(1) as f64));
+    cpu.insert_metrics(&mut result, total_operations);
+    result
+}
+
+fn main() {
+    println!("{}", "=".repeat(60));
+    println!("NUMA Locality Benchmark (Qwen435B, numa_placement)");
+    println!("{}", "=".repeat(60));
+
+    let args = args_from_env();
+    let baseline = baseline_from_args(&args);
+
+    let config = qwen435b_cache::CacheConfig {
+        max_capacity: KEYS_PER_NODE * 2 * numa::NumaTopology::detect().len(),
+        shard_amount: SHARDS,
+        numa_placement: true,
+        ..Default::default()
+    };
+    let cache = Arc::new(Cache435B::with_config(config));
+    let layout = cache.numa_layout().expect("numa_placement is on").clone();
+    let nodes = layout.topology().len();
+    println!("\n{} node(s), {} of {} shards placed", nodes, layout.placed_shards(), SHARDS);
+
+    let keys: Vec<Arc<Vec<String>>> = keys_by_node(&cache, nodes, KEYS_PER_NODE).into_iter().map(Arc::new).collect();
+    for key in keys.iter().flat_map(|node_keys| node_keys.iter()) {
+        cache.put(key.clone(), "value".to_string(), None, 5);
+    }
+
+    let mut report = BenchmarkReport::new("Rust Qwen435B (NUMA Locality)")
+        .note("nodes", &nodes.to_string())
+        .note("placed_shards", &format!("{} of {}", layout.placed_shards(), SHARDS));
+    if nodes == 1 {
+        report = report.note("cross_node", "one NUMA node: there is no cross-node pair to compare");
+    }
+    report.cache_size = Some(cache.len());
+
+    let (mut local, mut remote) = (Vec::new(), Vec::new());
+    for thread_node in 0..nodes {
+        for (memory_node, node_keys) in keys.iter().enumerate() {
+            let kind = if thread_node == memory_node { "node-local" } else { "cross-node" };
+            println!("\nThreads on node {}, shards on node {} ({})", thread_node, memory_node, kind);
+            println!("{}", "-".repeat(40));
+
+            let result = stats::repeated(args.runs, || benchmark_pair(&cache, thread_node, node_keys));
+            for (key, value) in &result {
+                println!("  {}: {}", key, value);
+            }
+            let ops_per_second = result["ops_per_second"].as_f64().unwrap_or(0.0);
+            if thread_node == memory_node { local.push(ops_per_second) } else { remote.push(ops_per_second) }
+            report.benchmarks.insert(format!("threads_node{}_shards_node{}", thread_node, memory_node), result);
+        }
+    }
+
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len().max(1) as f64;
+    let mut summary = BenchmarkResult::new();
+    summary.insert("node_local_ops_per_second".to_string(), Metric::from(mean(&local)));
+    if !remote.is_empty() {
+        summary.insert("cross_node_ops_per_second".to_string(), Metric::from(mean(&remote)));
+        // How much slower a cross-node access is than a local one
+        summary.insert("cross_node_penalty_pct".to_string(), Metric::from((1.0 - mean(&remote) / mean(&local)) * 100.0));
+    }
+    println!("\nSummary:");
+    for (key, value) in &summary {
+        println!("  {}: {}", key, value);
+    }
+    report.benchmarks.insert("summary".to_string(), summary);
+
+    let filename = report.save("Qwen435B", "numa");
+    println!("\nResults saved to: {}", filename);
+
+    let regressions = baseline.map(|baseline| baseline.compare(&report)).unwrap_or_default();
+    regression::finish(&regressions);
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use cache_benchmarks::report::{BenchmarkReport, Metric};
+
+// Charts from saved results: throughput vs threads and hit rate vs capacity from
+// scaling sweeps (--scaling), and a PUT latency CDF from the TTL churn benchmark of
+// full suite runs. Every chart has one line per implementation, so pass one file per
+// implementation to compare them.
+
+const USAGE: &str = "usage: plot_results [--format png|svg] [--out DIR] RESULTS.json...";
+const SIZE: (u32, u32) = (1000, 650);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scale {
+    Log2,
+    Log10,
+}
+
+impl Scale {
+    fn forward(self, value: f64) -> f64 {
+        match self {
+            Scale::Log2 => value.max(f64::MIN_POSITIVE).log2(),
+            Scale::Log10 => value.max(f64::MIN_POSITIVE).log10(),
+        }
+    }
+    
+    fn inverse(self, value: f64) -> f64 {
+        match self {
+            Scale::Log2 => 2f64.powf(value),
+            Scale::Log10 => 10f64.powf(value),
+        }
+    }
+}
+
+struct Series {
+    label: String,
+    points: Vec<(f64, f64)>,
+}
+
+struct Chart {
+    file_stem: String,
+    title: String,
+    x_desc: &'static str,
+    y_desc: &'static str,
+    x_scale: Scale,
+    series: Vec<Series>,
+}
+
+fn short_name(implementation: &str) -> String {
+    // "Rust Qwen30B (Scaling Sweep)" -> "Qwen30B"
+    implementation.trim_start_matches("Rust ").split(" (").next().unwrap_or(implementation).to_string()
+}
+
+fn scaling_charts(reports: &[BenchmarkReport]) -> Vec<Chart> {
+    let sweeps: Vec<_> = reports.iter()
+        .filter_map(|report| report.scaling.as_ref().map(|sweep| (short_name(&report.implementation), sweep)))
+        .collect();
+    if sweeps.is_empty() {
+        return Vec::new();
+    }
+    
+    // One throughput chart per capacity any sweep covered
+    let mut by_capacity: BTreeMap<usize, Vec<Series>> = BTreeMap::new();
+    for (name, sweep) in &sweeps {
+        for (row, capacity) in sweep.capacities.iter().enumerate() {
+            let points = sweep.threads.iter().zip(&sweep.ops_per_second[row])
+                .map(|(threads, ops)| (*threads as f64, ops / 1000.0))
+                .collect();
+            by_capacity.entry(*capacity).or_default().push(Series { label: name.clone(), points });
+        }
+    }
+    let mut charts: Vec<Chart> = by_capacity.into_iter().map(|(capacity, series)| Chart {
+        file_stem: format!("throughput_vs_threads_cap{}", capacity),
+        title: format!("Throughput vs threads, capacity {}", capacity),
+        x_desc: "threads",
+        y_desc: "kops/s",
+        x_scale: Scale::Log2,
+        series,
+    }).collect();
+    
+    // Hit rate at each capacity, averaged over the thread counts
+    let series = sweeps.iter().map(|(name, sweep)| {
+        let points = sweep.capacities.iter().filter_map(|capacity| {
+            let rates: Vec<f64> = sweep.cells.iter()
+                .filter(|cell| cell.get("capacity").and_then(Metric::as_f64) == Some(*capacity as f64))
+                .filter_map(|cell| cell.get("hit_rate_pct").and_then(Metric::as_f64))
+                .collect();
+            (!rates.is_empty()).then(|| (*capacity as f64, rates.iter().sum::<f64>() / rates.len() as f64))
+        }).collect();
+        Series { label: name.clone(), points }
+    }).collect();
+    charts.push(Chart {
+        file_stem: "hit_rate_vs_capacity".to_string(),
+        title: "Hit rate vs capacity (key space = 2 x capacity)".to_string(),
+        x_desc: "capacity",
+        y_desc: "hit rate %",
+        x_scale: Scale::Log10,
+        series,
+    });
+    charts
+}
+
+// Only the recorded percentiles are saved, so the CDF is drawn through those
+fn latency_cdf_chart(reports: &[BenchmarkReport]) -> Option<Chart> {
+    const PERCENTILES: [(&str, f64); 4] = [
+        ("p50_put_latency_us", 0.5),
+        ("p99_put_latency_us", 0.99),
+        ("p999_put_latency_us", 0.999),
+        ("max_put_latency_us", 1.0),
+    ];
+    let series: Vec<Series> = reports.iter().filter_map(|report| {
+        let churn = report.benchmarks.get("ttl_churn")?;
+        let points = PERCENTILES.iter()
+            .map(|(metric, fraction)| churn.get(*metric).and_then(Metric::as_f64).map(|us| (us, *fraction)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Series { label: short_name(&report.implementation), points })
+    }).collect();
+    if series.is_empty() {
+        return None;
+    }
+    Some(Chart {
+        file_stem: "latency_cdf".to_string(),
+        title: "PUT latency CDF under TTL churn".to_string(),
+        x_desc: "latency (us)",
+        y_desc: "fraction of PUTs",
+        x_scale: Scale::Log10,
+        series,
+    })
+}
+
+fn render<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, chart: &Chart) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let points = || chart.series.iter().flat_map(|series| series.points.iter());
+    let (mut x_min, mut x_max) = points().map(|(x, _)| chart.x_scale.forward(*x))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), x| (lo.min(x), hi.max(x)));
+    if x_min >= x_max {
+        x_min -= 1.0;
+        x_max += 1.0;
+    }
+    let y_max = points().map(|(_, y)| *y).fold(0.0, f64::max).max(f64::EPSILON) * 1.05;
+    
+    let mut context = ChartBuilder::on(&root)
+        .caption(&chart.title, ("sans-serif", 26))
+        .margin(15)
+        .x_label_area_size(45)
+        .y_label_area_size(70)
+        .build_cartesian_2d(x_min..x_max, 0.0..y_max)?;
+    let x_scale = chart.x_scale;
+    let x_formatter = move |value: &f64| {
+        let value = x_scale.inverse(*value);
+        if value >= 10.0 { format!("{:.0}", value) } else { format!("{:.2}", value) }
+    };
+    context.configure_mesh()
+        .x_desc(chart.x_desc)
+        .y_desc(chart.y_desc)
+        .x_label_formatter(&x_formatter)
+        .draw()?;
+    
+    for (i, series) in chart.series.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        let points: Vec<(f64, f64)> = series.points.iter().map(|(x, y)| (chart.x_scale.forward(*x), *y)).collect();
+        context.draw_series(LineSeries::new(points.clone(), color.stroke_width(2)))?
+            .label(&series.label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+        context.draw_series(points.into_iter().map(|point| Circle::new(point, 4, color.filled())))?;
+    }
+    context.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .position(SeriesLabelPosition::UpperLeft)
+        .draw()?;
+    root.present()?;
+    Ok(())
+}
+
+fn draw(chart: &Chart, dir: &Path, format: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let path = dir.join(format!("{}.{}", chart.file_stem, format));
+    match format {
+        "svg" => render(SVGBackend::new(&path, SIZE).into_drawing_area(), chart)?,
+        _ => render(BitMapBackend::new(&path, SIZE).into_drawing_area(), chart)?,
+    }
+    Ok(path)
+}
+
+fn main() {
+    let mut format = "png".to_string();
+    let mut out = PathBuf::from("results/charts");
+    let mut files = Vec::new();
+    
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().as_deref() {
+                Some(value @ ("png" | "svg")) => format = value.to_string(),
+                _ => exit_with(&format!("--format takes png or svg\n{}", USAGE)),
+            },
+            "--out" => match args.next() {
+                Some(dir) => out = PathBuf::from(dir),
+                None => exit_with(&format!("--out needs a value\n{}", USAGE)),
+            },
+            _ if arg.starts_with("--") => exit_with(&format!("unknown argument {:?}\n{}", arg, USAGE)),
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        exit_with(USAGE);
+    }
+    
+    let reports: Vec<BenchmarkReport> = files.iter()
+        .map(|file| BenchmarkReport::load(file).unwrap_or_else(|err| exit_with(&err)))
+        .collect();
+    let mut charts = scaling_charts(&reports);
+    charts.extend(latency_cdf_chart(&reports));
+    if charts.is_empty() {
+        exit_with("nothing to plot: pass --scaling results or full suite results with a ttl_churn benchmark");
+    }
+    
+    std::fs::create_dir_all(&out).unwrap();
+    for chart in &charts {
+        match draw(chart, &out, &format) {
+            Ok(path) => println!("Wrote {}", path.display()),
+            Err(err) => exit_with(&format!("cannot draw {}: {}", chart.file_stem, err)),
+        }
+    }
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(2);
+}
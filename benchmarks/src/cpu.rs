@@ -0,0 +1,63 @@
+// Process CPU time over a benchmark's timed part. Wall-clock ops/sec hides what the
+// throughput costs: an implementation that spins, or keeps busy background threads,
+// can match another's ops/sec while burning three times the cores. CPU time is the
+// whole process's, so it includes the cache's own threads along with the benchmark's
+// bookkeeping, which is the same for every implementation.
+
+use crate::report::{BenchmarkResult, Metric};
+use std::time::{Duration, Instant};
+
+// Started next to a benchmark's wall clock
+pub struct CpuTimer {
+    wall: Instant,
+    cpu: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CpuUsage {
+    pub wall: Duration,
+    // None where the platform has no process CPU clock
+    pub cpu: Option<Duration>,
+}
+
+impl CpuTimer {
+    pub fn start() -> Self {
+        CpuTimer { wall: Instant::now(), cpu: process_cpu_time() }
+    }
+
+    pub fn stop(&self) -> CpuUsage {
+        let cpu = match (self.cpu, process_cpu_time()) {
+            (Some(start), Some(end)) => Some(end.saturating_sub(start)),
+            _ => None,
+        };
+        CpuUsage { wall: self.wall.elapsed(), cpu }
+    }
+}
+
+impl CpuUsage {
+    // Adds cpu_seconds, cores_busy (CPU time over wall time) and ops_per_cpu_second
+    // for `operations` done in the timed part; nothing without a CPU clock
+    pub fn insert_metrics(&self, result: &mut BenchmarkResult, operations: usize) {
+        let Some(cpu) = self.cpu else {
+            return;
+        };
+        let cpu_secs = cpu.as_secs_f64();
+        let ops_per_cpu_second = if cpu_secs > 0.0 { operations as f64 / cpu_secs } else { 0.0 };
+        result.insert("cpu_seconds".to_string(), Metric::from(cpu_secs));
+        result.insert("cores_busy".to_string(), Metric::from(cpu_secs / self.wall.as_secs_f64().max(f64::EPSILON)));
+        result.insert("ops_per_cpu_second".to_string(), Metric::from(ops_per_cpu_second));
+    }
+}
+
+#[cfg(unix)]
+fn process_cpu_time() -> Option<Duration> {
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // clock_gettime only writes the timespec it is given
+    let status = unsafe { libc::clock_gettime(libc::CLOCK_PROCESS_CPUTIME_ID, &mut time) };
+    (status == 0).then(|| Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+#[cfg(not(unix))]
+fn process_cpu_time() -> Option<Duration> {
+    None
+}
@@ -1 +1,321 @@
-// Empty lib file - benchmarks are in bin
\ No newline at end of file
+// Helpers shared by the benchmark binaries - the benchmarks themselves are in bin
+
+pub mod baselines;
+pub mod cpu;
+pub mod loader;
+pub mod regression;
+pub mod report;
+pub mod scenario;
+pub mod stats;
+pub mod watchdog;
+pub mod workload;
+
+use loader::LoaderLatency;
+use std::time::Duration;
+
+// How evenly throughput was spread over the worker threads of one run. An
+// implementation that starves half its threads can still post a great aggregate
+// ops/sec, so the concurrent benchmarks report this next to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fairness {
+    // Per-worker ops/sec
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    // 0 when every worker did the same amount of work, approaching 1 when one did all of it
+    pub gini: f64,
+    // Jain's index: 1 when perfectly even, 1/n when one worker did all of it
+    pub jain_index: f64,
+}
+
+impl Fairness {
+    pub fn from_counts(per_worker: &[usize], elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        let mut rates: Vec<f64> = per_worker.iter().map(|&ops| ops as f64 / secs).collect();
+        rates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let n = rates.len() as f64;
+        let sum: f64 = rates.iter().sum();
+        if rates.is_empty() || sum == 0.0 {
+            return Fairness { min: 0.0, max: 0.0, mean: 0.0, stddev: 0.0, gini: 0.0, jain_index: 1.0 };
+        }
+
+        let mean = sum / n;
+        let variance = rates.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n;
+        let sum_squares: f64 = rates.iter().map(|r| r * r).sum();
+        // Sorted ascending, so rank-weighted sum over the 1-based index
+        let weighted: f64 = rates.iter().enumerate().map(|(i, r)| (i + 1) as f64 * r).sum();
+
+        Fairness {
+            min: rates[0],
+            max: rates[rates.len() - 1],
+            mean,
+            stddev: variance.sqrt(),
+            gini: 2.0 * weighted / (n * sum) - (n + 1.0) / n,
+            jain_index: sum * sum / (n * sum_squares),
+        }
+    }
+
+    pub fn to_metric(&self) -> report::Metric {
+        report::Metric::Group(metrics! {
+            "min_ops_per_second" => self.min,
+            "max_ops_per_second" => self.max,
+            "mean_ops_per_second" => self.mean,
+            "stddev_ops_per_second" => self.stddev,
+            "gini" => self.gini,
+            "jain_index" => self.jain_index,
+        })
+    }
+}
+
+// Write/read split and key-space size of the shared-workload benchmarks. Write-heavy
+// workloads over a small key space flatter lock-based designs, so they can be swept.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkloadMix {
+    // Fraction of operations that are PUTs; the rest are GETs
+    pub write_ratio: f64,
+    pub key_space: usize,
+}
+
+impl Default for WorkloadMix {
+    fn default() -> Self {
+        WorkloadMix { write_ratio: 0.7, key_space: 1000 }
+    }
+}
+
+impl std::fmt::Display for WorkloadMix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}/{:.0} write/read, {} keys", self.write_ratio * 100.0, (1.0 - self.write_ratio) * 100.0, self.key_space)
+    }
+}
+
+impl WorkloadMix {
+    // Key in the saved results; a single run keeps the plain name
+    pub fn result_key(&self, name: &str, runs: usize) -> String {
+        if runs == 1 {
+            name.to_string()
+        } else {
+            format!("{}_w{:.0}_k{}", name, self.write_ratio * 100.0, self.key_space)
+        }
+    }
+}
+
+// Whether a benchmark's cache starts empty or is prefilled before the timed part.
+// A cold run includes the fill phase; a warm one starts at `occupancy` of capacity,
+// so it measures steady state, with eviction already busy in a full cache.
+// --warm-cold runs the throughput benchmarks both ways.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheState {
+    Cold,
+    Warm { occupancy: f64 },
+}
+
+impl CacheState {
+    // Entries to insert before timing starts
+    pub fn prefill(self, capacity: usize) -> usize {
+        match self {
+            CacheState::Cold => 0,
+            CacheState::Warm { occupancy } => (capacity as f64 * occupancy).round() as usize,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CacheState::Cold => "cold",
+            CacheState::Warm { .. } => "warm",
+        }
+    }
+
+    // Key in the saved results; without --warm-cold the plain name
+    pub fn result_key(self, name: &str, states: usize) -> String {
+        if states == 1 {
+            name.to_string()
+        } else {
+            format!("{}_{}", name, self.name())
+        }
+    }
+}
+
+impl std::fmt::Display for CacheState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheState::Cold => write!(f, "cold cache"),
+            CacheState::Warm { occupancy } => write!(f, "warm cache, {:.0}% full", occupancy * 100.0),
+        }
+    }
+}
+
+// Every combination is run with --sweep
+pub const SWEEP_WRITE_RATIOS: [f64; 3] = [0.7, 0.3, 0.05];
+pub const SWEEP_KEY_SPACES: [usize; 2] = [1000, 1_000_000];
+
+pub const USAGE: &str =
+    "usage: [--write-ratio 0.7[,0.05,...]] [--keys 1000[,1000000,...]] [--sweep] [--watchdog SECS] [--scaling] [--runs N] [--baseline PATH[,PATH...]] [--threshold PCT] [--scenario PATH] [--loader SPEC] [--warm-cold] [--occupancy FRACTION] [--record PATH] [--replay PATH]";
+
+// Command line options shared by the benchmark binaries
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchArgs {
+    // Every combination of the given write ratios and key spaces
+    pub mixes: Vec<WorkloadMix>,
+    // Stall timeout for watchdog::start; None leaves the watchdog off
+    pub watchdog: Option<Duration>,
+    // fair_concurrent_all runs the thread-count x capacity sweep instead of its suite, at the
+    // first write ratio given
+    pub scaling: bool,
+    // Times each benchmark is repeated; see stats::repeated
+    pub runs: usize,
+    // Saved results to check this run against, one per implementation
+    pub baselines: Vec<String>,
+    // Percent change in the wrong direction that counts as a regression
+    pub threshold_pct: f64,
+    // fair_concurrent_all runs this scenario file instead of its suite
+    pub scenario: Option<String>,
+    // Miss cost in the loader_latency benchmark
+    pub loader: LoaderLatency,
+    // Cold only, or cold then warm with --warm-cold
+    pub cache_states: Vec<CacheState>,
+    // fair_concurrent_all saves each implementation's shared workload run here, with
+    // the implementation in the file name (see workload::record_path)
+    pub record: Option<String>,
+    // fair_concurrent_all replays this recorded workload instead of its suite
+    pub replay: Option<String>,
+}
+
+// Parses `--write-ratio` and `--keys`, each taking a comma-separated list,
+// `--sweep`, `--watchdog SECS`, `--scaling`, `--runs N`, `--baseline` (a
+// comma-separated list of result files), `--threshold PCT`, `--scenario PATH`,
+// `--loader SPEC` (see loader.rs), `--warm-cold`, `--occupancy FRACTION` (of
+// capacity for the warm runs, 1 by default), `--record PATH` and `--replay PATH`
+// (see workload.rs)
+pub fn parse_args<I>(args: I) -> Result<BenchArgs, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut watchdog = None;
+    let mut scaling = false;
+    let mut runs = 1;
+    let mut baselines = Vec::new();
+    let mut threshold_pct = 10.0;
+    let mut scenario = None;
+    let mut loader = LoaderLatency::default();
+    let mut warm_cold = false;
+    let mut occupancy = None;
+    let mut record = None;
+    let mut replay = None;
+    let mut write_ratios = vec![WorkloadMix::default().write_ratio];
+    let mut key_spaces = vec![WorkloadMix::default().key_space];
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--sweep" => {
+                write_ratios = SWEEP_WRITE_RATIOS.to_vec();
+                key_spaces = SWEEP_KEY_SPACES.to_vec();
+            }
+            "--write-ratio" => {
+                write_ratios = parse_list(&arg, args.next(), |value| {
+                    value.parse::<f64>().ok().filter(|ratio| (0.0..=1.0).contains(ratio))
+                })?;
+            }
+            "--keys" => {
+                key_spaces = parse_list(&arg, args.next(), |value| {
+                    value.replace('_', "").parse::<usize>().ok().filter(|keys| *keys > 0)
+                })?;
+            }
+            "--scaling" => scaling = true,
+            "--runs" => {
+                let counts = parse_list(&arg, args.next(), |value| value.parse::<usize>().ok().filter(|runs| *runs > 0))?;
+                match counts[..] {
+                    [count] => runs = count,
+                    _ => return Err(format!("--runs takes a single value\n{}", USAGE)),
+                }
+            }
+            "--baseline" => baselines = parse_list(&arg, args.next(), |value| Some(value.to_string()))?,
+            "--threshold" => {
+                let pcts = parse_list(&arg, args.next(), |value| value.parse::<f64>().ok().filter(|pct| *pct >= 0.0))?;
+                match pcts[..] {
+                    [pct] => threshold_pct = pct,
+                    _ => return Err(format!("--threshold takes a single value\n{}", USAGE)),
+                }
+            }
+            "--scenario" => scenario = Some(args.next().ok_or_else(|| format!("--scenario needs a value\n{}", USAGE))?),
+            "--record" => record = Some(args.next().ok_or_else(|| format!("--record needs a value\n{}", USAGE))?),
+            "--replay" => replay = Some(args.next().ok_or_else(|| format!("--replay needs a value\n{}", USAGE))?),
+            "--loader" => {
+                let spec = args.next().ok_or_else(|| format!("--loader needs a value\n{}", USAGE))?;
+                loader = spec.parse().map_err(|err| format!("{}\n{}", err, USAGE))?;
+            }
+            "--warm-cold" => warm_cold = true,
+            "--occupancy" => {
+                let fractions = parse_list(&arg, args.next(), |value| {
+                    value.parse::<f64>().ok().filter(|fraction| *fraction > 0.0 && *fraction <= 1.0)
+                })?;
+                match fractions[..] {
+                    [fraction] => occupancy = Some(fraction),
+                    _ => return Err(format!("--occupancy takes a single value\n{}", USAGE)),
+                }
+            }
+            "--watchdog" => {
+                let secs = parse_list(&arg, args.next(), |value| value.parse::<f64>().ok().filter(|secs| *secs > 0.0))?;
+                match secs[..] {
+                    [secs] => watchdog = Some(Duration::from_secs_f64(secs)),
+                    _ => return Err(format!("--watchdog takes a single value\n{}", USAGE)),
+                }
+            }
+            other => return Err(format!("unknown argument {:?}\n{}", other, USAGE)),
+        }
+    }
+
+    let cache_states = match (warm_cold, occupancy) {
+        (true, occupancy) => vec![CacheState::Cold, CacheState::Warm { occupancy: occupancy.unwrap_or(1.0) }],
+        (false, None) => vec![CacheState::Cold],
+        (false, Some(_)) => return Err(format!("--occupancy only applies with --warm-cold\n{}", USAGE)),
+    };
+    let mixes: Vec<WorkloadMix> = write_ratios
+        .iter()
+        .flat_map(|&write_ratio| key_spaces.iter().map(move |&key_space| WorkloadMix { write_ratio, key_space }))
+        .collect();
+    // One file per implementation holds one run
+    if record.is_some() && (replay.is_some() || mixes.len() > 1 || cache_states.len() > 1 || runs > 1) {
+        return Err(format!(
+            "--record saves a single shared workload run: it takes one write ratio and key space, no --warm-cold, --runs or --replay\n{}",
+            USAGE
+        ));
+    }
+    Ok(BenchArgs { mixes, watchdog, scaling, runs, baselines, threshold_pct, scenario, loader, cache_states, record, replay })
+}
+
+fn parse_list<T>(flag: &str, value: Option<String>, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value\n{}", flag, USAGE))?;
+    value
+        .split(',')
+        .map(|item| parse(item.trim()).ok_or_else(|| format!("invalid {} value {:?}\n{}", flag, item, USAGE)))
+        .collect()
+}
+
+// Options from the command line, exiting with usage on bad arguments. Starts the
+// watchdog when one was asked for.
+pub fn args_from_env() -> BenchArgs {
+    let args = parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+    if let Some(stall) = args.watchdog {
+        watchdog::start(stall);
+    }
+    args
+}
+
+// The --baseline results, exiting when one cannot be loaded
+pub fn baseline_from_args(args: &BenchArgs) -> Option<regression::Baseline> {
+    if args.baselines.is_empty() {
+        return None;
+    }
+    let baseline = regression::Baseline::load(&args.baselines, args.threshold_pct).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+    Some(baseline)
+}
@@ -0,0 +1,149 @@
+// The backend behind a read-through cache, for the loader_latency benchmark: a miss
+// pays a load drawn from one of these distributions, as a database or remote call
+// would. On the command line (--loader) they are written fixed:MS,
+// exponential:MEAN_MS or bimodal:FAST_MS:SLOW_MS:SLOW_SHARE; in a scenario file
+//
+//     loader = { distribution = "bimodal", fast_ms = 2, slow_ms = 200, slow_share = 0.05 }
+
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+pub enum LoaderLatency {
+    Fixed { ms: f64 },
+    Exponential { mean_ms: f64 },
+    // Mostly fast loads with a slow tail, e.g. cache-warm vs cold database pages
+    Bimodal { fast_ms: f64, slow_ms: f64, slow_share: f64 },
+}
+
+impl Default for LoaderLatency {
+    fn default() -> Self {
+        LoaderLatency::Exponential { mean_ms: 5.0 }
+    }
+}
+
+impl LoaderLatency {
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Duration {
+        let ms = match *self {
+            LoaderLatency::Fixed { ms } => ms,
+            LoaderLatency::Exponential { mean_ms } => -mean_ms * (1.0 - rng.gen::<f64>()).ln(),
+            LoaderLatency::Bimodal { fast_ms, slow_ms, slow_share } => {
+                if rng.gen::<f64>() < slow_share {
+                    slow_ms
+                } else {
+                    fast_ms
+                }
+            }
+        };
+        Duration::from_secs_f64(ms / 1000.0)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let times = match *self {
+            LoaderLatency::Fixed { ms } => vec![ms],
+            LoaderLatency::Exponential { mean_ms } => vec![mean_ms],
+            LoaderLatency::Bimodal { fast_ms, slow_ms, slow_share } => {
+                if !(0.0..=1.0).contains(&slow_share) {
+                    return Err(format!("slow_share must be between 0 and 1, got {}", slow_share));
+                }
+                vec![fast_ms, slow_ms]
+            }
+        };
+        match times.into_iter().find(|ms| !ms.is_finite() || *ms < 0.0) {
+            Some(ms) => Err(format!("loader latencies must be at least 0 ms, got {}", ms)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for LoaderLatency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoaderLatency::Fixed { ms } => write!(f, "fixed:{}", ms),
+            LoaderLatency::Exponential { mean_ms } => write!(f, "exponential:{}", mean_ms),
+            LoaderLatency::Bimodal { fast_ms, slow_ms, slow_share } => {
+                write!(f, "bimodal:{}:{}:{}", fast_ms, slow_ms, slow_share)
+            }
+        }
+    }
+}
+
+impl FromStr for LoaderLatency {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let (kind, params) = spec.split_once(':').unwrap_or((spec, ""));
+        let params: Vec<f64> = params
+            .split(':')
+            .map(|param| param.parse::<f64>().map_err(|_| format!("invalid loader parameter {:?}", param)))
+            .collect::<Result<_, _>>()?;
+        let latency = match (kind, &params[..]) {
+            ("fixed", &[ms]) => LoaderLatency::Fixed { ms },
+            ("exponential", &[mean_ms]) => LoaderLatency::Exponential { mean_ms },
+            ("bimodal", &[fast_ms, slow_ms, slow_share]) => LoaderLatency::Bimodal { fast_ms, slow_ms, slow_share },
+            _ => {
+                return Err(format!(
+                    "invalid loader {:?}, expected fixed:MS, exponential:MEAN_MS or bimodal:FAST_MS:SLOW_MS:SLOW_SHARE",
+                    spec
+                ))
+            }
+        };
+        latency.validate()?;
+        Ok(latency)
+    }
+}
+
+// Stampede protection: the first miss of a key runs the load and misses of the same
+// key arriving meanwhile wait for its value instead of loading again. A load that
+// panics leaves its waiters blocked, which is fine for a benchmark.
+pub struct SingleFlight<T> {
+    in_flight: Mutex<HashMap<String, Arc<Flight<T>>>>,
+}
+
+struct Flight<T> {
+    value: Mutex<Option<T>>,
+    done: Condvar,
+}
+
+impl<T: Clone> Default for SingleFlight<T> {
+    fn default() -> Self {
+        SingleFlight { in_flight: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The loaded value and whether this caller ran the load
+    pub fn load(&self, key: &str, load: impl FnOnce() -> T) -> (T, bool) {
+        let (flight, leader) = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(key) {
+                Some(flight) => (Arc::clone(flight), false),
+                None => {
+                    let flight = Arc::new(Flight { value: Mutex::new(None), done: Condvar::new() });
+                    in_flight.insert(key.to_string(), Arc::clone(&flight));
+                    (flight, true)
+                }
+            }
+        };
+
+        if !leader {
+            let value = flight.done.wait_while(flight.value.lock().unwrap(), |value| value.is_none()).unwrap();
+            return (value.clone().expect("a finished flight holds its value"), false);
+        }
+        let value = load();
+        *flight.value.lock().unwrap() = Some(value.clone());
+        self.in_flight.lock().unwrap().remove(key);
+        flight.done.notify_all();
+        (value, true)
+    }
+}
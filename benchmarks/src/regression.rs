@@ -0,0 +1,262 @@
+// Regression detection against saved results (--baseline). Every metric with a
+// known direction is compared with the same metric of the baseline report for the
+// same implementation; metrics that got worse by more than the threshold are
+// regressions. Summaries from --runs are compared by their mean.
+
+use crate::report::{BenchmarkReport, BenchmarkResult, Metric};
+
+// Exit code of a run with regressions
+pub const REGRESSION_EXIT_CODE: i32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    HigherIsBetter,
+    LowerIsBetter,
+}
+
+// Metrics without a direction (counts, parameters) are not compared
+pub fn direction(metric: &str) -> Option<Direction> {
+    let lower = metric.starts_with("stddev")
+        || metric.contains("latency")
+        || metric == "duration"
+        || metric.ends_with("_duration")
+        || metric.ends_with("_time_ms")
+        || metric.ends_with("_time_us")
+        || metric.ends_with("_time_ns")
+        || metric.ends_with("penalty_pct")
+        || metric.contains("residual")
+        || metric.starts_with("puts_over")
+        || metric.contains("recovery")
+        || metric == "gini"
+        || metric == "cpu_seconds";
+    // The fastest worker getting slower can just mean fairer scheduling
+    let higher = (metric.ends_with("per_second") && !metric.starts_with("max_"))
+        || matches!(
+            metric,
+            "hit_rate_pct"
+                | "speedup"
+                | "eviction_efficiency_pct"
+                | "expiry_rate_pct"
+                | "jain_index"
+                | "parallelism_factor"
+        );
+    if lower {
+        Some(Direction::LowerIsBetter)
+    } else if higher {
+        Some(Direction::HigherIsBetter)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub implementation: String,
+    // Dotted path under "benchmarks", e.g. shared_workload.ops_per_second
+    pub metric: String,
+    pub baseline: f64,
+    pub current: f64,
+    // Signed change relative to the baseline
+    pub change_pct: f64,
+}
+
+// One metric of a report next to the same metric of an earlier one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    // Dotted path under "benchmarks", e.g. shared_workload.ops_per_second
+    pub metric: String,
+    pub baseline: f64,
+    pub current: f64,
+    // Signed change relative to the baseline; None when the baseline is 0
+    pub change_pct: Option<f64>,
+    pub direction: Option<Direction>,
+}
+
+impl Delta {
+    // Worse than the baseline by more than threshold_pct
+    pub fn regressed(&self, threshold_pct: f64) -> bool {
+        match (self.direction, self.change_pct) {
+            (Some(Direction::HigherIsBetter), Some(change_pct)) => change_pct < -threshold_pct,
+            (Some(Direction::LowerIsBetter), Some(change_pct)) => change_pct > threshold_pct,
+            _ => false,
+        }
+    }
+
+    // Better than the baseline by more than threshold_pct
+    pub fn improved(&self, threshold_pct: f64) -> bool {
+        match (self.direction, self.change_pct) {
+            (Some(Direction::HigherIsBetter), Some(change_pct)) => change_pct > threshold_pct,
+            (Some(Direction::LowerIsBetter), Some(change_pct)) => change_pct < -threshold_pct,
+            _ => false,
+        }
+    }
+}
+
+// Every numeric metric the two reports share, groups included, in name order.
+// Summaries from --runs are compared by their mean; series are skipped.
+pub fn deltas(baseline: &BenchmarkReport, current: &BenchmarkReport) -> Vec<Delta> {
+    let mut deltas = Vec::new();
+    for (name, old) in &baseline.benchmarks {
+        if let Some(new) = current.benchmarks.get(name) {
+            result_deltas(name, old, new, &mut deltas);
+        }
+    }
+    deltas
+}
+
+fn result_deltas(path: &str, baseline: &BenchmarkResult, current: &BenchmarkResult, deltas: &mut Vec<Delta>) {
+    for (key, old) in baseline {
+        if let Some(new) = current.get(key) {
+            metric_delta(format!("{}.{}", path, key), old, new, deltas);
+        }
+    }
+}
+
+fn metric_delta(path: String, baseline: &Metric, current: &Metric, deltas: &mut Vec<Delta>) {
+    if let (Metric::Group(baseline), Metric::Group(current)) = (baseline, current) {
+        result_deltas(&path, baseline, current, deltas);
+        return;
+    }
+
+    // A plain metric or the mean of a --runs summary
+    if let (Some(old), Some(new)) = (baseline.as_f64(), current.as_f64()) {
+        let metric = path.rsplit('.').next().unwrap_or_default();
+        deltas.push(Delta {
+            direction: direction(metric),
+            change_pct: (old != 0.0).then(|| (new - old) / old.abs() * 100.0),
+            metric: path,
+            baseline: old,
+            current: new,
+        });
+    }
+}
+
+pub struct Baseline {
+    reports: Vec<BenchmarkReport>,
+    threshold_pct: f64,
+}
+
+impl Baseline {
+    pub fn load(paths: &[String], threshold_pct: f64) -> Result<Self, String> {
+        let reports = paths
+            .iter()
+            .map(|path| BenchmarkReport::load(path).map_err(|err| format!("bad baseline: {}", err)))
+            .collect::<Result<_, String>>()?;
+        Ok(Baseline { reports, threshold_pct })
+    }
+
+    // Regressions of `report` against the baseline for the same implementation
+    pub fn compare(&self, report: &BenchmarkReport) -> Vec<Regression> {
+        let implementation = report.implementation.as_str();
+        let Some(baseline) = self.reports.iter().find(|baseline| baseline.implementation == implementation) else {
+            println!("No baseline for {}", implementation);
+            return Vec::new();
+        };
+
+        let regressions: Vec<Regression> = deltas(baseline, report)
+            .into_iter()
+            .filter(|delta| delta.regressed(self.threshold_pct))
+            .map(|delta| Regression {
+                implementation: implementation.to_string(),
+                change_pct: delta.change_pct.unwrap_or_default(),
+                metric: delta.metric,
+                baseline: delta.baseline,
+                current: delta.current,
+            })
+            .collect();
+        if regressions.is_empty() {
+            println!("{}: no regressions beyond {}% against the baseline", implementation, self.threshold_pct);
+        }
+        for regression in &regressions {
+            println!(
+                "REGRESSION {}: {} {:.2} -> {:.2} ({:+.1}%)",
+                regression.implementation, regression.metric, regression.baseline, regression.current, regression.change_pct
+            );
+        }
+        regressions
+    }
+}
+
+// Print the outcome and exit non-zero if anything regressed
+pub fn finish(regressions: &[Regression]) {
+    if regressions.is_empty() {
+        return;
+    }
+    println!("\n{} metric(s) regressed against the baseline", regressions.len());
+    std::process::exit(REGRESSION_EXIT_CODE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::Summary;
+
+    fn report(metrics: &[(&str, Metric)]) -> BenchmarkReport {
+        let mut report = BenchmarkReport::new("test");
+        let result = metrics.iter().map(|(name, metric)| (name.to_string(), metric.clone())).collect();
+        report.benchmarks.insert("run".to_string(), result);
+        report
+    }
+
+    fn delta(metric: &str, baseline: f64, current: f64) -> Delta {
+        let deltas = deltas(&report(&[(metric, baseline.into())]), &report(&[(metric, current.into())]));
+        assert_eq!(deltas.len(), 1);
+        deltas.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_direction_rules() {
+        for metric in ["ops_per_second", "gets_per_second", "hit_rate_pct", "speedup", "jain_index"] {
+            assert_eq!(direction(metric), Some(Direction::HigherIsBetter), "{}", metric);
+        }
+        for metric in ["p99_latency_us", "stddev_ops", "duration", "warmup_duration", "cleanup_time_ms", "cpu_seconds", "gini"] {
+            assert_eq!(direction(metric), Some(Direction::LowerIsBetter), "{}", metric);
+        }
+        // The fastest worker alone says nothing about the run; counts and
+        // parameters have no better direction
+        for metric in ["max_ops_per_second", "threads", "operations", "cache_size"] {
+            assert_eq!(direction(metric), None, "{}", metric);
+        }
+    }
+
+    #[test]
+    fn test_regressed_and_improved() {
+        let slower = delta("ops_per_second", 1000.0, 850.0);
+        assert_eq!(slower.change_pct, Some(-15.0));
+        assert!(slower.regressed(10.0));
+        assert!(!slower.regressed(20.0));
+        assert!(!slower.improved(10.0));
+
+        let faster = delta("p99_latency_us", 200.0, 150.0);
+        assert!(faster.improved(10.0));
+        assert!(!faster.regressed(10.0));
+        assert!(delta("p99_latency_us", 200.0, 250.0).regressed(10.0));
+
+        // Metrics without a direction never fail the run
+        assert!(!delta("max_ops_per_second", 1000.0, 10.0).regressed(10.0));
+    }
+
+    #[test]
+    fn test_zero_baseline_gives_no_change() {
+        let from_zero = delta("p99_latency_us", 0.0, 500.0);
+        assert_eq!(from_zero.change_pct, None);
+        assert!(!from_zero.regressed(0.0));
+        assert!(!from_zero.improved(0.0));
+    }
+
+    #[test]
+    fn test_deltas_walk_groups_and_summaries() {
+        let group = |rate: f64| Metric::from(BenchmarkResult::from([("hit_rate_pct".to_string(), Metric::from(rate))]));
+        let summary = Summary::from_samples(&[90.0, 110.0]);
+        let baseline = report(&[("fairness", group(80.0)), ("ops_per_second", 100.0.into()), ("only_before", 1.into())]);
+        let current = report(&[("fairness", group(60.0)), ("ops_per_second", summary.into())]);
+
+        let deltas = deltas(&baseline, &current);
+        let metrics: Vec<&str> = deltas.iter().map(|delta| delta.metric.as_str()).collect();
+        assert_eq!(metrics, ["run.fairness.hit_rate_pct", "run.ops_per_second"]);
+        assert!(deltas[0].regressed(10.0));
+        // A --runs summary is compared by its mean
+        assert_eq!(deltas[1].current, 100.0);
+        assert_eq!(deltas[1].change_pct, Some(0.0));
+    }
+}
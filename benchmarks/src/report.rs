@@ -0,0 +1,160 @@
+// The results files written by the benchmark binaries. Every metric is a JSON
+// number; percentages carry a `_pct` suffix in their name rather than a "%" in the
+// value. SCHEMA_VERSION changes whenever a reader of older files would misread
+// newer ones, and loading a report checks it.
+
+use crate::stats::Summary;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+// One benchmark's metrics by name
+pub type BenchmarkResult = BTreeMap<String, Metric>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Metric {
+    Number(serde_json::Number),
+    // The same metric over repeated runs (--runs)
+    Summary(Summary),
+    // Samples over time or per phase, e.g. a timeline
+    Series(Vec<BenchmarkResult>),
+    // Related metrics, e.g. a fairness breakdown
+    Group(BenchmarkResult),
+}
+
+impl Metric {
+    // The number, or a summary's mean
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Metric::Number(n) => n.as_f64(),
+            Metric::Summary(summary) => Some(summary.mean),
+            Metric::Series(_) | Metric::Group(_) => None,
+        }
+    }
+}
+
+impl From<f64> for Metric {
+    // JSON has no NaN or infinity; those only come out of a zero-length run
+    fn from(value: f64) -> Self {
+        Metric::Number(serde_json::Number::from_f64(value).unwrap_or_else(|| 0.into()))
+    }
+}
+
+macro_rules! impl_metric_from_int {
+    ($($int:ty),*) => {
+        $(impl From<$int> for Metric {
+            fn from(value: $int) -> Self {
+                Metric::Number(value.into())
+            }
+        })*
+    };
+}
+
+impl_metric_from_int!(i32, i64, u8, u32, u64, usize);
+
+impl From<Summary> for Metric {
+    fn from(summary: Summary) -> Self {
+        Metric::Summary(summary)
+    }
+}
+
+impl From<Vec<BenchmarkResult>> for Metric {
+    fn from(series: Vec<BenchmarkResult>) -> Self {
+        Metric::Series(series)
+    }
+}
+
+impl From<BenchmarkResult> for Metric {
+    fn from(group: BenchmarkResult) -> Self {
+        Metric::Group(group)
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_string(self) {
+            Ok(json) => f.write_str(&json),
+            Err(_) => Err(fmt::Error),
+        }
+    }
+}
+
+// Build a BenchmarkResult: metrics!{"ops_per_second" => ops, "threads" => threads}
+#[macro_export]
+macro_rules! metrics {
+    ($($name:expr => $value:expr),* $(,)?) => {
+        $crate::report::BenchmarkResult::from([
+            $(($name.to_string(), $crate::report::Metric::from($value))),*
+        ])
+    };
+}
+
+// Throughput over thread counts (columns) and capacities (rows)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScalingMatrix {
+    pub threads: Vec<usize>,
+    pub capacities: Vec<usize>,
+    pub write_ratio: f64,
+    pub cell_duration_ms: u64,
+    pub ops_per_second: Vec<Vec<f64>>,
+    pub cells: Vec<BenchmarkResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub schema_version: u32,
+    pub implementation: String,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_size: Option<usize>,
+    #[serde(default)]
+    pub benchmarks: BTreeMap<String, BenchmarkResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scaling: Option<ScalingMatrix>,
+    #[serde(default)]
+    pub notes: BTreeMap<String, String>,
+}
+
+impl BenchmarkReport {
+    pub fn new(implementation: impl Into<String>) -> Self {
+        BenchmarkReport {
+            schema_version: SCHEMA_VERSION,
+            implementation: implementation.into(),
+            timestamp: chrono::Local::now().format("%Y%m%d_%H%M%S").to_string(),
+            cache_size: None,
+            benchmarks: BTreeMap::new(),
+            scaling: None,
+            notes: BTreeMap::new(),
+        }
+    }
+
+    pub fn note(mut self, name: &str, text: &str) -> Self {
+        self.notes.insert(name.to_string(), text.to_string());
+        self
+    }
+
+    // Read and validate a saved report
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path, err))?;
+        let value: serde_json::Value = serde_json::from_str(&text).map_err(|err| format!("cannot parse {}: {}", path, err))?;
+        match value.get("schema_version").and_then(|version| version.as_u64()) {
+            Some(version) if version == SCHEMA_VERSION as u64 => {}
+            Some(version) => {
+                return Err(format!("{} has schema version {}, expected {}", path, version, SCHEMA_VERSION))
+            }
+            None => return Err(format!("{} has no schema_version; it predates typed reports", path)),
+        }
+        serde_json::from_value(value).map_err(|err| format!("{} does not match the report schema: {}", path, err))
+    }
+
+    // Write to results/rust_<name>_<kind>_<timestamp>.json and return the path
+    pub fn save(&self, name: &str, kind: &str) -> String {
+        let filename = format!("results/rust_{}_{}_{}.json", name.to_lowercase(), kind, self.timestamp);
+        std::fs::create_dir_all("results").unwrap();
+        std::fs::write(&filename, serde_json::to_string_pretty(self).unwrap()).unwrap();
+        filename
+    }
+}
@@ -0,0 +1,178 @@
+// Scenario files (--scenario): a TOML list of benchmarks that fair_concurrent_all runs
+// in order instead of its fixed suite, so a comparison campaign can be kept next to
+// its results and rerun as is.
+//
+//     name = "read-heavy, large key space"
+//     runs = 3
+//
+//     [[benchmark]]
+//     type = "shared_workload"
+//     threads = 64
+//     operations = 50000
+//     capacity = 100000
+//     workload = { write_ratio = 0.05, key_space = 1000000 }
+//     implementations = ["Qwen235B", "Qwen435B"]
+//
+// Settings left out take the defaults of the fixed suite.
+
+use crate::loader::LoaderLatency;
+use crate::WorkloadMix;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkKind {
+    ProducerConsumer,
+    SharedWorkload,
+    IoSimulation,
+    Eviction,
+    Ttl,
+    BurstTraffic,
+    TtlChurn,
+    LoaderLatency,
+}
+
+impl BenchmarkKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            BenchmarkKind::ProducerConsumer => "producer_consumer",
+            BenchmarkKind::SharedWorkload => "shared_workload",
+            BenchmarkKind::IoSimulation => "io_simulation",
+            BenchmarkKind::Eviction => "eviction",
+            BenchmarkKind::Ttl => "ttl",
+            BenchmarkKind::BurstTraffic => "burst_traffic",
+            BenchmarkKind::TtlChurn => "ttl_churn",
+            BenchmarkKind::LoaderLatency => "loader_latency",
+        }
+    }
+
+    // Throughput benchmarks that --warm-cold runs against a prefilled cache as well
+    pub fn uses_cache_state(self) -> bool {
+        matches!(
+            self,
+            BenchmarkKind::ProducerConsumer
+                | BenchmarkKind::SharedWorkload
+                | BenchmarkKind::IoSimulation
+                | BenchmarkKind::LoaderLatency
+        )
+    }
+
+    // Settings the benchmark reads besides capacity and implementations
+    fn settings(self) -> &'static [&'static str] {
+        match self {
+            BenchmarkKind::ProducerConsumer => &["threads", "duration_secs"],
+            BenchmarkKind::SharedWorkload => &["threads", "operations", "workload"],
+            BenchmarkKind::IoSimulation => &["threads", "duration_secs"],
+            BenchmarkKind::Eviction => &["operations"],
+            BenchmarkKind::Ttl => &["operations", "ttl_ms"],
+            BenchmarkKind::BurstTraffic => &["threads", "bursts"],
+            BenchmarkKind::TtlChurn => &["threads", "duration_secs"],
+            BenchmarkKind::LoaderLatency => &["threads", "duration_secs", "ttl_ms", "keys", "loader"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioBenchmark {
+    #[serde(rename = "type")]
+    pub kind: BenchmarkKind,
+    // Key in the saved results; defaults to the type
+    pub name: Option<String>,
+    pub threads: Option<usize>,
+    pub duration_secs: Option<u64>,
+    pub capacity: Option<usize>,
+    // Operations, insertions or items, depending on the type
+    pub operations: Option<usize>,
+    pub ttl_ms: Option<u64>,
+    pub bursts: Option<usize>,
+    pub workload: Option<WorkloadMix>,
+    // Key space of the loader_latency benchmark
+    pub keys: Option<usize>,
+    pub loader: Option<LoaderLatency>,
+    // Implementation names as printed by the suite; all of them when left out
+    pub implementations: Option<Vec<String>>,
+}
+
+impl ScenarioBenchmark {
+    pub fn result_key(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.kind.name())
+    }
+
+    pub fn runs_on(&self, implementation: &str) -> bool {
+        self.implementations
+            .as_ref()
+            .is_none_or(|names| names.iter().any(|name| name.eq_ignore_ascii_case(implementation)))
+    }
+
+    fn given_settings(&self) -> Vec<&'static str> {
+        [
+            ("threads", self.threads.is_some()),
+            ("duration_secs", self.duration_secs.is_some()),
+            ("operations", self.operations.is_some()),
+            ("ttl_ms", self.ttl_ms.is_some()),
+            ("bursts", self.bursts.is_some()),
+            ("workload", self.workload.is_some()),
+            ("keys", self.keys.is_some()),
+            ("loader", self.loader.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(setting, given)| given.then_some(setting))
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: Option<String>,
+    // Overrides --runs
+    pub runs: Option<usize>,
+    #[serde(rename = "benchmark")]
+    pub benchmarks: Vec<ScenarioBenchmark>,
+}
+
+impl Scenario {
+    pub fn parse(text: &str, implementations: &[&str]) -> Result<Self, String> {
+        let scenario: Scenario = toml::from_str(text).map_err(|err| err.to_string())?;
+        if scenario.benchmarks.is_empty() {
+            return Err("no [[benchmark]] entries".to_string());
+        }
+        if scenario.runs == Some(0) {
+            return Err("runs must be at least 1".to_string());
+        }
+
+        let mut keys = Vec::new();
+        for benchmark in &scenario.benchmarks {
+            let key = benchmark.result_key();
+            // A setting the benchmark would ignore is almost certainly a mistake
+            if let Some(setting) = benchmark.given_settings().into_iter().find(|s| !benchmark.kind.settings().contains(s)) {
+                return Err(format!("{}: {} does not take {}", key, benchmark.kind.name(), setting));
+            }
+            if benchmark.threads == Some(0) || benchmark.capacity == Some(0) || benchmark.keys == Some(0) {
+                return Err(format!("{}: threads, capacity and keys must be at least 1", key));
+            }
+            if let Some(loader) = &benchmark.loader {
+                loader.validate().map_err(|err| format!("{}: {}", key, err))?;
+            }
+            if let Some(unknown) = benchmark
+                .implementations
+                .iter()
+                .flatten()
+                .find(|name| !implementations.iter().any(|known| known.eq_ignore_ascii_case(name)))
+            {
+                return Err(format!("{}: unknown implementation {:?}, expected one of {:?}", key, unknown, implementations));
+            }
+            if keys.contains(&key) {
+                return Err(format!("{} appears twice; give one of them a distinct name", key));
+            }
+            keys.push(key);
+        }
+        Ok(scenario)
+    }
+
+    pub fn load(path: &str, implementations: &[&str]) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("cannot read scenario {}: {}", path, err))?;
+        Scenario::parse(&text, implementations).map_err(|err| format!("bad scenario {}: {}", path, err))
+    }
+}
@@ -0,0 +1,188 @@
+// Repeated benchmark runs. Each numeric metric is summarised across runs as
+// mean, stddev and a 95% confidence interval, after dropping outliers by Tukey's
+// fences. Series such as timelines are taken from the first run.
+
+use crate::report::{BenchmarkResult, Metric};
+use serde::{Deserialize, Serialize};
+
+// Two-sided 95% Student t critical values for 1..=30 degrees of freedom
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160, 2.145, 2.131,
+    2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+// Outliers are only dropped once there are enough runs to tell what one is
+const MIN_RUNS_FOR_OUTLIERS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Summary {
+    pub mean: f64,
+    pub stddev: f64,
+    pub ci95_low: f64,
+    pub ci95_high: f64,
+    // Samples kept after outlier removal
+    pub samples: usize,
+    pub outliers: usize,
+}
+
+impl Summary {
+    pub fn from_samples(samples: &[f64]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let kept: Vec<f64> = if sorted.len() >= MIN_RUNS_FOR_OUTLIERS {
+            let q1 = quantile(&sorted, 0.25);
+            let q3 = quantile(&sorted, 0.75);
+            let fence = 1.5 * (q3 - q1);
+            sorted.iter().copied().filter(|x| (q1 - fence..=q3 + fence).contains(x)).collect()
+        } else {
+            sorted.clone()
+        };
+
+        let n = kept.len() as f64;
+        let mean = kept.iter().sum::<f64>() / n;
+        // Sample stddev; a single run has no spread to report
+        let stddev = if kept.len() > 1 {
+            (kept.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        let t = T_95.get(kept.len().saturating_sub(2)).copied().unwrap_or(1.96);
+        let margin = if kept.len() > 1 { t * stddev / n.sqrt() } else { 0.0 };
+
+        Summary {
+            mean,
+            stddev,
+            ci95_low: mean - margin,
+            ci95_high: mean + margin,
+            samples: kept.len(),
+            outliers: sorted.len() - kept.len(),
+        }
+    }
+}
+
+// Linear interpolation between closest ranks
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = (sorted.len() - 1) as f64 * q;
+    let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+fn summarise(values: &[&Metric]) -> Metric {
+    let first = values[0];
+    // Parameters such as thread counts are the same every run
+    if values.iter().all(|value| *value == first) {
+        return first.clone();
+    }
+    if let Metric::Number(_) = first {
+        let samples: Option<Vec<f64>> = values.iter().map(|value| value.as_f64()).collect();
+        if let Some(samples) = samples {
+            return Metric::Summary(Summary::from_samples(&samples));
+        }
+    }
+    if let Metric::Group(group) = first {
+        let groups: Vec<&BenchmarkResult> = values
+            .iter()
+            .filter_map(|value| match value {
+                Metric::Group(group) => Some(group),
+                _ => None,
+            })
+            .collect();
+        if groups.len() == values.len() {
+            return Metric::Group(summarise_results(&groups, group));
+        }
+    }
+    first.clone()
+}
+
+fn summarise_results(results: &[&BenchmarkResult], first: &BenchmarkResult) -> BenchmarkResult {
+    let mut summary = BenchmarkResult::new();
+    for key in first.keys() {
+        let values: Vec<&Metric> = results.iter().filter_map(|result| result.get(key)).collect();
+        if values.len() == results.len() {
+            summary.insert(key.clone(), summarise(&values));
+        }
+    }
+    summary
+}
+
+// Run a benchmark `runs` times and summarise every metric; a single run is
+// returned as is
+pub fn repeated<F>(runs: usize, mut benchmark: F) -> BenchmarkResult
+where
+    F: FnMut() -> BenchmarkResult,
+{
+    let results: Vec<BenchmarkResult> = (0..runs.max(1)).map(|_| benchmark()).collect();
+    if results.len() == 1 {
+        return results.into_iter().next().unwrap();
+    }
+    let borrowed: Vec<&BenchmarkResult> = results.iter().collect();
+    summarise_results(&borrowed, &results[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_summary_mean_stddev_and_interval() {
+        let summary = Summary::from_samples(&[14.0, 10.0, 12.0]);
+        assert!(close(summary.mean, 12.0));
+        assert!(close(summary.stddev, 2.0));
+        // t = 4.303 for two degrees of freedom
+        let margin = 4.303 * 2.0 / 3f64.sqrt();
+        assert!(close(summary.ci95_low, 12.0 - margin));
+        assert!(close(summary.ci95_high, 12.0 + margin));
+        assert_eq!((summary.samples, summary.outliers), (3, 0));
+    }
+
+    #[test]
+    fn test_summary_drops_outliers() {
+        let summary = Summary::from_samples(&[10.0, 11.0, 100.0, 10.0, 11.0]);
+        assert_eq!((summary.samples, summary.outliers), (4, 1));
+        assert!(close(summary.mean, 10.5));
+
+        // Too few runs to call 100 an outlier
+        let summary = Summary::from_samples(&[10.0, 11.0, 100.0]);
+        assert_eq!(summary.outliers, 0);
+        assert!(close(summary.mean, 121.0 / 3.0));
+    }
+
+    #[test]
+    fn test_summary_of_one_run() {
+        let summary = Summary::from_samples(&[7.5]);
+        assert!(close(summary.mean, 7.5));
+        assert_eq!((summary.stddev, summary.ci95_low, summary.ci95_high), (0.0, 7.5, 7.5));
+    }
+
+    #[test]
+    fn test_repeated_summarises_only_what_varies() {
+        let mut run = 0;
+        let result = repeated(3, || {
+            run += 1;
+            let mut group = BenchmarkResult::new();
+            group.insert("jain_index".to_string(), Metric::from(0.9 + run as f64 / 100.0));
+            let mut result = BenchmarkResult::new();
+            result.insert("threads".to_string(), Metric::from(8));
+            result.insert("ops_per_second".to_string(), Metric::from(run as f64 * 100.0));
+            result.insert("fairness".to_string(), Metric::from(group));
+            result
+        });
+
+        assert_eq!(result["threads"], Metric::from(8));
+        assert!(matches!(result["ops_per_second"], Metric::Summary(summary) if close(summary.mean, 200.0)));
+        let Metric::Group(group) = &result["fairness"] else {
+            panic!("expected a group");
+        };
+        assert!(matches!(group["jain_index"], Metric::Summary(summary) if summary.samples == 3));
+
+        // A single run is returned unsummarised
+        let single = repeated(1, || BenchmarkResult::from([("ops_per_second".to_string(), Metric::from(1.0))]));
+        assert_eq!(single["ops_per_second"], Metric::from(1.0));
+    }
+}
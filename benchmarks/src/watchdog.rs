@@ -0,0 +1,142 @@
+// Stall detection for benchmark runs. Once started, worker threads mark each cache
+// operation with Worker::enter; a monitor thread watches for any worker stuck in one
+// operation for the stall timeout, prints what every worker is doing and exits. Built
+// with the deadlock-detection feature it also prints any cycle among parking_lot
+// locks. The std locks behind 30b and 235b are not tracked by parking_lot, so for
+// those the per-worker dump is the diagnosis.
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Exit code of a run the watchdog stopped
+pub const STALLED_EXIT_CODE: i32 = 3;
+
+static WATCHDOG: OnceLock<Arc<Registry>> = OnceLock::new();
+
+struct Registry {
+    stall: Duration,
+    workers: Mutex<Vec<Arc<Slot>>>,
+}
+
+struct Slot {
+    name: String,
+    completed: AtomicU64,
+    // Operation, key and start of the operation in progress
+    current: Mutex<Option<(&'static str, String, Instant)>>,
+}
+
+// Start watching; workers registered before this are not tracked
+pub fn start(stall: Duration) {
+    let registry = Arc::new(Registry { stall, workers: Mutex::new(Vec::new()) });
+    if WATCHDOG.set(Arc::clone(&registry)).is_err() {
+        return;
+    }
+    println!("Watchdog: aborting if a worker makes no progress for {:?}", stall);
+    thread::spawn(move || registry.monitor());
+}
+
+// Handle for one worker thread; does nothing unless the watchdog was started
+pub fn worker(name: impl Into<String>) -> Worker {
+    let slot = WATCHDOG.get().map(|registry| {
+        let slot = Arc::new(Slot {
+            name: name.into(),
+            completed: AtomicU64::new(0),
+            current: Mutex::new(None),
+        });
+        registry.workers.lock().push(Arc::clone(&slot));
+        slot
+    });
+    Worker { slot }
+}
+
+pub struct Worker {
+    slot: Option<Arc<Slot>>,
+}
+
+impl Worker {
+    pub fn enter(&self, op: &'static str, key: &str) -> Operation<'_> {
+        if let Some(slot) = &self.slot {
+            *slot.current.lock() = Some((op, key.to_string(), Instant::now()));
+        }
+        Operation { worker: self }
+    }
+}
+
+// An operation in progress; finishing or dropping it counts as progress
+pub struct Operation<'a> {
+    worker: &'a Worker,
+}
+
+impl Operation<'_> {
+    pub fn finish(self) {}
+}
+
+impl Drop for Operation<'_> {
+    fn drop(&mut self) {
+        if let Some(slot) = &self.worker.slot {
+            *slot.current.lock() = None;
+            slot.completed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Registry {
+    fn monitor(&self) {
+        let interval = (self.stall / 4).max(Duration::from_millis(100));
+        loop {
+            thread::sleep(interval);
+            // Forget workers whose thread has finished
+            self.workers.lock().retain(|slot| Arc::strong_count(slot) > 1);
+
+            let stalled = self.workers.lock().iter().any(|slot| {
+                slot.current.lock().as_ref().is_some_and(|(_, _, since)| since.elapsed() >= self.stall)
+            });
+            if stalled {
+                self.report();
+                report_lock_cycles();
+                std::process::exit(STALLED_EXIT_CODE);
+            }
+        }
+    }
+
+    fn report(&self) {
+        eprintln!("\nWatchdog: a worker made no progress for {:?}", self.stall);
+        for slot in self.workers.lock().iter() {
+            let completed = slot.completed.load(Ordering::Relaxed);
+            match &*slot.current.lock() {
+                Some((op, key, since)) => eprintln!(
+                    "  {}: {} ops done, in {} {:?} for {:.1}s",
+                    slot.name,
+                    completed,
+                    op,
+                    key,
+                    since.elapsed().as_secs_f64()
+                ),
+                None => eprintln!("  {}: {} ops done, between operations", slot.name, completed),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "deadlock-detection")]
+fn report_lock_cycles() {
+    let cycles = parking_lot::deadlock::check_deadlock();
+    if cycles.is_empty() {
+        eprintln!("No parking_lot lock cycle found");
+        return;
+    }
+    for (i, cycle) in cycles.iter().enumerate() {
+        eprintln!("Lock cycle {}:", i + 1);
+        for thread in cycle {
+            eprintln!("  thread {:?}\n{:?}", thread.thread_id(), thread.backtrace());
+        }
+    }
+}
+
+#[cfg(not(feature = "deadlock-detection"))]
+fn report_lock_cycles() {
+    eprintln!("Build with --features deadlock-detection to check parking_lot locks for cycles");
+}
@@ -0,0 +1,169 @@
+// Recorded workloads (--record / --replay). A shared workload run is saved as it
+// actually happened: every operation with its key and priority, the worker that ran
+// it and when, relative to the start of the run. --replay runs the file against
+// every implementation, each worker issuing its operations in the recorded order and
+// no earlier than recorded, so a pathological run can be looked at again, or on
+// another implementation, with the same keys hitting the same threads.
+//
+// The file is little-endian binary: the magic and version, the cache capacity and
+// worker count, then per worker its operation count followed by the operations
+// (kind u8, priority u8, key u64, offset in nanoseconds u64).
+
+use crate::WorkloadMix;
+use rand::Rng;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::time::Duration;
+
+const MAGIC: &[u8; 4] = b"CWKL";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpKind {
+    Get,
+    Put,
+}
+
+impl OpKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            OpKind::Get => "GET",
+            OpKind::Put => "PUT",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Op {
+    pub kind: OpKind,
+    // Index of key_<n>
+    pub key: u64,
+    // 0 for gets
+    pub priority: u8,
+    // When the op was issued, from the start of the recorded run
+    pub at: Duration,
+}
+
+impl Op {
+    pub fn key(&self) -> String {
+        format!("key_{}", self.key)
+    }
+
+    pub fn value(&self) -> String {
+        format!("value_{}", self.key)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workload {
+    pub capacity: usize,
+    // Each worker's operations in the order it ran them
+    pub workers: Vec<Vec<Op>>,
+}
+
+// The shared workload's operations: puts cycle through the key space, gets pick keys
+// at random. Offsets are zero until a run records them.
+pub fn generate<R: Rng + ?Sized>(mix: WorkloadMix, num_operations: usize, rng: &mut R) -> Vec<Op> {
+    (0..num_operations)
+        .map(|i| {
+            if rng.gen::<f64>() < mix.write_ratio {
+                Op { kind: OpKind::Put, key: (i % mix.key_space) as u64, priority: rng.gen_range(1..=10), at: Duration::ZERO }
+            } else {
+                Op { kind: OpKind::Get, key: rng.gen_range(0..mix.key_space) as u64, priority: 0, at: Duration::ZERO }
+            }
+        })
+        .collect()
+}
+
+impl Workload {
+    pub fn len(&self) -> usize {
+        self.workers.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let file = std::fs::File::create(path).map_err(|err| format!("cannot write workload {}: {}", path, err))?;
+        let mut out = BufWriter::new(file);
+        let mut write = || -> std::io::Result<()> {
+            out.write_all(MAGIC)?;
+            out.write_all(&VERSION.to_le_bytes())?;
+            out.write_all(&(self.capacity as u64).to_le_bytes())?;
+            out.write_all(&(self.workers.len() as u64).to_le_bytes())?;
+            for ops in &self.workers {
+                out.write_all(&(ops.len() as u64).to_le_bytes())?;
+                for op in ops {
+                    out.write_all(&[op.kind as u8, op.priority])?;
+                    out.write_all(&op.key.to_le_bytes())?;
+                    out.write_all(&(op.at.as_nanos() as u64).to_le_bytes())?;
+                }
+            }
+            out.flush()
+        };
+        write().map_err(|err| format!("cannot write workload {}: {}", path, err))
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|err| format!("cannot read workload {}: {}", path, err))?;
+        let mut input = BufReader::new(file);
+        let invalid = |what: &str| format!("invalid workload {}: {}", path, what);
+        let truncated = |_| invalid("file is truncated");
+
+        let mut magic = [0; 4];
+        input.read_exact(&mut magic).map_err(truncated)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a recorded workload"));
+        }
+        let version = read_u32(&mut input).map_err(truncated)?;
+        if version != VERSION {
+            return Err(invalid(&format!("version {} is not supported", version)));
+        }
+        let capacity = read_u64(&mut input).map_err(truncated)? as usize;
+        let num_workers = read_u64(&mut input).map_err(truncated)?;
+
+        let mut workers = Vec::new();
+        for _ in 0..num_workers {
+            let count = read_u64(&mut input).map_err(truncated)?;
+            let mut ops = Vec::new();
+            for _ in 0..count {
+                let mut head = [0; 2];
+                input.read_exact(&mut head).map_err(truncated)?;
+                let kind = match head[0] {
+                    0 => OpKind::Get,
+                    1 => OpKind::Put,
+                    other => return Err(invalid(&format!("unknown operation {}", other))),
+                };
+                let key = read_u64(&mut input).map_err(truncated)?;
+                let at = Duration::from_nanos(read_u64(&mut input).map_err(truncated)?);
+                ops.push(Op { kind, key, priority: head[1], at });
+            }
+            workers.push(ops);
+        }
+        Ok(Workload { capacity, workers })
+    }
+}
+
+// Where --record PATH saves an implementation's run: workload.bin becomes
+// workload_qwen30b.bin
+pub fn record_path(path: &str, implementation: &str) -> String {
+    let path = std::path::Path::new(path);
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("workload");
+    let name = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => format!("{}_{}.{}", stem, implementation.to_lowercase(), extension),
+        None => format!("{}_{}", stem, implementation.to_lowercase()),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn read_u32(input: &mut impl Read) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &mut impl Read) -> std::io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
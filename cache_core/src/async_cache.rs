@@ -0,0 +1,129 @@
+// Async access for async frameworks. Every implementation is synchronous, so
+// Blocking runs each call on the runtime's blocking pool (spawn_blocking) and
+// lock waits never stall an executor thread. It must be used inside a Tokio runtime.
+//
+//     let cache = Blocking::from(Backend::Qwen435B.build(10_000));
+//     let page = cache.get_or_compute(url, None, 5, || fetch(url)).await?;
+
+use crate::{DynCache, Key, Value};
+use std::future::Future;
+use std::panic;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub trait AsyncCache: Send + Sync {
+    fn get(&self, key: &str) -> impl Future<Output = Option<Value>> + Send;
+    fn put(&self, key: Key, value: Value, ttl: Option<Duration>, priority: u8) -> impl Future<Output = bool> + Send;
+    fn delete(&self, key: &str) -> impl Future<Output = bool> + Send;
+
+    // Read-through: a miss awaits compute and caches its value. A failed compute
+    // caches nothing and passes the error on. Concurrent misses on one key each compute.
+    fn get_or_compute<F, Fut, E>(
+        &self,
+        key: &str,
+        ttl: Option<Duration>,
+        priority: u8,
+        compute: F,
+    ) -> impl Future<Output = Result<Value, E>> + Send
+    where
+        F: FnOnce() -> Fut + Send,
+        Fut: Future<Output = Result<Value, E>> + Send,
+    {
+        async move {
+            if let Some(value) = self.get(key).await {
+                return Ok(value);
+            }
+            let value = compute().await?;
+            self.put(key.to_string(), value.clone(), ttl, priority).await;
+            Ok(value)
+        }
+    }
+}
+
+// Any synchronous cache behind AsyncCache
+pub struct Blocking<C: ?Sized> {
+    inner: Arc<C>,
+}
+
+impl<C: DynCache + ?Sized + 'static> Blocking<C> {
+    pub fn new(cache: Arc<C>) -> Self {
+        Blocking { inner: cache }
+    }
+
+    // The wrapped cache, for calls that are cheap enough to make directly
+    pub fn inner(&self) -> &Arc<C> {
+        &self.inner
+    }
+
+    async fn run<T, F>(&self, call: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&C) -> T + Send + 'static,
+    {
+        let cache = Arc::clone(&self.inner);
+        match tokio::task::spawn_blocking(move || call(&cache)).await {
+            Ok(result) => result,
+            // A panic in the cache surfaces in the caller, as it would without the adapter
+            Err(err) => panic::resume_unwind(err.into_panic()),
+        }
+    }
+}
+
+// What Backend::build returns
+impl From<Box<dyn DynCache>> for Blocking<dyn DynCache> {
+    fn from(cache: Box<dyn DynCache>) -> Self {
+        Blocking { inner: Arc::from(cache) }
+    }
+}
+
+impl<C: ?Sized> Clone for Blocking<C> {
+    fn clone(&self) -> Self {
+        Blocking { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<C: DynCache + ?Sized + 'static> AsyncCache for Blocking<C> {
+    async fn get(&self, key: &str) -> Option<Value> {
+        let key = key.to_string();
+        self.run(move |cache| cache.get(&key)).await
+    }
+
+    async fn put(&self, key: Key, value: Value, ttl: Option<Duration>, priority: u8) -> bool {
+        self.run(move |cache| cache.put(key, value, ttl, priority)).await
+    }
+
+    async fn delete(&self, key: &str) -> bool {
+        let key = key.to_string();
+        self.run(move |cache| cache.delete(&key)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Backend;
+
+    #[tokio::test]
+    async fn test_blocking_adapter() {
+        for backend in Backend::ALL {
+            let cache = Blocking::from(backend.build(10));
+            assert!(cache.put("a".to_string(), vec![1], None, 5).await);
+            assert_eq!(cache.get("a").await, Some(vec![1]), "{}", backend);
+            assert!(cache.delete("a").await);
+            assert_eq!(cache.get("a").await, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_or_compute() {
+        let cache = Blocking::from(Backend::Qwen435B.build(10));
+        let failed: Result<Value, &str> = cache.get_or_compute("a", None, 5, || async { Err("unavailable") }).await;
+        assert_eq!(failed, Err("unavailable"));
+        assert!(!cache.inner().contains_key("a"), "a failed compute caches nothing");
+
+        let computed = cache.get_or_compute("a", None, 5, || async { Ok::<_, ()>(vec![7]) }).await;
+        assert_eq!(computed, Ok(vec![7]));
+        let cached = cache.get_or_compute("a", None, 5, || async { Err("not called") }).await;
+        assert_eq!(cached, Ok(vec![7]));
+    }
+}
@@ -0,0 +1,248 @@
+// Value types that keep small payloads inside the cache entry. A Vec<u8> or String
+// value costs a heap allocation per entry however short it is, and another on every
+// get that clones it out. CompactBytes and CompactString store up to N bytes (64 by
+// default, at most 255) inline and only allocate past that; cloning an inline value
+// is a copy. They work as the value type of any of the caches:
+//
+//     let cache: SmartCache<u64, CompactString> = SmartCache::new(100_000);
+//     cache.put(42, CompactString::from("a1b2c3d4"), None, 5);
+//
+// The inline buffer is always N bytes, so N trades entry size for how many values
+// avoid the heap; pick it just above the common value length.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+#[derive(Clone, Copy)]
+struct InlineBuf<const N: usize> {
+    len: u8,
+    bytes: [u8; N],
+}
+
+impl<const N: usize> InlineBuf<N> {
+    fn new(bytes: &[u8]) -> Option<Self> {
+        const { assert!(N <= u8::MAX as usize, "inline capacity is at most 255 bytes") };
+        if bytes.len() > N {
+            return None;
+        }
+        let mut buf = [0; N];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Some(InlineBuf { len: bytes.len() as u8, bytes: buf })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.bytes[..usize::from(self.len)]
+    }
+}
+
+#[derive(Clone)]
+enum Repr<const N: usize> {
+    Inline(InlineBuf<N>),
+    Heap(Box<[u8]>),
+}
+
+#[derive(Clone)]
+pub struct CompactBytes<const N: usize = 64> {
+    repr: Repr<N>,
+}
+
+impl<const N: usize> CompactBytes<N> {
+    pub fn new(bytes: &[u8]) -> Self {
+        let repr = match InlineBuf::new(bytes) {
+            Some(inline) => Repr::Inline(inline),
+            None => Repr::Heap(bytes.into()),
+        };
+        CompactBytes { repr }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        match &self.repr {
+            Repr::Inline(inline) => inline.as_slice(),
+            Repr::Heap(bytes) => bytes,
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline(_))
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        match self.repr {
+            Repr::Inline(inline) => inline.as_slice().to_vec(),
+            Repr::Heap(bytes) => bytes.into_vec(),
+        }
+    }
+}
+
+impl<const N: usize> Default for CompactBytes<N> {
+    fn default() -> Self {
+        CompactBytes::new(&[])
+    }
+}
+
+impl<const N: usize> Deref for CompactBytes<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for CompactBytes<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> From<&[u8]> for CompactBytes<N> {
+    fn from(bytes: &[u8]) -> Self {
+        CompactBytes::new(bytes)
+    }
+}
+
+// A long Vec keeps its allocation (shrunk to fit); a short one is copied inline
+impl<const N: usize> From<Vec<u8>> for CompactBytes<N> {
+    fn from(bytes: Vec<u8>) -> Self {
+        match InlineBuf::new(&bytes) {
+            Some(inline) => CompactBytes { repr: Repr::Inline(inline) },
+            None => CompactBytes { repr: Repr::Heap(bytes.into_boxed_slice()) },
+        }
+    }
+}
+
+impl<const N: usize> From<CompactBytes<N>> for Vec<u8> {
+    fn from(bytes: CompactBytes<N>) -> Self {
+        bytes.into_vec()
+    }
+}
+
+impl<const N: usize> PartialEq for CompactBytes<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for CompactBytes<N> {}
+
+impl<const N: usize> Hash for CompactBytes<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<const N: usize> fmt::Debug for CompactBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+// CompactBytes that always hold UTF-8
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct CompactString<const N: usize = 64> {
+    bytes: CompactBytes<N>,
+}
+
+impl<const N: usize> CompactString<N> {
+    pub fn new(text: &str) -> Self {
+        CompactString { bytes: CompactBytes::new(text.as_bytes()) }
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(self.bytes.as_slice()).expect("CompactString is built from UTF-8")
+    }
+
+    pub fn is_inline(&self) -> bool {
+        self.bytes.is_inline()
+    }
+
+    pub fn into_string(self) -> String {
+        String::from_utf8(self.bytes.into_vec()).expect("CompactString is built from UTF-8")
+    }
+}
+
+impl<const N: usize> Deref for CompactString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for CompactString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> From<&str> for CompactString<N> {
+    fn from(text: &str) -> Self {
+        CompactString::new(text)
+    }
+}
+
+impl<const N: usize> From<String> for CompactString<N> {
+    fn from(text: String) -> Self {
+        CompactString { bytes: CompactBytes::from(text.into_bytes()) }
+    }
+}
+
+impl<const N: usize> From<CompactString<N>> for String {
+    fn from(text: CompactString<N>) -> Self {
+        text.into_string()
+    }
+}
+
+impl<const N: usize> PartialEq<str> for CompactString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for CompactString<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> fmt::Debug for CompactString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for CompactString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_up_to_capacity() {
+        let small: CompactBytes<8> = CompactBytes::from(vec![1; 8]);
+        let large: CompactBytes<8> = CompactBytes::from(vec![1; 9]);
+        assert!(small.is_inline());
+        assert!(!large.is_inline());
+        assert_eq!(small.len(), 8);
+        assert_eq!(large.into_vec(), vec![1; 9]);
+        assert_eq!(CompactBytes::<8>::new(&[1, 2]), CompactBytes::from(vec![1, 2]));
+
+        let id: CompactString = CompactString::from("order:7f3a9c");
+        assert!(id.is_inline());
+        assert_eq!(id, "order:7f3a9c");
+        assert_eq!(id.to_uppercase(), "ORDER:7F3A9C");
+        assert!(!CompactString::<4>::from("héllo").is_inline());
+        assert_eq!(String::from(CompactString::<4>::from("héllo")), "héllo");
+    }
+
+    #[test]
+    fn test_as_cache_value() {
+        let cache: qwen435b_cache::SmartCache<u64, CompactString> = qwen435b_cache::SmartCache::new(10);
+        cache.put(1, CompactString::from("a1b2c3"), None, 5);
+        assert_eq!(cache.get(&1).as_deref(), Some("a1b2c3"));
+    }
+}
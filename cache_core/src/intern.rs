@@ -0,0 +1,138 @@
+// Shared String keys. The caches clone a key into every structure that tracks the
+// entry (data map, LRU list or queue, access traces, events), so a String key is
+// copied several times per entry. An InternedKey is an Arc<str>: those clones are a
+// reference count bump, and a KeyInterner hands out the same allocation for equal
+// strings, so a key that is put again, or used in several caches, is stored once:
+//
+//     let keys = KeyInterner::new();
+//     let cache: SmartCache<InternedKey, Vec<u8>> = SmartCache::new(100_000);
+//     cache.put(keys.intern("user:1"), payload, None, 5);
+//     cache.get("user:1");
+//
+// The interner keeps every string it has handed out until purge() drops the ones
+// nothing else holds anymore.
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InternedKey(Arc<str>);
+
+impl InternedKey {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // Whether both keys are the same allocation, not just equal
+    pub fn ptr_eq(&self, other: &InternedKey) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+// Hash and Eq come from the str, so lookups can borrow the key as &str
+impl Borrow<str> for InternedKey {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for InternedKey {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// An uninterned key, for callers that don't share a KeyInterner
+impl From<&str> for InternedKey {
+    fn from(key: &str) -> Self {
+        InternedKey(Arc::from(key))
+    }
+}
+
+impl From<String> for InternedKey {
+    fn from(key: String) -> Self {
+        InternedKey(Arc::from(key))
+    }
+}
+
+impl fmt::Debug for InternedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for InternedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Default)]
+pub struct KeyInterner {
+    keys: Mutex<HashSet<Arc<str>>>,
+}
+
+impl KeyInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&self, key: &str) -> InternedKey {
+        let mut keys = self.keys.lock().unwrap();
+        if let Some(existing) = keys.get(key) {
+            return InternedKey(existing.clone());
+        }
+        let key: Arc<str> = Arc::from(key);
+        keys.insert(key.clone());
+        InternedKey(key)
+    }
+
+    // Drops the strings only the interner still holds; returns how many
+    pub fn purge(&self) -> usize {
+        let mut keys = self.keys.lock().unwrap();
+        let before = keys.len();
+        keys.retain(|key| Arc::strong_count(key) > 1);
+        before - keys.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_keys_share_one_allocation() {
+        let keys = KeyInterner::new();
+        let a = keys.intern("user:1");
+        let b = keys.intern(&format!("user:{}", 1));
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&InternedKey::from("user:1")));
+        assert_eq!(a, InternedKey::from("user:1"));
+        assert_eq!(keys.len(), 1);
+    }
+
+    #[test]
+    fn test_purge_keeps_keys_held_by_a_cache() {
+        let keys = KeyInterner::new();
+        let cache: qwen30b_cache::SmartCache<InternedKey, u32> = qwen30b_cache::SmartCache::new(10);
+        cache.put(keys.intern("a"), 1, None, 5);
+        cache.put(keys.intern("b"), 2, None, 5);
+        cache.put(keys.intern("a"), 3, None, 5);
+        assert_eq!(cache.get("a"), Some(3));
+        assert_eq!(keys.purge(), 0);
+
+        cache.delete("b");
+        assert_eq!(keys.purge(), 1);
+        assert_eq!(keys.len(), 1);
+    }
+}
@@ -0,0 +1,162 @@
+// Implementation-independent access to the Rust caches. DynCache is object safe and
+// fixed to String keys and byte values, so the backing implementation can be picked
+// from configuration at runtime and different implementations can sit side by side,
+// e.g. in a Vec<Box<dyn DynCache>>. AsyncCache (async_cache.rs) is the same surface
+// for async code, compact.rs has value types that keep small values inline, and
+// intern.rs has String keys that share one allocation across clones.
+//
+//     let backend: Backend = config.backend.parse()?;
+//     let cache = backend.build(10_000);
+//     cache.put("user:1".to_string(), payload, None, 5);
+
+pub mod async_cache;
+pub mod compact;
+pub mod intern;
+
+pub use async_cache::{AsyncCache, Blocking};
+pub use cache_types::{FrozenCache, FrozenEntry};
+pub use compact::{CompactBytes, CompactString};
+pub use intern::{InternedKey, KeyInterner};
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub type Key = String;
+pub type Value = Vec<u8>;
+
+pub trait DynCache: Send + Sync {
+    // The implementation name, as the benchmark suite prints it
+    fn name(&self) -> &'static str;
+    fn put(&self, key: Key, value: Value, ttl: Option<Duration>, priority: u8) -> bool;
+    fn get(&self, key: &str) -> Option<Value>;
+    fn delete(&self, key: &str) -> bool;
+    fn contains_key(&self, key: &str) -> bool;
+    fn len(&self) -> usize;
+    fn clear(&self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// The calls that look the same in every implementation
+macro_rules! impl_dyn_cache {
+    ($cache:ty, $name:literal, $len:ident) => {
+        impl DynCache for $cache {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn put(&self, key: Key, value: Value, ttl: Option<Duration>, priority: u8) -> bool {
+                Self::put(self, key, value, ttl, priority)
+            }
+
+            fn get(&self, key: &str) -> Option<Value> {
+                Self::get(self, key)
+            }
+
+            fn delete(&self, key: &str) -> bool {
+                Self::delete(self, key)
+            }
+
+            fn contains_key(&self, key: &str) -> bool {
+                Self::contains_key(self, key)
+            }
+
+            fn len(&self) -> usize {
+                Self::$len(self)
+            }
+
+            fn clear(&self) {
+                Self::clear(self)
+            }
+        }
+    };
+}
+
+impl_dyn_cache!(qwen30b_cache::SmartCache<Key, Value>, "Qwen30B", size);
+impl_dyn_cache!(qwen235b_cache::SmartCache<Key, Value>, "Qwen235B", len);
+impl_dyn_cache!(qwen435b_cache::SmartCache<Key, Value>, "Qwen435B", len);
+impl_dyn_cache!(glm45_cache::SmartCache<Key, Value>, "GLM45", size);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    Qwen30B,
+    Qwen235B,
+    Qwen435B,
+    Glm45,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 4] = [Backend::Qwen30B, Backend::Qwen235B, Backend::Qwen435B, Backend::Glm45];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Qwen30B => "Qwen30B",
+            Backend::Qwen235B => "Qwen235B",
+            Backend::Qwen435B => "Qwen435B",
+            Backend::Glm45 => "GLM45",
+        }
+    }
+
+    pub fn build(self, max_capacity: usize) -> Box<dyn DynCache> {
+        match self {
+            Backend::Qwen30B => Box::new(qwen30b_cache::SmartCache::<Key, Value>::new(max_capacity)),
+            Backend::Qwen235B => Box::new(qwen235b_cache::SmartCache::<Key, Value>::new(max_capacity)),
+            Backend::Qwen435B => Box::new(qwen435b_cache::SmartCache::<Key, Value>::new(max_capacity)),
+            Backend::Glm45 => Box::new(glm45_cache::SmartCache::<Key, Value>::new(max_capacity)),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Case-insensitive, so configuration can say "qwen435b"
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Backend::ALL
+            .into_iter()
+            .find(|backend| backend.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let known: Vec<_> = Backend::ALL.iter().map(|backend| backend.name()).collect();
+                format!("unknown cache backend {:?}, expected one of {:?}", name, known)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_backend_behind_dyn_cache() {
+        let caches: Vec<Box<dyn DynCache>> = Backend::ALL.iter().map(|backend| backend.build(10)).collect();
+        for (cache, backend) in caches.iter().zip(Backend::ALL) {
+            assert_eq!(cache.name(), backend.name());
+            assert!(cache.put("a".to_string(), vec![1, 2, 3], None, 5));
+            assert_eq!(cache.get("a"), Some(vec![1, 2, 3]), "{}", backend);
+            assert!(cache.contains_key("a"));
+            assert_eq!(cache.len(), 1, "{}", backend);
+
+            assert!(cache.delete("a"));
+            assert_eq!(cache.get("a"), None);
+            cache.put("b".to_string(), Vec::new(), None, 5);
+            cache.clear();
+            assert!(cache.is_empty(), "{}", backend);
+        }
+    }
+
+    #[test]
+    fn test_backend_from_config_name() {
+        assert_eq!("qwen435b".parse(), Ok(Backend::Qwen435B));
+        assert_eq!("GLM45".parse(), Ok(Backend::Glm45));
+        assert!("redis".parse::<Backend>().unwrap_err().contains("Qwen30B"));
+    }
+}
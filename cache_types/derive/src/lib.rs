@@ -0,0 +1,112 @@
+// #[derive(MemSize)] for cache_types: heap_size sums the heap sizes of all the
+// fields, so each field's type must implement MemSize too. A field marked
+// #[mem_size(skip)] is left out, e.g. a handle shared with the rest of the program
+// or a type from another crate that has no impl. Type parameters get a MemSize
+// bound. The expansion names ::cache_types, so a crate using the derive depends
+// on cache_types itself.
+//
+//     #[derive(MemSize)]
+//     struct Profile {
+//         name: String,
+//         tags: Vec<String>,
+//         #[mem_size(skip)]
+//         client: Arc<Client>,
+//     }
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Fields, GenericParam};
+
+#[proc_macro_derive(MemSize, attributes(mem_size))]
+pub fn derive_mem_size(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn skipped(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("mem_size")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown setting; expected skip"))
+            }
+        })?;
+    }
+    Ok(skip)
+}
+
+// A pattern binding the fields that count, and the sum of their heap sizes
+fn measure(fields: &Fields) -> syn::Result<(TokenStream2, TokenStream2)> {
+    let mut bindings = Vec::new();
+    let mut counted = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let skip = skipped(&field.attrs)?;
+        let binding = match &field.ident {
+            Some(name) if skip => quote!(#name: _),
+            Some(name) => {
+                counted.push(name.clone());
+                quote!(#name)
+            }
+            None if skip => quote!(_),
+            None => {
+                let name = format_ident!("field{}", index);
+                counted.push(name.clone());
+                quote!(#name)
+            }
+        };
+        bindings.push(binding);
+    }
+
+    let pattern = match fields {
+        Fields::Named(_) => quote!({ #(#bindings),* }),
+        Fields::Unnamed(_) => quote!(( #(#bindings),* )),
+        Fields::Unit => quote!(),
+    };
+    let size = quote!(0 #(+ ::cache_types::MemSize::heap_size(#counted))*);
+    Ok((pattern, size))
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(::cache_types::MemSize));
+        }
+    }
+
+    let arms: Vec<TokenStream2> = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, size) = measure(&data.fields)?;
+            vec![quote!(Self #pattern => #size)]
+        }
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let name = &variant.ident;
+                let (pattern, size) = measure(&variant.fields)?;
+                Ok(quote!(Self::#name #pattern => #size))
+            })
+            .collect::<syn::Result<_>>()?,
+        Data::Union(_) => return Err(Error::new(input.ident.span(), "MemSize cannot be derived for unions")),
+    };
+    // An enum without variants has no value to measure
+    let body = if arms.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote!(match self { #(#arms,)* })
+    };
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::cache_types::MemSize for #name #type_generics #where_clause {
+            fn heap_size(&self) -> usize {
+                #body
+            }
+        }
+    })
+}
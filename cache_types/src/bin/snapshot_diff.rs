@@ -0,0 +1,102 @@
+// Compare two snapshot files written by save_snapshot or the snapshot scheduler
+// of any implementation.
+// Usage: snapshot_diff <before> <after>
+//
+// Keys and values are read as plain JSON, so snapshots of any key/value type work.
+
+use cache_types::snapshot::{diff_snapshots, read_snapshot, Snapshot, SnapshotError};
+use cache_types::ExportedEntry;
+use serde_json::Value;
+use std::env;
+use std::path::Path;
+use std::process;
+
+// JSON values are not hashable, so keys are compared by their serialized form
+fn load(path: &Path) -> Result<Snapshot<String, Value>, SnapshotError> {
+    let snapshot: Snapshot<Value, Value> = read_snapshot(path)?;
+    Ok(Snapshot {
+        version: snapshot.version,
+        created_at_unix_ms: snapshot.created_at_unix_ms,
+        entries: snapshot
+            .entries
+            .into_iter()
+            .map(|entry| ExportedEntry {
+                key: entry.key.to_string(),
+                value: entry.value,
+                priority: entry.priority,
+                ttl_remaining: entry.ttl_remaining,
+                access_count: entry.access_count,
+            })
+            .collect(),
+    })
+}
+
+fn describe(entry: &ExportedEntry<String, Value>) -> String {
+    format!(
+        "{} = {} (priority {}, ttl {:.1}s, accesses {})",
+        entry.key,
+        entry.value,
+        entry.priority,
+        entry.ttl_remaining.as_secs_f64(),
+        entry.access_count
+    )
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <before> <after>", args[0]);
+        process::exit(2);
+    }
+
+    let (before, after) = match (load(Path::new(&args[1])), load(Path::new(&args[2]))) {
+        (Ok(before), Ok(after)) => (before, after),
+        (Err(err), _) | (_, Err(err)) => {
+            eprintln!("error: {}", err);
+            process::exit(2);
+        }
+    };
+
+    let diff = diff_snapshots(&before, &after);
+    println!(
+        "{} -> {} ({:.1}s apart)",
+        args[1],
+        args[2],
+        (after.created_at_unix_ms as f64 - before.created_at_unix_ms as f64) / 1000.0
+    );
+    println!(
+        "added: {}, removed: {}, changed: {}, unchanged: {}",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        diff.unchanged
+    );
+
+    for entry in &diff.added {
+        println!("+ {}", describe(entry));
+    }
+    for entry in &diff.removed {
+        println!("- {}", describe(entry));
+    }
+    for change in &diff.changed {
+        let mut details = Vec::new();
+        if change.value_changed {
+            details.push(format!("value {} -> {}", change.before.value, change.after.value));
+        }
+        if change.priority_delta != 0 {
+            details.push(format!("priority {:+}", change.priority_delta));
+        }
+        if change.expiry_shift_ms != 0 {
+            details.push(format!("expiry {:+.1}s", change.expiry_shift_ms as f64 / 1000.0));
+        }
+        if change.access_count_delta != 0 {
+            details.push(format!("accesses {:+}", change.access_count_delta));
+        }
+        println!("~ {}: {}", change.key, details.join(", "));
+    }
+
+    // Mirror diff(1): non-zero when the snapshots differ
+    if !diff.is_empty() {
+        process::exit(1);
+    }
+}
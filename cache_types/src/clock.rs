@@ -0,0 +1,44 @@
+// The caches' time source. Built with the mock-clock feature, advance() moves
+// it forward for every cache in the process, so tests can expire entries
+// without sleeping; otherwise now() is Instant::now().
+//
+// advance_thread() moves only the calling thread's clock. The implementations'
+// own unit tests, which run on parallel threads, use it so one test's jumps
+// don't expire another's entries; background threads keep real time there.
+
+use std::time::Instant;
+#[cfg(feature = "mock-clock")]
+use std::cell::Cell;
+#[cfg(feature = "mock-clock")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "mock-clock")]
+use std::time::Duration;
+
+#[cfg(feature = "mock-clock")]
+static OFFSET_NANOS: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "mock-clock")]
+thread_local! {
+    static THREAD_OFFSET: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+#[cfg(not(feature = "mock-clock"))]
+#[inline]
+pub fn now() -> Instant {
+    Instant::now()
+}
+
+#[cfg(feature = "mock-clock")]
+pub fn now() -> Instant {
+    Instant::now() + Duration::from_nanos(OFFSET_NANOS.load(Ordering::Relaxed)) + THREAD_OFFSET.with(Cell::get)
+}
+
+#[cfg(feature = "mock-clock")]
+pub fn advance(by: Duration) {
+    OFFSET_NANOS.fetch_add(by.as_nanos() as u64, Ordering::Relaxed);
+}
+
+#[cfg(feature = "mock-clock")]
+pub fn advance_thread(by: Duration) {
+    THREAD_OFFSET.with(|offset| offset.set(offset.get() + by));
+}
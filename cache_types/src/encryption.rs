@@ -0,0 +1,127 @@
+// Chunked AES-256-GCM for snapshots at rest.
+//
+// Layout: MAGIC | key id (u32 LE) | nonce prefix (8 bytes) | chunks, each stored as
+// ciphertext length (u32 LE) + ciphertext. Chunk i is sealed with nonce prefix || i
+// and authenticates the header, its index and whether it is the final chunk, so
+// edited, reordered, dropped or appended chunks and truncated files all fail to open.
+
+use crate::snapshot::SnapshotError;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+
+const MAGIC: &[u8; 8] = b"CSNAPAE1";
+const HEADER_LEN: usize = MAGIC.len() + 4 + 8;
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Supplies AES-256 keys; the key id is written into each file so keys can rotate
+pub trait KeyProvider: Send + Sync {
+    // Key for new snapshots
+    fn current_key(&self) -> Result<(u32, [u8; 32]), String>;
+    // Key for reading a snapshot written under `key_id`
+    fn key(&self, key_id: u32) -> Result<[u8; 32], String>;
+}
+
+// One fixed key, e.g. fetched from a secrets manager at startup
+pub struct StaticKeyProvider {
+    key_id: u32,
+    key: [u8; 32],
+}
+
+impl StaticKeyProvider {
+    pub fn new(key_id: u32, key: [u8; 32]) -> Self {
+        Self { key_id, key }
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key(&self) -> Result<(u32, [u8; 32]), String> {
+        Ok((self.key_id, self.key))
+    }
+
+    fn key(&self, key_id: u32) -> Result<[u8; 32], String> {
+        if key_id == self.key_id {
+            Ok(self.key)
+        } else {
+            Err(format!("unknown key id {}", key_id))
+        }
+    }
+}
+
+fn chunk_nonce(header: &[u8], index: usize) -> Nonce<aes_gcm::aead::consts::U12> {
+    let mut nonce = [0u8; 12];
+    nonce[..8].copy_from_slice(&header[MAGIC.len() + 4..HEADER_LEN]);
+    nonce[8..].copy_from_slice(&(index as u32).to_be_bytes());
+    Nonce::clone_from_slice(&nonce)
+}
+
+fn chunk_aad(header: &[u8], index: usize, last: bool) -> Vec<u8> {
+    let mut aad = header.to_vec();
+    aad.extend_from_slice(&(index as u32).to_be_bytes());
+    aad.push(last as u8);
+    aad
+}
+
+pub(crate) fn seal(plaintext: &[u8], keys: &dyn KeyProvider) -> Result<Vec<u8>, SnapshotError> {
+    let (key_id, key) = keys.current_key().map_err(SnapshotError::Encryption)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+
+    let mut sealed = Vec::with_capacity(HEADER_LEN + plaintext.len() + plaintext.len() / CHUNK_SIZE * 20 + 20);
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&key_id.to_le_bytes());
+    sealed.extend_from_slice(&Aes256Gcm::generate_nonce(&mut OsRng)[..8]);
+    let header = sealed.clone();
+
+    // An empty payload still gets one chunk, so there is always a final chunk
+    let chunks: Vec<&[u8]> = if plaintext.is_empty() {
+        vec![plaintext]
+    } else {
+        plaintext.chunks(CHUNK_SIZE).collect()
+    };
+    let last = chunks.len() - 1;
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let aad = chunk_aad(&header, index, index == last);
+        let ciphertext = cipher
+            .encrypt(&chunk_nonce(&header, index), Payload { msg: chunk, aad: &aad })
+            .map_err(|_| SnapshotError::Encryption(format!("failed to encrypt chunk {}", index)))?;
+        sealed.extend_from_slice(&(ciphertext.len() as u32).to_le_bytes());
+        sealed.extend_from_slice(&ciphertext);
+    }
+    Ok(sealed)
+}
+
+pub(crate) fn open(sealed: &[u8], keys: &dyn KeyProvider) -> Result<Vec<u8>, SnapshotError> {
+    if sealed.len() < HEADER_LEN || &sealed[..MAGIC.len()] != MAGIC {
+        return Err(SnapshotError::Encryption("not an encrypted snapshot".to_string()));
+    }
+    let (header, mut rest) = sealed.split_at(HEADER_LEN);
+    let key_id = u32::from_le_bytes(header[MAGIC.len()..MAGIC.len() + 4].try_into().unwrap());
+    let key = keys.key(key_id).map_err(SnapshotError::Encryption)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+
+    let truncated = || SnapshotError::Encryption("encrypted snapshot is truncated".to_string());
+    let mut plaintext = Vec::new();
+    for index in 0.. {
+        if rest.len() < 4 {
+            return Err(truncated());
+        }
+        let (length, tail) = rest.split_at(4);
+        let length = u32::from_le_bytes(length.try_into().unwrap()) as usize;
+        if tail.len() < length {
+            return Err(truncated());
+        }
+        let (ciphertext, tail) = tail.split_at(length);
+        let last = tail.is_empty();
+
+        let aad = chunk_aad(header, index, last);
+        let chunk = cipher
+            .decrypt(&chunk_nonce(header, index), Payload { msg: ciphertext, aad: &aad })
+            .map_err(|_| SnapshotError::Encryption(format!("chunk {} failed authentication", index)))?;
+        plaintext.extend_from_slice(&chunk);
+
+        if last {
+            break;
+        }
+        rest = tail;
+    }
+    Ok(plaintext)
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Portable entry record for moving cache contents between processes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedEntry<K, V> {
+    pub key: K,
+    pub value: V,
+    pub priority: u8,
+    pub ttl_remaining: Duration,
+    pub access_count: usize,
+}
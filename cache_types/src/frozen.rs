@@ -0,0 +1,139 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// A read-only copy of the live entries, from freeze(). Reads take no locks and
+// leave the cache alone: no recency or access updates, no stats, and entries
+// stay readable past their TTL. Clones share the one copy, so it can be handed
+// to other threads cheaply. Each implementation decides what counts as live
+// when it builds the copy.
+#[derive(Debug)]
+pub struct FrozenCache<K, V> {
+    entries: Arc<HashMap<K, FrozenEntry<V>>>,
+    frozen_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct FrozenEntry<V> {
+    pub value: V,
+    pub priority: u8,
+    pub expires_at: Instant,
+}
+
+impl<K, V> Clone for FrozenCache<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+            frozen_at: self.frozen_at,
+        }
+    }
+}
+
+impl<K, V> FrozenCache<K, V> {
+    pub fn new(frozen_at: Instant, entries: HashMap<K, FrozenEntry<V>>) -> Self {
+        Self { entries: Arc::new(entries), frozen_at }
+    }
+}
+
+impl<K: Eq + Hash, V> FrozenCache<K, V> {
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|entry| &entry.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.contains_key(key)
+    }
+
+    pub fn priority<Q>(&self, key: &Q) -> Option<u8>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|entry| entry.priority)
+    }
+
+    // Time the entry had left to live when frozen
+    pub fn ttl_remaining<Q>(&self, key: &Q) -> Option<Duration>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|entry| entry.expires_at.saturating_duration_since(self.frozen_at))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, entry)| (key, &entry.value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn frozen_at(&self) -> Instant {
+        self.frozen_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn frozen() -> FrozenCache<u32, String> {
+        let frozen_at = Instant::now();
+        let entry = |value: &str, priority, ttl| FrozenEntry {
+            value: value.to_string(),
+            priority,
+            expires_at: frozen_at + ttl,
+        };
+        let entries = HashMap::from([
+            (1, entry("one", 7, Duration::from_secs(3600))),
+            (2, entry("two", 5, Duration::from_millis(15))),
+        ]);
+        FrozenCache::new(frozen_at, entries)
+    }
+
+    #[test]
+    fn test_lookups() {
+        let frozen = frozen();
+        assert_eq!(frozen.len(), 2);
+        assert!(!frozen.is_empty());
+        assert_eq!(frozen.get(&1).map(String::as_str), Some("one"));
+        assert_eq!(frozen.get(&3), None);
+        assert!(frozen.contains_key(&2));
+        assert_eq!(frozen.priority(&1), Some(7));
+        assert_eq!(frozen.ttl_remaining(&2), Some(Duration::from_millis(15)));
+        assert_eq!(frozen.ttl_remaining(&3), None);
+        assert!(FrozenCache::<u32, String>::new(Instant::now(), HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_clones_share_one_copy() {
+        let frozen = frozen();
+        let reader = frozen.clone();
+        assert!(Arc::ptr_eq(&frozen.entries, &reader.entries));
+        let values = thread::spawn(move || {
+            let mut values: Vec<String> = reader.iter().map(|(_, value)| value.clone()).collect();
+            values.sort();
+            values
+        })
+        .join()
+        .unwrap();
+        assert_eq!(values, vec!["one".to_string(), "two".to_string()]);
+        assert_eq!(frozen.frozen_at(), frozen.clone().frozen_at());
+    }
+}
@@ -0,0 +1,28 @@
+use std::time::{Duration, Instant};
+
+// Point-in-time diagnostics from an implementation's health(), for readiness probes
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthReport {
+    // False once the cleanup thread has died, e.g. from a panic, or was shut down
+    pub cleanup_alive: bool,
+    // When the cleanup thread last finished a pass; None before the first one
+    pub last_cleanup: Option<Instant>,
+    // No pass for more than two cleanup intervals while maintenance is not paused
+    pub cleanup_overdue: bool,
+    // Events waiting for a dispatcher; 0 without an async event queue
+    pub event_queue_depth: usize,
+    // How long health() waited for the entry map's read lock (the longest wait
+    // for any one lock where the map is sharded)
+    pub lock_wait: Duration,
+    // Stored entries, expired ones included
+    pub entries: usize,
+    pub capacity: usize,
+    // Entries past their TTL or invalidated that cleanup has not removed yet
+    pub expired_unreclaimed: usize,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.cleanup_alive && !self.cleanup_overdue
+    }
+}
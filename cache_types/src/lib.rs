@@ -0,0 +1,29 @@
+// Types shared by the Rust implementations. cache_core depends on every
+// implementation, so anything the implementations themselves return has to
+// live below them, here, and cache_core re-exports it.
+
+// Lets #[derive(MemSize)] name this crate from inside it as well
+extern crate self as cache_types;
+
+pub mod clock;
+mod encryption;
+mod exported;
+pub mod frozen;
+mod health;
+pub mod memsize;
+mod priority;
+pub mod shared;
+pub mod snapshot;
+pub mod striped;
+mod ttl;
+
+pub use cache_types_derive::MemSize;
+pub use encryption::{KeyProvider, StaticKeyProvider};
+pub use exported::ExportedEntry;
+pub use frozen::{FrozenCache, FrozenEntry};
+pub use health::HealthReport;
+pub use memsize::MemSize;
+pub use priority::{PriorityBoost, PriorityDecay};
+pub use shared::Shared;
+pub use striped::StripedCounter;
+pub use ttl::TtlDistribution;
@@ -0,0 +1,174 @@
+// Approximate memory accounting for the caches' estimated_memory_bytes. heap_size is what
+// a value owns on the heap beyond its own size_of: a String's buffer, a Vec's
+// elements and whatever they own in turn. Allocator overhead and alignment
+// padding are not counted, so figures are estimates rather than exact usage.
+// #[derive(MemSize)] implements it for your own types.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::mem::size_of;
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub trait MemSize {
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! no_heap {
+    ($($ty:ty),*) => {
+        $(impl MemSize for $ty {
+            fn heap_size(&self) -> usize {
+                0
+            }
+        })*
+    };
+}
+
+no_heap!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, ());
+
+// Borrowed data is owned by someone else
+impl<T: ?Sized> MemSize for &T {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl MemSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: MemSize> MemSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<T: MemSize> MemSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + T::heap_size(self)
+    }
+}
+
+// Shared values are counted in full by every owner, so caching many clones of
+// one Arc overstates the total
+impl<T: MemSize> MemSize for Arc<T> {
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + T::heap_size(self)
+    }
+}
+
+impl<T: MemSize> MemSize for Rc<T> {
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + T::heap_size(self)
+    }
+}
+
+impl<T: MemSize> MemSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: MemSize> MemSize for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: MemSize, const N: usize> MemSize for [T; N] {
+    fn heap_size(&self) -> usize {
+        self.iter().map(T::heap_size).sum()
+    }
+}
+
+// Hash tables keep a control byte per slot next to the slots themselves
+impl<K: MemSize, V: MemSize, S> MemSize for HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * (size_of::<(K, V)>() + 1)
+            + self.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum::<usize>()
+    }
+}
+
+impl<T: MemSize, S> MemSize for HashSet<T, S> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * (size_of::<T>() + 1) + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+// B-tree nodes are not exposed; counted as one slot per element
+impl<K: MemSize, V: MemSize> MemSize for BTreeMap<K, V> {
+    fn heap_size(&self) -> usize {
+        self.iter().map(|(k, v)| size_of::<(K, V)>() + k.heap_size() + v.heap_size()).sum()
+    }
+}
+
+impl<T: MemSize> MemSize for BTreeSet<T> {
+    fn heap_size(&self) -> usize {
+        self.iter().map(|item| size_of::<T>() + item.heap_size()).sum()
+    }
+}
+
+macro_rules! tuple {
+    ($($name:ident),+) => {
+        impl<$($name: MemSize),+> MemSize for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn heap_size(&self) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.heap_size())+
+            }
+        }
+    };
+}
+
+tuple!(A);
+tuple!(A, B);
+tuple!(A, B, C);
+tuple!(A, B, C, D);
+tuple!(A, B, C, D, E);
+tuple!(A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemSize;
+
+    #[derive(MemSize)]
+    struct Profile {
+        name: String,
+        tags: Vec<u32>,
+        #[mem_size(skip)]
+        _shared: Arc<String>,
+    }
+
+    #[derive(MemSize)]
+    enum Payload<T> {
+        Empty,
+        One(T),
+        Many { items: Vec<T> },
+    }
+
+    #[test]
+    fn test_std_types() {
+        assert_eq!(7u32.heap_size(), 0);
+        assert_eq!(String::with_capacity(10).heap_size(), 10);
+        assert_eq!(Some(String::with_capacity(3)).heap_size(), 3);
+        assert_eq!(Arc::new(String::with_capacity(5)).heap_size(), size_of::<String>() + 5);
+        let mut map = HashMap::with_capacity(1);
+        map.insert(1u8, String::with_capacity(4));
+        assert_eq!(map.heap_size(), map.capacity() * (size_of::<(u8, String)>() + 1) + 4);
+    }
+
+    #[test]
+    fn test_derive() {
+        let profile = Profile {
+            name: String::with_capacity(16),
+            tags: Vec::with_capacity(4),
+            _shared: Arc::new("x".repeat(100)),
+        };
+        assert_eq!(profile.heap_size(), 16 + 4 * 4);
+        assert_eq!(Payload::<String>::Empty.heap_size(), 0);
+        assert_eq!(Payload::One(String::with_capacity(8)).heap_size(), 8);
+        assert_eq!(Payload::Many { items: vec![1u64, 2] }.heap_size(), 16);
+    }
+}
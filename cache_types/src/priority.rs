@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Priority aging (CacheConfig::priority_decay): while an entry goes unaccessed, its
+// priority halves its distance to `floor` every `half_life`, so a stale priority-10
+// entry cannot squat forever. Eviction uses the decayed value; the stored priority
+// is untouched and counts in full again from the next access.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriorityDecay {
+    pub half_life: Duration,
+    pub floor: u8,
+}
+
+impl PriorityDecay {
+    // `priority` after `idle` without an access
+    pub fn apply(&self, priority: u8, idle: Duration) -> f64 {
+        // Priorities already below the floor stay where they are
+        let floor = f64::from(self.floor.min(priority));
+        let half_lives = idle.as_secs_f64() / self.half_life.as_secs_f64().max(f64::MIN_POSITIVE);
+        floor + (f64::from(priority) - floor) * 0.5f64.powf(half_lives)
+    }
+}
+
+// Priority boosting (CacheConfig::priority_boost): an entry read more than
+// `min_rate` times a second on average over its life ranks one step above its
+// declared priority, plus one more for every doubling of that rate, up to
+// `max_boost`. Traffic corrects a low admission-time guess; the stored priority
+// is untouched. Entries younger than a second are rated as a second old.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriorityBoost {
+    pub min_rate: f64,
+    pub max_boost: u8,
+}
+
+impl PriorityBoost {
+    // Steps to add to the priority of an entry read `access_count` times over `age`
+    pub fn apply(&self, access_count: usize, age: Duration) -> f64 {
+        if access_count == 0 {
+            return 0.0;
+        }
+        let rate = access_count as f64 / age.as_secs_f64().max(1.0);
+        if rate < self.min_rate {
+            return 0.0;
+        }
+        ((rate / self.min_rate).log2().floor() + 1.0).min(f64::from(self.max_boost))
+    }
+}
@@ -0,0 +1,69 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+// State a fork() shares with its parent. Reads go through the Arc; the first
+// mutable access on either side while it is shared copies it for that side
+// (Arc::make_mut), and after that the side has its own copy again. Any mutable
+// access counts, even one that ends up changing nothing.
+pub struct Shared<T>(Arc<T>);
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Shared(Arc::new(value))
+    }
+
+    // Whether the two still share one copy
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Default> Default for Shared<T> {
+    fn default() -> Self {
+        Shared::new(T::default())
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone> DerefMut for Shared<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copies_on_first_write() {
+        let parent = Shared::new(vec![1, 2, 3]);
+        let mut fork = parent.clone();
+        assert!(Shared::ptr_eq(&parent, &fork));
+        assert_eq!(fork.len(), 3);
+        assert!(Shared::ptr_eq(&parent, &fork));
+
+        fork.push(4);
+        assert!(!Shared::ptr_eq(&parent, &fork));
+        assert_eq!(*parent, vec![1, 2, 3]);
+        assert_eq!(*fork, vec![1, 2, 3, 4]);
+
+        // Once unshared, writes go in place
+        let before: *const Vec<i32> = &*fork;
+        fork.push(5);
+        assert!(std::ptr::eq(before, &*fork));
+    }
+}
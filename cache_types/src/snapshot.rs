@@ -0,0 +1,487 @@
+// Snapshot files: the exported entries of a cache written to disk as JSON,
+// optionally gzip-compressed, plus the periodic scheduler's retention helpers.
+// Every implementation's save_snapshot/load_snapshot reads and writes this format,
+// so a snapshot taken from one cache loads into any other.
+//
+// Each entry is stored as its own JSON string with a CRC-32C, and the file carries a
+// checksum over the header and all records, so damage is detected per record.
+// Encrypted snapshots wrap the same (always gzipped) payload; see encryption.rs.
+
+use crate::encryption::{self, KeyProvider};
+use crate::ExportedEntry;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+const FILE_PREFIX: &str = "snapshot-";
+
+// Periodic snapshot settings (see CacheConfig::snapshot_schedule)
+#[derive(Clone)]
+pub struct SnapshotSchedule {
+    pub directory: PathBuf,
+    pub interval: Duration,
+    // Number of most recent snapshot files kept in `directory`
+    pub retain: usize,
+    pub compress: bool,
+    // When set, scheduled snapshots are encrypted (and always compressed)
+    pub encryption: Option<Arc<dyn KeyProvider>>,
+}
+
+impl fmt::Debug for SnapshotSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotSchedule")
+            .field("directory", &self.directory)
+            .field("interval", &self.interval)
+            .field("retain", &self.retain)
+            .field("compress", &self.compress)
+            .field("encrypted", &self.encryption.is_some())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Snapshot<K, V> {
+    pub version: u32,
+    pub created_at_unix_ms: u64,
+    pub entries: Vec<ExportedEntry<K, V>>,
+}
+
+impl<K, V> Snapshot<K, V> {
+    pub fn new(entries: Vec<ExportedEntry<K, V>>) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            created_at_unix_ms: unix_millis(SystemTime::now()),
+            entries,
+        }
+    }
+}
+
+// On-disk layout
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    version: u32,
+    created_at_unix_ms: u64,
+    records: Vec<SnapshotRecord>,
+    checksum: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotRecord {
+    data: String,
+    crc32c: u32,
+}
+
+// Result of checking a snapshot file; record indices are positions in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotVerification {
+    pub records: usize,
+    pub corrupt_records: Vec<usize>,
+    // False when records were altered, dropped or reordered, or the header changed
+    pub checksum_ok: bool,
+}
+
+impl SnapshotVerification {
+    pub fn is_valid(&self) -> bool {
+        self.checksum_ok && self.corrupt_records.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotLoadReport {
+    pub loaded: usize,
+    pub verification: SnapshotVerification,
+}
+
+// Outcome of one scheduled snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotEvent {
+    Saved { path: PathBuf, entries: usize },
+    Failed { path: PathBuf, error: String },
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Format(String),
+    UnsupportedVersion(u32),
+    Corrupt(SnapshotVerification),
+    // Missing key, wrong key or tampered ciphertext; nothing is loaded
+    Encryption(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "snapshot I/O failed: {}", err),
+            SnapshotError::Format(msg) => write!(f, "malformed snapshot: {}", msg),
+            SnapshotError::UnsupportedVersion(version) => write!(f, "unsupported snapshot version {}", version),
+            SnapshotError::Corrupt(report) => write!(
+                f,
+                "snapshot failed verification: {} of {} records corrupt, file checksum {}",
+                report.corrupt_records.len(),
+                report.records,
+                if report.checksum_ok { "ok" } else { "mismatch" }
+            ),
+            SnapshotError::Encryption(msg) => write!(f, "snapshot encryption: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(err: serde_json::Error) -> Self {
+        SnapshotError::Format(err.to_string())
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+fn file_checksum(version: u32, created_at_unix_ms: u64, records: &[SnapshotRecord]) -> u32 {
+    let mut crc = crc32c::crc32c(&version.to_le_bytes());
+    crc = crc32c::crc32c_append(crc, &created_at_unix_ms.to_le_bytes());
+    for record in records {
+        crc = crc32c::crc32c_append(crc, record.data.as_bytes());
+    }
+    crc
+}
+
+fn encode<K: Serialize, V: Serialize>(snapshot: &Snapshot<K, V>) -> Result<SnapshotFile, SnapshotError> {
+    let records = snapshot
+        .entries
+        .iter()
+        .map(|entry| {
+            let data = serde_json::to_string(entry)?;
+            let crc32c = crc32c::crc32c(data.as_bytes());
+            Ok(SnapshotRecord { data, crc32c })
+        })
+        .collect::<Result<Vec<_>, SnapshotError>>()?;
+
+    Ok(SnapshotFile {
+        version: snapshot.version,
+        created_at_unix_ms: snapshot.created_at_unix_ms,
+        checksum: file_checksum(snapshot.version, snapshot.created_at_unix_ms, &records),
+        records,
+    })
+}
+
+fn decode<K, V>(path: &Path) -> Result<(Snapshot<K, V>, SnapshotVerification), SnapshotError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    let file = BufReader::new(File::open(path)?);
+    if is_compressed(path) {
+        decode_from(GzDecoder::new(file))
+    } else {
+        decode_from(file)
+    }
+}
+
+fn decode_encrypted<K, V>(path: &Path, keys: &dyn KeyProvider) -> Result<(Snapshot<K, V>, SnapshotVerification), SnapshotError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    let payload = encryption::open(&fs::read(path)?, keys)?;
+    decode_from(GzDecoder::new(payload.as_slice()))
+}
+
+// Decode every record that passes its checksum; the rest are reported, not loaded
+fn decode_from<K, V, R>(reader: R) -> Result<(Snapshot<K, V>, SnapshotVerification), SnapshotError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+    R: Read,
+{
+    let file: SnapshotFile = serde_json::from_reader(reader)?;
+    if file.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(file.version));
+    }
+
+    let mut verification = SnapshotVerification {
+        records: file.records.len(),
+        corrupt_records: Vec::new(),
+        checksum_ok: file_checksum(file.version, file.created_at_unix_ms, &file.records) == file.checksum,
+    };
+    let mut entries = Vec::with_capacity(file.records.len());
+    for (index, record) in file.records.iter().enumerate() {
+        let entry = (crc32c::crc32c(record.data.as_bytes()) == record.crc32c)
+            .then(|| serde_json::from_str::<ExportedEntry<K, V>>(&record.data).ok())
+            .flatten();
+        match entry {
+            Some(entry) => entries.push(entry),
+            None => verification.corrupt_records.push(index),
+        }
+    }
+
+    let snapshot = Snapshot {
+        version: file.version,
+        created_at_unix_ms: file.created_at_unix_ms,
+        entries,
+    };
+    Ok((snapshot, verification))
+}
+
+// Written to a temporary sibling first so readers never see a half-written file
+fn write_atomically<F>(path: &Path, write: F) -> Result<(), SnapshotError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), SnapshotError>,
+{
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let mut file = BufWriter::new(File::create(&tmp_path)?);
+    write(&mut file)?;
+    file.flush()?;
+
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+pub fn write_snapshot<K: Serialize, V: Serialize>(path: &Path, snapshot: &Snapshot<K, V>) -> Result<(), SnapshotError> {
+    let file = encode(snapshot)?;
+    write_atomically(path, |out| {
+        if is_compressed(path) {
+            let mut encoder = GzEncoder::new(out, Compression::default());
+            serde_json::to_writer(&mut encoder, &file)?;
+            encoder.finish()?;
+        } else {
+            serde_json::to_writer(out, &file)?;
+        }
+        Ok(())
+    })
+}
+
+// The plaintext never touches the disk: it is compressed and sealed in memory
+pub fn write_encrypted_snapshot<K, V>(path: &Path, snapshot: &Snapshot<K, V>, keys: &dyn KeyProvider) -> Result<(), SnapshotError>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, &encode(snapshot)?)?;
+    let sealed = encryption::seal(&encoder.finish()?, keys)?;
+    write_atomically(path, |out| Ok(out.write_all(&sealed)?))
+}
+
+// Strict read: any corrupt record or checksum mismatch is an error
+pub fn read_snapshot<K: DeserializeOwned, V: DeserializeOwned>(path: &Path) -> Result<Snapshot<K, V>, SnapshotError> {
+    let (snapshot, verification) = decode(path)?;
+    if !verification.is_valid() {
+        return Err(SnapshotError::Corrupt(verification));
+    }
+    Ok(snapshot)
+}
+
+// Lenient read: returns the intact entries alongside what was wrong with the rest
+pub fn read_snapshot_lossy<K, V>(path: &Path) -> Result<(Snapshot<K, V>, SnapshotVerification), SnapshotError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    decode(path)
+}
+
+// Encrypted counterparts of read_snapshot / read_snapshot_lossy. Authentication
+// failures are always errors: a tampered file yields no entries at all.
+pub fn read_encrypted_snapshot<K, V>(path: &Path, keys: &dyn KeyProvider) -> Result<Snapshot<K, V>, SnapshotError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    let (snapshot, verification) = decode_encrypted(path, keys)?;
+    if !verification.is_valid() {
+        return Err(SnapshotError::Corrupt(verification));
+    }
+    Ok(snapshot)
+}
+
+pub fn read_encrypted_snapshot_lossy<K, V>(
+    path: &Path,
+    keys: &dyn KeyProvider,
+) -> Result<(Snapshot<K, V>, SnapshotVerification), SnapshotError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    decode_encrypted(path, keys)
+}
+
+// Check a snapshot's checksums without knowing its key/value types
+pub fn verify_snapshot(path: &Path) -> Result<SnapshotVerification, SnapshotError> {
+    decode::<serde_json::Value, serde_json::Value>(path).map(|(_, verification)| verification)
+}
+
+// Zero-padded millisecond timestamps keep lexical and chronological order identical
+pub fn scheduled_snapshot_path(schedule: &SnapshotSchedule, now: SystemTime) -> PathBuf {
+    let extension = match (&schedule.encryption, schedule.compress) {
+        (Some(_), _) => "enc",
+        (None, true) => "json.gz",
+        (None, false) => "json",
+    };
+    schedule
+        .directory
+        .join(format!("{}{:015}.{}", FILE_PREFIX, unix_millis(now), extension))
+}
+
+// One scheduled run: write `entries` to a new file in the schedule's directory,
+// then prune it down to the newest `retain` files
+pub fn write_scheduled_snapshot<K, V>(schedule: &SnapshotSchedule, entries: Vec<ExportedEntry<K, V>>) -> SnapshotEvent
+where
+    K: Serialize,
+    V: Serialize,
+{
+    let count = entries.len();
+    let path = scheduled_snapshot_path(schedule, SystemTime::now());
+    let result = fs::create_dir_all(&schedule.directory)
+        .map_err(SnapshotError::from)
+        .and_then(|_| match &schedule.encryption {
+            Some(keys) => write_encrypted_snapshot(&path, &Snapshot::new(entries), keys.as_ref()),
+            None => write_snapshot(&path, &Snapshot::new(entries)),
+        })
+        .and_then(|_| prune_snapshots(&schedule.directory, schedule.retain).map_err(SnapshotError::from));
+    match result {
+        Ok(_) => SnapshotEvent::Saved { path, entries: count },
+        Err(err) => SnapshotEvent::Failed { path, error: err.to_string() },
+    }
+}
+
+// Scheduled snapshot files in `directory`, oldest first
+pub fn list_snapshots(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+            name.starts_with(FILE_PREFIX) && !name.ends_with(".tmp")
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+// Delete all but the newest `retain` scheduled snapshots; returns how many were removed
+pub fn prune_snapshots(directory: &Path, retain: usize) -> io::Result<usize> {
+    let paths = list_snapshots(directory)?;
+    let excess = paths.len().saturating_sub(retain);
+    for path in &paths[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
+// Expiry instants are reconstructed from the snapshot timestamp, which is taken a
+// moment after the entries are exported; shifts below this are noise
+const EXPIRY_TOLERANCE_MS: i64 = 1000;
+
+// A key present in both snapshots whose value or metadata differs
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryChange<K, V> {
+    pub key: K,
+    pub before: ExportedEntry<K, V>,
+    pub after: ExportedEntry<K, V>,
+    pub value_changed: bool,
+    // How far the absolute expiry moved (positive = expires later)
+    pub expiry_shift_ms: i64,
+    pub priority_delta: i16,
+    pub access_count_delta: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotDiff<K, V> {
+    pub added: Vec<ExportedEntry<K, V>>,
+    pub removed: Vec<ExportedEntry<K, V>>,
+    pub changed: Vec<EntryChange<K, V>>,
+    pub unchanged: usize,
+}
+
+impl<K, V> SnapshotDiff<K, V> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn expires_at_ms<K, V>(snapshot: &Snapshot<K, V>, entry: &ExportedEntry<K, V>) -> i64 {
+    snapshot.created_at_unix_ms as i64 + entry.ttl_remaining.as_millis() as i64
+}
+
+// Compare an older snapshot against a newer one. Results follow the entry order of
+// the snapshot they come from.
+pub fn diff_snapshots<K, V>(before: &Snapshot<K, V>, after: &Snapshot<K, V>) -> SnapshotDiff<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + PartialEq,
+{
+    let old: HashMap<&K, &ExportedEntry<K, V>> = before.entries.iter().map(|entry| (&entry.key, entry)).collect();
+    let new: HashMap<&K, &ExportedEntry<K, V>> = after.entries.iter().map(|entry| (&entry.key, entry)).collect();
+
+    let mut diff = SnapshotDiff {
+        added: Vec::new(),
+        removed: before
+            .entries
+            .iter()
+            .filter(|entry| !new.contains_key(&entry.key))
+            .cloned()
+            .collect(),
+        changed: Vec::new(),
+        unchanged: 0,
+    };
+
+    for entry in &after.entries {
+        let Some(previous) = old.get(&entry.key) else {
+            diff.added.push(entry.clone());
+            continue;
+        };
+
+        let mut expiry_shift_ms = expires_at_ms(after, entry) - expires_at_ms(before, previous);
+        if expiry_shift_ms.abs() < EXPIRY_TOLERANCE_MS {
+            expiry_shift_ms = 0;
+        }
+        let change = EntryChange {
+            key: entry.key.clone(),
+            before: (*previous).clone(),
+            after: entry.clone(),
+            value_changed: previous.value != entry.value,
+            expiry_shift_ms,
+            priority_delta: entry.priority as i16 - previous.priority as i16,
+            access_count_delta: entry.access_count as i64 - previous.access_count as i64,
+        };
+
+        if change.value_changed
+            || change.expiry_shift_ms != 0
+            || change.priority_delta != 0
+            || change.access_count_delta != 0
+        {
+            diff.changed.push(change);
+        } else {
+            diff.unchanged += 1;
+        }
+    }
+
+    diff
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+// Stored entries by time left to live, from each implementation's ttl_distribution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TtlDistribution {
+    // Past their TTL or invalidated, waiting for cleanup
+    pub expired: usize,
+    pub under_1m: usize,
+    pub under_10m: usize,
+    pub under_1h: usize,
+    pub over_1h: usize,
+}
+
+impl TtlDistribution {
+    // Count one entry; None for one that has expired
+    pub fn record(&mut self, remaining: Option<Duration>) {
+        const MINUTE: Duration = Duration::from_secs(60);
+        match remaining {
+            None => self.expired += 1,
+            Some(left) if left < MINUTE => self.under_1m += 1,
+            Some(left) if left < 10 * MINUTE => self.under_10m += 1,
+            Some(left) if left < 60 * MINUTE => self.under_1h += 1,
+            Some(_) => self.over_1h += 1,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// ConformantCache for each implementation
+
+use crate::{ConformantCache, Event, EventLog, Settings, Stats};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub type Qwen30B = qwen30b_cache::SmartCache<String, String>;
+pub type Qwen235B = qwen235b_cache::SmartCache<String, String>;
+pub type Qwen435B = qwen435b_cache::SmartCache<String, String>;
+pub type Glm45 = glm45_cache::SmartCache<String, String>;
+
+// The calls that look the same in every implementation
+macro_rules! shared_operations {
+    () => {
+        fn put(&self, key: &str, value: &str, ttl: Option<Duration>, priority: u8) -> bool {
+            Self::put(self, key.to_string(), value.to_string(), ttl, priority)
+        }
+
+        fn put_if_absent(&self, key: &str, value: &str, ttl: Option<Duration>, priority: u8) -> Option<String> {
+            Self::put_if_absent(self, key.to_string(), value.to_string(), ttl, priority)
+        }
+
+        fn replace(&self, key: &str, value: &str) -> Option<String> {
+            Self::replace(self, key.to_string(), value.to_string())
+        }
+
+        fn get(&self, key: &str) -> Option<String> {
+            Self::get(self, key)
+        }
+
+        fn delete(&self, key: &str) -> bool {
+            Self::delete(self, key)
+        }
+
+        fn contains_key(&self, key: &str) -> bool {
+            Self::contains_key(self, key)
+        }
+
+        fn invalidate_all(&self) {
+            Self::invalidate_all(self)
+        }
+    };
+}
+
+impl ConformantCache for Qwen30B {
+    const NAME: &'static str = "Qwen30B";
+
+    fn with_settings(settings: Settings) -> Self {
+        Self::with_config(qwen30b_cache::CacheConfig {
+            max_capacity: settings.capacity,
+            default_ttl: settings.default_ttl,
+            cleanup_interval: settings.cleanup_interval,
+            ..Default::default()
+        })
+    }
+
+    shared_operations!();
+
+    fn stored_len(&self) -> usize {
+        self.size()
+    }
+
+    fn stats(&self) -> Stats {
+        let stats = self.get_stats();
+        Stats { hits: stats.hits, misses: stats.misses, insertions: stats.insertions, evictions: stats.evictions }
+    }
+
+    fn record_events(&self) -> Option<EventLog> {
+        None
+    }
+
+    fn advance_clock(by: Duration) {
+        qwen30b_cache::clock::advance(by);
+    }
+}
+
+struct EventRecorder(EventLog);
+
+impl qwen235b_cache::CacheCallback<String, String> for EventRecorder {
+    fn on_event(&self, event: qwen235b_cache::CacheEvent<String, String>) {
+        use qwen235b_cache::CacheEvent;
+        let event = match event {
+            CacheEvent::Insert(key) => Event::Insert(key),
+            CacheEvent::Update { key, .. } => Event::Update(key),
+            CacheEvent::Hit(key) => Event::Hit(key),
+            CacheEvent::Miss(key) => Event::Miss(key),
+            CacheEvent::Delete(key) => Event::Delete(key),
+            CacheEvent::Eviction { key, .. } => Event::Eviction(key),
+            CacheEvent::TTLExpiry { key, .. } => Event::Expiry(key),
+            _ => return,
+        };
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+impl ConformantCache for Qwen235B {
+    const NAME: &'static str = "Qwen235B";
+
+    fn with_settings(settings: Settings) -> Self {
+        Self::with_config(qwen235b_cache::CacheConfig {
+            max_capacity: settings.capacity,
+            default_ttl: settings.default_ttl,
+            cleanup_interval: settings.cleanup_interval,
+            ..Default::default()
+        })
+    }
+
+    shared_operations!();
+
+    fn stored_len(&self) -> usize {
+        self.size()
+    }
+
+    fn stats(&self) -> Stats {
+        let stats = self.get_stats();
+        Stats { hits: stats.hits, misses: stats.misses, insertions: stats.insertions, evictions: stats.evictions }
+    }
+
+    fn record_events(&self) -> Option<EventLog> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        self.add_callback(Box::new(EventRecorder(Arc::clone(&log))));
+        Some(log)
+    }
+
+    fn advance_clock(by: Duration) {
+        qwen235b_cache::clock::advance(by);
+    }
+}
+
+impl ConformantCache for Qwen435B {
+    const NAME: &'static str = "Qwen435B";
+
+    fn with_settings(settings: Settings) -> Self {
+        Self::with_config(qwen435b_cache::CacheConfig {
+            max_capacity: settings.capacity,
+            default_ttl: settings.default_ttl,
+            cleanup_interval: settings.cleanup_interval,
+            // The default follows the core count; pinned so results do not depend on the machine
+            shard_amount: 4,
+            ..Default::default()
+        })
+    }
+
+    shared_operations!();
+
+    fn stored_len(&self) -> usize {
+        self.get_stats().size
+    }
+
+    fn stats(&self) -> Stats {
+        let stats = self.get_stats();
+        Stats { hits: stats.hits, misses: stats.misses, insertions: stats.insertions, evictions: stats.evictions }
+    }
+
+    fn record_events(&self) -> Option<EventLog> {
+        None
+    }
+
+    fn advance_clock(by: Duration) {
+        qwen435b_cache::clock::advance(by);
+    }
+}
+
+impl ConformantCache for Glm45 {
+    const NAME: &'static str = "GLM45";
+
+    // There is no with_config; the settings go in as a config reload
+    fn with_settings(settings: Settings) -> Self {
+        let cache = Self::new(settings.capacity);
+        cache
+            .reload_config(glm45_cache::CacheConfig {
+                max_capacity: settings.capacity,
+                default_ttl: settings.default_ttl,
+                cleanup_interval: settings.cleanup_interval,
+                ..Default::default()
+            })
+            .expect("conformance settings are a valid config");
+        cache
+    }
+
+    shared_operations!();
+
+    fn stored_len(&self) -> usize {
+        self.size()
+    }
+
+    fn stats(&self) -> Stats {
+        let stats = self.get_stats();
+        Stats { hits: stats.hits, misses: stats.misses, insertions: stats.insertions, evictions: stats.evictions }
+    }
+
+    fn record_events(&self) -> Option<EventLog> {
+        None
+    }
+
+    fn advance_clock(by: Duration) {
+        glm45_cache::clock::advance(by);
+    }
+}
@@ -0,0 +1,295 @@
+// Behavioural conformance suite for the Rust cache implementations. Every
+// implementation is wrapped in ConformantCache and run through the same checks
+// (tests/conformance.rs); where one knowingly departs from the intended
+// behaviour, its test is ignored with the reason, so the deviations are listed in
+// one place.
+//
+// The intended behaviour, following challenge_specification.md:
+// - get returns the value of the latest put and None after a delete
+// - an entry is never returned once its TTL (or the default TTL) has passed
+// - the cache never holds more than its capacity once a put returns
+// - eviction removes the lowest priority first, least recently used among equals
+// - hits, misses, insertions and evictions are counted exactly
+// - implementations with callbacks report every insert, hit, miss, delete and eviction
+
+pub mod adapters;
+pub mod model;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// How a check sets up its cache
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    pub capacity: usize,
+    pub default_ttl: Duration,
+    pub cleanup_interval: Duration,
+}
+
+impl Settings {
+    pub fn capacity(capacity: usize) -> Self {
+        Settings {
+            capacity,
+            default_ttl: Duration::from_secs(3600),
+            cleanup_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Insert(String),
+    Update(String),
+    Hit(String),
+    Miss(String),
+    Delete(String),
+    Eviction(String),
+    Expiry(String),
+}
+
+pub type EventLog = Arc<Mutex<Vec<Event>>>;
+
+// The operations every implementation shares, over String keys and values
+pub trait ConformantCache: Send + Sync + 'static {
+    const NAME: &'static str;
+
+    fn with_settings(settings: Settings) -> Self;
+    fn put(&self, key: &str, value: &str, ttl: Option<Duration>, priority: u8) -> bool;
+    fn put_if_absent(&self, key: &str, value: &str, ttl: Option<Duration>, priority: u8) -> Option<String>;
+    fn replace(&self, key: &str, value: &str) -> Option<String>;
+    fn get(&self, key: &str) -> Option<String>;
+    fn delete(&self, key: &str) -> bool;
+    fn contains_key(&self, key: &str) -> bool;
+    fn invalidate_all(&self);
+    // Entries held, expired ones awaiting cleanup included
+    fn stored_len(&self) -> usize;
+    fn stats(&self) -> Stats;
+    // Starts recording events; None when the implementation has no callbacks
+    fn record_events(&self) -> Option<EventLog>;
+    // Moves the implementation's mock clock forward, for every cache of its kind
+    fn advance_clock(by: Duration);
+}
+
+fn key(i: usize) -> String {
+    format!("key_{}", i)
+}
+
+pub fn put_get_delete<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(10));
+    assert!(cache.put("a", "1", None, 5));
+    assert_eq!(cache.get("a").as_deref(), Some("1"));
+    assert_eq!(cache.get("missing"), None);
+    assert!(cache.contains_key("a"));
+
+    assert!(cache.delete("a"), "delete of a live key returns true");
+    assert_eq!(cache.get("a"), None);
+    assert!(!cache.contains_key("a"));
+    assert!(!cache.delete("a"), "delete of a missing key returns false");
+}
+
+pub fn overwrite_keeps_latest_value<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(10));
+    cache.put("a", "1", None, 5);
+    cache.put("a", "2", None, 5);
+    assert_eq!(cache.get("a").as_deref(), Some("2"));
+    assert_eq!(cache.stored_len(), 1, "an overwrite replaces the entry");
+}
+
+pub fn conditional_writes<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(10));
+    assert_eq!(cache.put_if_absent("a", "1", None, 5), None, "put_if_absent stores a new key");
+    assert_eq!(cache.put_if_absent("a", "2", None, 5).as_deref(), Some("1"), "and returns the value it keeps");
+    assert_eq!(cache.get("a").as_deref(), Some("1"));
+
+    assert_eq!(cache.replace("b", "1"), None, "replace does not store a new key");
+    assert!(!cache.contains_key("b"));
+    assert_eq!(cache.replace("a", "3").as_deref(), Some("1"), "replace returns the old value");
+    assert_eq!(cache.get("a").as_deref(), Some("3"));
+
+    // An expired entry is absent, and a replaced one keeps its expiry
+    cache.put("short", "1", Some(Duration::from_millis(50)), 5);
+    cache.put("kept", "1", Some(Duration::from_millis(50)), 5);
+    assert_eq!(cache.replace("kept", "2").as_deref(), Some("1"));
+    thread::sleep(Duration::from_millis(120));
+    assert_eq!(cache.replace("short", "2"), None);
+    assert_eq!(cache.put_if_absent("short", "3", None, 5), None);
+    assert_eq!(cache.get("short").as_deref(), Some("3"));
+    assert_eq!(cache.get("kept"), None, "replace keeps the entry's TTL");
+}
+
+pub fn put_if_absent_has_one_winner<C: ConformantCache>() {
+    let cache = Arc::new(C::with_settings(Settings::capacity(10)));
+    let barrier = Arc::new(std::sync::Barrier::new(8));
+    let writers: Vec<_> = (0..8)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                cache.put_if_absent("k", &t.to_string(), None, 5).is_none().then_some(t)
+            })
+        })
+        .collect();
+    let winners: Vec<usize> = writers.into_iter().filter_map(|writer| writer.join().unwrap()).collect();
+    assert_eq!(winners.len(), 1, "exactly one racing put_if_absent stores");
+    assert_eq!(cache.get("k"), Some(winners[0].to_string()));
+}
+
+pub fn invalidate_all_hides_entries<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(50));
+    for i in 0..50 {
+        cache.put(&key(i), "v", None, 5);
+    }
+    cache.get(&key(0));
+    cache.invalidate_all();
+
+    assert_eq!(cache.get(&key(0)), None, "invalidated entries are gone at once");
+    assert!((1..50).all(|i| !cache.contains_key(&key(i))));
+    assert_eq!(cache.stats().hits, 1, "stats survive invalidation");
+
+    // Invalidated entries may be reclaimed lazily, but make room before live ones
+    for i in 0..50 {
+        assert!(cache.put(&format!("new_{}", i), "1", None, 1));
+    }
+    assert!((0..50).all(|i| cache.get(&format!("new_{}", i)).as_deref() == Some("1")));
+    assert!(cache.stored_len() <= 50);
+}
+
+pub fn ttl_expires_entry<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(10));
+    cache.put("short", "1", Some(Duration::from_millis(50)), 5);
+    cache.put("long", "2", Some(Duration::from_secs(60)), 5);
+    assert_eq!(cache.get("short").as_deref(), Some("1"));
+
+    thread::sleep(Duration::from_millis(120));
+    assert_eq!(cache.get("short"), None, "expired entries are never returned");
+    assert!(!cache.contains_key("short"));
+    assert_eq!(cache.get("long").as_deref(), Some("2"));
+}
+
+pub fn default_ttl_applies<C: ConformantCache>() {
+    let cache = C::with_settings(Settings { default_ttl: Duration::from_millis(50), ..Settings::capacity(10) });
+    cache.put("a", "1", None, 5);
+    assert_eq!(cache.get("a").as_deref(), Some("1"));
+
+    thread::sleep(Duration::from_millis(120));
+    assert_eq!(cache.get("a"), None, "a put without a TTL takes the default TTL");
+}
+
+pub fn cleanup_removes_expired<C: ConformantCache>() {
+    let cache = C::with_settings(Settings { cleanup_interval: Duration::from_millis(20), ..Settings::capacity(100) });
+    for i in 0..50 {
+        cache.put(&key(i), "v", Some(Duration::from_millis(10)), 5);
+    }
+
+    // Expired entries go without being looked up
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(cache.stored_len(), 0, "expired entries are cleaned up in the background");
+}
+
+pub fn capacity_is_enforced<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(10));
+    for i in 0..100 {
+        cache.put(&key(i), "v", None, 5);
+        assert!(cache.stored_len() <= 10, "{} entries with capacity 10", cache.stored_len());
+    }
+    assert_eq!(cache.get(&key(99)).as_deref(), Some("v"), "the newest entry survives");
+}
+
+pub fn concurrent_puts_respect_capacity<C: ConformantCache>() {
+    let cache = Arc::new(C::with_settings(Settings::capacity(64)));
+    let writers: Vec<_> = (0..8)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for i in 0..2000 {
+                    cache.put(&key(t * 10_000 + i), "v", None, 5);
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    assert!(cache.stored_len() <= 64, "{} entries with capacity 64", cache.stored_len());
+}
+
+pub fn lru_among_equal_priorities<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(3));
+    cache.put("a", "1", None, 5);
+    cache.put("b", "2", None, 5);
+    cache.put("c", "3", None, 5);
+    cache.get("a");
+    cache.put("d", "4", None, 5);
+
+    assert_eq!(cache.get("b"), None, "the least recently used entry is evicted");
+    for present in ["a", "c", "d"] {
+        assert!(cache.contains_key(present), "{} was evicted instead", present);
+    }
+}
+
+pub fn lowest_priority_evicted_first<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(3));
+    cache.put("low", "1", None, 1);
+    cache.put("high1", "2", None, 9);
+    cache.put("high2", "3", None, 9);
+    // Recently used, but still the lowest priority
+    cache.get("low");
+    cache.put("high3", "4", None, 9);
+
+    assert_eq!(cache.get("low"), None, "the lowest priority entry is evicted");
+    for present in ["high1", "high2", "high3"] {
+        assert!(cache.contains_key(present), "{} was evicted instead", present);
+    }
+}
+
+pub fn stats_are_exact<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(2));
+    cache.put("a", "1", None, 5);
+    cache.put("b", "2", None, 5);
+    cache.get("a");
+    cache.get("a");
+    cache.get("missing");
+    // Over capacity: one eviction
+    cache.put("c", "3", None, 5);
+
+    let stats = cache.stats();
+    assert_eq!(stats, Stats { hits: 2, misses: 1, insertions: 3, evictions: 1 });
+}
+
+pub fn events_are_delivered<C: ConformantCache>() {
+    let cache = C::with_settings(Settings::capacity(1));
+    let Some(events) = cache.record_events() else {
+        return;
+    };
+    cache.put("a", "1", None, 5);
+    cache.get("a");
+    cache.get("missing");
+    cache.put("a", "2", None, 5);
+    // Evicts a
+    cache.put("b", "3", None, 5);
+    cache.delete("b");
+
+    let expected = [
+        Event::Insert("a".to_string()),
+        Event::Hit("a".to_string()),
+        Event::Miss("missing".to_string()),
+        Event::Update("a".to_string()),
+        Event::Eviction("a".to_string()),
+        Event::Insert("b".to_string()),
+        Event::Delete("b".to_string()),
+    ];
+    let events = events.lock().unwrap();
+    for event in &expected {
+        assert!(events.contains(event), "missing {:?} in {:?}", event, events);
+    }
+}
@@ -0,0 +1,92 @@
+// The intended behaviour written as plainly as possible, for the differential
+// tests (tests/differential.rs) to hold the implementations against. Time is
+// logical: it only moves when advance is called, like the implementations under
+// the mock clock.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+struct Entry {
+    value: String,
+    priority: u8,
+    expires: Duration,
+    // Tick of the last put or get, for LRU order among equal priorities
+    used: u64,
+}
+
+pub struct Model {
+    capacity: usize,
+    default_ttl: Duration,
+    now: Duration,
+    tick: u64,
+    entries: HashMap<String, Entry>,
+}
+
+impl Model {
+    pub fn new(capacity: usize, default_ttl: Duration) -> Self {
+        Model { capacity, default_ttl, now: Duration::ZERO, tick: 0, entries: HashMap::new() }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn is_live(&self, key: &str) -> bool {
+        self.entries.get(key).is_some_and(|entry| self.now < entry.expires)
+    }
+
+    pub fn put(&mut self, key: &str, value: &str, ttl: Option<Duration>, priority: u8) {
+        self.entries.retain(|_, entry| self.now < entry.expires);
+        if !self.entries.contains_key(key) && self.entries.len() >= self.capacity {
+            let victim = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| (entry.priority, entry.used))
+                .map(|(key, _)| key.clone());
+            if let Some(victim) = victim {
+                self.entries.remove(&victim);
+            }
+        }
+        let entry = Entry {
+            value: value.to_string(),
+            priority,
+            expires: self.now + ttl.unwrap_or(self.default_ttl),
+            used: self.next_tick(),
+        };
+        self.entries.insert(key.to_string(), entry);
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<String> {
+        if !self.is_live(key) {
+            return None;
+        }
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(key)?;
+        entry.used = tick;
+        Some(entry.value.clone())
+    }
+
+    pub fn delete(&mut self, key: &str) -> bool {
+        let live = self.is_live(key);
+        self.entries.remove(key);
+        live
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.is_live(key)
+    }
+
+    // Live entries only
+    pub fn len(&self) -> usize {
+        self.entries.values().filter(|entry| self.now < entry.expires).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn advance(&mut self, by: Duration) {
+        self.now += by;
+    }
+}
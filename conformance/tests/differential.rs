@@ -0,0 +1,135 @@
+// Randomized operation sequences run against every implementation and the
+// reference model (src/model.rs) side by side, failing on the first get, size or
+// surviving key they disagree on. Time moves through the mock clock, so TTLs are
+// whole seconds and nothing sleeps. As in tests/conformance.rs, a property an
+// implementation knowingly breaks is ignored with the reason.
+
+use cache_conformance::adapters::{Glm45, Qwen235B, Qwen30B, Qwen435B};
+use cache_conformance::model::Model;
+use cache_conformance::{ConformantCache, Settings};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// The mock clocks are process-wide, so cases must not overlap
+static CLOCK: Mutex<()> = Mutex::new(());
+
+const CASES: u32 = 64;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Put { key: usize, ttl_secs: Option<u64>, priority: u8 },
+    Get(usize),
+    Delete(usize),
+    Advance(u64),
+}
+
+fn op(keys: usize, with_ttl: bool) -> impl Strategy<Value = Op> {
+    let ttl_secs = if with_ttl { proptest::option::of(1..=4u64).boxed() } else { Just(None).boxed() };
+    prop_oneof![
+        4 => (0..keys, ttl_secs, 1..=10u8).prop_map(|(key, ttl_secs, priority)| Op::Put { key, ttl_secs, priority }),
+        4 => (0..keys).prop_map(Op::Get),
+        1 => (0..keys).prop_map(Op::Delete),
+        1 => (0..=3u64).prop_map(Op::Advance),
+    ]
+}
+
+fn key(i: usize) -> String {
+    format!("key_{}", i)
+}
+
+// Runs ops against a fresh cache and model, comparing every get, then which keys
+// survive and, when check_len is set, how many entries the cache holds
+fn agrees_with_model<C: ConformantCache>(capacity: usize, keys: usize, ops: &[Op], check_len: bool) -> Result<(), TestCaseError> {
+    let settings = Settings::capacity(capacity);
+    let cache = C::with_settings(settings);
+    let mut model = Model::new(capacity, settings.default_ttl);
+
+    for (step, op) in ops.iter().enumerate() {
+        match *op {
+            Op::Put { key: i, ttl_secs, priority } => {
+                let ttl = ttl_secs.map(Duration::from_secs);
+                let value = format!("v{}", step);
+                cache.put(&key(i), &value, ttl, priority);
+                model.put(&key(i), &value, ttl, priority);
+            }
+            Op::Get(i) => prop_assert_eq!(cache.get(&key(i)), model.get(&key(i)), "get {} at step {}", key(i), step),
+            Op::Delete(i) => {
+                cache.delete(&key(i));
+                model.delete(&key(i));
+            }
+            Op::Advance(secs) => {
+                C::advance_clock(Duration::from_secs(secs));
+                model.advance(Duration::from_secs(secs));
+            }
+        }
+    }
+
+    let survivors = |contains: &dyn Fn(&str) -> bool| (0..keys).filter(|&i| contains(&key(i))).collect::<Vec<_>>();
+    prop_assert_eq!(survivors(&|k| cache.contains_key(k)), survivors(&|k| model.contains_key(k)), "surviving keys");
+    if check_len {
+        prop_assert_eq!(cache.stored_len(), model.len(), "stored entries");
+    }
+    Ok(())
+}
+
+fn run(ops: impl Strategy<Value = Vec<Op>>, test: impl Fn(&[Op]) -> Result<(), TestCaseError>) {
+    let _clock = CLOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut runner = TestRunner::new(Config { cases: CASES, ..Config::default() });
+    if let Err(err) = runner.run(&ops, |ops| test(&ops)) {
+        panic!("{}", err);
+    }
+}
+
+// Room for every key, so only TTLs and deletes decide what is there
+pub fn ttl_semantics_match<C: ConformantCache>() {
+    const KEYS: usize = 8;
+    run(proptest::collection::vec(op(KEYS, true), 1..60), |ops| agrees_with_model::<C>(KEYS, KEYS, ops, false));
+}
+
+// More keys than room and no TTLs, so eviction decides what is there
+pub fn eviction_semantics_match<C: ConformantCache>() {
+    const KEYS: usize = 10;
+    run(proptest::collection::vec(op(KEYS, false), 1..60), |ops| agrees_with_model::<C>(4, KEYS, ops, true));
+}
+
+macro_rules! differential_tests {
+    ($module:ident, $cache:ty, [$($(#[$attr:meta])* $property:ident),* $(,)?]) => {
+        mod $module {
+            use super::*;
+
+            $(
+                #[test]
+                $(#[$attr])*
+                fn $property() {
+                    super::$property::<$cache>();
+                }
+            )*
+        }
+    };
+}
+
+differential_tests!(qwen30b, Qwen30B, [
+    ttl_semantics_match,
+    #[ignore = "gets are buffered and reach the LRU order only at the next eviction, after any puts made since"]
+    eviction_semantics_match,
+]);
+
+differential_tests!(qwen235b, Qwen235B, [
+    ttl_semantics_match,
+    #[ignore = "evicts the highest idle time / priority, so a long-idle high-priority entry goes before a fresh low-priority one"]
+    eviction_semantics_match,
+]);
+
+differential_tests!(qwen435b, Qwen435B, [
+    ttl_semantics_match,
+    #[ignore = "evicts within the inserting key's shard, so the victim depends on how keys hash"]
+    eviction_semantics_match,
+]);
+
+differential_tests!(glm45, Glm45, [
+    ttl_semantics_match,
+    #[ignore = "evicts by whole idle seconds / priority, so entries idle under a second go in LRU order"]
+    eviction_semantics_match,
+]);
@@ -0,0 +1,78 @@
+// get_copy promises a hit path without heap allocation for Copy values. Allocations
+// are counted per thread, so the checks can run alongside each other.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ENTRIES: u64 = 1_000;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+macro_rules! get_copy_allocates_nothing {
+    ($name:ident, $cache:expr) => {
+        #[test]
+        fn $name() {
+            let cache = $cache;
+            for i in 0..ENTRIES {
+                cache.put(i, i as f64, None, 5);
+            }
+            // Warm up once, so lazily created per-thread state is not counted
+            for i in 0..ENTRIES {
+                assert_eq!(cache.get_copy(&i), Some(i as f64));
+            }
+
+            let allocations = allocations_during(|| {
+                for _ in 0..10 {
+                    for i in 0..ENTRIES {
+                        std::hint::black_box(cache.get_copy(&i));
+                    }
+                }
+            });
+            assert_eq!(allocations, 0);
+        }
+    };
+}
+
+get_copy_allocates_nothing!(qwen30b, qwen30b_cache::SmartCache::<u64, f64>::new(ENTRIES as usize * 2));
+get_copy_allocates_nothing!(qwen235b, qwen235b_cache::SmartCache::<u64, f64>::new(ENTRIES as usize * 2));
+get_copy_allocates_nothing!(qwen435b, qwen435b_cache::SmartCache::<u64, f64>::new(ENTRIES as usize * 2));
+get_copy_allocates_nothing!(glm45, glm45_without_trace_log(false));
+get_copy_allocates_nothing!(glm45_read_index, glm45_without_trace_log(true));
+
+// The trace log, on by default, renders every lookup
+fn glm45_without_trace_log(read_fast_path: bool) -> glm45_cache::SmartCache<u64, f64> {
+    let cache = glm45_cache::SmartCache::new(ENTRIES as usize * 2);
+    cache
+        .reload_config(glm45_cache::CacheConfig {
+            max_capacity: ENTRIES as usize * 2,
+            enable_trace_log: false,
+            read_fast_path,
+            ..Default::default()
+        })
+        .expect("a valid config");
+    cache
+}
@@ -0,0 +1,10 @@
+// The cache's time source, cache_types::clock. Built with the mock-clock
+// feature, advance() moves it forward for every cache in the process. In this
+// crate's own unit tests, which run on parallel threads, advance() moves only
+// the calling thread's clock, so one test's jumps don't expire another's entries.
+
+pub(crate) use cache_types::clock::now;
+#[cfg(all(feature = "mock-clock", not(test)))]
+pub use cache_types::clock::advance;
+#[cfg(test)]
+pub use cache_types::clock::advance_thread as advance;
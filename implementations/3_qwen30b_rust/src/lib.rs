@@ -1,19 +1,271 @@
 // Qwen3-30B Rust Implementation - Score: 85/100
-// Basic Rust implementation with RwLock and VecDeque
+// Basic Rust implementation with RwLock and a slab-backed LRU list
 
-use std::collections::{HashMap, VecDeque};
-use std::sync::{Arc, RwLock, Mutex};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::{Arc, RwLock, Mutex, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use std::thread;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
 
-#[derive(Debug, Clone)]
-pub struct CacheEntry<V: Clone> {
-    value: V,
+pub mod clock;
+
+pub use cache_types::{ExportedEntry, FrozenCache, HealthReport, KeyProvider, MemSize, PriorityBoost, PriorityDecay, StaticKeyProvider, TtlDistribution};
+use cache_types::{FrozenEntry, Shared, StripedCounter};
+use cache_types::striped::stripe_index;
+use cache_types::snapshot;
+pub use cache_types::snapshot::{
+    diff_snapshots, list_snapshots, read_snapshot, read_snapshot_lossy, verify_snapshot, write_snapshot, EntryChange,
+    Snapshot, SnapshotDiff, SnapshotError, SnapshotEvent, SnapshotLoadReport, SnapshotSchedule, SnapshotVerification,
+    SNAPSHOT_VERSION,
+};
+
+// Values that know their own lifetime (tokens, DNS records); see put_expirable
+pub trait Expirable {
+    fn ttl(&self) -> Option<Duration>;
+}
+
+#[derive(Debug)]
+// Values live behind an Arc so non-Clone types (handles, compiled regex sets) can be cached
+pub struct CacheEntry<V> {
+    value: ValueSlot<V>,
     priority: u8,
     ttl: Instant,
     created_at: Instant,
     last_accessed: Instant,
     access_count: usize,
+    lru_slot: LruSlot,
+    // The cache's generation when stored; see invalidate_all
+    generation: u64,
+}
+
+// By hand: the derived Clone would need V: Clone, and copying the entry table
+// on a write after fork() only clones the Arcs
+impl<V> Clone for CacheEntry<V> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            priority: self.priority,
+            ttl: self.ttl,
+            created_at: self.created_at,
+            last_accessed: self.last_accessed,
+            access_count: self.access_count,
+            lru_slot: self.lru_slot,
+            generation: self.generation,
+        }
+    }
+}
+
+// Entry bookkeeping handed out alongside a value
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryMetadata {
+    pub priority: u8,
+    // What eviction compares: `priority` after decay and boost
+    pub effective_priority: f64,
+    pub access_count: usize,
+    pub age: Duration,
+    pub idle: Duration,
+    pub ttl_remaining: Duration,
+}
+
+// A soft slot holds only a Weak (see soften_low_priority): the value survives while
+// a caller still holds the Arc from get_arc and reads as a miss once it is freed
+#[derive(Debug)]
+enum ValueSlot<V> {
+    Strong(Arc<V>),
+    Soft(Weak<V>),
+}
+
+impl<V> Clone for ValueSlot<V> {
+    fn clone(&self) -> Self {
+        match self {
+            ValueSlot::Strong(value) => ValueSlot::Strong(Arc::clone(value)),
+            ValueSlot::Soft(value) => ValueSlot::Soft(Weak::clone(value)),
+        }
+    }
+}
+
+impl<V> ValueSlot<V> {
+    fn upgrade(&self) -> Option<Arc<V>> {
+        match self {
+            ValueSlot::Strong(value) => Some(Arc::clone(value)),
+            ValueSlot::Soft(value) => value.upgrade(),
+        }
+    }
+    
+    fn into_arc(self) -> Option<Arc<V>> {
+        match self {
+            ValueSlot::Strong(value) => Some(value),
+            ValueSlot::Soft(value) => value.upgrade(),
+        }
+    }
+    
+    fn is_reclaimed(&self) -> bool {
+        matches!(self, ValueSlot::Soft(value) if value.strong_count() == 0)
+    }
+}
+
+// The entry table and recency order, as the cache and its cleanup thread hold them
+type EntryTable<K, V> = Arc<RwLock<Shared<HashMap<K, CacheEntry<V>>>>>;
+type RecencyOrder<K> = Arc<Mutex<Shared<LruOrder<K>>>>;
+
+// Which writes go through, decided under the data lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteIf {
+    Always,
+    // Only when the key holds no live entry
+    Absent,
+    // Only over a live entry, keeping its expiry and priority
+    Present,
+    // When the key holds no live entry or one of lower priority than the write's
+    HigherPriority,
+}
+
+impl From<ConflictPolicy> for WriteIf {
+    fn from(policy: ConflictPolicy) -> Self {
+        match policy {
+            ConflictPolicy::KeepExisting => WriteIf::Absent,
+            ConflictPolicy::Overwrite => WriteIf::Always,
+            ConflictPolicy::KeepHigherPriority => WriteIf::HigherPriority,
+        }
+    }
+}
+
+// Stored: Ok with the live value replaced. Not stored: Err with the live value
+// that stopped an Absent or HigherPriority write, if that was the reason.
+type WriteResult<V> = Result<Option<Arc<V>>, Option<Arc<V>>>;
+
+impl<V> CacheEntry<V> {
+    // The priority eviction compares, capped at the top of the scale
+    fn effective_priority(&self, config: &CacheConfig, now: Instant) -> f64 {
+        let decayed = match &config.priority_decay {
+            Some(decay) => decay.apply(self.priority, now.saturating_duration_since(self.last_accessed)),
+            None => f64::from(self.priority),
+        };
+        let boost = config.priority_boost.map_or(0.0, |boost| {
+            boost.apply(self.access_count, now.saturating_duration_since(self.created_at))
+        });
+        (decayed + boost).min(10.0)
+    }
+    
+    // effective_priority, except that expired entries, invalidated ones included,
+    // rank below any live one
+    fn eviction_rank(&self, config: &CacheConfig, now: Instant, generation: u64) -> f64 {
+        if now > self.expires_at(generation) {
+            f64::NEG_INFINITY
+        } else {
+            self.effective_priority(config, now)
+        }
+    }
+    
+    // An entry stored before the last invalidate_all expired just before it was
+    // created, so it reads as expired even at that instant
+    fn expires_at(&self, generation: u64) -> Instant {
+        if self.generation == generation {
+            self.ttl
+        } else {
+            self.created_at.checked_sub(Duration::from_nanos(1)).unwrap_or(self.created_at)
+        }
+    }
+    
+    // `idle` is measured against the access before the current one
+    fn metadata(&self, config: &CacheConfig, now: Instant, previous_access: Instant) -> EntryMetadata {
+        EntryMetadata {
+            priority: self.priority,
+            effective_priority: self.effective_priority(config, now),
+            access_count: self.access_count,
+            age: now.saturating_duration_since(self.created_at),
+            idle: now.saturating_duration_since(previous_access),
+            ttl_remaining: self.ttl.saturating_duration_since(now),
+        }
+    }
+}
+
+// What `import` does when a key is already present
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    KeepExisting,
+    Overwrite,
+    KeepHigherPriority,
+}
+
+// One row of a warm-up seed file (CSV header: key,value,ttl_secs,priority)
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedRecord<K, V> {
+    pub key: K,
+    pub value: V,
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    #[serde(default = "default_seed_priority")]
+    pub priority: u8,
+}
+
+fn default_seed_priority() -> u8 {
+    5
+}
+
+// (key, value, ttl, priority) for bulk_load, the arguments put takes
+pub type BulkEntry<K, V> = (K, V, Option<Duration>, u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmupReport {
+    pub loaded: usize,
+    pub evicted: usize,
+}
+
+#[derive(Debug)]
+pub enum WarmupError {
+    Io(std::io::Error),
+    Parse(String),
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for WarmupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarmupError::Io(err) => write!(f, "failed to read seed file: {}", err),
+            WarmupError::Parse(msg) => write!(f, "invalid seed record: {}", msg),
+            WarmupError::UnsupportedFormat(ext) => write!(f, "unsupported seed file format: {:?}", ext),
+        }
+    }
+}
+
+impl std::error::Error for WarmupError {}
+
+impl From<std::io::Error> for WarmupError {
+    fn from(err: std::io::Error) -> Self {
+        WarmupError::Io(err)
+    }
+}
+
+// Seed files are loaded in batches so readers are not locked out for the whole file
+const WARMUP_BATCH: usize = 10_000;
+
+fn read_seed_file<K, V>(path: &Path) -> Result<Vec<SeedRecord<K, V>>, WarmupError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let reader = BufReader::new(File::open(path)?);
+            serde_json::from_reader(reader).map_err(|err| WarmupError::Parse(err.to_string()))
+        }
+        Some("csv") => {
+            let mut reader = csv::Reader::from_reader(File::open(path)?);
+            reader
+                .deserialize()
+                .collect::<Result<_, _>>()
+                .map_err(|err| WarmupError::Parse(err.to_string()))
+        }
+        other => Err(WarmupError::UnsupportedFormat(other.unwrap_or_default().to_string())),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +273,19 @@ pub struct CacheConfig {
     pub max_capacity: usize,
     pub default_ttl: Duration,
     pub cleanup_interval: Duration,
+    // Entries below this priority are held weakly by soften_low_priority; None disables it
+    pub soft_priority_threshold: Option<u8>,
+    // Decay the priorities of unaccessed entries for eviction; None keeps them fixed
+    pub priority_decay: Option<PriorityDecay>,
+    // Raise the priorities of frequently read entries for eviction; None keeps them as declared
+    pub priority_boost: Option<PriorityBoost>,
+    // Entries the background cleanup examines per pass, resuming where the last
+    // pass stopped, plus the ones an earlier pass saw falling due. Bounds each
+    // pass's work whatever the cache size; None scans every entry each pass.
+    // Empty LRU slots count toward the budget.
+    pub expiry_scan_budget: Option<usize>,
+    // Used by start_snapshot_scheduler; None disables periodic snapshots
+    pub snapshot_schedule: Option<SnapshotSchedule>,
 }
 
 impl Default for CacheConfig {
@@ -29,6 +294,11 @@ impl Default for CacheConfig {
             max_capacity: 1000,
             default_ttl: Duration::from_secs(3600),
             cleanup_interval: Duration::from_secs(60),
+            soft_priority_threshold: None,
+            priority_decay: None,
+            priority_boost: None,
+            expiry_scan_budget: None,
+            snapshot_schedule: None,
         }
     }
 }
@@ -36,21 +306,318 @@ impl Default for CacheConfig {
 pub struct SmartCache<K, V> 
 where
     K: Clone + Eq + std::hash::Hash,
-    V: Clone,
 {
-    data: Arc<RwLock<HashMap<K, CacheEntry<V>>>>,
-    lru_queue: Arc<Mutex<VecDeque<K>>>,
+    data: EntryTable<K, V>,
+    lru_order: RecencyOrder<K>,
+    read_buffer: Arc<ReadBuffer>,
     config: CacheConfig,
-    stats: Arc<RwLock<CacheStats>>,
+    stats: Arc<StatCounters>,
+    // Set by pause_maintenance; the cleanup thread skips its passes while set
+    maintenance_paused: Arc<AtomicBool>,
+    // Bumped by invalidate_all; shared with the cleanup and snapshot threads
+    generation: Arc<AtomicU64>,
+    // Detached; the cleanup loop runs for the life of the process. Kept so
+    // health() can tell whether it is still alive.
     cleanup_handle: Option<thread::JoinHandle<()>>,
+    // When the cleanup thread last finished a pass
+    last_cleanup: Arc<Mutex<Option<Instant>>>,
+    // Set by start_snapshot_scheduler. The thread keeps only a Weak of this
+    // slot, which no one else holds, to notice the cache being dropped.
+    snapshot_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    created_at: Instant,
+}
+
+// Where an entry sits in LruOrder. The generation tells a slot apart from a later
+// entry reusing the same node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LruSlot {
+    index: u32,
+    generation: u32,
+}
+
+impl LruSlot {
+    // Packed for the read buffer
+    fn to_bits(self) -> u64 {
+        (u64::from(self.index) << 32) | u64::from(self.generation)
+    }
+    
+    fn from_bits(bits: u64) -> Self {
+        LruSlot {
+            index: (bits >> 32) as u32,
+            generation: bits as u32,
+        }
+    }
+}
+
+const NIL: usize = usize::MAX;
+
+#[derive(Clone)]
+struct LruNode<K> {
+    key: Option<K>,
+    generation: u32,
+    prev: usize,
+    next: usize,
+}
+
+// Recency order, least recently used first: a doubly linked list over a slab of
+// nodes. Each entry keeps the slot it was given, so moving it to the back or
+// unlinking it is O(1) rather than a scan of the whole order. Freed nodes are
+// reused with a new generation, so stale slots (e.g. in the read buffer) miss.
+#[derive(Clone)]
+struct LruOrder<K> {
+    nodes: Vec<LruNode<K>>,
+    free: Vec<usize>,
+    head: usize,
+    tail: usize,
+    len: usize,
+}
+
+impl<K> LruOrder<K> {
+    fn new() -> Self {
+        LruOrder {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            len: 0,
+        }
+    }
+    
+    fn len(&self) -> usize {
+        self.len
+    }
+    
+    fn push_back(&mut self, key: K) -> LruSlot {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index].key = Some(key);
+                index
+            }
+            None => {
+                self.nodes.push(LruNode { key: Some(key), generation: 0, prev: NIL, next: NIL });
+                self.nodes.len() - 1
+            }
+        };
+        self.link_back(index);
+        self.len += 1;
+        LruSlot {
+            index: index as u32,
+            generation: self.nodes[index].generation,
+        }
+    }
+    
+    fn live(&self, slot: LruSlot) -> Option<usize> {
+        let index = slot.index as usize;
+        self.nodes
+            .get(index)
+            .filter(|node| node.generation == slot.generation && node.key.is_some())
+            .map(|_| index)
+    }
+    
+    fn key(&self, slot: LruSlot) -> Option<&K> {
+        self.live(slot).and_then(|index| self.nodes[index].key.as_ref())
+    }
+    
+    fn move_to_back(&mut self, slot: LruSlot) {
+        if let Some(index) = self.live(slot) {
+            if index != self.tail {
+                self.unlink(index);
+                self.link_back(index);
+            }
+        }
+    }
+    
+    fn remove(&mut self, slot: LruSlot) -> Option<K> {
+        let index = self.live(slot)?;
+        self.unlink(index);
+        self.free.push(index);
+        self.len -= 1;
+        let node = &mut self.nodes[index];
+        node.generation = node.generation.wrapping_add(1);
+        node.key.take()
+    }
+    
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+    
+    // Keys least recently used first, leaving the order empty
+    fn drain(&mut self) -> Vec<K> {
+        let mut keys = Vec::with_capacity(self.len());
+        let mut index = self.head;
+        while index != NIL {
+            let node = &mut self.nodes[index];
+            index = node.next;
+            keys.extend(node.key.take());
+        }
+        self.clear();
+        keys
+    }
+    
+    // Least recently used first
+    fn iter(&self) -> impl Iterator<Item = (LruSlot, &K)> + '_ {
+        let mut index = self.head;
+        std::iter::from_fn(move || {
+            let node = self.nodes.get(index)?;
+            let slot = LruSlot {
+                index: index as u32,
+                generation: node.generation,
+            };
+            index = node.next;
+            node.key.as_ref().map(|key| (slot, key))
+        })
+    }
+    
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = (self.nodes[index].prev, self.nodes[index].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.nodes[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.nodes[next].prev = prev,
+        }
+    }
+    
+    fn link_back(&mut self, index: usize) {
+        self.nodes[index].prev = self.tail;
+        self.nodes[index].next = NIL;
+        match self.tail {
+            NIL => self.head = index,
+            tail => self.nodes[tail].next = index,
+        }
+        self.tail = index;
+    }
+}
+
+// Deferred LRU promotion (moka-style): a hit records its entry's LruSlot in the
+// calling thread's buffer stripe instead of taking the lru_order lock. A full
+// stripe is applied as one batch; eviction, drain and the cleanup thread apply
+// everything pending first. A stripe busy with another thread drops the record,
+// so recency is approximate under heavy contention. Stripes keep their capacity
+// across batches, so recording never allocates once a stripe has been applied.
+const READ_BUFFER_STRIPES: usize = 16;
+const READ_BUFFER_BATCH: usize = 64;
+
+// Expired entries removed per write-lock acquisition in a cleanup pass
+const CLEANUP_CHUNK: usize = 1_000;
+
+// Where the incremental cleanup (CacheConfig::expiry_scan_budget) left off in the
+// LRU slab, and the keys it saw expiring before the next pass
+struct ExpiryScan<K> {
+    budget: usize,
+    horizon: Duration,
+    cursor: usize,
+    due: Vec<(Instant, K)>,
+}
+
+impl<K: Clone + Eq + std::hash::Hash> ExpiryScan<K> {
+    fn new(budget: usize, horizon: Duration) -> Self {
+        Self {
+            budget: budget.max(1),
+            horizon,
+            cursor: 0,
+            due: Vec::new(),
+        }
+    }
+    
+    // Keys that are now due, plus the expired ones among the next `budget` slots
+    fn step<V>(&mut self, data: &HashMap<K, CacheEntry<V>>, lru_order: &LruOrder<K>, now: Instant, generation: u64) -> Vec<K> {
+        let mut expired = Vec::new();
+        self.due.retain(|(at, key)| {
+            if now <= *at {
+                return true;
+            }
+            expired.push(key.clone());
+            false
+        });
+        
+        let slots = lru_order.nodes.len();
+        for _ in 0..self.budget.min(slots) {
+            if self.cursor >= slots {
+                self.cursor = 0;
+            }
+            let node = &lru_order.nodes[self.cursor];
+            self.cursor += 1;
+            let Some((key, entry)) = node.key.as_ref().and_then(|key| data.get_key_value(key)) else {
+                continue;
+            };
+            let expires_at = entry.expires_at(generation);
+            if now > expires_at || entry.value.is_reclaimed() {
+                expired.push(key.clone());
+            } else if expires_at <= now + self.horizon {
+                self.due.push((expires_at, key.clone()));
+            }
+        }
+        expired
+    }
+}
+
+struct ReadBuffer {
+    stripes: Vec<Mutex<Vec<u64>>>,
+}
+
+impl ReadBuffer {
+    fn new() -> Self {
+        Self {
+            stripes: (0..READ_BUFFER_STRIPES).map(|_| Mutex::new(Vec::with_capacity(READ_BUFFER_BATCH))).collect(),
+        }
+    }
+    
+    // Returns the stripe's batch once it is full, for the caller to apply
+    fn record(&self, slot: LruSlot) -> Option<Vec<u64>> {
+        let Ok(mut stripe) = self.stripes[stripe_index() % READ_BUFFER_STRIPES].try_lock() else {
+            return None;
+        };
+        stripe.push(slot.to_bits());
+        (stripe.len() >= READ_BUFFER_BATCH).then(|| stripe.drain(..).collect())
+    }
+    
+    // Never hands back a batch, so never allocates: a full stripe drops the record
+    // and waits for the next get, eviction or cleanup pass to apply it
+    fn record_deferred(&self, slot: LruSlot) {
+        if let Ok(mut stripe) = self.stripes[stripe_index() % READ_BUFFER_STRIPES].try_lock() {
+            if stripe.len() < READ_BUFFER_BATCH {
+                stripe.push(slot.to_bits());
+            }
+        }
+    }
+    
+    fn take_pending(&self) -> Vec<u64> {
+        self.stripes
+            .iter()
+            .flat_map(|stripe| stripe.lock().unwrap().drain(..).collect::<Vec<_>>())
+            .collect()
+    }
+    
+    // Move recorded entries to the most recently used end, in the order read;
+    // entries removed since they were read are skipped
+    fn apply<K>(&self, batch: Vec<u64>, lru_order: &mut LruOrder<K>) {
+        for bits in batch {
+            lru_order.move_to_back(LruSlot::from_bits(bits));
+        }
+    }
+    
+    fn apply_pending<K>(&self, lru_order: &mut LruOrder<K>) {
+        self.apply(self.take_pending(), lru_order);
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct CacheStats {
     pub hits: u64,
     pub misses: u64,
+    // capacity_evictions + expirations
     pub evictions: u64,
     pub insertions: u64,
+    pub capacity_evictions: u64,
+    // Expired entries removed by a lookup, purge_expired or the cleanup thread
+    pub expirations: u64,
+    pub last_eviction: Option<Instant>,
+    // Only get_stats_with_memory fills this in, since it takes a pass over
+    // every entry; None from get_stats
+    pub estimated_memory_bytes: Option<usize>,
 }
 
 impl CacheStats {
@@ -64,10 +631,71 @@ impl CacheStats {
     }
 }
 
+struct StatCounters {
+    hits: StripedCounter,
+    misses: StripedCounter,
+    capacity_evictions: StripedCounter,
+    expirations: StripedCounter,
+    insertions: StripedCounter,
+    // Nanoseconds after `epoch` of the latest eviction, 0 for none yet
+    last_eviction: AtomicU64,
+    epoch: Instant,
+}
+
+impl StatCounters {
+    fn new() -> Self {
+        Self {
+            hits: StripedCounter::new(),
+            misses: StripedCounter::new(),
+            capacity_evictions: StripedCounter::new(),
+            expirations: StripedCounter::new(),
+            insertions: StripedCounter::new(),
+            last_eviction: AtomicU64::new(0),
+            epoch: clock::now(),
+        }
+    }
+    
+    fn record_capacity_evictions(&self, n: u64) {
+        self.capacity_evictions.add(n);
+        self.mark_eviction(n);
+    }
+    
+    fn record_expirations(&self, n: u64) {
+        self.expirations.add(n);
+        self.mark_eviction(n);
+    }
+    
+    fn mark_eviction(&self, n: u64) {
+        if n > 0 {
+            let since_epoch = clock::now().saturating_duration_since(self.epoch).as_nanos() as u64;
+            self.last_eviction.fetch_max(since_epoch.max(1), Ordering::Relaxed);
+        }
+    }
+    
+    fn snapshot(&self) -> CacheStats {
+        let capacity_evictions = self.capacity_evictions.get();
+        let expirations = self.expirations.get();
+        let last_eviction = match self.last_eviction.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(self.epoch + Duration::from_nanos(nanos)),
+        };
+        CacheStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            evictions: capacity_evictions + expirations,
+            insertions: self.insertions.get(),
+            capacity_evictions,
+            expirations,
+            last_eviction,
+            estimated_memory_bytes: None,
+        }
+    }
+}
+
 impl<K, V> SmartCache<K, V>
 where
     K: Clone + Eq + std::hash::Hash + Send + Sync + 'static,
-    V: Clone + Send + Sync + 'static,
+    V: Send + Sync + 'static,
 {
     pub fn new(max_capacity: usize) -> Self {
         let config = CacheConfig {
@@ -78,155 +706,884 @@ where
     }
     
     pub fn with_config(config: CacheConfig) -> Self {
-        let data = Arc::new(RwLock::new(HashMap::new()));
-        let lru_queue = Arc::new(Mutex::new(VecDeque::new()));
-        let stats = Arc::new(RwLock::new(CacheStats::default()));
+        let data = Arc::new(RwLock::new(Shared::default()));
+        let lru_order = Arc::new(Mutex::new(Shared::new(LruOrder::new())));
+        let read_buffer = Arc::new(ReadBuffer::new());
+        let stats = Arc::new(StatCounters::new());
+        let maintenance_paused = Arc::new(AtomicBool::new(false));
+        let generation = Arc::new(AtomicU64::new(0));
+        let last_cleanup = Arc::new(Mutex::new(None));
         
-        // Start cleanup thread
+        // Start cleanup thread; it also applies buffered reads to the LRU
         let data_clone = Arc::clone(&data);
-        let lru_clone = Arc::clone(&lru_queue);
+        let lru_clone = Arc::clone(&lru_order);
+        let read_buffer_clone = Arc::clone(&read_buffer);
+        let stats_clone = Arc::clone(&stats);
+        let paused_clone = Arc::clone(&maintenance_paused);
+        let generation_clone = Arc::clone(&generation);
+        let last_cleanup_clone = Arc::clone(&last_cleanup);
         let cleanup_interval = config.cleanup_interval;
+        let mut scan = config.expiry_scan_budget.map(|budget| ExpiryScan::new(budget, cleanup_interval));
         
         let cleanup_handle = thread::spawn(move || {
             loop {
                 thread::sleep(cleanup_interval);
-                Self::cleanup_expired(&data_clone, &lru_clone);
+                if !paused_clone.load(Ordering::Acquire) {
+                    let generation = generation_clone.load(Ordering::Acquire);
+                    match &mut scan {
+                        Some(scan) => Self::cleanup_step(&data_clone, &lru_clone, &read_buffer_clone, &stats_clone, &paused_clone, generation, scan),
+                        None => Self::cleanup_expired(&data_clone, &lru_clone, &read_buffer_clone, &stats_clone, generation, Some(&paused_clone)),
+                    };
+                    *last_cleanup_clone.lock().unwrap() = Some(clock::now());
+                }
             }
         });
         
         Self {
             data,
-            lru_queue,
+            lru_order,
+            read_buffer,
             config,
             stats,
+            maintenance_paused,
+            generation,
             cleanup_handle: Some(cleanup_handle),
+            last_cleanup,
+            snapshot_handle: Arc::new(Mutex::new(None)),
+            created_at: clock::now(),
         }
     }
     
     pub fn put(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool {
-        let ttl = ttl.unwrap_or(self.config.default_ttl);
-        
+        self.put_arc(key, Arc::new(value), ttl, priority)
+    }
+    
+    // Store a value that is already shared; get_arc hands back the same allocation
+    pub fn put_arc(&self, key: K, value: Arc<V>, ttl: Option<Duration>, priority: u8) -> bool {
+        self.write(key, value, ttl, priority, 0, WriteIf::Always).is_ok()
+    }
+    
+    // Store only if the key holds no live entry. Returns the live value that is
+    // kept instead, or None once stored (or refused while maintenance is paused
+    // and the cache is full).
+    pub fn put_if_absent(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.write(key, Arc::new(value), ttl, priority, 0, WriteIf::Absent).err().flatten().map(Arc::unwrap_or_clone)
+    }
+    
+    // Swap in a new value only if the key holds a live entry, which keeps its
+    // expiry and priority. Returns the replaced value, None if nothing was stored.
+    pub fn replace(&self, key: K, value: V) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.write(key, Arc::new(value), None, 0, 0, WriteIf::Present).ok().flatten().map(Arc::unwrap_or_clone)
+    }
+    
+    // `access_count` starts the new entry's count; only import carries one over
+    fn write(&self, key: K, value: Arc<V>, ttl: Option<Duration>, priority: u8, access_count: usize, when: WriteIf) -> WriteResult<V> {
         let mut data = self.data.write().unwrap();
-        let mut lru_queue = self.lru_queue.lock().unwrap();
+        let mut lru_order = self.lru_order.lock().unwrap();
         
-        // Check capacity and evict if necessary
+        let now = clock::now();
+        let generation = self.generation();
+        let current = data.get(&key).filter(|entry| now <= entry.expires_at(generation)).and_then(|entry| entry.value.upgrade());
+        let (ttl, priority) = match (when, &current) {
+            (WriteIf::Absent, Some(_)) => return Err(current),
+            (WriteIf::HigherPriority, Some(_)) if priority <= data[&key].priority => return Err(current),
+            (WriteIf::Present, None) => return Err(None),
+            (WriteIf::Present, Some(_)) => {
+                let entry = &data[&key];
+                (entry.ttl.saturating_duration_since(now), entry.priority)
+            }
+            _ => (ttl.unwrap_or(self.config.default_ttl), priority),
+        };
+        
+        // Check capacity and evict if necessary; while paused, no room is made
         if !data.contains_key(&key) && data.len() >= self.config.max_capacity {
-            self.evict_if_necessary(&mut data, &mut lru_queue);
+            if self.is_maintenance_paused() {
+                return Err(None);
+            }
+            self.evict_if_necessary(&mut data, &mut lru_order);
         }
         
+        // A rewritten key keeps its node and moves to the most recent end
+        let lru_slot = match data.get(&key) {
+            Some(existing) => {
+                lru_order.move_to_back(existing.lru_slot);
+                existing.lru_slot
+            }
+            None => lru_order.push_back(key.clone()),
+        };
+        
         // Create entry
         let entry = CacheEntry {
-            value,
-            priority: priority.min(10).max(1),
-            ttl: Instant::now() + ttl,
-            created_at: Instant::now(),
-            last_accessed: Instant::now(),
-            access_count: 0,
+            value: ValueSlot::Strong(value),
+            priority: priority.clamp(1, 10),
+            ttl: clock::now() + ttl,
+            created_at: clock::now(),
+            last_accessed: clock::now(),
+            access_count,
+            lru_slot,
+            generation,
         };
-        
-        // Update data structures
-        data.insert(key.clone(), entry);
-        lru_queue.retain(|k| k != &key);
-        lru_queue.push_back(key);
+        data.insert(key, entry);
         
         // Update stats
-        self.stats.write().unwrap().insertions += 1;
+        self.stats.insertions.add(1);
         
-        true
+        Ok(current)
+    }
+    
+    // put() for self-expiring values: an explicit `ttl` wins, then the value's own,
+    // then the configured default
+    pub fn put_expirable(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool
+    where
+        V: Expirable,
+    {
+        let ttl = ttl.or_else(|| value.ttl());
+        self.put(key, value, ttl, priority)
+    }
+    
+    // Lookups take any borrowed form of the key, e.g. &str for String keys
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.access(key, false, |value, _, _| V::clone(value))
+    }
+    
+    // Hit path for Copy values (counters, ids, scores): the value is copied out and
+    // nothing is allocated. Its LRU record is dropped when the thread's read buffer
+    // stripe is full rather than applying the batch here (see ReadBuffer).
+    pub fn get_copy<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+        V: Copy,
+    {
+        self.access(key, true, |value, _, _| **value)
+    }
+    
+    // Like get, but shares the stored value instead of cloning it; works for any V
+    pub fn get_arc<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.access(key, false, |value, _, _| Arc::clone(value))
+    }
+    
+    // Value and metadata from a single lookup (one LRU update, one hit)
+    pub fn get_with_metadata<Q>(&self, key: &Q) -> Option<(V, EntryMetadata)>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.access(key, false, |value, entry, previous_access| {
+            (V::clone(value), entry.metadata(&self.config, entry.last_accessed, previous_access))
+        })
     }
     
-    pub fn get(&self, key: &K) -> Option<V> {
+    fn access<Q, R>(&self, key: &Q, defer_promotion: bool, read: impl FnOnce(&Arc<V>, &CacheEntry<V>, Instant) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
         let mut data = self.data.write().unwrap();
         
         if let Some(entry) = data.get_mut(key) {
-            // Check TTL
-            if Instant::now() > entry.ttl {
-                data.remove(key);
-                self.lru_queue.lock().unwrap().retain(|k| k != key);
-                self.stats.write().unwrap().misses += 1;
-                return None;
-            }
+            // Check TTL, and whether a softened value has been freed
+            let now = clock::now();
+            let expires_at = entry.expires_at(self.generation());
+            let value = match entry.value.upgrade() {
+                Some(value) if now <= expires_at => value,
+                _ => {
+                    if now > expires_at {
+                        self.stats.record_expirations(1);
+                    }
+                    let slot = entry.lru_slot;
+                    data.remove(key);
+                    self.lru_order.lock().unwrap().remove(slot);
+                    self.stats.misses.add(1);
+                    return None;
+                }
+            };
+            
+            // A hit makes a softened entry strong again
+            entry.value = ValueSlot::Strong(Arc::clone(&value));
             
             // Update access metadata
-            entry.last_accessed = Instant::now();
+            let previous_access = entry.last_accessed;
+            entry.last_accessed = now;
             entry.access_count += 1;
-            let value = entry.value.clone();
+            let result = read(&value, entry, previous_access);
             
-            // Update LRU
-            let mut lru_queue = self.lru_queue.lock().unwrap();
-            lru_queue.retain(|k| k != key);
-            lru_queue.push_back(key.clone());
+            // Defer the LRU move to a batch (see ReadBuffer)
+            if defer_promotion {
+                self.read_buffer.record_deferred(entry.lru_slot);
+            } else if let Some(batch) = self.read_buffer.record(entry.lru_slot) {
+                self.read_buffer.apply(batch, &mut self.lru_order.lock().unwrap());
+            }
             
             // Update stats
-            self.stats.write().unwrap().hits += 1;
+            self.stats.hits.add(1);
             
-            Some(value)
+            Some(result)
         } else {
-            self.stats.write().unwrap().misses += 1;
+            self.stats.misses.add(1);
             None
         }
     }
     
-    pub fn delete(&self, key: &K) -> bool {
+    // Existence check that leaves stats, access counts and LRU order untouched
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.data
+            .read()
+            .unwrap()
+            .get(key)
+            .is_some_and(|entry| clock::now() <= entry.expires_at(self.generation()) && !entry.value.is_reclaimed())
+    }
+    
+    pub fn delete<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
         let mut data = self.data.write().unwrap();
-        if data.remove(key).is_some() {
-            self.lru_queue.lock().unwrap().retain(|k| k != key);
-            true
-        } else {
-            false
+        match data.remove(key) {
+            Some(entry) => {
+                self.lru_order.lock().unwrap().remove(entry.lru_slot);
+                true
+            }
+            None => false,
         }
     }
     
-    pub fn clear(&self) {
-        self.data.write().unwrap().clear();
-        self.lru_queue.lock().unwrap().clear();
+    // Keep only entries the predicate accepts; returns how many were removed
+    pub fn retain<F>(&self, mut keep: F) -> usize
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        let mut data = self.data.write().unwrap();
+        let mut lru_order = self.lru_order.lock().unwrap();
+        let now = clock::now();
+        
+        let before = data.len();
+        data.retain(|key, entry| {
+            let kept = match entry.value.upgrade() {
+                Some(value) => keep(key, &value, &entry.metadata(&self.config, now, entry.last_accessed)),
+                None => false,
+            };
+            if !kept {
+                lru_order.remove(entry.lru_slot);
+            }
+            kept
+        });
+        
+        before - data.len()
     }
     
-    pub fn get_stats(&self) -> CacheStats {
-        self.stats.read().unwrap().clone()
+    // Empty the cache and hand back the live values, least recently used first.
+    // Values still shared through get_arc are cloned; the rest are moved out.
+    pub fn drain(&self) -> Vec<(K, V)>
+    where
+        V: Clone,
+    {
+        let mut data = self.data.write().unwrap();
+        let mut lru_order = self.lru_order.lock().unwrap();
+        self.read_buffer.apply_pending(&mut lru_order);
+        let now = clock::now();
+        let generation = self.generation();
+        
+        let drained = lru_order
+            .drain()
+            .into_iter()
+            .filter_map(|key| data.remove(&key).map(|entry| (key, entry)))
+            .filter(|(_, entry)| now <= entry.expires_at(generation))
+            .filter_map(|(key, entry)| entry.value.into_arc().map(|value| (key, Arc::unwrap_or_clone(value))))
+            .collect();
+        data.clear();
+        drained
     }
     
-    pub fn size(&self) -> usize {
-        self.data.read().unwrap().len()
+    // Copy out live entries matching the predicate, with their remaining TTLs
+    pub fn export_where<F>(&self, predicate: F) -> Vec<ExportedEntry<K, V>>
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+        V: Clone,
+    {
+        Self::export_entries(&self.config, &self.data.read().unwrap(), self.generation(), predicate)
     }
     
-    fn evict_if_necessary(&self, data: &mut HashMap<K, CacheEntry<V>>, lru_queue: &mut VecDeque<K>) {
-        // Find entry with lowest priority
-        let mut eviction_candidate: Option<(K, u8)> = None;
+    fn export_entries<F>(config: &CacheConfig, data: &HashMap<K, CacheEntry<V>>, generation: u64, mut predicate: F) -> Vec<ExportedEntry<K, V>>
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+        V: Clone,
+    {
+        let now = clock::now();
         
-        for key in lru_queue.iter() {
-            if let Some(entry) = data.get(key) {
-                match &eviction_candidate {
-                    None => eviction_candidate = Some((key.clone(), entry.priority)),
-                    Some((_, priority)) if entry.priority < *priority => {
-                        eviction_candidate = Some((key.clone(), entry.priority));
-                    }
-                    _ => {}
-                }
+        data.iter()
+            .filter(|(_, entry)| now <= entry.expires_at(generation))
+            .filter_map(|(key, entry)| {
+                let value = entry.value.upgrade()?;
+                let meta = entry.metadata(config, now, entry.last_accessed);
+                predicate(key, &value, &meta).then(|| ExportedEntry {
+                    key: key.clone(),
+                    value: V::clone(&value),
+                    priority: meta.priority,
+                    ttl_remaining: meta.ttl_remaining,
+                    access_count: meta.access_count,
+                })
+            })
+            .collect()
+    }
+    
+    // Load exported records through the normal put path (capacity and
+    // eviction rules apply), keeping their access counts. The conflict check
+    // and the write happen under one lock, so a concurrent put can't slip in
+    // between them. Returns how many records were written.
+    pub fn import(&self, entries: Vec<ExportedEntry<K, V>>, policy: ConflictPolicy) -> usize {
+        let mut imported = 0;
+        for record in entries {
+            if record.ttl_remaining.is_zero() {
+                continue;
+            }
+            let value = Arc::new(record.value);
+            if self.write(record.key, value, Some(record.ttl_remaining), record.priority, record.access_count, policy.into()).is_ok() {
+                imported += 1;
             }
         }
+        imported
+    }
+    
+    // Bulk-load a CSV or JSON seed file before traffic arrives
+    pub fn warm_from_file<P: AsRef<Path>>(&self, path: P) -> Result<WarmupReport, WarmupError>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        self.warm_from_file_with_progress(path, |_, _| {})
+    }
+    
+    // Same as warm_from_file, reporting (loaded, total) after every batch.
+    // Capacity is only enforced once, after the last batch.
+    pub fn warm_from_file_with_progress<P, F>(&self, path: P, mut progress: F) -> Result<WarmupReport, WarmupError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(usize, usize),
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let records = read_seed_file(path.as_ref())?;
+        let total = records.len();
+        let entries = records
+            .into_iter()
+            .map(|record| (record.key, record.value, record.ttl_secs.map(Duration::from_secs), record.priority));
+        Ok(self.load_batches(entries, |loaded| progress(loaded, total)))
+    }
+    
+    // Insert many entries with one capacity pass and one stats update at the
+    // end instead of per-entry bookkeeping. Entries go in in batches, so lookups
+    // are not locked out for the whole load; later duplicates win.
+    pub fn bulk_load<I>(&self, entries: I) -> WarmupReport
+    where
+        I: IntoIterator<Item = BulkEntry<K, V>>,
+    {
+        self.load_batches(entries.into_iter(), |_| {})
+    }
+    
+    fn load_batches<I>(&self, entries: I, mut progress: impl FnMut(usize)) -> WarmupReport
+    where
+        I: Iterator<Item = BulkEntry<K, V>>,
+    {
+        let mut entries = entries.peekable();
+        let mut loaded = 0;
         
-        if let Some((key, _)) = eviction_candidate {
-            data.remove(&key);
-            lru_queue.retain(|k| k != &key);
-            self.stats.write().unwrap().evictions += 1;
+        while entries.peek().is_some() {
+            let batch: Vec<_> = entries.by_ref().take(WARMUP_BATCH).collect();
+            loaded += batch.len();
+            self.insert_unchecked(batch);
+            progress(loaded);
         }
+        
+        self.stats.insertions.add(loaded as u64);
+        let evicted = self.evict_to_capacity();
+        WarmupReport { loaded, evicted }
     }
     
-    fn cleanup_expired(data: &Arc<RwLock<HashMap<K, CacheEntry<V>>>>, lru_queue: &Arc<Mutex<VecDeque<K>>>) {
-        let mut data = data.write().unwrap();
-        let mut lru_queue = lru_queue.lock().unwrap();
-        let now = Instant::now();
+    // Insert without per-entry capacity checks; pair with evict_to_capacity
+    fn insert_unchecked(&self, batch: Vec<BulkEntry<K, V>>) {
+        let mut data = self.data.write().unwrap();
+        let mut lru_order = self.lru_order.lock().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
         
-        let expired_keys: Vec<K> = data
+        for (key, value, ttl, priority) in batch {
+            let ttl = ttl.unwrap_or(self.config.default_ttl);
+            let lru_slot = match data.get(&key) {
+                Some(existing) => {
+                    lru_order.move_to_back(existing.lru_slot);
+                    existing.lru_slot
+                }
+                None => lru_order.push_back(key.clone()),
+            };
+            let entry = CacheEntry {
+                value: ValueSlot::Strong(Arc::new(value)),
+                priority: priority.clamp(1, 10),
+                ttl: now + ttl,
+                created_at: now,
+                last_accessed: now,
+                access_count: 0,
+                lru_slot,
+                generation,
+            };
+            data.insert(key, entry);
+        }
+    }
+    
+    // One eviction pass in the order evict_if_necessary would pick victims
+    fn evict_to_capacity(&self) -> usize {
+        let mut data = self.data.write().unwrap();
+        let mut lru_order = self.lru_order.lock().unwrap();
+        let excess = data.len().saturating_sub(self.config.max_capacity);
+        if excess == 0 || self.is_maintenance_paused() {
+            return 0;
+        }
+        self.read_buffer.apply_pending(&mut lru_order);
+        
+        let order = self.eviction_order(&data, &lru_order);
+        let mut evicted = 0;
+        for (_, slot) in order.into_iter().take(excess) {
+            if let Some(key) = lru_order.remove(slot) {
+                data.remove(&key);
+                evicted += 1;
+            }
+        }
+        
+        self.stats.record_capacity_evictions(evicted as u64);
+        evicted
+    }
+    
+    // The `n` entries capacity eviction would take next, each with the effective
+    // priority it is ranked by (lowest goes first; minus infinity for expired
+    // entries). Nothing is evicted.
+    pub fn next_eviction_candidates(&self, n: usize) -> Vec<(K, f64)> {
+        let data = self.data.read().unwrap();
+        let mut lru_order = self.lru_order.lock().unwrap();
+        self.read_buffer.apply_pending(&mut lru_order);
+        
+        let mut order = self.eviction_order(&data, &lru_order);
+        order.truncate(n);
+        order
+            .into_iter()
+            .filter_map(|(priority, slot)| lru_order.key(slot).map(|key| (key.clone(), priority)))
+            .collect()
+    }
+    
+    // Effective priorities and LRU slots in eviction order: expired entries, then
+    // lowest priority first, least recently used among equals (the sort is stable)
+    fn eviction_order(&self, data: &HashMap<K, CacheEntry<V>>, lru_order: &LruOrder<K>) -> Vec<(f64, LruSlot)> {
+        let now = clock::now();
+        let generation = self.generation();
+        let mut order: Vec<(f64, LruSlot)> = lru_order
             .iter()
-            .filter(|(_, entry)| now > entry.ttl)
-            .map(|(key, _)| key.clone())
+            .filter_map(|(slot, key)| data.get(key).map(|entry| (entry.eviction_rank(&self.config, now, generation), slot)))
+            .collect();
+        order.sort_by(|a, b| a.0.total_cmp(&b.0));
+        order
+    }
+    
+    // Memory-pressure hook: hold entries below `soft_priority_threshold` weakly.
+    // Values no caller still holds are freed (and their entries dropped) right
+    // away; the rest go once their last Arc is released. Returns entries softened.
+    pub fn soften_low_priority(&self) -> usize {
+        let Some(threshold) = self.config.soft_priority_threshold else {
+            return 0;
+        };
+        let mut data = self.data.write().unwrap();
+        let mut lru_order = self.lru_order.lock().unwrap();
+        
+        let mut softened = 0;
+        for entry in data.values_mut() {
+            if let ValueSlot::Strong(value) = &entry.value {
+                if entry.priority < threshold {
+                    entry.value = ValueSlot::Soft(Arc::downgrade(value));
+                    softened += 1;
+                }
+            }
+        }
+        
+        data.retain(|_, entry| {
+            let reclaimed = entry.value.is_reclaimed();
+            if reclaimed {
+                lru_order.remove(entry.lru_slot);
+            }
+            !reclaimed
+        });
+        softened
+    }
+    
+    // Swaps in empty tables rather than clearing in place, so a table still
+    // shared with a fork isn't copied just to be emptied
+    pub fn clear(&self) {
+        *self.data.write().unwrap() = Shared::default();
+        *self.lru_order.lock().unwrap() = Shared::new(LruOrder::new());
+    }
+    
+    // clear() for large caches: bumps the generation, and every entry stored
+    // before reads as expired from then on. No lock is taken and no entry
+    // touched, so traffic doesn't wait at all. The entries are reclaimed like any
+    // expired ones: by the cleanup thread, purge_expired, a put of the same key,
+    // or capacity eviction, which takes them first. Stats are kept.
+    pub fn invalidate_all(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+    
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+    
+    pub fn get_stats(&self) -> CacheStats {
+        self.stats.snapshot()
+    }
+    
+    // A pass over every entry, so unlike get_stats this is not free; a growing
+    // `expired` count means cleanup is falling behind. Soft values freed since
+    // count as expired.
+    pub fn ttl_distribution(&self) -> TtlDistribution {
+        let now = clock::now();
+        let generation = self.generation();
+        let mut distribution = TtlDistribution::default();
+        for entry in self.data.read().unwrap().values() {
+            let expires_at = entry.expires_at(generation);
+            let live = now <= expires_at && !entry.value.is_reclaimed();
+            distribution.record(live.then(|| expires_at - now));
+        }
+        distribution
+    }
+    
+    // Takes the read lock for the copy, so the view is consistent. Values are
+    // shared with the cache rather than copied, and soft values freed by then
+    // are left out.
+    pub fn freeze(&self) -> FrozenCache<K, Arc<V>> {
+        let data = self.data.read().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
+        let entries = data
+            .iter()
+            .filter(|(_, entry)| now <= entry.expires_at(generation))
+            .filter_map(|(key, entry)| {
+                let value = entry.value.upgrade()?;
+                Some((key.clone(), FrozenEntry { value, priority: entry.priority, expires_at: entry.ttl }))
+            })
             .collect();
+        FrozenCache::new(now, entries)
+    }
+    
+    // A new cache holding this one's entries in the same recency order, with the
+    // same config, and independent of it from then on. Nothing is copied here:
+    // the two share the entry table and recency order, and the first write on
+    // either side copies them for that side (see Shared). Values stay shared
+    // Arcs, so only the table is copied, never a value. A hit counts as a write,
+    // since it updates access metadata. Values with interior mutability see each
+    // other's changes. The fork runs its own cleanup and starts with zeroed stats.
+    pub fn fork(&self) -> Self {
+        let fork = Self::with_config(self.config.clone());
+        let data = self.data.read().unwrap();
+        // The shared entries stay live in the fork until it is invalidated itself
+        fork.generation.store(self.generation(), Ordering::Release);
+        let mut lru_order = self.lru_order.lock().unwrap();
+        self.read_buffer.apply_pending(&mut lru_order);
+        *fork.data.write().unwrap() = Shared::clone(&data);
+        *fork.lru_order.lock().unwrap() = Shared::clone(&lru_order);
+        fork
+    }
+    
+    pub fn size(&self) -> usize {
+        self.data.read().unwrap().len()
+    }
+    
+    fn evict_if_necessary(&self, data: &mut HashMap<K, CacheEntry<V>>, lru_order: &mut LruOrder<K>) {
+        self.read_buffer.apply_pending(lru_order);
         
-        for key in expired_keys {
+        // Find entry with lowest (effective) priority, expired ones first
+        let now = clock::now();
+        let generation = self.generation();
+        let mut eviction_candidate: Option<(LruSlot, f64)> = None;
+        
+        for (slot, key) in lru_order.iter() {
+            if let Some(entry) = data.get(key) {
+                let priority = entry.eviction_rank(&self.config, now, generation);
+                match &eviction_candidate {
+                    None => eviction_candidate = Some((slot, priority)),
+                    Some((_, lowest)) if priority < *lowest => {
+                        eviction_candidate = Some((slot, priority));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        
+        if let Some(key) = eviction_candidate.and_then(|(slot, _)| lru_order.remove(slot)) {
             data.remove(&key);
-            lru_queue.retain(|k| k != &key);
+            self.stats.record_capacity_evictions(1);
+        }
+    }
+    
+    // Freeze the cache's own mutations for backups and debugging: the background
+    // cleanup pass is skipped and nothing is evicted for capacity, so puts of new
+    // keys into a full cache are rejected. Bulk loads may still overshoot capacity
+    // until resume_maintenance trims them. A pass in progress stops before its next
+    // chunk, so nothing is cleaned up once this returns.
+    pub fn pause_maintenance(&self) {
+        let _data = self.data.write().unwrap();
+        self.maintenance_paused.store(true, Ordering::Release);
+    }
+    
+    // Returns the entries evicted to get back within capacity
+    pub fn resume_maintenance(&self) -> usize {
+        self.maintenance_paused.store(false, Ordering::Release);
+        self.evict_to_capacity()
+    }
+    
+    pub fn is_maintenance_paused(&self) -> bool {
+        self.maintenance_paused.load(Ordering::Acquire)
+    }
+    
+    // Diagnostics for readiness probes. Counting expired entries is a pass over
+    // the whole map under the read lock, so probe at a modest rate. Entries whose
+    // soft value was freed count as expired.
+    pub fn health(&self) -> HealthReport {
+        let cleanup_alive = self.cleanup_handle.as_ref().is_some_and(|handle| !handle.is_finished());
+        let last_cleanup = *self.last_cleanup.lock().unwrap();
+        let now = clock::now();
+        let idle = now.saturating_duration_since(last_cleanup.unwrap_or(self.created_at));
+        let cleanup_overdue = !self.is_maintenance_paused() && idle > self.config.cleanup_interval.saturating_mul(2);
+        
+        let waiting = Instant::now();
+        let data = self.data.read().unwrap();
+        let lock_wait = waiting.elapsed();
+        let generation = self.generation();
+        let expired_unreclaimed = data
+            .values()
+            .filter(|entry| now > entry.expires_at(generation) || entry.value.is_reclaimed())
+            .count();
+        HealthReport {
+            cleanup_alive,
+            last_cleanup,
+            cleanup_overdue,
+            event_queue_depth: 0,
+            lock_wait,
+            entries: data.len(),
+            capacity: self.config.max_capacity,
+            expired_unreclaimed,
+        }
+    }
+    
+    // Run a cleanup pass now instead of waiting for the background interval
+    pub fn purge_expired(&self) -> usize {
+        Self::cleanup_expired(&self.data, &self.lru_order, &self.read_buffer, &self.stats, self.generation(), None)
+    }
+    
+    // Expired keys are found under the read lock, then removed CLEANUP_CHUNK at a
+    // time with the locks released in between, so a large pass never holds up
+    // lookups for its whole length. The background pass passes the pause flag and
+    // stops at the next chunk once maintenance is paused.
+    fn cleanup_expired(
+        data: &EntryTable<K, V>,
+        lru_order: &RecencyOrder<K>,
+        read_buffer: &ReadBuffer,
+        stats: &StatCounters,
+        generation: u64,
+        paused: Option<&AtomicBool>,
+    ) -> usize {
+        read_buffer.apply_pending(&mut lru_order.lock().unwrap());
+        let now = clock::now();
+        let candidates: Vec<K> = data
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| now > entry.expires_at(generation) || entry.value.is_reclaimed())
+            .map(|(key, _)| key.clone())
+            .collect();
+        Self::remove_expired(data, lru_order, stats, generation, paused, &candidates)
+    }
+    
+    // The background pass with expiry_scan_budget set: only the scan's next
+    // budget of slots and its due keys are looked at
+    fn cleanup_step(
+        data: &EntryTable<K, V>,
+        lru_order: &RecencyOrder<K>,
+        read_buffer: &ReadBuffer,
+        stats: &StatCounters,
+        paused: &AtomicBool,
+        generation: u64,
+        scan: &mut ExpiryScan<K>,
+    ) -> usize {
+        read_buffer.apply_pending(&mut lru_order.lock().unwrap());
+        let candidates = {
+            let data = data.read().unwrap();
+            let order = lru_order.lock().unwrap();
+            scan.step(&data, &order, clock::now(), generation)
+        };
+        Self::remove_expired(data, lru_order, stats, generation, Some(paused), &candidates)
+    }
+    
+    fn remove_expired(
+        data: &EntryTable<K, V>,
+        lru_order: &RecencyOrder<K>,
+        stats: &StatCounters,
+        generation: u64,
+        paused: Option<&AtomicBool>,
+        candidates: &[K],
+    ) -> usize {
+        let mut removed = 0;
+        for chunk in candidates.chunks(CLEANUP_CHUNK) {
+            let mut data = data.write().unwrap();
+            if paused.is_some_and(|paused| paused.load(Ordering::Acquire)) {
+                break;
+            }
+            let mut lru_order = lru_order.lock().unwrap();
+            let now = clock::now();
+            
+            // Freed soft values are removed here too but are not expirations.
+            // A key rewritten since the scan is live again and stays.
+            let mut expired = 0;
+            for key in chunk {
+                if !data.get(key).is_some_and(|entry| now > entry.expires_at(generation) || entry.value.is_reclaimed()) {
+                    continue;
+                }
+                if let Some(entry) = data.remove(key) {
+                    lru_order.remove(entry.lru_slot);
+                    removed += 1;
+                    if now > entry.expires_at(generation) {
+                        expired += 1;
+                    }
+                }
+            }
+            stats.record_expirations(expired);
+            
+            drop(lru_order);
+            drop(data);
+            thread::yield_now();
+        }
+        removed
+    }
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + Serialize + DeserializeOwned + 'static,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    // Write all live entries to `path` (gzip-compressed when it ends in .gz).
+    // Soft values already freed are left out, as in export_where.
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<usize, SnapshotError> {
+        let entries = self.export_where(|_, _, _| true);
+        let count = entries.len();
+        write_snapshot(path.as_ref(), &Snapshot::new(entries))?;
+        Ok(count)
+    }
+    
+    // Restore a snapshot through import(); TTLs continue from where they were saved.
+    // Records failing their checksum are skipped and listed in the report.
+    pub fn load_snapshot<P: AsRef<Path>>(&self, path: P, policy: ConflictPolicy) -> Result<SnapshotLoadReport, SnapshotError> {
+        let (snapshot, verification) = read_snapshot_lossy(path.as_ref())?;
+        Ok(SnapshotLoadReport {
+            loaded: self.import(snapshot.entries, policy),
+            verification,
+        })
+    }
+    
+    // Start the background thread described by `config.snapshot_schedule`,
+    // handing the outcome of every run to `on_event`. Returns false when no
+    // schedule is configured or the thread already runs. The thread stops on its
+    // next tick once the cache has been dropped.
+    pub fn start_snapshot_scheduler<F>(&self, mut on_event: F) -> bool
+    where
+        F: FnMut(SnapshotEvent) + Send + 'static,
+    {
+        let Some(schedule) = self.config.snapshot_schedule.clone() else {
+            return false;
+        };
+        let mut handle = self.snapshot_handle.lock().unwrap();
+        if handle.is_some() {
+            return false;
+        }
+        
+        let config = self.config.clone();
+        let data = Arc::clone(&self.data);
+        let generation = Arc::clone(&self.generation);
+        let alive = Arc::downgrade(&self.snapshot_handle);
+        let spawned = thread::Builder::new()
+            .name("cache-snapshots".to_string())
+            .spawn(move || loop {
+                thread::sleep(schedule.interval);
+                if alive.strong_count() == 0 {
+                    break;
+                }
+                let entries = Self::export_entries(&config, &data.read().unwrap(), generation.load(Ordering::Acquire), |_, _, _| true);
+                on_event(snapshot::write_scheduled_snapshot(&schedule, entries));
+            })
+            .expect("failed to spawn snapshot scheduler");
+        *handle = Some(spawned);
+        true
+    }
+}
+
+// What each part of the cache owns on the heap, for estimated_memory_bytes. A
+// soft value is kept alive by whoever holds its Arc, so the cache counts none of it.
+impl<V: MemSize> MemSize for ValueSlot<V> {
+    fn heap_size(&self) -> usize {
+        match self {
+            ValueSlot::Strong(value) => value.heap_size(),
+            ValueSlot::Soft(_) => 0,
+        }
+    }
+}
+
+impl<V: MemSize> MemSize for CacheEntry<V> {
+    fn heap_size(&self) -> usize {
+        self.value.heap_size()
+    }
+}
+
+impl<K: MemSize> MemSize for LruNode<K> {
+    fn heap_size(&self) -> usize {
+        self.key.heap_size()
+    }
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + MemSize + 'static,
+    V: Send + Sync + MemSize + 'static,
+{
+    // Approximate RAM held by the cache: the entry table and LRU slab including
+    // their unused capacity, plus what each key and value own per MemSize. A pass
+    // over all entries under the read lock. A table still shared with a fork is
+    // counted by both caches.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        let data = self.data.read().unwrap();
+        let lru_order = self.lru_order.lock().unwrap();
+        HashMap::heap_size(&data) + Vec::heap_size(&lru_order.nodes) + Vec::heap_size(&lru_order.free)
+    }
+    
+    // get_stats plus estimated_memory_bytes
+    pub fn get_stats_with_memory(&self) -> CacheStats {
+        CacheStats {
+            estimated_memory_bytes: Some(self.estimated_memory_bytes()),
+            ..self.get_stats()
         }
     }
 }
@@ -271,7 +1628,7 @@ mod tests {
         cache.put(1, "value1", Some(Duration::from_millis(100)), 5);
         assert_eq!(cache.get(&1), Some("value1"));
         
-        thread::sleep(Duration::from_millis(150));
+        clock::advance(Duration::from_millis(150));
         assert_eq!(cache.get(&1), None); // Expired
     }
     
@@ -288,4 +1645,784 @@ mod tests {
         assert_eq!(stats.misses, 1);
         assert_eq!(stats.insertions, 1);
     }
+    
+    #[test]
+    fn test_get_with_metadata() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "value1", Some(Duration::from_secs(60)), 7);
+        cache.get(&1);
+        
+        let (value, meta) = cache.get_with_metadata(&1).unwrap();
+        assert_eq!(value, "value1");
+        assert_eq!(meta.priority, 7);
+        assert_eq!(meta.access_count, 2);
+        assert!(meta.ttl_remaining <= Duration::from_secs(60));
+        assert!(meta.ttl_remaining > Duration::from_secs(59));
+        assert!(cache.get_with_metadata(&2).is_none());
+        
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+    }
+    
+    #[test]
+    fn test_contains_key_has_no_side_effects() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "value1", None, 5);
+        cache.put(2, "value2", Some(Duration::from_millis(20)), 5);
+        
+        assert!(cache.contains_key(&1));
+        assert!(!cache.contains_key(&3));
+        
+        clock::advance(Duration::from_millis(40));
+        assert!(!cache.contains_key(&2));
+        
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 0);
+        assert_eq!(cache.get_with_metadata(&1).unwrap().1.access_count, 1);
+    }
+    
+    #[test]
+    fn test_purge_expired() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "short", Some(Duration::from_millis(20)), 5);
+        cache.put(2, "short", Some(Duration::from_millis(20)), 5);
+        cache.put(3, "long", None, 5);
+        
+        assert_eq!(cache.purge_expired(), 0);
+        clock::advance(Duration::from_millis(40));
+        assert_eq!(cache.purge_expired(), 2);
+        assert_eq!(cache.size(), 1);
+    }
+    
+    #[test]
+    fn test_invalidate_all() {
+        let cache = SmartCache::new(4);
+        for i in 0..4 {
+            cache.put(i, i, None, 9);
+        }
+        cache.get(&0);
+        let fork = cache.fork();
+        cache.invalidate_all();
+        
+        // Gone at once, still stored until reclaimed; the fork keeps its copies
+        assert_eq!(cache.get(&0), None);
+        assert!(!cache.contains_key(&1));
+        assert_eq!(cache.size(), 3);
+        assert_eq!(cache.get_stats().hits, 1);
+        assert!(fork.contains_key(&1));
+        
+        // Invalidated entries are evicted before any live one, whatever their priority
+        for i in 10..13 {
+            cache.put(i, i, None, 1);
+        }
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.size(), 3);
+        assert!((10..13).all(|i| cache.contains_key(&i)));
+    }
+    
+    #[test]
+    fn test_retain() {
+        let cache = SmartCache::new(10);
+        
+        for i in 0..6 {
+            cache.put(i, i * 10, None, (i + 1) as u8);
+        }
+        
+        let removed = cache.retain(|key, value, meta| key % 2 == 0 && *value < 40 && meta.priority < 10);
+        assert_eq!(removed, 4);
+        assert_eq!(cache.size(), 2);
+        assert!(cache.contains_key(&0));
+        assert!(cache.contains_key(&2));
+        assert_eq!(cache.lru_order.lock().unwrap().len(), 2);
+    }
+    
+    #[test]
+    fn test_drain() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "one", None, 5);
+        cache.put(2, "two", None, 5);
+        cache.put(3, "expired", Some(Duration::from_millis(10)), 5);
+        cache.get(&1);
+        clock::advance(Duration::from_millis(20));
+        
+        assert_eq!(cache.drain(), vec![(2, "two"), (1, "one")]);
+        assert_eq!(cache.size(), 0);
+        assert_eq!(cache.lru_order.lock().unwrap().len(), 0);
+    }
+    
+    #[test]
+    fn test_export_import_roundtrip() {
+        let source = SmartCache::new(10);
+        source.put("tenant_a:1".to_string(), 1, Some(Duration::from_secs(60)), 7);
+        source.put("tenant_a:2".to_string(), 2, None, 3);
+        source.put("tenant_b:1".to_string(), 3, None, 5);
+        
+        source.get(&"tenant_a:1".to_string());
+        source.get(&"tenant_a:1".to_string());
+        
+        let exported = source.export_where(|key, _, _| key.starts_with("tenant_a:"));
+        assert_eq!(exported.len(), 2);
+        
+        let json = serde_json::to_string(&exported).unwrap();
+        let records: Vec<ExportedEntry<String, i32>> = serde_json::from_str(&json).unwrap();
+        
+        let target = SmartCache::new(10);
+        target.put("tenant_a:2".to_string(), 20, None, 5);
+        assert_eq!(target.import(records.clone(), ConflictPolicy::KeepExisting), 1);
+        assert_eq!(target.get(&"tenant_a:2".to_string()), Some(20));
+        assert_eq!(target.import(records.clone(), ConflictPolicy::KeepHigherPriority), 0);
+        assert_eq!(target.get(&"tenant_a:2".to_string()), Some(20));
+        assert_eq!(target.import(records.clone(), ConflictPolicy::Overwrite), 2);
+        assert_eq!(target.get(&"tenant_a:2".to_string()), Some(2));
+        
+        let (_, meta) = target.get_with_metadata(&"tenant_a:1".to_string()).unwrap();
+        assert_eq!(meta.priority, 7);
+        assert!(meta.ttl_remaining <= Duration::from_secs(60));
+        // Two reads before the export, plus this one
+        assert_eq!(meta.access_count, 3);
+        
+        // Only records actually stored are counted
+        let full = SmartCache::new(1);
+        full.put("other".to_string(), 0, None, 5);
+        full.pause_maintenance();
+        assert_eq!(full.import(records, ConflictPolicy::Overwrite), 0);
+    }
+    
+    fn write_seed_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}_{}_{}", env!("CARGO_PKG_NAME"), std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+    
+    #[test]
+    fn test_warm_from_file() {
+        let csv = write_seed_file("seed.csv", "key,value,ttl_secs,priority\na,1,60,9\nb,2,,1\nc,3,,5\n");
+        let json = write_seed_file("seed.json", r#"[{"key": "d", "value": 4}, {"key": "e", "value": 5, "priority": 2}]"#);
+        
+        let cache: SmartCache<String, i32> = SmartCache::new(4);
+        let mut progress = Vec::new();
+        let report = cache.warm_from_file_with_progress(&csv, |loaded, total| progress.push((loaded, total))).unwrap();
+        assert_eq!(report, WarmupReport { loaded: 3, evicted: 0 });
+        assert_eq!(progress, vec![(3, 3)]);
+        
+        // Over capacity by one: the single eviction pass drops the lowest priority entry
+        let report = cache.warm_from_file(&json).unwrap();
+        assert_eq!(report, WarmupReport { loaded: 2, evicted: 1 });
+        assert!(!cache.contains_key(&"b".to_string()));
+        
+        let (value, meta) = cache.get_with_metadata(&"a".to_string()).unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(meta.priority, 9);
+        assert!(meta.ttl_remaining <= Duration::from_secs(60));
+        
+        assert!(matches!(cache.warm_from_file("seed.txt"), Err(WarmupError::UnsupportedFormat(_))));
+        std::fs::remove_file(csv).unwrap();
+        std::fs::remove_file(json).unwrap();
+    }
+    
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}_{}", env!("CARGO_PKG_NAME"), std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let dir = temp_dir("snapshot_roundtrip");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("a".to_string(), "1".to_string(), Some(Duration::from_secs(30)), 8);
+        cache.put("b".to_string(), "2".to_string(), None, 2);
+        
+        for name in ["plain.json", "packed.json.gz"] {
+            let path = dir.join(name);
+            assert_eq!(cache.save_snapshot(&path).unwrap(), 2);
+            
+            let restored: SmartCache<String, String> = SmartCache::new(10);
+            let report = restored.load_snapshot(&path, ConflictPolicy::Overwrite).unwrap();
+            assert_eq!(report.loaded, 2);
+            assert!(report.verification.is_valid());
+            let (value, meta) = restored.get_with_metadata(&"a".to_string()).unwrap();
+            assert_eq!(value, "1");
+            assert_eq!(meta.priority, 8);
+            assert!(meta.ttl_remaining <= Duration::from_secs(30));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_diff() {
+        let dir = temp_dir("snapshot_diff");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("read".to_string(), "1".to_string(), None, 5);
+        cache.put("lost".to_string(), "2".to_string(), None, 5);
+        cache.save_snapshot(dir.join("before.json")).unwrap();
+        
+        cache.get("read");
+        cache.get("read");
+        cache.delete("lost");
+        cache.put("new".to_string(), "3".to_string(), None, 5);
+        cache.save_snapshot(dir.join("after.json")).unwrap();
+        
+        let before: Snapshot<String, String> = read_snapshot(&dir.join("before.json")).unwrap();
+        let after: Snapshot<String, String> = read_snapshot(&dir.join("after.json")).unwrap();
+        let diff = diff_snapshots(&before, &after);
+        assert_eq!(diff.added.iter().map(|e| e.key.as_str()).collect::<Vec<_>>(), vec!["new"]);
+        assert_eq!(diff.removed.iter().map(|e| e.key.as_str()).collect::<Vec<_>>(), vec!["lost"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert!(!diff.changed[0].value_changed);
+        assert_eq!(diff.changed[0].access_count_delta, 2);
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_corruption_detection() {
+        let dir = temp_dir("snapshot_corruption");
+        let path = dir.join("damaged.json");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        for key in ["a", "b", "c"] {
+            cache.put(key.to_string(), key.to_uppercase(), None, 5);
+        }
+        cache.save_snapshot(&path).unwrap();
+        assert!(verify_snapshot(&path).unwrap().is_valid());
+        
+        // Flip a byte inside one record's payload
+        let mut raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let data = raw["records"][2]["data"].as_str().unwrap().replacen("\"value\":\"", "\"value\":\"X", 1);
+        raw["records"][2]["data"] = serde_json::Value::String(data);
+        std::fs::write(&path, raw.to_string()).unwrap();
+        
+        let verification = verify_snapshot(&path).unwrap();
+        assert_eq!(verification.corrupt_records, vec![2]);
+        assert!(!verification.checksum_ok);
+        assert!(matches!(read_snapshot::<String, String>(&path), Err(SnapshotError::Corrupt(_))));
+        
+        let restored: SmartCache<String, String> = SmartCache::new(10);
+        let report = restored.load_snapshot(&path, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(report.loaded, 2);
+        assert_eq!(report.verification.corrupt_records, vec![2]);
+        assert_eq!(restored.size(), 2);
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_scheduler_retention() {
+        let dir = temp_dir("snapshot_scheduler");
+        let config = CacheConfig {
+            snapshot_schedule: Some(SnapshotSchedule {
+                directory: dir.clone(),
+                interval: Duration::from_millis(20),
+                retain: 2,
+                compress: true,
+                encryption: None,
+            }),
+            ..Default::default()
+        };
+        let cache: SmartCache<String, String> = SmartCache::with_config(config);
+        cache.put("a".to_string(), "1".to_string(), None, 5);
+        
+        let (sender, events) = std::sync::mpsc::channel();
+        assert!(cache.start_snapshot_scheduler(move |event| {
+            let _ = sender.send(event);
+        }));
+        assert!(!cache.start_snapshot_scheduler(|_| {}));
+        for _ in 0..3 {
+            let event = events.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(matches!(event, SnapshotEvent::Saved { entries: 1, .. }));
+        }
+        
+        // The thread exits once the cache is gone, so no run can land between
+        // its write and its prune while the directory is listed
+        let scheduler = cache.snapshot_handle.lock().unwrap().take().unwrap();
+        drop(cache);
+        scheduler.join().unwrap();
+        let snapshots = list_snapshots(&dir).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        let latest: Snapshot<String, String> = read_snapshot(snapshots.last().unwrap()).unwrap();
+        assert_eq!(latest.entries.len(), 1);
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[derive(Clone)]
+    struct Token {
+        expires_in: Option<Duration>,
+    }
+    
+    impl Expirable for Token {
+        fn ttl(&self) -> Option<Duration> {
+            self.expires_in
+        }
+    }
+    
+    #[test]
+    fn test_put_expirable() {
+        let cache: SmartCache<String, Token> = SmartCache::new(10);
+        let short = Token { expires_in: Some(Duration::from_millis(50)) };
+        
+        cache.put_expirable("own".to_string(), short.clone(), None, 5);
+        cache.put_expirable("explicit".to_string(), short, Some(Duration::from_secs(60)), 5);
+        cache.put_expirable("default".to_string(), Token { expires_in: None }, None, 5);
+        
+        clock::advance(Duration::from_millis(80));
+        assert!(cache.get(&"own".to_string()).is_none());
+        assert!(cache.get(&"explicit".to_string()).is_some());
+        assert!(cache.get(&"default".to_string()).is_some());
+    }
+    
+    #[test]
+    fn test_borrowed_key_lookups() {
+        let cache: SmartCache<String, i32> = SmartCache::new(10);
+        cache.put("alpha".to_string(), 1, None, 5);
+        
+        assert_eq!(cache.get("alpha"), Some(1));
+        assert_eq!(cache.get_with_metadata("alpha").map(|(_, meta)| meta.access_count), Some(2));
+        assert!(cache.contains_key("alpha"));
+        assert_eq!(cache.get("beta"), None);
+        
+        assert!(cache.delete("alpha"));
+        assert!(!cache.contains_key("alpha"));
+    }
+    
+    // Deliberately not Clone
+    struct Resource {
+        id: u32,
+    }
+    
+    #[test]
+    fn test_non_clone_values() {
+        let cache: SmartCache<String, Resource> = SmartCache::new(10);
+        cache.put("a".to_string(), Resource { id: 1 }, None, 5);
+        assert_eq!(cache.get_arc("a").map(|resource| resource.id), Some(1));
+        
+        let shared = Arc::new(Resource { id: 2 });
+        cache.put_arc("b".to_string(), Arc::clone(&shared), None, 5);
+        assert!(Arc::ptr_eq(&cache.get_arc("b").unwrap(), &shared));
+        
+        assert_eq!(cache.retain(|_, resource, _| resource.id == 2), 1);
+        assert!(cache.delete("b"));
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+    
+    #[test]
+    fn test_soften_low_priority() {
+        let config = CacheConfig {
+            max_capacity: 10,
+            soft_priority_threshold: Some(5),
+            ..Default::default()
+        };
+        let cache: SmartCache<String, Resource> = SmartCache::with_config(config);
+        cache.put("idle".to_string(), Resource { id: 1 }, None, 2);
+        cache.put("in_use".to_string(), Resource { id: 2 }, None, 2);
+        cache.put("important".to_string(), Resource { id: 3 }, None, 8);
+        let in_flight = cache.get_arc("in_use").unwrap();
+        
+        assert_eq!(cache.soften_low_priority(), 2);
+        assert!(!cache.contains_key("idle"));
+        assert!(cache.contains_key("important"));
+        assert_eq!(cache.size(), 2);
+        
+        // Still referenced, so still served; the hit makes it strong again
+        assert!(Arc::ptr_eq(&cache.get_arc("in_use").unwrap(), &in_flight));
+        drop(in_flight);
+        assert_eq!(cache.get_arc("in_use").map(|resource| resource.id), Some(2));
+    }
+    
+    #[test]
+    fn test_striped_stats_across_threads() {
+        let cache = Arc::new(SmartCache::<String, String>::new(10));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        cache.get("missing");
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(cache.get_stats().misses, 8000);
+    }
+    
+    #[test]
+    fn test_lru_order() {
+        let mut order = LruOrder::new();
+        let a = order.push_back("a");
+        let b = order.push_back("b");
+        let c = order.push_back("c");
+        order.move_to_back(a);
+        assert_eq!(order.remove(b), Some("b"));
+        assert_eq!(order.iter().map(|(_, key)| *key).collect::<Vec<_>>(), vec!["c", "a"]);
+        
+        // "d" reuses b's node; the stale slot no longer reaches it
+        let d = order.push_back("d");
+        assert_eq!(d.index, b.index);
+        order.move_to_back(b);
+        assert_eq!(order.remove(b), None);
+        assert_eq!(order.key(d), Some(&"d"));
+        assert_eq!(order.drain(), vec!["c", "a", "d"]);
+        assert_eq!(order.key(c), None);
+    }
+    
+    #[test]
+    fn test_read_buffer_defers_lru_updates() {
+        let cache = SmartCache::new(3);
+        cache.put("a", 1, None, 5);
+        cache.put("b", 2, None, 5);
+        cache.put("c", 3, None, 5);
+        
+        // The hit is buffered, not yet reflected in the LRU order
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.lru_order.lock().unwrap().iter().next().map(|(_, key)| *key), Some("a"));
+        
+        // Eviction applies pending reads first, so "b" is the LRU victim
+        cache.put("d", 4, None, 5);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(1));
+        
+        // A full stripe is applied as one batch
+        for _ in 0..READ_BUFFER_BATCH {
+            cache.get("c");
+        }
+        assert_eq!(cache.lru_order.lock().unwrap().iter().last().map(|(_, key)| *key), Some("c"));
+    }
+    
+    #[test]
+    fn test_eviction_breakdown() {
+        let cache = SmartCache::new(2);
+        assert_eq!(cache.get_stats().last_eviction, None);
+        
+        cache.put(1, "short", Some(Duration::from_millis(10)), 5);
+        cache.put(2, "shorter", Some(Duration::from_millis(10)), 5);
+        clock::advance(Duration::from_millis(20));
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.purge_expired(), 1);
+        
+        cache.put(3, "low", None, 1);
+        cache.put(4, "high", None, 5);
+        cache.put(5, "high", None, 5);
+        
+        let stats = cache.get_stats();
+        assert_eq!(stats.expirations, 2);
+        assert_eq!(stats.capacity_evictions, 1);
+        assert_eq!(stats.evictions, 3);
+        assert!(stats.last_eviction.is_some_and(|at| at.elapsed() < Duration::from_secs(1)));
+    }
+    
+    #[test]
+    fn test_priority_decay() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            priority_decay: Some(PriorityDecay { half_life: Duration::from_millis(10), floor: 1 }),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("stale", 1, None, 10);
+        clock::advance(Duration::from_millis(60));
+        cache.put("fresh", 2, None, 3);
+        
+        // Six half-lives unaccessed: "stale" is down to about 1.1, below "fresh"
+        cache.put("new", 3, None, 3);
+        assert!(!cache.contains_key("stale"));
+        assert!(cache.contains_key("fresh"));
+        assert!(cache.contains_key("new"));
+    }
+    
+    #[test]
+    fn test_priority_boost() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            priority_boost: Some(PriorityBoost { min_rate: 5.0, max_boost: 4 }),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("hot", 1, None, 3);
+        cache.put("cold", 2, None, 6);
+        for _ in 0..40 {
+            cache.get("hot");
+        }
+        let (_, meta) = cache.get_with_metadata("hot").unwrap();
+        assert_eq!(meta.priority, 3);
+        assert_eq!(meta.effective_priority, 7.0, "41 reads in the first second: boosted by the max of 4");
+        
+        cache.put("new", 3, None, 5);
+        assert!(cache.contains_key("hot"));
+        assert!(!cache.contains_key("cold"));
+    }
+    
+    #[test]
+    fn test_next_eviction_candidates() {
+        let cache = SmartCache::new(10);
+        cache.put("a", 1, None, 5);
+        cache.put("b", 2, None, 2);
+        cache.put("c", 3, None, 5);
+        cache.put("d", 4, None, 2);
+        assert_eq!(cache.next_eviction_candidates(3), [("b", 2.0), ("d", 2.0), ("a", 5.0)]);
+        
+        // A read moves "b" behind "d" among equals; previews evict nothing
+        cache.get("b");
+        assert_eq!(cache.next_eviction_candidates(2), [("d", 2.0), ("b", 2.0)]);
+        assert_eq!(cache.next_eviction_candidates(10).len(), 4);
+        assert_eq!(cache.size(), 4);
+    }
+    
+    #[test]
+    fn test_pause_maintenance() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            cleanup_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("a", 1, Some(Duration::from_millis(20)), 5);
+        cache.put("b", 2, None, 5);
+        cache.pause_maintenance();
+        assert!(cache.is_maintenance_paused());
+        
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.size(), 2, "the cleaner leaves the expired entry alone");
+        assert!(!cache.put("c", 3, None, 5), "no room is made for a new key");
+        assert!(cache.put("b", 20, None, 5));
+        
+        assert_eq!(cache.resume_maintenance(), 0);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.size() > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.size(), 1);
+        assert!(cache.put("c", 3, None, 5));
+    }
+    
+    #[test]
+    fn test_health() {
+        let config = CacheConfig {
+            max_capacity: 10,
+            cleanup_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("a", 1, None, 5);
+        let started = SmartCache::<&str, i32>::new(10).health();
+        assert!(started.is_healthy() && started.last_cleanup.is_none());
+        
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.health().last_cleanup.is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        let health = cache.health();
+        assert!(health.is_healthy());
+        assert!(health.last_cleanup.is_some());
+        assert_eq!((health.entries, health.capacity, health.expired_unreclaimed), (1, 10, 0));
+        
+        // A paused cleaner is not overdue, but expired entries pile up
+        cache.pause_maintenance();
+        cache.put("b", 2, Some(Duration::from_millis(5)), 5);
+        clock::advance(Duration::from_millis(50));
+        let health = cache.health();
+        assert!(health.is_healthy());
+        assert_eq!((health.entries, health.expired_unreclaimed), (2, 1));
+    }
+    
+    #[test]
+    fn test_ttl_distribution() {
+        let cache = SmartCache::new(10);
+        cache.put(1, 1, Some(Duration::from_millis(1)), 5);
+        for (key, secs) in [(2, 30), (3, 300), (4, 1800), (5, 7200), (6, 7200)] {
+            cache.put(key, key, Some(Duration::from_secs(secs)), 5);
+        }
+        clock::advance(Duration::from_millis(10));
+        assert_eq!(
+            cache.ttl_distribution(),
+            TtlDistribution {
+                expired: 1,
+                under_1m: 1,
+                under_10m: 1,
+                under_1h: 1,
+                over_1h: 2,
+            }
+        );
+    }
+    
+    #[test]
+    fn test_bulk_load() {
+        let cache = SmartCache::new(3);
+        let report = cache.bulk_load((0..5).map(|i| (i, i * 10, None, if i == 4 { 1 } else { 5 })));
+        assert_eq!(report, WarmupReport { loaded: 5, evicted: 2 });
+        assert_eq!(cache.get_stats().insertions, 5);
+        
+        // The one capacity pass takes the low-priority entry, then the least recent
+        assert!(!cache.contains_key(&4));
+        assert!(!cache.contains_key(&0));
+        assert_eq!(cache.get(&3), Some(30));
+        assert_eq!(cache.size(), 3);
+    }
+    
+    #[test]
+    fn test_get_copy() {
+        let cache = SmartCache::new(3);
+        for i in 0..3 {
+            cache.put(i, i as f64 / 2.0, None, 5);
+        }
+        assert_eq!(cache.get_copy(&0), Some(0.0));
+        assert_eq!(cache.get_copy(&9), None);
+        
+        // The deferred read still counts when eviction applies the buffer
+        cache.put(3, 1.5, None, 5);
+        assert!(cache.contains_key(&0));
+        assert!(!cache.contains_key(&1));
+        let stats = cache.get_stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+    }
+    
+    #[test]
+    fn test_chunked_cleanup() {
+        let cache = SmartCache::new(10_000);
+        for i in 0..2_500 {
+            cache.put(i, i, Some(Duration::from_millis(20)), 5);
+        }
+        cache.put(9_999, 0, None, 5);
+        clock::advance(Duration::from_millis(40));
+        
+        assert_eq!(cache.purge_expired(), 2_500);
+        assert_eq!(cache.size(), 1);
+        assert_eq!(cache.lru_order.lock().unwrap().len(), 1);
+        assert_eq!(cache.get_stats().expirations, 2_500);
+    }
+    
+    #[test]
+    fn test_incremental_expiry_scan() {
+        let cache = SmartCache::new(10);
+        for key in 0..4 {
+            cache.put(key, key, Some(Duration::from_millis(5)), 5);
+        }
+        cache.put(4, 4, Some(Duration::from_millis(30)), 5);
+        cache.put(5, 5, None, 5);
+        clock::advance(Duration::from_millis(10));
+        
+        let mut scan = ExpiryScan::new(3, Duration::from_secs(1));
+        let mut step = || {
+            let data = cache.data.read().unwrap();
+            let lru_order = cache.lru_order.lock().unwrap();
+            let mut keys = scan.step(&data, &lru_order, clock::now(), 0);
+            keys.sort();
+            (keys, scan.due.len())
+        };
+        // Three slots per step, picking up where the last stopped; key 4 expires
+        // within the horizon, so it is kept as due
+        assert_eq!(step(), (vec![0, 1, 2], 0));
+        assert_eq!(step(), (vec![3], 1));
+        clock::advance(Duration::from_millis(30));
+        assert_eq!(step(), (vec![0, 1, 2, 4], 0));
+        
+        // The background pass gets through everything a budget at a time
+        let config = CacheConfig {
+            cleanup_interval: Duration::from_millis(5),
+            expiry_scan_budget: Some(2),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        for key in 0..10 {
+            cache.put(key, key, Some(Duration::from_millis(1)), 5);
+        }
+        cache.put(10, 10, None, 5);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.size() > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.size(), 1);
+        assert_eq!(cache.get_stats().expirations, 10);
+    }
+
+    #[test]
+    fn test_freeze() {
+        let cache = SmartCache::new(10);
+        cache.put(1, "one".to_string(), None, 7);
+        cache.put(2, "two".to_string(), Some(Duration::from_millis(20)), 5);
+        cache.put(3, "three".to_string(), Some(Duration::from_millis(1)), 5);
+        clock::advance(Duration::from_millis(5));
+        
+        let frozen = cache.freeze();
+        assert_eq!(frozen.len(), 2);
+        assert!(!frozen.contains_key(&3));
+        assert_eq!(frozen.priority(&1), Some(7));
+        assert!(frozen.ttl_remaining(&2).unwrap() <= Duration::from_millis(15));
+        
+        // Later writes and expiry don't reach the frozen copy, and reading it
+        // leaves the cache's stats alone
+        cache.put(1, "uno".to_string(), None, 5);
+        cache.put(4, "four".to_string(), None, 5);
+        clock::advance(Duration::from_millis(20));
+        assert_eq!(frozen.get(&1).map(|value| value.as_str()), Some("one"));
+        assert_eq!(frozen.get(&2).map(|value| value.as_str()), Some("two"));
+        assert_eq!(frozen.get(&4), None);
+        assert_eq!(cache.get_stats().hits, 0);
+    }
+    
+    #[test]
+    fn test_fork() {
+        let cache = SmartCache::new(4);
+        for key in 0..3 {
+            cache.put(key, key.to_string(), None, 5);
+        }
+        cache.put(9, "gone".to_string(), Some(Duration::from_millis(1)), 5);
+        clock::advance(Duration::from_millis(5));
+        cache.get(&0);
+        
+        // Nothing is copied until one side writes, and then only the table
+        let fork = cache.fork();
+        let shares_table = |fork: &SmartCache<i32, String>| Shared::ptr_eq(&cache.data.read().unwrap(), &fork.data.read().unwrap());
+        assert!(shares_table(&fork));
+        assert!(Shared::ptr_eq(&cache.lru_order.lock().unwrap(), &fork.lru_order.lock().unwrap()));
+        assert!(!fork.contains_key(&9));
+        assert_eq!(fork.get_stats().hits, 0);
+        
+        fork.put(1, "fork".to_string(), None, 5);
+        assert!(!shares_table(&fork));
+        assert!(Arc::ptr_eq(&cache.get_arc(&0).unwrap(), &fork.get_arc(&0).unwrap()));
+        cache.delete(&2);
+        assert_eq!(cache.get(&1), Some("1".to_string()));
+        assert_eq!(cache.size(), 3);
+        
+        // The fork kept the recency order: 0 was read after 2 before forking, so
+        // 2 is least recent and goes first
+        fork.put(3, "3".to_string(), None, 5);
+        fork.put(4, "4".to_string(), None, 5);
+        assert!(!fork.contains_key(&2));
+        assert!(fork.contains_key(&0));
+        assert_eq!(fork.get(&1), Some("fork".to_string()));
+    }
+    
+    #[test]
+    fn test_estimated_memory_bytes() {
+        let config = CacheConfig {
+            max_capacity: 10,
+            soft_priority_threshold: Some(5),
+            ..Default::default()
+        };
+        let cache: SmartCache<String, String> = SmartCache::with_config(config);
+        let empty = cache.estimated_memory_bytes();
+        cache.put("big".to_string(), "x".repeat(1000), None, 2);
+        cache.put("small".to_string(), "y".repeat(10), None, 8);
+        let before = cache.estimated_memory_bytes();
+        assert!(before > empty + 1010);
+        assert_eq!(cache.get_stats().estimated_memory_bytes, None);
+        assert_eq!(cache.get_stats_with_memory().estimated_memory_bytes, Some(before));
+        
+        // Once soft, the value belongs to whoever holds the Arc
+        let in_flight = cache.get_arc("big").unwrap();
+        assert_eq!(cache.soften_low_priority(), 1);
+        assert!(cache.estimated_memory_bytes() + 1000 <= before);
+        drop(in_flight);
+    }
 }
@@ -0,0 +1,115 @@
+// Admission throttling: token buckets on insertions, one global bucket plus one
+// per namespace (see SmartCache::with_namespaces)
+
+use crate::clock;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// `rate_per_sec` tokens are added continuously, up to `burst`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdmissionLimit {
+    pub rate_per_sec: f64,
+    pub burst: u32,
+}
+
+// What happens to a put once its bucket is empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    // put returns false
+    Reject,
+    // put sleeps until a token is available
+    Block,
+    // the write only goes in if it outranks the lowest-priority entry, which it replaces
+    DropLowestPriority,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdmissionConfig {
+    pub global: Option<AdmissionLimit>,
+    // Applied to each namespace separately; keys without a namespace only see `global`
+    pub per_namespace: Option<AdmissionLimit>,
+    pub overflow: OverflowPolicy,
+}
+
+struct TokenBucket {
+    limit: AdmissionLimit,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: AdmissionLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.rate_per_sec).min(self.limit.burst as f64);
+        self.refilled_at = now;
+    }
+
+    // Time until one token is available (zero if one is available now)
+    fn wait_time(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else if self.limit.rate_per_sec <= 0.0 {
+            Duration::MAX
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.limit.rate_per_sec)
+        }
+    }
+}
+
+pub(crate) struct AdmissionLimiter {
+    config: AdmissionConfig,
+    global: Option<TokenBucket>,
+    namespaces: HashMap<String, TokenBucket>,
+}
+
+impl AdmissionLimiter {
+    pub(crate) fn new(config: AdmissionConfig) -> Self {
+        let now = clock::now();
+        Self {
+            global: config.global.map(|limit| TokenBucket::new(limit, now)),
+            namespaces: HashMap::new(),
+            config,
+        }
+    }
+
+    pub(crate) fn policy(&self) -> OverflowPolicy {
+        self.config.overflow
+    }
+
+    // Take a token from the global bucket and the namespace's bucket together, or
+    // from neither; on failure returns how long until both have one
+    pub(crate) fn try_acquire(&mut self, namespace: Option<&str>) -> Result<(), Duration> {
+        let now = clock::now();
+        let namespace_bucket = match (namespace, self.config.per_namespace) {
+            (Some(name), Some(limit)) => {
+                if !self.namespaces.contains_key(name) {
+                    self.namespaces.insert(name.to_string(), TokenBucket::new(limit, now));
+                }
+                self.namespaces.get_mut(name)
+            }
+            _ => None,
+        };
+
+        let mut buckets: Vec<&mut TokenBucket> = self.global.iter_mut().chain(namespace_bucket).collect();
+        for bucket in buckets.iter_mut() {
+            bucket.refill(now);
+        }
+        let wait = buckets.iter().map(|bucket| bucket.wait_time()).max().unwrap_or_default();
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for bucket in buckets {
+            bucket.tokens -= 1.0;
+        }
+        Ok(())
+    }
+}
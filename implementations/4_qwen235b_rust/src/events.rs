@@ -0,0 +1,275 @@
+// Delivery of CacheEvents to registered callbacks: inline on the emitting thread
+// by default, or through bounded queues drained by a pool of dispatcher threads
+// when CacheConfig::async_events is set. Each dispatcher owns one queue and events
+// are routed by key hash, so the events of one key are delivered in order while
+// slow listeners (ones doing I/O, say) on different keys run in parallel. Events
+// without a key (Clear, snapshots) go to the first dispatcher.
+
+use crate::{CacheCallback, CacheEvent};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// What emitting does once the queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOverflow {
+    // Discard the oldest queued event to make room
+    DropOldest,
+    // Discard the event being emitted
+    DropNewest,
+    // Wait for the dispatcher to make room. Events are emitted under the cache
+    // locks, so callbacks must not call back into the cache with this policy.
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsyncEvents {
+    // Queued events per dispatcher thread
+    pub capacity: usize,
+    pub overflow: EventOverflow,
+    // Dispatcher threads, at least one
+    pub threads: usize,
+    // Dispatchers are named "<thread_name>-<n>"
+    pub thread_name: &'static str,
+}
+
+impl Default for AsyncEvents {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow: EventOverflow::DropNewest,
+            threads: 1,
+            thread_name: "cache-events",
+        }
+    }
+}
+
+struct EventQueue<K, V> {
+    events: Mutex<VecDeque<CacheEvent<K, V>>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+pub(crate) struct EventBus<K, V> {
+    callbacks: RwLock<Vec<Box<dyn CacheCallback<K, V>>>>,
+    // Callback count, readable while dispatchers hold `callbacks`
+    listeners: AtomicUsize,
+    // Set along with a non-empty `queues`
+    config: Option<AsyncEvents>,
+    queues: Vec<EventQueue<K, V>>,
+    dropped: AtomicU64,
+    // Dispatchers restarted after a callback panicked
+    restarts: AtomicU64,
+    // Events taken off the queues but not yet handed to the callbacks
+    in_flight: AtomicUsize,
+    // Set by shutdown: dispatchers exit once their queue is empty
+    stopping: AtomicBool,
+    dispatchers: Mutex<Vec<JoinHandle<()>>>,
+    include_values: bool,
+}
+
+// How often an idle dispatcher checks whether the cache is gone
+const DISPATCH_POLL: Duration = Duration::from_millis(100);
+
+// The key an event is about, which picks its dispatcher
+fn event_key<K, V>(event: &CacheEvent<K, V>) -> Option<&K> {
+    match event {
+        CacheEvent::Hit(key) | CacheEvent::Miss(key) | CacheEvent::Insert(key) | CacheEvent::Delete(key) => Some(key),
+        CacheEvent::Update { key, .. } | CacheEvent::Eviction { key, .. } | CacheEvent::TTLExpiry { key, .. } => Some(key),
+        CacheEvent::Clear { .. } | CacheEvent::SnapshotSaved { .. } | CacheEvent::SnapshotFailed { .. } => None,
+    }
+}
+
+impl<K, V> EventBus<K, V>
+where
+    K: Clone + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    pub(crate) fn new(async_events: Option<AsyncEvents>, include_values: bool) -> Arc<Self> {
+        let config = async_events.map(|config| AsyncEvents {
+            capacity: config.capacity.max(1),
+            threads: config.threads.max(1),
+            ..config
+        });
+        let queues = config.map_or(0, |config| config.threads);
+        let bus = Arc::new(Self {
+            callbacks: RwLock::new(Vec::new()),
+            listeners: AtomicUsize::new(0),
+            config,
+            queues: (0..queues)
+                .map(|_| EventQueue {
+                    events: Mutex::new(VecDeque::new()),
+                    not_empty: Condvar::new(),
+                    not_full: Condvar::new(),
+                })
+                .collect(),
+            dropped: AtomicU64::new(0),
+            restarts: AtomicU64::new(0),
+            in_flight: AtomicUsize::new(0),
+            stopping: AtomicBool::new(false),
+            dispatchers: Mutex::new(Vec::new()),
+            include_values,
+        });
+        if let Some(config) = config {
+            let mut dispatchers = bus.dispatchers.lock().unwrap();
+            for index in 0..config.threads {
+                let weak = Arc::downgrade(&bus);
+                let handle = thread::Builder::new()
+                    .name(format!("{}-{}", config.thread_name, index))
+                    .spawn(move || Self::run_dispatcher(weak, index))
+                    .expect("failed to spawn event dispatcher");
+                dispatchers.push(handle);
+            }
+        }
+        bus
+    }
+
+    pub(crate) fn add(&self, callback: Box<dyn CacheCallback<K, V>>) {
+        let mut callbacks = self.callbacks.write().unwrap();
+        callbacks.push(callback);
+        self.listeners.store(callbacks.len(), Ordering::Relaxed);
+    }
+
+    // Whether Eviction and TTLExpiry events carry the removed value
+    pub(crate) fn include_values(&self) -> bool {
+        self.include_values
+    }
+
+    // Events lost to a full queue
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn restarts(&self) -> u64 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    // Events emitted but not yet handed to the callbacks
+    pub(crate) fn queued(&self) -> usize {
+        let waiting: usize = self.queues.iter().map(|queue| queue.events.lock().unwrap().len()).sum();
+        waiting + self.in_flight.load(Ordering::Relaxed)
+    }
+
+    // Let the dispatchers drain their queues and exit, waiting until `deadline`.
+    // Returns the dispatchers still running and the events not yet delivered.
+    pub(crate) fn shutdown(&self, deadline: Instant) -> (Vec<String>, usize) {
+        self.stopping.store(true, Ordering::Release);
+        for queue in &self.queues {
+            let _events = queue.events.lock().unwrap();
+            queue.not_empty.notify_all();
+            queue.not_full.notify_all();
+        }
+        let dispatchers = std::mem::take(&mut *self.dispatchers.lock().unwrap());
+        (crate::join_until(dispatchers, deadline), self.queued())
+    }
+
+    fn queue_for(&self, event: &CacheEvent<K, V>) -> &EventQueue<K, V> {
+        let index = match event_key(event) {
+            Some(key) if self.queues.len() > 1 => {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                (hasher.finish() % self.queues.len() as u64) as usize
+            }
+            _ => 0,
+        };
+        &self.queues[index]
+    }
+
+    // The event is only built when someone is listening
+    pub(crate) fn emit_with(&self, event: impl FnOnce() -> CacheEvent<K, V>) {
+        let Some(config) = &self.config else {
+            let callbacks = self.callbacks.read().unwrap();
+            if callbacks.is_empty() {
+                return;
+            }
+            let event = event();
+            for callback in callbacks.iter() {
+                callback.on_event(event.clone());
+            }
+            return;
+        };
+        if self.listeners.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        // No dispatcher is left to deliver it
+        if self.stopping.load(Ordering::Acquire) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        let event = event();
+        let queue = self.queue_for(&event);
+        let mut events = queue.events.lock().unwrap();
+        while events.len() >= config.capacity {
+            match config.overflow {
+                EventOverflow::DropOldest => {
+                    events.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                EventOverflow::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                EventOverflow::Block if self.stopping.load(Ordering::Acquire) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                EventOverflow::Block => events = queue.not_full.wait(events).unwrap(),
+            }
+        }
+        events.push_back(event);
+        queue.not_empty.notify_one();
+    }
+
+    // A panicking callback unwinds the dispatcher, which starts over on the same
+    // thread with the rest of its batch. Only the event being delivered misses
+    // the callbacks after the one that panicked.
+    fn run_dispatcher(bus: Weak<Self>, index: usize) {
+        let mut pending = VecDeque::new();
+        while panic::catch_unwind(AssertUnwindSafe(|| Self::dispatch(&bus, index, &mut pending))).is_err() {
+            match bus.upgrade() {
+                Some(bus) => bus.restarts.fetch_add(1, Ordering::Relaxed),
+                None => return,
+            };
+        }
+    }
+
+    // Deliver this dispatcher's queued events in order until the cache is dropped,
+    // or shut down and the queue is empty
+    fn dispatch(bus: &Weak<Self>, index: usize, pending: &mut VecDeque<CacheEvent<K, V>>) {
+        while let Some(bus) = bus.upgrade() {
+            if pending.is_empty() {
+                let queue = &bus.queues[index];
+                let events = queue.events.lock().unwrap();
+                let (mut events, _) = queue
+                    .not_empty
+                    .wait_timeout_while(events, DISPATCH_POLL, |events| {
+                        events.is_empty() && !bus.stopping.load(Ordering::Acquire)
+                    })
+                    .unwrap();
+                if events.is_empty() {
+                    if bus.stopping.load(Ordering::Acquire) {
+                        return;
+                    }
+                    continue;
+                }
+                bus.in_flight.fetch_add(events.len(), Ordering::Relaxed);
+                pending.extend(events.drain(..));
+                queue.not_full.notify_all();
+            }
+
+            let callbacks = bus.callbacks.read().unwrap();
+            while let Some(event) = pending.pop_front() {
+                bus.in_flight.fetch_sub(1, Ordering::Relaxed);
+                for callback in callbacks.iter() {
+                    callback.on_event(event.clone());
+                }
+            }
+        }
+    }
+}
@@ -1,19 +1,46 @@
 // Qwen3-235B Rust Implementation - Score: 91/100
 // Sophisticated architecture with custom doubly-linked list for O(1) LRU
 
-use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Weak;
 use std::sync::{Arc, RwLock, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use std::thread;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+mod admission;
+pub mod clock;
+mod events;
+
+use admission::AdmissionLimiter;
+pub use admission::{AdmissionConfig, AdmissionLimit, OverflowPolicy};
+pub use cache_types::{ExportedEntry, FrozenCache, HealthReport, KeyProvider, MemSize, PriorityBoost, PriorityDecay, StaticKeyProvider, TtlDistribution};
+use cache_types::{FrozenEntry, Shared, StripedCounter};
+use events::EventBus;
+pub use events::{AsyncEvents, EventOverflow};
+
+pub use cache_types::snapshot::{
+    diff_snapshots, list_snapshots, read_encrypted_snapshot, read_encrypted_snapshot_lossy, read_snapshot,
+    read_snapshot_lossy, verify_snapshot, write_encrypted_snapshot, write_snapshot, EntryChange, Snapshot,
+    SnapshotDiff, SnapshotError, SnapshotLoadReport, SnapshotSchedule, SnapshotVerification, SNAPSHOT_VERSION,
+};
+use cache_types::snapshot::{self, SnapshotEvent};
 
 // Custom doubly-linked list for perfect O(1) LRU operations
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct LruNode<K: Clone> {
-    key: K,
     prev: Option<K>,
     next: Option<K>,
 }
 
+#[derive(Clone)]
 struct LruList<K: Clone + Eq + std::hash::Hash> {
     nodes: HashMap<K, LruNode<K>>,
     head: Option<K>,
@@ -31,7 +58,6 @@ impl<K: Clone + Eq + std::hash::Hash> LruList<K> {
     
     fn push_front(&mut self, key: K) {
         let node = LruNode {
-            key: key.clone(),
             prev: None,
             next: self.head.clone(),
         };
@@ -50,40 +76,50 @@ impl<K: Clone + Eq + std::hash::Hash> LruList<K> {
         }
     }
     
-    fn remove(&mut self, key: &K) -> bool {
-        if let Some(node) = self.nodes.remove(key) {
-            // Update prev node's next
-            if let Some(ref prev_key) = node.prev {
-                if let Some(prev_node) = self.nodes.get_mut(prev_key) {
-                    prev_node.next = node.next.clone();
-                }
-            } else {
-                // This was the head
-                self.head = node.next.clone();
+    // Unlink `key`, handing back the owned key stored in the list
+    fn remove<Q>(&mut self, key: &Q) -> Option<K>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        let (key, node) = self.nodes.remove_entry(key)?;
+        self.unlink(node);
+        Some(key)
+    }
+    
+    fn unlink(&mut self, node: LruNode<K>) {
+        // Update prev node's next
+        if let Some(ref prev_key) = node.prev {
+            if let Some(prev_node) = self.nodes.get_mut(prev_key) {
+                prev_node.next = node.next.clone();
             }
-            
-            // Update next node's prev
-            if let Some(ref next_key) = node.next {
-                if let Some(next_node) = self.nodes.get_mut(next_key) {
-                    next_node.prev = node.prev.clone();
-                }
-            } else {
-                // This was the tail
-                self.tail = node.prev.clone();
+        } else {
+            // This was the head
+            self.head = node.next.clone();
+        }
+        
+        // Update next node's prev
+        if let Some(ref next_key) = node.next {
+            if let Some(next_node) = self.nodes.get_mut(next_key) {
+                next_node.prev = node.prev.clone();
             }
-            
-            true
         } else {
-            false
+            // This was the tail
+            self.tail = node.prev;
         }
     }
     
-    fn touch(&mut self, key: &K) {
-        if self.remove(key) {
-            self.push_front(key.clone());
+    fn touch<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        if let Some(key) = self.remove(key) {
+            self.push_front(key);
         }
     }
     
+    #[cfg(test)]
     fn pop_back(&mut self) -> Option<K> {
         if let Some(tail_key) = self.tail.clone() {
             self.remove(&tail_key);
@@ -93,10 +129,20 @@ impl<K: Clone + Eq + std::hash::Hash> LruList<K> {
         }
     }
     
-    fn iter(&self) -> LruIterator<K> {
+    fn iter(&self) -> LruIterator<'_, K> {
         LruIterator {
             nodes: &self.nodes,
             current: self.head.clone(),
+            from_tail: false,
+        }
+    }
+    
+    // Least recently used first
+    fn iter_from_tail(&self) -> LruIterator<'_, K> {
+        LruIterator {
+            nodes: &self.nodes,
+            current: self.tail.clone(),
+            from_tail: true,
         }
     }
 }
@@ -104,6 +150,7 @@ impl<K: Clone + Eq + std::hash::Hash> LruList<K> {
 struct LruIterator<'a, K: Clone + Eq + std::hash::Hash> {
     nodes: &'a HashMap<K, LruNode<K>>,
     current: Option<K>,
+    from_tail: bool,
 }
 
 impl<'a, K: Clone + Eq + std::hash::Hash> Iterator for LruIterator<'a, K> {
@@ -112,7 +159,7 @@ impl<'a, K: Clone + Eq + std::hash::Hash> Iterator for LruIterator<'a, K> {
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(key) = self.current.clone() {
             if let Some(node) = self.nodes.get(&key) {
-                self.current = node.next.clone();
+                self.current = if self.from_tail { node.prev.clone() } else { node.next.clone() };
                 Some(key)
             } else {
                 None
@@ -123,6 +170,76 @@ impl<'a, K: Clone + Eq + std::hash::Hash> Iterator for LruIterator<'a, K> {
     }
 }
 
+// Where the incremental cleanup (CacheConfig::expiry_scan_budget) left off, as
+// the next key to look at walking from the LRU tail, and the keys it saw falling
+// due before the next pass
+struct ExpiryScan<K> {
+    budget: usize,
+    horizon: Duration,
+    cursor: Option<K>,
+    due: Vec<(Instant, K)>,
+}
+
+impl<K: Clone + Eq + std::hash::Hash> ExpiryScan<K> {
+    fn new(budget: usize, horizon: Duration) -> Self {
+        Self {
+            budget: budget.max(1),
+            horizon,
+            cursor: None,
+            due: Vec::new(),
+        }
+    }
+    
+    // Keys now past `retention` after their TTL or invalidated, among the due ones and the next
+    // `budget` in LRU order. The walk restarts at the tail once it reaches the
+    // head or its next key is gone.
+    fn step<V: Clone>(
+        &mut self,
+        data: &HashMap<K, CacheEntry<V>>,
+        lru_list: &LruList<K>,
+        now: Instant,
+        retention: Duration,
+        generation: u64,
+    ) -> Vec<K> {
+        let mut expired = Vec::new();
+        self.due.retain(|(at, key)| {
+            if now <= *at {
+                return true;
+            }
+            expired.push(key.clone());
+            false
+        });
+        
+        let mut next = self
+            .cursor
+            .take()
+            .filter(|key| lru_list.nodes.contains_key(key))
+            .or_else(|| lru_list.tail.clone());
+        for _ in 0..self.budget {
+            let Some(key) = next else {
+                break;
+            };
+            next = lru_list.nodes.get(&key).and_then(|node| node.prev.clone());
+            let Some(entry) = data.get(&key) else {
+                continue;
+            };
+            let expires_at = entry.ttl + retention;
+            if now > expires_at || entry.is_invalidated(generation) {
+                expired.push(key);
+            } else if expires_at <= now + self.horizon {
+                self.due.push((expires_at, key));
+            }
+        }
+        self.cursor = next;
+        expired
+    }
+}
+
+// Values that know their own lifetime (tokens, DNS records); see put_expirable
+pub trait Expirable {
+    fn ttl(&self) -> Option<Duration>;
+}
+
 // Cache entry with metadata
 #[derive(Debug, Clone)]
 pub struct CacheEntry<V: Clone> {
@@ -132,34 +249,329 @@ pub struct CacheEntry<V: Clone> {
     created_at: Instant,
     last_accessed: Instant,
     access_count: usize,
+    // Set by put_refreshing; its task stops once this entry is replaced or removed
+    refresh_id: Option<u64>,
+    // CRC-32C of the serialized value when value checksums are enabled
+    checksum: Option<u32>,
+    // The last load for this key failed; it may be served within error_grace
+    load_failed: bool,
+    // The cache's generation when stored; see invalidate_all
+    generation: u64,
 }
 
+// The entry table, shared with a fork() until either side writes (see Shared)
+type Entries<K, V> = RwLock<Shared<HashMap<K, CacheEntry<V>>>>;
+
 // Event system with trait-based approach
-pub trait CacheCallback<K>: Send + Sync {
-    fn on_event(&self, event: CacheEvent<K>);
+pub trait CacheCallback<K, V>: Send + Sync {
+    fn on_event(&self, event: CacheEvent<K, V>);
+}
+
+// Entry bookkeeping handed out alongside a value
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryMetadata {
+    pub priority: u8,
+    // What eviction scores with: `priority` after decay and boost
+    pub effective_priority: f64,
+    pub access_count: usize,
+    pub age: Duration,
+    pub idle: Duration,
+    pub ttl_remaining: Duration,
+}
+
+impl<V: Clone> CacheEntry<V> {
+    // Stored before the last invalidate_all: never served, not even stale, and
+    // reclaimed without callbacks or events
+    fn is_invalidated(&self, generation: u64) -> bool {
+        self.generation != generation
+    }
+    
+    fn is_live(&self, now: Instant, generation: u64) -> bool {
+        now <= self.ttl && !self.is_invalidated(generation)
+    }
+    
+    // Age over effective priority, highest evicted first; invalidated entries
+    // go before any other
+    fn eviction_score(&self, config: &CacheConfig, now: Instant, generation: u64) -> f64 {
+        if self.is_invalidated(generation) {
+            return f64::INFINITY;
+        }
+        let age = now.saturating_duration_since(self.last_accessed).as_secs_f64();
+        age / self.effective_priority(config, now)
+    }
+    
+    // The priority eviction scores with, capped at the top of the scale
+    fn effective_priority(&self, config: &CacheConfig, now: Instant) -> f64 {
+        let decayed = match &config.priority_decay {
+            Some(decay) => decay.apply(self.priority, now.saturating_duration_since(self.last_accessed)),
+            None => f64::from(self.priority),
+        };
+        let boost = config.priority_boost.map_or(0.0, |boost| {
+            boost.apply(self.access_count, now.saturating_duration_since(self.created_at))
+        });
+        (decayed + boost).min(10.0)
+    }
+    
+    // `idle` is measured against the access before the current one
+    fn metadata(&self, config: &CacheConfig, now: Instant, previous_access: Instant) -> EntryMetadata {
+        EntryMetadata {
+            priority: self.priority,
+            effective_priority: self.effective_priority(config, now),
+            access_count: self.access_count,
+            age: now.saturating_duration_since(self.created_at),
+            idle: now.saturating_duration_since(previous_access),
+            ttl_remaining: self.ttl.saturating_duration_since(now),
+        }
+    }
+    
+    // Event payload for an entry that has just been evicted or expired
+    fn into_removed(self, include_value: bool) -> RemovedEntry<V> {
+        RemovedEntry {
+            priority: self.priority,
+            age: clock::now().saturating_duration_since(self.created_at),
+            access_count: self.access_count,
+            value: include_value.then(|| Arc::new(self.value)),
+        }
+    }
+}
+
+// What was lost with an evicted or expired entry, so listeners need not look it up
+#[derive(Debug, Clone)]
+pub struct RemovedEntry<V> {
+    pub priority: u8,
+    pub age: Duration,
+    pub access_count: usize,
+    // Only with CacheConfig::include_values_in_events
+    pub value: Option<Arc<V>>,
+}
+
+// What `import` does when a key is already present
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    KeepExisting,
+    Overwrite,
+    KeepHigherPriority,
+}
+
+// One row of a warm-up seed file (CSV header: key,value,ttl_secs,priority)
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedRecord<K, V> {
+    pub key: K,
+    pub value: V,
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    #[serde(default = "default_seed_priority")]
+    pub priority: u8,
+}
+
+fn default_seed_priority() -> u8 {
+    5
+}
+
+// (key, value, ttl, priority) for bulk_load, the arguments put takes
+pub type BulkEntry<K, V> = (K, V, Option<Duration>, u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmupReport {
+    pub loaded: usize,
+    pub evicted: usize,
+}
+
+#[derive(Debug)]
+pub enum WarmupError {
+    Io(std::io::Error),
+    Parse(String),
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for WarmupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarmupError::Io(err) => write!(f, "failed to read seed file: {}", err),
+            WarmupError::Parse(msg) => write!(f, "invalid seed record: {}", msg),
+            WarmupError::UnsupportedFormat(ext) => write!(f, "unsupported seed file format: {:?}", ext),
+        }
+    }
+}
+
+impl std::error::Error for WarmupError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheError {
+    // The stored value no longer matches the checksum taken at put; the entry
+    // has been dropped
+    Corrupted { expected: u32, actual: u32 },
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Corrupted { expected, actual } => {
+                write!(f, "cached value corrupted: checksum {:08x}, expected {:08x}", actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+impl From<std::io::Error> for WarmupError {
+    fn from(err: std::io::Error) -> Self {
+        WarmupError::Io(err)
+    }
+}
+
+// Seed files are loaded in batches so readers are not locked out for the whole file
+const WARMUP_BATCH: usize = 10_000;
+
+fn read_seed_file<K, V>(path: &Path) -> Result<Vec<SeedRecord<K, V>>, WarmupError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let reader = BufReader::new(File::open(path)?);
+            serde_json::from_reader(reader).map_err(|err| WarmupError::Parse(err.to_string()))
+        }
+        Some("csv") => {
+            let mut reader = csv::Reader::from_reader(File::open(path)?);
+            reader
+                .deserialize()
+                .collect::<Result<_, _>>()
+                .map_err(|err| WarmupError::Parse(err.to_string()))
+        }
+        other => Err(WarmupError::UnsupportedFormat(other.unwrap_or_default().to_string())),
+    }
 }
 
 #[derive(Debug, Clone)]
-pub enum CacheEvent<K> {
+pub enum CacheEvent<K, V> {
     Hit(K),
     Miss(K),
+    // A key that held no live entry was stored
     Insert(K),
-    Eviction(K),
-    TTLExpiry(K),
+    // A live entry was overwritten; the replaced value is shared between listeners
+    Update { key: K, old_value: Arc<V> },
+    Delete(K),
+    // clear() removed `entries` live entries at once
+    Clear { entries: usize },
+    Eviction { key: K, entry: RemovedEntry<V> },
+    TTLExpiry { key: K, entry: RemovedEntry<V> },
+    SnapshotSaved { path: PathBuf, entries: usize },
+    SnapshotFailed { path: PathBuf, error: String },
+}
+
+// Per-entry hook from put_with_callback, run at most once
+type EntryCallback<K, V> = Box<dyn FnOnce(&K, &V) + Send>;
+type EntryCallbacks<K, V> = Arc<Mutex<HashMap<K, EntryCallback<K, V>>>>;
+
+pub type LoadError = Box<dyn std::error::Error + Send + Sync>;
+
+// Fetches a key's value from the backing store (see with_loader)
+type Loader<K, V> = Arc<dyn Fn(&K) -> Result<V, LoadError> + Send + Sync>;
+
+// Maps a key to its namespace (tenant, key prefix, ...); None means no namespace
+type NamespaceFn<K> = Arc<dyn Fn(&K) -> Option<String> + Send + Sync>;
+
+// How often reads re-check value checksums (see with_value_checksums)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityCheck {
+    Always,
+    // Every n-th read across the cache
+    Sampled(u32),
+}
+
+type ChecksumFn<V> = Arc<dyn Fn(&V) -> Option<u32> + Send + Sync>;
+
+struct Integrity<V> {
+    check: IntegrityCheck,
+    checksum: ChecksumFn<V>,
+    reads: AtomicU64,
+}
+
+impl<V> Integrity<V> {
+    fn should_verify(&self) -> bool {
+        match self.check {
+            IntegrityCheck::Always => true,
+            IntegrityCheck::Sampled(n) => self.reads.fetch_add(1, Ordering::Relaxed).is_multiple_of(n.max(1) as u64),
+        }
+    }
+}
+
+// Why an expired entry may still be returned
+enum StaleServe {
+    // Inside stale_grace, with a reload under way
+    Revalidating,
+    // Inside error_grace after a failed load
+    Degraded,
+}
+
+enum Admission {
+    Admitted,
+    Rejected,
+    // Throttled under OverflowPolicy::DropLowestPriority
+    MustOutrankLowest,
+}
+
+// Which writes go through, decided under the data lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteIf {
+    Always,
+    // Only when the key holds no live entry
+    Absent,
+    // Only over a live entry, keeping its expiry and priority
+    Present,
+    // When the key holds no live entry or one of lower priority than the write's
+    HigherPriority,
+}
+
+impl From<ConflictPolicy> for WriteIf {
+    fn from(policy: ConflictPolicy) -> Self {
+        match policy {
+            ConflictPolicy::KeepExisting => WriteIf::Absent,
+            ConflictPolicy::Overwrite => WriteIf::Always,
+            ConflictPolicy::KeepHigherPriority => WriteIf::HigherPriority,
+        }
+    }
 }
 
+// Stored: Ok with the live value replaced. Not stored: Err with the live value
+// that stopped an Absent or HigherPriority write, if that was the reason.
+type WriteResult<V> = Result<Option<Arc<V>>, Option<V>>;
+
 // Main cache implementation
 pub struct SmartCache<K, V> 
 where
     K: Clone + Eq + std::hash::Hash,
     V: Clone,
 {
-    data: Arc<RwLock<HashMap<K, CacheEntry<V>>>>,
-    lru_list: Arc<Mutex<LruList<K>>>,
+    data: Arc<Entries<K, V>>,
+    lru_list: Arc<Mutex<Shared<LruList<K>>>>,
     config: CacheConfig,
-    stats: Arc<Mutex<CacheStats>>,
-    callbacks: Arc<Mutex<Vec<Box<dyn CacheCallback<K>>>>>,
-    cleanup_handle: Option<thread::JoinHandle<()>>,
+    stats: Arc<StatCounters>,
+    events: Arc<EventBus<K, V>>,
+    entry_callbacks: EntryCallbacks<K, V>,
+    next_refresh_id: AtomicU64,
+    namespace_of: Option<NamespaceFn<K>>,
+    admission: Option<Mutex<AdmissionLimiter>>,
+    integrity: Option<Integrity<V>>,
+    loader: Option<Loader<K, V>>,
+    // Keys with a background revalidation in flight
+    revalidating: Arc<Mutex<HashSet<K>>>,
+    // Set by pause_maintenance; the cleanup thread skips its passes while set
+    maintenance_paused: Arc<AtomicBool>,
+    // Bumped by invalidate_all; shared with the background threads
+    generation: Arc<AtomicU64>,
+    // Set by shutdown; background loops exit at their next wake-up
+    stopping: Arc<AtomicBool>,
+    cleanup_handle: Mutex<Option<thread::JoinHandle<()>>>,
+    snapshot_handle: Mutex<Option<thread::JoinHandle<()>>>,
+    // put_refreshing tasks, joined by shutdown
+    refresh_handles: Mutex<Vec<thread::JoinHandle<()>>>,
+    // When the cleanup thread last finished a pass
+    last_cleanup: Arc<Mutex<Option<Instant>>>,
+    created_at: Instant,
 }
 
 #[derive(Debug, Clone)]
@@ -167,6 +579,42 @@ pub struct CacheConfig {
     pub max_capacity: usize,
     pub default_ttl: Duration,
     pub cleanup_interval: Duration,
+    // Used by start_snapshot_scheduler; None disables periodic snapshots
+    pub snapshot_schedule: Option<SnapshotSchedule>,
+    // Token-bucket limit on puts; None admits everything
+    pub admission: Option<AdmissionConfig>,
+    // Capacity reserved per namespace (see with_namespaces); whatever is left of
+    // max_capacity, plus reservations not in use, forms a shared elastic pool
+    pub namespace_quotas: HashMap<String, usize>,
+    // With a loader, expired entries keep being served for this long while one
+    // background load refreshes them (stale-while-revalidate)
+    pub stale_grace: Option<Duration>,
+    // After a failed load, expired values can still be served for this long past
+    // their TTL, so backend blips do not surface as errors
+    pub error_grace: Option<Duration>,
+    // Hand events to callbacks on a pool of dispatcher threads through bounded
+    // queues; None runs callbacks inline, under the cache locks
+    pub async_events: Option<AsyncEvents>,
+    // Attach the removed value to Eviction and TTLExpiry events
+    pub include_values_in_events: bool,
+    // Decay the priorities of unaccessed entries for eviction; None keeps them fixed
+    pub priority_decay: Option<PriorityDecay>,
+    // Raise the priorities of frequently read entries for eviction; None keeps them as declared
+    pub priority_boost: Option<PriorityBoost>,
+    // When a put evicts, score only this many entries from the LRU tail rather
+    // than the whole list; None scans every entry
+    pub eviction_sample_size: Option<usize>,
+    // Entries the background cleanup examines per pass, resuming where the last
+    // pass stopped, plus the ones an earlier pass saw falling due. Bounds each
+    // pass's work whatever the cache size; None scans every entry each pass.
+    pub expiry_scan_budget: Option<usize>,
+}
+
+impl CacheConfig {
+    // How long past its TTL an entry may still be needed
+    fn expired_retention(&self) -> Duration {
+        self.stale_grace.unwrap_or_default().max(self.error_grace.unwrap_or_default())
+    }
 }
 
 impl Default for CacheConfig {
@@ -175,6 +623,17 @@ impl Default for CacheConfig {
             max_capacity: 1000,
             default_ttl: Duration::from_secs(3600),
             cleanup_interval: Duration::from_secs(60),
+            snapshot_schedule: None,
+            admission: None,
+            namespace_quotas: HashMap::new(),
+            stale_grace: None,
+            error_grace: None,
+            async_events: None,
+            include_values_in_events: false,
+            priority_decay: None,
+            priority_boost: None,
+            eviction_sample_size: None,
+            expiry_scan_budget: None,
         }
     }
 }
@@ -186,6 +645,69 @@ pub struct CacheStats {
     pub evictions: u64,
     pub insertions: u64,
     pub ttl_expirations: u64,
+    // Puts refused by admission throttling
+    pub throttled: u64,
+    // Entries dropped after failing a checksum check
+    pub corruptions: u64,
+    // Hits served from an expired entry inside its stale grace period
+    pub stale_hits: u64,
+    // Expired values served because loading a fresh one failed
+    pub degraded_hits: u64,
+    // Events discarded by a full async event queue
+    pub events_dropped: u64,
+    // Event dispatchers restarted after a callback panicked
+    pub dispatcher_restarts: u64,
+    // Only get_stats_with_memory fills these in, since they take a pass over
+    // every entry: memory_stats' total_bytes and by_namespace
+    pub estimated_memory_bytes: Option<usize>,
+    pub memory_by_namespace: HashMap<Option<String>, usize>,
+}
+
+// One counter per CacheStats field except the event ones, which the event bus keeps
+struct StatCounters {
+    hits: StripedCounter,
+    misses: StripedCounter,
+    evictions: StripedCounter,
+    insertions: StripedCounter,
+    ttl_expirations: StripedCounter,
+    throttled: StripedCounter,
+    corruptions: StripedCounter,
+    stale_hits: StripedCounter,
+    degraded_hits: StripedCounter,
+}
+
+impl StatCounters {
+    fn new() -> Self {
+        Self {
+            hits: StripedCounter::new(),
+            misses: StripedCounter::new(),
+            evictions: StripedCounter::new(),
+            insertions: StripedCounter::new(),
+            ttl_expirations: StripedCounter::new(),
+            throttled: StripedCounter::new(),
+            corruptions: StripedCounter::new(),
+            stale_hits: StripedCounter::new(),
+            degraded_hits: StripedCounter::new(),
+        }
+    }
+    
+    fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            evictions: self.evictions.get(),
+            insertions: self.insertions.get(),
+            ttl_expirations: self.ttl_expirations.get(),
+            throttled: self.throttled.get(),
+            corruptions: self.corruptions.get(),
+            stale_hits: self.stale_hits.get(),
+            degraded_hits: self.degraded_hits.get(),
+            events_dropped: 0,
+            dispatcher_restarts: 0,
+            estimated_memory_bytes: None,
+            memory_by_namespace: HashMap::new(),
+        }
+    }
 }
 
 impl<K, V> SmartCache<K, V>
@@ -202,193 +724,1438 @@ where
     }
     
     pub fn with_config(config: CacheConfig) -> Self {
-        let data = Arc::new(RwLock::new(HashMap::new()));
-        let lru_list = Arc::new(Mutex::new(LruList::new()));
-        let stats = Arc::new(Mutex::new(CacheStats::default()));
-        let callbacks = Arc::new(Mutex::new(Vec::new()));
+        Self::build(config, None)
+    }
+    
+    // Keys are grouped into namespaces by `namespace_of`, e.g. by tenant prefix;
+    // per-namespace admission limits apply to each group separately
+    pub fn with_namespaces<F>(config: CacheConfig, namespace_of: F) -> Self
+    where
+        F: Fn(&K) -> Option<String> + Send + Sync + 'static,
+    {
+        Self::build(config, Some(Arc::new(namespace_of)))
+    }
+    
+    fn build(config: CacheConfig, namespace_of: Option<NamespaceFn<K>>) -> Self {
+        let data = Arc::new(RwLock::new(Shared::default()));
+        let lru_list = Arc::new(Mutex::new(Shared::new(LruList::new())));
+        let stats = Arc::new(StatCounters::new());
+        let events = EventBus::new(config.async_events, config.include_values_in_events);
+        let entry_callbacks = Arc::new(Mutex::new(HashMap::new()));
+        let maintenance_paused = Arc::new(AtomicBool::new(false));
+        let generation = Arc::new(AtomicU64::new(0));
+        let stopping = Arc::new(AtomicBool::new(false));
+        let last_cleanup = Arc::new(Mutex::new(None));
         
         // Start cleanup thread
+        let paused_clone = Arc::clone(&maintenance_paused);
+        let generation_clone = Arc::clone(&generation);
+        let stopping_clone = Arc::clone(&stopping);
+        let last_cleanup_clone = Arc::clone(&last_cleanup);
         let data_clone = Arc::clone(&data);
         let lru_clone = Arc::clone(&lru_list);
         let stats_clone = Arc::clone(&stats);
-        let callbacks_clone = Arc::clone(&callbacks);
+        let events_clone = Arc::clone(&events);
+        let entry_callbacks_clone = Arc::clone(&entry_callbacks);
         let cleanup_interval = config.cleanup_interval;
+        let retention = config.expired_retention();
+        let admission = config.admission.clone().map(|admission| Mutex::new(AdmissionLimiter::new(admission)));
+        let mut scan = config.expiry_scan_budget.map(|budget| ExpiryScan::new(budget, cleanup_interval));
         
-        let cleanup_handle = thread::spawn(move || {
-            loop {
-                thread::sleep(cleanup_interval);
-                Self::cleanup_expired(&data_clone, &lru_clone, &stats_clone, &callbacks_clone);
-            }
-        });
+        let cleanup_handle = thread::Builder::new()
+            .name("cache-cleanup".to_string())
+            .spawn(move || {
+                while pause(&stopping_clone, cleanup_interval) {
+                    if paused_clone.load(Ordering::Acquire) {
+                        continue;
+                    }
+                    Self::cleanup_expired(
+                        &data_clone,
+                        &lru_clone,
+                        &stats_clone,
+                        &events_clone,
+                        &entry_callbacks_clone,
+                        retention,
+                        generation_clone.load(Ordering::Acquire),
+                        scan.as_mut(),
+                    );
+                    *last_cleanup_clone.lock().unwrap() = Some(clock::now());
+                }
+            })
+            .expect("failed to spawn cleanup thread");
         
         Self {
             data,
             lru_list,
             config,
             stats,
-            callbacks,
-            cleanup_handle: Some(cleanup_handle),
+            events,
+            entry_callbacks,
+            next_refresh_id: AtomicU64::new(1),
+            admission,
+            namespace_of,
+            integrity: None,
+            loader: None,
+            revalidating: Arc::new(Mutex::new(HashSet::new())),
+            maintenance_paused,
+            generation,
+            stopping,
+            cleanup_handle: Mutex::new(Some(cleanup_handle)),
+            snapshot_handle: Mutex::new(None),
+            refresh_handles: Mutex::new(Vec::new()),
+            last_cleanup,
+            created_at: clock::now(),
         }
     }
     
+    // Backing-store loader used to revalidate stale entries (see
+    // CacheConfig::stale_grace). It runs on a background thread, never under a lock.
+    pub fn with_loader<F>(mut self, loader: F) -> Self
+    where
+        F: Fn(&K) -> Result<V, LoadError> + Send + Sync + 'static,
+    {
+        self.loader = Some(Arc::new(loader));
+        self
+    }
+    
     pub fn put(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool {
-        let ttl = ttl.unwrap_or(self.config.default_ttl);
+        self.insert_entry(key, value, ttl, priority, None, None, 0, WriteIf::Always).is_ok()
+    }
+    
+    // Store only if the key holds no live entry. Returns the live value that is
+    // kept instead, or None once stored (or refused by admission or paused
+    // maintenance).
+    pub fn put_if_absent(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> Option<V> {
+        self.insert_entry(key, value, ttl, priority, None, None, 0, WriteIf::Absent).err().flatten()
+    }
+    
+    // Swap in a new value only if the key holds a live entry, which keeps its
+    // expiry and priority; like any overwrite it drops the entry's callback.
+    // Returns the replaced value, None if nothing was stored.
+    pub fn replace(&self, key: K, value: V) -> Option<V> {
+        self.insert_entry(key, value, None, 0, None, None, 0, WriteIf::Present)
+            .ok()
+            .flatten()
+            .map(Arc::unwrap_or_clone)
+    }
+    
+    // put() plus a closure run exactly once when this entry expires or is evicted.
+    // It is dropped unrun if the entry is deleted, drained, retained away or
+    // overwritten. Like the global callbacks it runs under the cache locks, so it
+    // must not call back into the cache.
+    pub fn put_with_callback<F>(&self, key: K, value: V, ttl: Option<Duration>, priority: u8, on_expire: F) -> bool
+    where
+        F: FnOnce(&K, &V) + Send + 'static,
+    {
+        self.insert_entry(key, value, ttl, priority, Some(Box::new(on_expire)), None, 0, WriteIf::Always).is_ok()
+    }
+    
+    // Store the refresher's current value and re-pull it every `interval` on a
+    // background thread, whether or not anyone reads it. A successful refresh swaps
+    // the value in and restarts the default TTL; a refresher returning None keeps
+    // the old value, which then ages out normally. The task ends once the entry is
+    // replaced or removed, or the cache is dropped or shut down. Nothing is stored
+    // if the first call returns None.
+    pub fn put_refreshing<F>(&self, key: K, refresher: F, interval: Duration, priority: u8) -> bool
+    where
+        F: Fn(&K) -> Option<V> + Send + 'static,
+    {
+        let Some(value) = refresher(&key) else {
+            return false;
+        };
+        let refresh_id = self.next_refresh_id.fetch_add(1, Ordering::Relaxed);
+        let _ = self.insert_entry(key.clone(), value, None, priority, None, Some(refresh_id), 0, WriteIf::Always);
+        
+        let data = Arc::downgrade(&self.data);
+        let generation = Arc::clone(&self.generation);
+        let ttl = self.config.default_ttl;
+        let checksum = self.integrity.as_ref().map(|integrity| Arc::clone(&integrity.checksum));
+        let stopping = Arc::clone(&self.stopping);
+        let handle = thread::Builder::new()
+            .name("cache-refresh".to_string())
+            .spawn(move || {
+                while pause(&stopping, interval) {
+                    let Some(data) = data.upgrade() else {
+                        break;
+                    };
+                    let is_current = |entry: &CacheEntry<V>| {
+                        entry.refresh_id == Some(refresh_id) && !entry.is_invalidated(generation.load(Ordering::Acquire))
+                    };
+                    if !data.read().unwrap().get(&key).is_some_and(is_current) {
+                        break;
+                    }
+                    
+                    // The refresher runs without holding any cache lock
+                    let Some(value) = refresher(&key) else {
+                        continue;
+                    };
+                    let mut entries = data.write().unwrap();
+                    match entries.get_mut(&key) {
+                        Some(entry) if is_current(entry) => {
+                            entry.checksum = checksum.as_ref().and_then(|checksum| checksum(&value));
+                            entry.value = value;
+                            entry.ttl = clock::now() + ttl;
+                        }
+                        _ => break,
+                    }
+                }
+            })
+            .expect("failed to spawn refresh thread");
+        let mut handles = self.refresh_handles.lock().unwrap();
+        handles.retain(|handle| !handle.is_finished());
+        handles.push(handle);
+        true
+    }
+    
+    // `access_count` starts the new entry's count; only import carries one over
+    #[allow(clippy::too_many_arguments)]
+    fn insert_entry(
+        &self,
+        key: K,
+        value: V,
+        ttl: Option<Duration>,
+        priority: u8,
+        on_expire: Option<EntryCallback<K, V>>,
+        refresh_id: Option<u64>,
+        access_count: usize,
+        when: WriteIf,
+    ) -> WriteResult<V> {
+        let admission = self.admit(&key);
+        if let Admission::Rejected = admission {
+            return Err(None);
+        }
         
         // WARNING: Potential deadlock if locks taken in different order!
         let mut data = self.data.write().unwrap();
         let mut lru_list = self.lru_list.lock().unwrap();
-        let mut stats = self.stats.lock().unwrap();
+        
+        let now = clock::now();
+        let generation = self.generation();
+        let current = data.get(&key).filter(|entry| entry.is_live(now, generation));
+        let (ttl, priority) = match (when, current) {
+            (WriteIf::Absent, Some(entry)) => return Err(Some(entry.value.clone())),
+            (WriteIf::HigherPriority, Some(entry)) if priority <= entry.priority => return Err(Some(entry.value.clone())),
+            (WriteIf::Present, None) => return Err(None),
+            (WriteIf::Present, Some(entry)) => (entry.ttl.saturating_duration_since(now), entry.priority),
+            _ => (ttl.unwrap_or(self.config.default_ttl), priority.clamp(1, 10)),
+        };
+        
+        // Nothing is evicted to make room while maintenance is paused
+        let needs_room = !data.contains_key(&key) && data.len() >= self.config.max_capacity;
+        if self.is_maintenance_paused() && (needs_room || matches!(admission, Admission::MustOutrankLowest)) {
+            return Err(None);
+        }
+        
+        if let Admission::MustOutrankLowest = admission {
+            // Lowest (effective) priority, least recently used among equals
+            let now = clock::now();
+            let lowest = lru_list
+                .iter()
+                .filter_map(|k| data.get(&k).map(|entry| (entry.effective_priority(&self.config, now), k)))
+                .reduce(|lowest, candidate| if candidate.0 <= lowest.0 { candidate } else { lowest });
+            match lowest {
+                Some((lowest_priority, victim)) if f64::from(priority) > lowest_priority => {
+                    self.evict_entry(&mut data, &mut lru_list, victim);
+                }
+                _ => {
+                    self.stats.throttled.add(1);
+                    return Err(None);
+                }
+            }
+        }
         
         // Check capacity and evict if necessary
         if !data.contains_key(&key) && data.len() >= self.config.max_capacity {
-            self.evict_lowest_priority(&mut data, &mut lru_list, &mut stats);
+            let evicted = match self.eviction_scope(&data, &key) {
+                Some(scope) => {
+                    let namespace_of = self.namespace_of.as_ref().unwrap();
+                    self.evict_lowest_priority(&mut data, &mut lru_list, |k| scope.contains(&namespace_of(k)))
+                }
+                None => false,
+            };
+            if !evicted {
+                self.evict_lowest_priority(&mut data, &mut lru_list, |_| true);
+            }
         }
         
         // Create entry
         let entry = CacheEntry {
+            checksum: self.checksum_of(&value),
             value,
-            priority: priority.min(10).max(1),
-            ttl: Instant::now() + ttl,
-            created_at: Instant::now(),
-            last_accessed: Instant::now(),
-            access_count: 0,
+            priority,
+            ttl: clock::now() + ttl,
+            created_at: clock::now(),
+            last_accessed: clock::now(),
+            access_count,
+            refresh_id,
+            load_failed: false,
+            generation,
         };
         
-        // Update data structures
-        data.insert(key.clone(), entry);
-        lru_list.touch(&key);
+        // Update data structures; an overwritten entry's callback is dropped
+        let replaced = data.insert(key.clone(), entry).filter(|old| old.is_live(clock::now(), generation));
+        lru_list.remove(&key);
+        lru_list.push_front(key.clone());
+        
+        let mut entry_callbacks = self.entry_callbacks.lock().unwrap();
+        match on_expire {
+            Some(on_expire) => entry_callbacks.insert(key.clone(), on_expire),
+            None => entry_callbacks.remove(&key),
+        };
+        drop(entry_callbacks);
         
-        stats.insertions += 1;
+        self.stats.insertions.add(1);
         
         // Notify callbacks
-        self.notify_callbacks(CacheEvent::Insert(key));
+        let replaced = replaced.map(|old| Arc::new(old.value));
+        self.notify_callbacks(match &replaced {
+            Some(old_value) => CacheEvent::Update { key, old_value: Arc::clone(old_value) },
+            None => CacheEvent::Insert(key),
+        });
         
-        true
+        Ok(replaced)
+    }
+    
+    // put() for self-expiring values: an explicit `ttl` wins, then the value's own,
+    // then the configured default
+    pub fn put_expirable(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool
+    where
+        V: Expirable,
+    {
+        let ttl = ttl.or_else(|| value.ttl());
+        self.put(key, value, ttl, priority)
+    }
+    
+    // Lookups take any borrowed form of the key, e.g. &str for String keys.
+    // The key is only copied into an owned K when a callback wants a Hit/Miss event.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        self.access(key, |entry, _| entry.value.clone()).unwrap_or(None)
+    }
+    
+    // get() for Copy values (counters, ids, scores): copied out of the entry, with no
+    // allocation on a hit unless an event subscriber or checksum needs one
+    pub fn get_copy<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+        V: Copy,
+    {
+        self.access(key, |entry, _| entry.value).unwrap_or(None)
+    }
+    
+    // get() that reports a failed checksum instead of treating it as a miss
+    pub fn try_get<Q>(&self, key: &Q) -> Result<Option<V>, CacheError>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        self.access(key, |entry, _| entry.value.clone())
+    }
+    
+    // Value and metadata from a single lookup (one LRU touch, one Hit event)
+    pub fn get_with_metadata<Q>(&self, key: &Q) -> Option<(V, EntryMetadata)>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        self.access(key, |entry, previous_access| {
+            (entry.value.clone(), entry.metadata(&self.config, entry.last_accessed, previous_access))
+        })
+        .unwrap_or(None)
     }
     
-    pub fn get(&self, key: &K) -> Option<V> {
+    fn checksum_of(&self, value: &V) -> Option<u32> {
+        self.integrity.as_ref().and_then(|integrity| (integrity.checksum)(value))
+    }
+    
+    // A corrupted entry is removed and counted as a miss
+    fn access<Q, R>(&self, key: &Q, read: impl FnOnce(&CacheEntry<V>, Instant) -> R) -> Result<Option<R>, CacheError>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
         let mut data = self.data.write().unwrap();
         
         if let Some(entry) = data.get_mut(key) {
-            // Check TTL
-            if Instant::now() > entry.ttl {
+            if entry.is_invalidated(self.generation()) {
                 data.remove(key);
                 self.lru_list.lock().unwrap().remove(key);
+                self.entry_callbacks.lock().unwrap().remove(key);
+                self.stats.misses.add(1);
+                self.notify_with(|| CacheEvent::Miss(key.to_owned()));
+                return Ok(None);
+            }
+            
+            // Check TTL
+            let now = clock::now();
+            let stale = if now > entry.ttl {
+                self.stale_serving(entry, now)
+            } else {
+                None
+            };
+            if now > entry.ttl && stale.is_none() {
+                // Keep the old value around in case a read-through load fails
+                if self.config.error_grace.is_some_and(|grace| now <= entry.ttl + grace) {
+                    self.stats.misses.add(1);
+                    self.notify_with(|| CacheEvent::Miss(key.to_owned()));
+                    return Ok(None);
+                }
+                
+                let (expired_key, expired) = data.remove_entry(key).unwrap();
+                self.lru_list.lock().unwrap().remove(key);
                 
-                let mut stats = self.stats.lock().unwrap();
-                stats.ttl_expirations += 1;
-                stats.misses += 1;
+                self.stats.ttl_expirations.add(1);
+                self.stats.misses.add(1);
                 
-                self.notify_callbacks(CacheEvent::TTLExpiry(key.clone()));
-                return None;
+                Self::fire_entry_callback(&self.entry_callbacks, &expired_key, &expired.value);
+                self.notify_with(|| CacheEvent::TTLExpiry {
+                    key: expired_key,
+                    entry: expired.into_removed(self.events.include_values()),
+                });
+                return Ok(None);
+            }
+            
+            if let (Some(integrity), Some(expected)) = (&self.integrity, entry.checksum) {
+                let actual = integrity.should_verify().then(|| (integrity.checksum)(&entry.value)).flatten();
+                if let Some(actual) = actual.filter(|actual| *actual != expected) {
+                    let (corrupted_key, _) = data.remove_entry(key).unwrap();
+                    self.lru_list.lock().unwrap().remove(key);
+                    self.entry_callbacks.lock().unwrap().remove(key);
+                    
+                    self.stats.corruptions.add(1);
+                    self.stats.misses.add(1);
+                    
+                    self.notify_callbacks(CacheEvent::Delete(corrupted_key));
+                    return Err(CacheError::Corrupted { expected, actual });
+                }
             }
             
             // Update access metadata
-            entry.last_accessed = Instant::now();
+            let previous_access = entry.last_accessed;
+            entry.last_accessed = now;
             entry.access_count += 1;
-            let value = entry.value.clone();
+            let result = read(entry, previous_access);
             
             // Update LRU with O(1) operation
             self.lru_list.lock().unwrap().touch(key);
             
-            self.stats.lock().unwrap().hits += 1;
-            self.notify_callbacks(CacheEvent::Hit(key.clone()));
+            self.stats.hits.add(1);
+            match stale {
+                Some(StaleServe::Revalidating) => self.stats.stale_hits.add(1),
+                Some(StaleServe::Degraded) => self.stats.degraded_hits.add(1),
+                None => {}
+            }
+            if stale.is_some() {
+                self.revalidate(key.to_owned());
+            }
+            self.notify_with(|| CacheEvent::Hit(key.to_owned()));
             
-            Some(value)
+            Ok(Some(result))
         } else {
-            self.stats.lock().unwrap().misses += 1;
-            self.notify_callbacks(CacheEvent::Miss(key.clone()));
-            None
+            self.stats.misses.add(1);
+            self.notify_with(|| CacheEvent::Miss(key.to_owned()));
+            Ok(None)
         }
     }
     
-    pub fn delete(&self, key: &K) -> bool {
-        let mut data = self.data.write().unwrap();
-        if data.remove(key).is_some() {
-            self.lru_list.lock().unwrap().remove(key);
-            true
+    fn stale_serving(&self, entry: &CacheEntry<V>, now: Instant) -> Option<StaleServe> {
+        let within = |grace: Option<Duration>| grace.is_some_and(|grace| now <= entry.ttl + grace);
+        if self.loader.is_some() && within(self.config.stale_grace) {
+            Some(StaleServe::Revalidating)
+        } else if entry.load_failed && within(self.config.error_grace) {
+            Some(StaleServe::Degraded)
         } else {
-            false
+            None
         }
     }
     
-    pub fn add_callback<C: CacheCallback<K> + 'static>(&self, callback: Box<C>) {
-        self.callbacks.lock().unwrap().push(callback);
+    // Read-through: a miss calls the loader and caches the result. If the load
+    // fails and the key's expired value is still within error_grace, that value
+    // is returned instead and counted in stats.degraded_hits.
+    pub fn get_or_load(&self, key: &K) -> Result<V, LoadError> {
+        if let Some(value) = self.get(key) {
+            return Ok(value);
+        }
+        let Some(loader) = self.loader.clone() else {
+            return Err("no loader registered".into());
+        };
+        
+        match loader(key) {
+            Ok(value) => {
+                let priority = self.data.read().unwrap().get(key).map_or(5, |entry| entry.priority);
+                self.put(key.clone(), value.clone(), None, priority);
+                Ok(value)
+            }
+            Err(err) => self.serve_degraded(key).ok_or(err),
+        }
     }
     
-    fn evict_lowest_priority(
-        &self,
-        data: &mut HashMap<K, CacheEntry<V>>,
-        lru_list: &mut LruList<K>,
-        stats: &mut CacheStats,
-    ) {
-        // Find entry with lowest priority score (age / priority)
-        let mut eviction_candidate: Option<(K, f64)> = None;
+    fn serve_degraded(&self, key: &K) -> Option<V> {
+        let grace = self.config.error_grace?;
+        let mut data = self.data.write().unwrap();
+        let generation = self.generation();
+        let entry = data
+            .get_mut(key)
+            .filter(|entry| clock::now() <= entry.ttl + grace && !entry.is_invalidated(generation))?;
+        entry.load_failed = true;
+        self.stats.degraded_hits.add(1);
+        Some(entry.value.clone())
+    }
+    
+    // Reload a stale entry in the background; at most one load per key is in
+    // flight. A failed load is recorded so the value can be served within
+    // error_grace once the stale grace period is over.
+    fn revalidate(&self, key: K) {
+        let Some(loader) = self.loader.clone() else {
+            return;
+        };
+        if !self.revalidating.lock().unwrap().insert(key.clone()) {
+            return;
+        }
         
-        for key in lru_list.iter() {
-            if let Some(entry) = data.get(&key) {
-                let age = entry.last_accessed.elapsed().as_secs() as f64;
-                let score = age / entry.priority as f64;
-                
-                match &eviction_candidate {
-                    None => eviction_candidate = Some((key.clone(), score)),
-                    Some((_, best_score)) if score > *best_score => {
-                        eviction_candidate = Some((key.clone(), score));
+        let data = Arc::downgrade(&self.data);
+        let revalidating = Arc::clone(&self.revalidating);
+        let ttl = self.config.default_ttl;
+        let checksum = self.integrity.as_ref().map(|integrity| Arc::clone(&integrity.checksum));
+        thread::spawn(move || {
+            let loaded = loader(&key);
+            if let Some(data) = data.upgrade() {
+                // Only refresh in place; a deleted entry stays deleted
+                if let Some(entry) = data.write().unwrap().get_mut(&key) {
+                    match loaded {
+                        Ok(value) => {
+                            entry.checksum = checksum.as_ref().and_then(|checksum| checksum(&value));
+                            entry.value = value;
+                            entry.ttl = clock::now() + ttl;
+                            entry.load_failed = false;
+                        }
+                        Err(_) => entry.load_failed = true,
                     }
-                    _ => {}
                 }
             }
-        }
-        
-        if let Some((key, _)) = eviction_candidate {
-            data.remove(&key);
-            lru_list.remove(&key);
-            stats.evictions += 1;
-            self.notify_callbacks(CacheEvent::Eviction(key));
+            revalidating.lock().unwrap().remove(&key);
+        });
+    }
+    
+    // Existence check that leaves stats, access counts and LRU order untouched
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.data
+            .read()
+            .unwrap()
+            .get(key)
+            .is_some_and(|entry| entry.is_live(clock::now(), self.generation()))
+    }
+    
+    pub fn delete<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        let mut data = self.data.write().unwrap();
+        if let Some((removed_key, _)) = data.remove_entry(key) {
+            self.lru_list.lock().unwrap().remove(key);
+            self.entry_callbacks.lock().unwrap().remove(key);
+            self.notify_callbacks(CacheEvent::Delete(removed_key));
+            true
+        } else {
+            false
         }
     }
     
-    fn cleanup_expired(
-        data: &Arc<RwLock<HashMap<K, CacheEntry<V>>>>,
-        lru_list: &Arc<Mutex<LruList<K>>>,
-        stats: &Arc<Mutex<CacheStats>>,
-        callbacks: &Arc<Mutex<Vec<Box<dyn CacheCallback<K>>>>>,
-    ) {
-        let mut data = data.write().unwrap();
-        let mut lru_list = lru_list.lock().unwrap();
-        let now = Instant::now();
+    // Keep only entries the predicate accepts; each removal emits a Delete event
+    pub fn retain<F>(&self, mut keep: F) -> usize
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        let mut data = self.data.write().unwrap();
+        let mut lru_list = self.lru_list.lock().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
         
-        let expired_keys: Vec<K> = data
+        let removed_keys: Vec<K> = data
             .iter()
-            .filter(|(_, entry)| now > entry.ttl)
+            .filter(|(_, entry)| !entry.is_invalidated(generation))
+            .filter(|(key, entry)| !keep(key, &entry.value, &entry.metadata(&self.config, now, entry.last_accessed)))
             .map(|(key, _)| key.clone())
             .collect();
         
-        if !expired_keys.is_empty() {
-            let mut stats = stats.lock().unwrap();
-            for key in expired_keys {
-                data.remove(&key);
-                lru_list.remove(&key);
-                stats.ttl_expirations += 1;
+        let mut entry_callbacks = self.entry_callbacks.lock().unwrap();
+        for key in &removed_keys {
+            data.remove(key);
+            lru_list.remove(key);
+            entry_callbacks.remove(key);
+            self.notify_callbacks(CacheEvent::Delete(key.clone()));
+        }
+        
+        removed_keys.len()
+    }
+    
+    // Empty the cache and hand back the live values, least recently used first
+    pub fn drain(&self) -> Vec<(K, V)> {
+        let mut data = self.data.write().unwrap();
+        let mut lru_list = self.lru_list.lock().unwrap();
+        let now = clock::now();
+        
+        let mut keys: Vec<K> = lru_list.iter().collect();
+        keys.reverse();
+        *lru_list = Shared::new(LruList::new());
+        self.entry_callbacks.lock().unwrap().clear();
+        
+        let generation = self.generation();
+        let mut drained = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(entry) = data.remove(&key) {
+                if entry.is_live(now, generation) {
+                    self.notify_callbacks(CacheEvent::Delete(key.clone()));
+                    drained.push((key, entry.value));
+                }
+            }
+        }
+        data.clear();
+        drained
+    }
+    
+    // Copy out live entries matching the predicate, with their remaining TTLs
+    pub fn export_where<F>(&self, predicate: F) -> Vec<ExportedEntry<K, V>>
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        Self::export_entries(&self.config, &self.data.read().unwrap(), self.generation(), predicate)
+    }
+    
+    fn export_entries<F>(config: &CacheConfig, data: &HashMap<K, CacheEntry<V>>, generation: u64, mut predicate: F) -> Vec<ExportedEntry<K, V>>
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        let now = clock::now();
+        
+        data.iter()
+            .filter(|(_, entry)| entry.is_live(now, generation))
+            .filter_map(|(key, entry)| {
+                let meta = entry.metadata(config, now, entry.last_accessed);
+                predicate(key, &entry.value, &meta).then(|| ExportedEntry {
+                    key: key.clone(),
+                    value: entry.value.clone(),
+                    priority: meta.priority,
+                    ttl_remaining: meta.ttl_remaining,
+                    access_count: meta.access_count,
+                })
+            })
+            .collect()
+    }
+    
+    // Load exported records through the normal put path (admission, capacity
+    // and eviction rules apply), keeping their access counts. The conflict check
+    // and the write happen under one lock, so a concurrent put can't slip in
+    // between them. Returns how many records were written.
+    pub fn import(&self, entries: Vec<ExportedEntry<K, V>>, policy: ConflictPolicy) -> usize {
+        let mut imported = 0;
+        for record in entries {
+            if record.ttl_remaining.is_zero() {
+                continue;
+            }
+            let ttl = Some(record.ttl_remaining);
+            let written = self.insert_entry(record.key, record.value, ttl, record.priority, None, None, record.access_count, policy.into());
+            if written.is_ok() {
+                imported += 1;
+            }
+        }
+        imported
+    }
+    
+    // Bulk-load a CSV or JSON seed file before traffic arrives
+    pub fn warm_from_file<P: AsRef<Path>>(&self, path: P) -> Result<WarmupReport, WarmupError>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        self.warm_from_file_with_progress(path, |_, _| {})
+    }
+    
+    // Same as warm_from_file, reporting (loaded, total) after every batch.
+    // Capacity is only enforced once, after the last batch.
+    pub fn warm_from_file_with_progress<P, F>(&self, path: P, mut progress: F) -> Result<WarmupReport, WarmupError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(usize, usize),
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let records = read_seed_file(path.as_ref())?;
+        let total = records.len();
+        let entries = records
+            .into_iter()
+            .map(|record| (record.key, record.value, record.ttl_secs.map(Duration::from_secs), record.priority));
+        Ok(self.load_batches(entries, |loaded| progress(loaded, total)))
+    }
+    
+    // Insert many entries with one capacity pass and one stats update at the
+    // end instead of per-entry bookkeeping. Entries go in in batches, so lookups
+    // are not locked out for the whole load; later duplicates win.
+    pub fn bulk_load<I>(&self, entries: I) -> WarmupReport
+    where
+        I: IntoIterator<Item = BulkEntry<K, V>>,
+    {
+        self.load_batches(entries.into_iter(), |_| {})
+    }
+    
+    fn load_batches<I>(&self, entries: I, mut progress: impl FnMut(usize)) -> WarmupReport
+    where
+        I: Iterator<Item = BulkEntry<K, V>>,
+    {
+        let mut entries = entries.peekable();
+        let mut loaded = 0;
+        
+        while entries.peek().is_some() {
+            let batch: Vec<_> = entries.by_ref().take(WARMUP_BATCH).collect();
+            loaded += batch.len();
+            self.insert_unchecked(batch);
+            progress(loaded);
+        }
+        
+        self.stats.insertions.add(loaded as u64);
+        let evicted = self.evict_to_capacity();
+        WarmupReport { loaded, evicted }
+    }
+    
+    // Insert without per-entry capacity checks; pair with evict_to_capacity
+    fn insert_unchecked(&self, batch: Vec<BulkEntry<K, V>>) {
+        let mut data = self.data.write().unwrap();
+        let mut lru_list = self.lru_list.lock().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
+        
+        for (key, value, ttl, priority) in batch {
+            let ttl = ttl.unwrap_or(self.config.default_ttl);
+            let entry = CacheEntry {
+                checksum: self.checksum_of(&value),
+                value,
+                priority: priority.clamp(1, 10),
+                ttl: now + ttl,
+                created_at: now,
+                last_accessed: now,
+                access_count: 0,
+                refresh_id: None,
+                load_failed: false,
+                generation,
+            };
+            let replaced = data.insert(key.clone(), entry).filter(|old| old.is_live(now, generation));
+            lru_list.remove(&key);
+            lru_list.push_front(key.clone());
+            self.entry_callbacks.lock().unwrap().remove(&key);
+            self.notify_callbacks(match replaced {
+                Some(old) => CacheEvent::Update { key, old_value: Arc::new(old.value) },
+                None => CacheEvent::Insert(key),
+            });
+        }
+    }
+    
+    // One eviction pass in eviction_order
+    fn evict_to_capacity(&self) -> usize {
+        let mut data = self.data.write().unwrap();
+        let mut lru_list = self.lru_list.lock().unwrap();
+        let excess = data.len().saturating_sub(self.config.max_capacity);
+        if excess == 0 || self.is_maintenance_paused() {
+            return 0;
+        }
+        
+        let scored = self.eviction_order(&data, &lru_list);
+        let mut evicted = 0;
+        for (_, _, key) in scored.into_iter().take(excess) {
+            evicted += usize::from(self.evict_entry(&mut data, &mut lru_list, key));
+        }
+        evicted
+    }
+    
+    // The `n` entries capacity eviction would take next, each with its
+    // evict_lowest_priority score (highest goes first). Nothing is evicted;
+    // namespace quotas may still narrow which of them a given put can take.
+    pub fn next_eviction_candidates(&self, n: usize) -> Vec<(K, f64)> {
+        let data = self.data.read().unwrap();
+        let lru_list = self.lru_list.lock().unwrap();
+        
+        let mut scored = self.eviction_order(&data, &lru_list);
+        scored.truncate(n);
+        scored.into_iter().map(|(score, _, key)| (key, score)).collect()
+    }
+    
+    // Scores (age / effective priority) and LRU positions, highest score first,
+    // ties going to the entry closer to the LRU tail
+    fn eviction_order(&self, data: &HashMap<K, CacheEntry<V>>, lru_list: &LruList<K>) -> Vec<(f64, usize, K)> {
+        let now = clock::now();
+        let generation = self.generation();
+        let mut scored: Vec<(f64, usize, K)> = lru_list
+            .iter()
+            .enumerate()
+            .filter_map(|(position, key)| {
+                let score = data.get(&key)?.eviction_score(&self.config, now, generation);
+                Some((score, position, key))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.cmp(&a.1)));
+        scored
+    }
+    
+    pub fn add_callback<C: CacheCallback<K, V> + 'static>(&self, callback: Box<C>) {
+        self.events.add(callback);
+    }
+    
+    // Returns false if no entry passed `in_scope`. With eviction_sample_size the
+    // victim is the best of the least recently used entries in scope, so a newer
+    // low-priority entry outside the sample can outlive an older high-priority one.
+    fn evict_lowest_priority(
+        &self,
+        data: &mut HashMap<K, CacheEntry<V>>,
+        lru_list: &mut LruList<K>,
+        in_scope: impl Fn(&K) -> bool,
+    ) -> bool {
+        // Find entry with lowest priority score (age / effective priority)
+        let now = clock::now();
+        let generation = self.generation();
+        let mut eviction_candidate: Option<(K, f64)> = None;
+        let sample_size = self.config.eviction_sample_size.unwrap_or(usize::MAX);
+        
+        for key in lru_list.iter_from_tail().filter(|key| in_scope(key)).take(sample_size) {
+            if let Some(entry) = data.get(&key) {
+                let score = entry.eviction_score(&self.config, now, generation);
                 
-                // Notify callbacks
-                let callbacks = callbacks.lock().unwrap();
-                for callback in callbacks.iter() {
-                    callback.on_event(CacheEvent::TTLExpiry(key.clone()));
+                // Ties go to the entry closer to the LRU tail
+                match &eviction_candidate {
+                    None => eviction_candidate = Some((key.clone(), score)),
+                    Some((_, best_score)) if score > *best_score => {
+                        eviction_candidate = Some((key.clone(), score));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        
+        match eviction_candidate {
+            Some((key, _)) => {
+                self.evict_entry(data, lru_list, key);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Namespaces a put of `key` into a full cache may evict from, or None when
+    // quotas are not in use. A namespace at or over its reservation only evicts
+    // its own entries, so its bursts cannot push out anyone else; one still under
+    // its reservation reclaims space from namespaces borrowing beyond theirs.
+    fn eviction_scope(&self, data: &HashMap<K, CacheEntry<V>>, key: &K) -> Option<HashSet<Option<String>>> {
+        let namespace_of = self.namespace_of.as_ref()?;
+        if self.config.namespace_quotas.is_empty() {
+            return None;
+        }
+        let quota = |namespace: &Option<String>| {
+            namespace
+                .as_ref()
+                .and_then(|name| self.config.namespace_quotas.get(name))
+                .copied()
+                .unwrap_or(0)
+        };
+        
+        let mut usage: HashMap<Option<String>, usize> = HashMap::new();
+        for k in data.keys() {
+            *usage.entry(namespace_of(k)).or_default() += 1;
+        }
+        
+        let namespace = namespace_of(key);
+        let used = usage.get(&namespace).copied().unwrap_or(0);
+        if used > 0 && used >= quota(&namespace) {
+            return Some(HashSet::from([namespace]));
+        }
+        Some(
+            usage
+                .into_iter()
+                .filter(|(namespace, used)| *used > quota(namespace))
+                .map(|(namespace, _)| namespace)
+                .collect(),
+        )
+    }
+    
+    // Returns false for an invalidated entry, which goes without a trace
+    fn evict_entry(
+        &self,
+        data: &mut HashMap<K, CacheEntry<V>>,
+        lru_list: &mut LruList<K>,
+        key: K,
+    ) -> bool {
+        lru_list.remove(&key);
+        match data.remove(&key) {
+            Some(entry) if entry.is_invalidated(self.generation()) => {
+                self.entry_callbacks.lock().unwrap().remove(&key);
+                false
+            }
+            removed => {
+                self.stats.evictions.add(1);
+                if let Some(entry) = removed {
+                    Self::fire_entry_callback(&self.entry_callbacks, &key, &entry.value);
+                    self.notify_with(|| CacheEvent::Eviction {
+                        key,
+                        entry: entry.into_removed(self.events.include_values()),
+                    });
+                }
+                true
+            }
+        }
+    }
+    
+    // Token-bucket check for a put; Block sleeps here, before any cache lock is taken
+    fn admit(&self, key: &K) -> Admission {
+        let Some(limiter) = &self.admission else {
+            return Admission::Admitted;
+        };
+        let namespace = self.namespace_of.as_ref().and_then(|namespace_of| namespace_of(key));
+        
+        loop {
+            let (acquired, policy) = {
+                let mut limiter = limiter.lock().unwrap();
+                (limiter.try_acquire(namespace.as_deref()), limiter.policy())
+            };
+            match (acquired, policy) {
+                (Ok(()), _) => return Admission::Admitted,
+                (Err(wait), OverflowPolicy::Block) => thread::sleep(wait),
+                (Err(_), OverflowPolicy::Reject) => {
+                    self.stats.throttled.add(1);
+                    return Admission::Rejected;
                 }
+                (Err(_), OverflowPolicy::DropLowestPriority) => return Admission::MustOutrankLowest,
+            }
+        }
+    }
+    
+    // Freeze the cache's own mutations for backups and debugging: the background
+    // cleanup pass is skipped and nothing is evicted for capacity or admission, so
+    // puts that need room are rejected. Bulk loads may still overshoot capacity
+    // until resume_maintenance trims them. Returns once a pass in progress is done.
+    pub fn pause_maintenance(&self) {
+        let _data = self.data.write().unwrap();
+        self.maintenance_paused.store(true, Ordering::Release);
+    }
+    
+    // Returns the entries evicted to get back within capacity
+    pub fn resume_maintenance(&self) -> usize {
+        self.maintenance_paused.store(false, Ordering::Release);
+        self.evict_to_capacity()
+    }
+    
+    pub fn is_maintenance_paused(&self) -> bool {
+        self.maintenance_paused.load(Ordering::Acquire)
+    }
+    
+    // Diagnostics for readiness probes. Counting expired entries is a pass over
+    // the whole map under the read lock, so probe at a modest rate. Entries still
+    // within their stale or error grace period don't count as expired.
+    pub fn health(&self) -> HealthReport {
+        let cleanup_alive = self
+            .cleanup_handle
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished());
+        let last_cleanup = *self.last_cleanup.lock().unwrap();
+        let now = clock::now();
+        let idle = now.saturating_duration_since(last_cleanup.unwrap_or(self.created_at));
+        let cleanup_overdue = !self.is_maintenance_paused() && idle > self.config.cleanup_interval.saturating_mul(2);
+        
+        let retention = self.config.expired_retention();
+        let waiting = Instant::now();
+        let data = self.data.read().unwrap();
+        let lock_wait = waiting.elapsed();
+        let generation = self.generation();
+        let expired_unreclaimed = data
+            .values()
+            .filter(|entry| now > entry.ttl + retention || entry.is_invalidated(generation))
+            .count();
+        HealthReport {
+            cleanup_alive,
+            last_cleanup,
+            cleanup_overdue,
+            event_queue_depth: self.events.queued(),
+            lock_wait,
+            entries: data.len(),
+            capacity: self.config.max_capacity,
+            expired_unreclaimed,
+        }
+    }
+    
+    // Run a cleanup pass now instead of waiting for the background interval
+    pub fn purge_expired(&self) -> usize {
+        Self::cleanup_expired(
+            &self.data,
+            &self.lru_list,
+            &self.stats,
+            &self.events,
+            &self.entry_callbacks,
+            self.config.expired_retention(),
+            self.generation(),
+            None,
+        )
+    }
+    
+    // Entries are kept `retention` past their TTL so they can still be served
+    // stale. With a scan only its next budget of entries and its due keys are
+    // looked at; without one, every entry is. Invalidated entries are reclaimed
+    // too, but neither counted nor reported as expiries.
+    #[allow(clippy::too_many_arguments)]
+    fn cleanup_expired(
+        data: &Arc<Entries<K, V>>,
+        lru_list: &Arc<Mutex<Shared<LruList<K>>>>,
+        stats: &StatCounters,
+        events: &EventBus<K, V>,
+        entry_callbacks: &EntryCallbacks<K, V>,
+        retention: Duration,
+        generation: u64,
+        scan: Option<&mut ExpiryScan<K>>,
+    ) -> usize {
+        let mut data = data.write().unwrap();
+        let mut lru_list = lru_list.lock().unwrap();
+        let now = clock::now();
+        let reclaimable = |entry: &CacheEntry<V>| now > entry.ttl + retention || entry.is_invalidated(generation);
+        
+        let expired_keys: Vec<K> = match scan {
+            Some(scan) => scan.step(&data, &lru_list, now, retention, generation),
+            None => data
+                .iter()
+                .filter(|(_, entry)| reclaimable(entry))
+                .map(|(key, _)| key.clone())
+                .collect(),
+        };
+        
+        let mut removed = 0;
+        for key in expired_keys {
+            // A due key may have been rewritten since the scan noted it
+            if !data.get(&key).is_some_and(reclaimable) {
+                continue;
+            }
+            lru_list.remove(&key);
+            removed += 1;
+            if data[&key].is_invalidated(generation) {
+                data.remove(&key);
+                entry_callbacks.lock().unwrap().remove(&key);
+                continue;
+            }
+            stats.ttl_expirations.add(1);
+            if let Some(entry) = data.remove(&key) {
+                Self::fire_entry_callback(entry_callbacks, &key, &entry.value);
+                events.emit_with(|| CacheEvent::TTLExpiry {
+                    key,
+                    entry: entry.into_removed(events.include_values()),
+                });
             }
         }
+        removed
     }
     
-    fn notify_callbacks(&self, event: CacheEvent<K>) {
-        let callbacks = self.callbacks.lock().unwrap();
-        for callback in callbacks.iter() {
-            callback.on_event(event.clone());
+    // The entry expired or was evicted: run its put_with_callback hook, if any
+    fn fire_entry_callback(entry_callbacks: &EntryCallbacks<K, V>, key: &K, value: &V) {
+        let callback = entry_callbacks.lock().unwrap().remove(key);
+        if let Some(callback) = callback {
+            callback(key, value);
         }
     }
     
+    fn notify_callbacks(&self, event: CacheEvent<K, V>) {
+        self.notify_with(move || event);
+    }
+    
+    // Build the event only when someone is listening
+    fn notify_with(&self, event: impl FnOnce() -> CacheEvent<K, V>) {
+        self.events.emit_with(event);
+    }
+    
     pub fn get_stats(&self) -> CacheStats {
-        self.stats.lock().unwrap().clone()
+        let mut stats = self.stats.snapshot();
+        stats.events_dropped = self.events.dropped();
+        stats.dispatcher_restarts = self.events.restarts();
+        stats
+    }
+    
+    // A pass over every entry, so unlike get_stats this is not free; a growing
+    // `expired` count means cleanup is falling behind. Entries kept past their TTL
+    // to be served stale count as expired.
+    pub fn ttl_distribution(&self) -> TtlDistribution {
+        let now = clock::now();
+        let generation = self.generation();
+        let mut distribution = TtlDistribution::default();
+        for entry in self.data.read().unwrap().values() {
+            let live = !entry.is_invalidated(generation);
+            distribution.record(entry.ttl.checked_duration_since(now).filter(|_| live));
+        }
+        distribution
+    }
+    
+    // Takes the read lock for the copy, so the view is consistent. Entries past
+    // their TTL are left out, even ones that could still be served stale.
+    pub fn freeze(&self) -> FrozenCache<K, V> {
+        let data = self.data.read().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
+        let entries = data
+            .iter()
+            .filter(|(_, entry)| entry.is_live(now, generation))
+            .map(|(key, entry)| {
+                let frozen = FrozenEntry {
+                    value: entry.value.clone(),
+                    priority: entry.priority,
+                    expires_at: entry.ttl,
+                };
+                (key.clone(), frozen)
+            })
+            .collect();
+        FrozenCache::new(now, entries)
+    }
+    
+    // Remove everything with a single Clear event counting the live entries;
+    // per-entry callbacks are dropped unrun and stats are kept. Empty tables are
+    // swapped in, so a table still shared with a fork isn't copied to be emptied.
+    pub fn clear(&self) {
+        let mut data = self.data.write().unwrap();
+        let mut lru_list = self.lru_list.lock().unwrap();
+        let now = clock::now();
+        *lru_list = Shared::new(LruList::new());
+        self.entry_callbacks.lock().unwrap().clear();
+        let generation = self.generation();
+        let entries = data.values().filter(|entry| entry.is_live(now, generation)).count();
+        *data = Shared::default();
+        self.notify_callbacks(CacheEvent::Clear { entries });
+    }
+    
+    // clear() for large caches: bumps the generation, and every entry stored
+    // before is gone from then on, stale serving included. Only the read lock is
+    // taken, to count them, and no entry is touched. They are reclaimed lazily by
+    // the cleanup thread, purge_expired, a lookup or put of the same key, or
+    // capacity eviction, which takes them first; their per-entry callbacks are
+    // dropped unrun then. The Clear event counts stored entries, expired ones
+    // included, since counting live ones would mean a pass over all of them.
+    pub fn invalidate_all(&self) {
+        let entries = {
+            let data = self.data.read().unwrap();
+            self.generation.fetch_add(1, Ordering::AcqRel);
+            data.len()
+        };
+        self.notify_callbacks(CacheEvent::Clear { entries });
+    }
+    
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+    
+    // A new cache holding this one's entries in the same recency order, with the
+    // same config, namespaces, loader and checksums, and independent of it from
+    // then on. Nothing is copied here: the two share the entry table and LRU list,
+    // and the first write on either side copies them for that side (see Shared),
+    // values included. A hit counts as a write, since it updates access metadata.
+    // The fork starts with zeroed stats, its own cleanup and no subscribers,
+    // per-entry callbacks, put_refreshing tasks or snapshot scheduler; refreshed
+    // entries age out in the fork like any other.
+    pub fn fork(&self) -> Self {
+        let mut fork = Self::build(self.config.clone(), self.namespace_of.clone());
+        fork.loader = self.loader.clone();
+        fork.integrity = self.integrity.as_ref().map(|integrity| Integrity {
+            check: integrity.check,
+            checksum: Arc::clone(&integrity.checksum),
+            reads: AtomicU64::new(0),
+        });
+        let data = self.data.read().unwrap();
+        let lru_list = self.lru_list.lock().unwrap();
+        // The shared entries stay live in the fork until it is invalidated itself
+        fork.generation.store(self.generation(), Ordering::Release);
+        *fork.data.write().unwrap() = Shared::clone(&data);
+        *fork.lru_list.lock().unwrap() = Shared::clone(&lru_list);
+        fork
+    }
+    
+    // Stored entries, including expired ones not yet cleaned up
+    pub fn size(&self) -> usize {
+        self.data.read().unwrap().len()
+    }
+    
+    // Live entries only
+    pub fn len(&self) -> usize {
+        let now = clock::now();
+        let generation = self.generation();
+        self.data.read().unwrap().values().filter(|entry| entry.is_live(now, generation)).count()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    // Stop the background work for an orderly exit: the cleanup, snapshot and
+    // refresh threads finish their current pass and exit, then the event
+    // dispatchers deliver what is queued. Waits at most `timeout` overall; anything
+    // still running then is left detached and listed in the report. The cache
+    // stays usable afterwards, but nothing runs in the background any more and
+    // async events emitted later are dropped. Calling it again is a no-op.
+    pub fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        let deadline = Instant::now() + timeout;
+        self.stopping.store(true, Ordering::Release);
+        
+        let mut handles: Vec<_> = self.cleanup_handle.lock().unwrap().take().into_iter().collect();
+        handles.extend(self.snapshot_handle.lock().unwrap().take());
+        handles.append(&mut self.refresh_handles.lock().unwrap());
+        for handle in &handles {
+            handle.thread().unpark();
+        }
+        let mut threads_running = join_until(handles, deadline);
+        
+        let (dispatchers_running, events_undelivered) = self.events.shutdown(deadline);
+        threads_running.extend(dispatchers_running);
+        ShutdownReport {
+            threads_running,
+            events_undelivered,
+            snapshot: None,
+        }
+    }
+}
+
+// What shutdown() managed to stop before its timeout
+#[derive(Debug)]
+pub struct ShutdownReport {
+    // Names of the background threads that did not exit before the timeout
+    pub threads_running: Vec<String>,
+    // Queued events the dispatchers had not delivered by the timeout
+    pub events_undelivered: usize,
+    // Set by shutdown_with_snapshot
+    pub snapshot: Option<Result<usize, SnapshotError>>,
+}
+
+impl ShutdownReport {
+    // Everything stopped, every event was delivered and the snapshot, if any, was written
+    pub fn is_clean(&self) -> bool {
+        self.threads_running.is_empty()
+            && self.events_undelivered == 0
+            && !matches!(self.snapshot, Some(Err(_)))
+    }
+}
+
+// How often join_until checks on the threads it waits for
+const JOIN_POLL: Duration = Duration::from_millis(1);
+
+// Sleep for `interval`, cut short when shutdown unparks the thread. Returns false
+// once the cache is shutting down.
+fn pause(stopping: &AtomicBool, interval: Duration) -> bool {
+    let deadline = Instant::now() + interval;
+    while !stopping.load(Ordering::Acquire) {
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        thread::park_timeout(deadline - now);
+    }
+    false
+}
+
+// Join the threads that finish by `deadline` and return the names of the rest,
+// which are left running detached
+pub(crate) fn join_until(mut handles: Vec<thread::JoinHandle<()>>, deadline: Instant) -> Vec<String> {
+    loop {
+        let (finished, running): (Vec<_>, Vec<_>) = handles.into_iter().partition(|handle| handle.is_finished());
+        for handle in finished {
+            let _ = handle.join();
+        }
+        handles = running;
+        if handles.is_empty() || Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(JOIN_POLL);
+    }
+    handles
+        .iter()
+        .map(|handle| handle.thread().name().unwrap_or("<unnamed>").to_string())
+        .collect()
+}
+
+// What memory_stats() found, for estimated_memory_bytes and get_stats_with_memory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    // Entries plus the unused capacity of the entry map and LRU list
+    pub total_bytes: usize,
+    pub entry_bytes: usize,
+    // Entry bytes per namespace (None for keys outside any); empty unless the
+    // cache was built with_namespaces
+    pub by_namespace: HashMap<Option<String>, usize>,
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + MemSize + 'static,
+    V: Clone + Send + Sync + MemSize + 'static,
+{
+    // Approximate RAM held by the cache; see memory_stats
+    pub fn estimated_memory_bytes(&self) -> usize {
+        self.memory_stats().total_bytes
+    }
+    
+    // Sums each entry's slots in the entry map and LRU list with the heap its key
+    // and value own, per MemSize. A pass over all entries under the read lock.
+    pub fn memory_stats(&self) -> MemoryStats {
+        let map_slot = std::mem::size_of::<(K, CacheEntry<V>)>() + 1;
+        let lru_slot = std::mem::size_of::<(K, LruNode<K>)>() + 1;
+        let data = self.data.read().unwrap();
+        let lru_list = self.lru_list.lock().unwrap();
+        
+        let mut stats = MemoryStats::default();
+        for (key, entry) in data.iter() {
+            // The LRU list holds a copy of the key and its neighbours hold up to two more
+            let bytes = map_slot + lru_slot + 4 * key.heap_size() + entry.value.heap_size();
+            stats.entry_bytes += bytes;
+            if let Some(namespace_of) = &self.namespace_of {
+                *stats.by_namespace.entry(namespace_of(key)).or_default() += bytes;
+            }
+        }
+        let unused = data.capacity().saturating_sub(data.len()) * map_slot
+            + lru_list.nodes.capacity().saturating_sub(lru_list.nodes.len()) * lru_slot;
+        stats.total_bytes = stats.entry_bytes + unused;
+        stats
+    }
+    
+    // get_stats plus the memory_stats figures
+    pub fn get_stats_with_memory(&self) -> CacheStats {
+        let memory = self.memory_stats();
+        CacheStats {
+            estimated_memory_bytes: Some(memory.total_bytes),
+            memory_by_namespace: memory.by_namespace,
+            ..self.get_stats()
+        }
+    }
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + Serialize + 'static,
+{
+    // Store a CRC-32C of each value's serialized form on put and re-check it on
+    // reads, to catch in-memory bit flips. Only entries written after this call are
+    // covered; a mismatch drops the entry and surfaces CacheError::Corrupted from
+    // try_get (plain get treats it as a miss).
+    pub fn with_value_checksums(mut self, check: IntegrityCheck) -> Self {
+        self.integrity = Some(Integrity {
+            check,
+            checksum: Arc::new(|value: &V| serde_json::to_vec(value).ok().map(|bytes| crc32c::crc32c(&bytes))),
+            reads: AtomicU64::new(0),
+        });
+        self
+    }
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + Serialize + DeserializeOwned + 'static,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    // Write all live entries to `path` (gzip-compressed when it ends in .gz)
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<usize, SnapshotError> {
+        let entries = self.export_where(|_, _, _| true);
+        let count = entries.len();
+        write_snapshot(path.as_ref(), &Snapshot::new(entries))?;
+        Ok(count)
+    }
+    
+    // Restore a snapshot through import(); TTLs continue from where they were saved.
+    // Records failing their checksum are skipped and listed in the report.
+    pub fn load_snapshot<P: AsRef<Path>>(&self, path: P, policy: ConflictPolicy) -> Result<SnapshotLoadReport, SnapshotError> {
+        let (snapshot, verification) = read_snapshot_lossy(path.as_ref())?;
+        Ok(SnapshotLoadReport {
+            loaded: self.import(snapshot.entries, policy),
+            verification,
+        })
+    }
+    
+    // save_snapshot with AES-256-GCM; nothing is written to disk in plaintext
+    pub fn save_snapshot_encrypted<P: AsRef<Path>>(&self, path: P, keys: &dyn KeyProvider) -> Result<usize, SnapshotError> {
+        let entries = self.export_where(|_, _, _| true);
+        let count = entries.len();
+        write_encrypted_snapshot(path.as_ref(), &Snapshot::new(entries), keys)?;
+        Ok(count)
+    }
+    
+    // Fails closed: a wrong key or any tampering loads nothing
+    pub fn load_snapshot_encrypted<P: AsRef<Path>>(
+        &self,
+        path: P,
+        keys: &dyn KeyProvider,
+        policy: ConflictPolicy,
+    ) -> Result<SnapshotLoadReport, SnapshotError> {
+        let (snapshot, verification) = read_encrypted_snapshot_lossy(path.as_ref(), keys)?;
+        Ok(SnapshotLoadReport {
+            loaded: self.import(snapshot.entries, policy),
+            verification,
+        })
+    }
+    
+    // Start the background thread described by `config.snapshot_schedule`.
+    // Returns false when no schedule is configured or the thread already runs.
+    // The thread stops on its next tick once the cache has been dropped, and at
+    // once when it is shut down.
+    pub fn start_snapshot_scheduler(&self) -> bool {
+        let Some(schedule) = self.config.snapshot_schedule.clone() else {
+            return false;
+        };
+        let mut handle = self.snapshot_handle.lock().unwrap();
+        if handle.is_some() {
+            return false;
+        }
+        
+        let config = self.config.clone();
+        let data = Arc::downgrade(&self.data);
+        let events = Arc::downgrade(&self.events);
+        let generation = Arc::clone(&self.generation);
+        let stopping = Arc::clone(&self.stopping);
+        let spawned = thread::Builder::new()
+            .name("cache-snapshots".to_string())
+            .spawn(move || {
+                while pause(&stopping, schedule.interval) {
+                    let generation = generation.load(Ordering::Acquire);
+                    if !Self::run_scheduled_snapshot(&schedule, &config, &data, &events, generation) {
+                        break;
+                    }
+                }
+            })
+            .expect("failed to spawn snapshot scheduler");
+        *handle = Some(spawned);
+        true
+    }
+    
+    // shutdown, then write what is left in the cache to `path` as a final
+    // snapshot. The snapshot is taken after the timeout, however long it takes.
+    pub fn shutdown_with_snapshot<P: AsRef<Path>>(&self, timeout: Duration, path: P) -> ShutdownReport {
+        let mut report = self.shutdown(timeout);
+        report.snapshot = Some(self.save_snapshot(path));
+        report
+    }
+    
+    // Returns false once the cache is gone
+    fn run_scheduled_snapshot(
+        schedule: &SnapshotSchedule,
+        config: &CacheConfig,
+        data: &Weak<Entries<K, V>>,
+        events: &Weak<EventBus<K, V>>,
+        generation: u64,
+    ) -> bool {
+        let (Some(data), Some(events)) = (data.upgrade(), events.upgrade()) else {
+            return false;
+        };
+        
+        let entries = Self::export_entries(config, &data.read().unwrap(), generation, |_, _, _| true);
+        let outcome = snapshot::write_scheduled_snapshot(schedule, entries);
+        events.emit_with(|| match outcome {
+            SnapshotEvent::Saved { path, entries } => CacheEvent::SnapshotSaved { path, entries },
+            SnapshotEvent::Failed { path, error } => CacheEvent::SnapshotFailed { path, error },
+        });
+        true
     }
 }
 
@@ -429,4 +2196,1204 @@ mod tests {
         assert_eq!(cache.get(&2), Some("high"));
         assert_eq!(cache.get(&3), Some("medium"));
     }
+    
+    #[test]
+    fn test_get_with_metadata() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "value1", Some(Duration::from_secs(60)), 3);
+        
+        let (value, meta) = cache.get_with_metadata(&1).unwrap();
+        assert_eq!(value, "value1");
+        assert_eq!(meta.priority, 3);
+        assert_eq!(meta.access_count, 1);
+        assert!(meta.ttl_remaining > Duration::from_secs(59));
+        assert!(cache.get_with_metadata(&2).is_none());
+        
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+    }
+    
+    #[test]
+    fn test_contains_key_has_no_side_effects() {
+        let cache = SmartCache::new(2);
+        
+        cache.put(1, "first", None, 5);
+        cache.put(2, "second", None, 5);
+        
+        // Probing key 1 must not refresh its recency
+        assert!(cache.contains_key(&1));
+        assert!(!cache.contains_key(&3));
+        cache.put(3, "third", None, 5);
+        
+        assert!(!cache.contains_key(&1));
+        assert!(cache.contains_key(&2));
+        
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 0);
+    }
+    
+    #[test]
+    fn test_purge_expired() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "short", Some(Duration::from_millis(20)), 5);
+        cache.put(2, "long", None, 5);
+        
+        assert_eq!(cache.purge_expired(), 0);
+        clock::advance(Duration::from_millis(40));
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.get_stats().ttl_expirations, 1);
+        assert!(cache.contains_key(&2));
+    }
+    
+    struct DeleteCounter(Arc<Mutex<Vec<i32>>>);
+    
+    impl<V> CacheCallback<i32, V> for DeleteCounter {
+        fn on_event(&self, event: CacheEvent<i32, V>) {
+            if let CacheEvent::Delete(key) = event {
+                self.0.lock().unwrap().push(key);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_retain_emits_delete_events() {
+        let cache = SmartCache::new(10);
+        let deleted = Arc::new(Mutex::new(Vec::new()));
+        cache.add_callback(Box::new(DeleteCounter(Arc::clone(&deleted))));
+        
+        for i in 0..5 {
+            cache.put(i, "value", None, (i + 1) as u8);
+        }
+        
+        assert_eq!(cache.retain(|_, _, meta| meta.priority > 2), 2);
+        assert!(!cache.contains_key(&0));
+        assert!(!cache.contains_key(&1));
+        assert_eq!(cache.lru_list.lock().unwrap().iter().count(), 3);
+        
+        cache.delete(&4);
+        let mut deleted = deleted.lock().unwrap().clone();
+        deleted.sort();
+        assert_eq!(deleted, vec![0, 1, 4]);
+    }
+    
+    #[test]
+    fn test_drain() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "one", None, 5);
+        cache.put(2, "two", None, 5);
+        cache.put(3, "three", None, 5);
+        cache.get(&1);
+        
+        assert_eq!(cache.drain(), vec![(2, "two"), (3, "three"), (1, "one")]);
+        assert!(!cache.contains_key(&1));
+        assert_eq!(cache.lru_list.lock().unwrap().iter().count(), 0);
+    }
+    
+    #[test]
+    fn test_export_import() {
+        let source = SmartCache::new(10);
+        source.put(1, "a", Some(Duration::from_secs(30)), 8);
+        source.put(2, "b", None, 2);
+        source.get(&1);
+        
+        let exported = source.export_where(|_, _, meta| meta.priority > 5);
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].key, 1);
+        
+        let target = SmartCache::new(10);
+        target.put(1, "existing", None, 9);
+        assert_eq!(target.import(exported.clone(), ConflictPolicy::KeepHigherPriority), 0);
+        assert_eq!(target.import(exported.clone(), ConflictPolicy::KeepExisting), 0);
+        assert_eq!(target.import(exported.clone(), ConflictPolicy::Overwrite), 1);
+        assert_eq!(target.get(&1), Some("a"));
+        // The read before the export, plus the two here
+        assert_eq!(target.get_with_metadata(&1).unwrap().1.access_count, 3);
+        
+        // Only records actually stored are counted
+        let full = SmartCache::new(1);
+        full.put(3, "other", None, 5);
+        full.pause_maintenance();
+        assert_eq!(full.import(exported, ConflictPolicy::Overwrite), 0);
+    }
+    
+    fn write_seed_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}_{}_{}", env!("CARGO_PKG_NAME"), std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+    
+    #[test]
+    fn test_warm_from_file() {
+        let csv = write_seed_file("seed.csv", "key,value,ttl_secs,priority\na,1,60,9\nb,2,,1\nc,3,,5\n");
+        let json = write_seed_file("seed.json", r#"[{"key": "d", "value": 4}, {"key": "e", "value": 5, "priority": 2}]"#);
+        
+        let cache: SmartCache<String, i32> = SmartCache::new(4);
+        let mut progress = Vec::new();
+        let report = cache.warm_from_file_with_progress(&csv, |loaded, total| progress.push((loaded, total))).unwrap();
+        assert_eq!(report, WarmupReport { loaded: 3, evicted: 0 });
+        assert_eq!(progress, vec![(3, 3)]);
+        
+        // Over capacity by one: the single eviction pass drops the lowest priority entry
+        let report = cache.warm_from_file(&json).unwrap();
+        assert_eq!(report, WarmupReport { loaded: 2, evicted: 1 });
+        assert!(!cache.contains_key(&"b".to_string()));
+        
+        let (value, meta) = cache.get_with_metadata(&"a".to_string()).unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(meta.priority, 9);
+        assert!(meta.ttl_remaining <= Duration::from_secs(60));
+        
+        assert!(matches!(cache.warm_from_file("seed.txt"), Err(WarmupError::UnsupportedFormat(_))));
+        std::fs::remove_file(csv).unwrap();
+        std::fs::remove_file(json).unwrap();
+    }
+    
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}_{}", env!("CARGO_PKG_NAME"), std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let dir = temp_dir("snapshot_roundtrip");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("a".to_string(), "1".to_string(), Some(Duration::from_secs(30)), 8);
+        cache.put("b".to_string(), "2".to_string(), None, 2);
+        
+        for name in ["plain.json", "packed.json.gz"] {
+            let path = dir.join(name);
+            assert_eq!(cache.save_snapshot(&path).unwrap(), 2);
+            
+            let restored: SmartCache<String, String> = SmartCache::new(10);
+            let report = restored.load_snapshot(&path, ConflictPolicy::Overwrite).unwrap();
+            assert_eq!(report.loaded, 2);
+            assert!(report.verification.is_valid());
+            let (value, meta) = restored.get_with_metadata(&"a".to_string()).unwrap();
+            assert_eq!(value, "1");
+            assert_eq!(meta.priority, 8);
+            assert!(meta.ttl_remaining <= Duration::from_secs(30));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    struct SnapshotEvents(Arc<Mutex<Vec<CacheEvent<String, String>>>>);
+    
+    impl CacheCallback<String, String> for SnapshotEvents {
+        fn on_event(&self, event: CacheEvent<String, String>) {
+            if matches!(event, CacheEvent::SnapshotSaved { .. } | CacheEvent::SnapshotFailed { .. }) {
+                self.0.lock().unwrap().push(event);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_snapshot_scheduler_retention() {
+        let dir = temp_dir("snapshot_scheduler");
+        let config = CacheConfig {
+            snapshot_schedule: Some(SnapshotSchedule {
+                directory: dir.clone(),
+                interval: Duration::from_millis(20),
+                retain: 2,
+                compress: true,
+                encryption: None,
+            }),
+            ..Default::default()
+        };
+        let cache: SmartCache<String, String> = SmartCache::with_config(config);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        cache.add_callback(Box::new(SnapshotEvents(Arc::clone(&events))));
+        cache.put("a".to_string(), "1".to_string(), None, 5);
+        
+        assert!(cache.start_snapshot_scheduler());
+        assert!(!cache.start_snapshot_scheduler());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while events.lock().unwrap().len() < 3 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        
+        // Stop the scheduler so a run cannot land between its write and its prune
+        assert!(cache.shutdown(Duration::from_secs(5)).is_clean());
+        let snapshots = list_snapshots(&dir).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        let latest: Snapshot<String, String> = read_snapshot(snapshots.last().unwrap()).unwrap();
+        assert_eq!(latest.entries.len(), 1);
+        
+        let events = events.lock().unwrap();
+        assert!(events.len() >= 3);
+        assert!(events.iter().all(|event| matches!(event, CacheEvent::SnapshotSaved { entries: 1, .. })));
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_scheduler_requires_schedule() {
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        assert!(!cache.start_snapshot_scheduler());
+    }
+    
+    #[test]
+    fn test_snapshot_diff() {
+        let dir = temp_dir("snapshot_diff");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("kept".to_string(), "1".to_string(), Some(Duration::from_secs(60)), 5);
+        cache.put("lost".to_string(), "2".to_string(), None, 5);
+        cache.put("changed".to_string(), "3".to_string(), Some(Duration::from_secs(60)), 5);
+        cache.save_snapshot(dir.join("before.json")).unwrap();
+        
+        cache.delete(&"lost".to_string());
+        cache.put("changed".to_string(), "4".to_string(), Some(Duration::from_secs(600)), 9);
+        cache.put("new".to_string(), "5".to_string(), None, 5);
+        cache.save_snapshot(dir.join("after.json")).unwrap();
+        
+        let before: Snapshot<String, String> = read_snapshot(&dir.join("before.json")).unwrap();
+        let after: Snapshot<String, String> = read_snapshot(&dir.join("after.json")).unwrap();
+        let diff = diff_snapshots(&before, &after);
+        
+        assert_eq!(diff.added.iter().map(|e| e.key.as_str()).collect::<Vec<_>>(), vec!["new"]);
+        assert_eq!(diff.removed.iter().map(|e| e.key.as_str()).collect::<Vec<_>>(), vec!["lost"]);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.changed.len(), 1);
+        let change = &diff.changed[0];
+        assert_eq!(change.key, "changed");
+        assert!(change.value_changed);
+        assert_eq!(change.priority_delta, 4);
+        assert!(change.expiry_shift_ms > 500_000);
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_corruption_detection() {
+        let dir = temp_dir("snapshot_corruption");
+        let path = dir.join("damaged.json");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        for key in ["a", "b", "c"] {
+            cache.put(key.to_string(), key.to_uppercase(), None, 5);
+        }
+        cache.save_snapshot(&path).unwrap();
+        assert!(verify_snapshot(&path).unwrap().is_valid());
+        
+        // Flip a byte inside one record's payload
+        let mut raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let data = raw["records"][1]["data"].as_str().unwrap().replacen("\"value\":\"", "\"value\":\"X", 1);
+        raw["records"][1]["data"] = serde_json::Value::String(data);
+        std::fs::write(&path, raw.to_string()).unwrap();
+        
+        let verification = verify_snapshot(&path).unwrap();
+        assert_eq!(verification.records, 3);
+        assert_eq!(verification.corrupt_records, vec![1]);
+        assert!(!verification.checksum_ok);
+        assert!(matches!(read_snapshot::<String, String>(&path), Err(SnapshotError::Corrupt(_))));
+        
+        let restored: SmartCache<String, String> = SmartCache::new(10);
+        let report = restored.load_snapshot(&path, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(report.loaded, 2);
+        assert_eq!(report.verification.corrupt_records, vec![1]);
+        
+        // A truncated file is reported, not loaded
+        let raw = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &raw[..raw.len() / 2]).unwrap();
+        assert!(matches!(restored.load_snapshot(&path, ConflictPolicy::Overwrite), Err(SnapshotError::Format(_))));
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[derive(Clone)]
+    struct Token {
+        expires_in: Option<Duration>,
+    }
+    
+    impl Expirable for Token {
+        fn ttl(&self) -> Option<Duration> {
+            self.expires_in
+        }
+    }
+    
+    #[test]
+    fn test_put_expirable() {
+        let cache: SmartCache<String, Token> = SmartCache::new(10);
+        let short = Token { expires_in: Some(Duration::from_millis(50)) };
+        
+        cache.put_expirable("own".to_string(), short.clone(), None, 5);
+        cache.put_expirable("explicit".to_string(), short, Some(Duration::from_secs(60)), 5);
+        cache.put_expirable("default".to_string(), Token { expires_in: None }, None, 5);
+        
+        clock::advance(Duration::from_millis(80));
+        assert!(cache.get(&"own".to_string()).is_none());
+        assert!(cache.get(&"explicit".to_string()).is_some());
+        assert!(cache.get(&"default".to_string()).is_some());
+    }
+    
+    #[test]
+    fn test_borrowed_key_lookups() {
+        let cache: SmartCache<String, i32> = SmartCache::new(10);
+        cache.put("alpha".to_string(), 1, None, 5);
+        
+        assert_eq!(cache.get("alpha"), Some(1));
+        assert_eq!(cache.get_with_metadata("alpha").map(|(_, meta)| meta.access_count), Some(2));
+        assert!(cache.contains_key("alpha"));
+        assert_eq!(cache.get("beta"), None);
+        
+        assert!(cache.delete("alpha"));
+        assert!(!cache.contains_key("alpha"));
+    }
+    
+    #[test]
+    fn test_put_with_callback() {
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let record = |fired: &Arc<Mutex<Vec<String>>>| {
+            let fired = Arc::clone(fired);
+            move |key: &String, value: &i32| fired.lock().unwrap().push(format!("{}={}", key, value))
+        };
+        let cache: SmartCache<String, i32> = SmartCache::new(2);
+        
+        cache.put_with_callback("expiring".to_string(), 1, Some(Duration::from_millis(20)), 5, record(&fired));
+        cache.put_with_callback("deleted".to_string(), 2, None, 5, record(&fired));
+        assert!(cache.delete("deleted"));
+        
+        clock::advance(Duration::from_millis(40));
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.purge_expired(), 0);
+        
+        cache.put_with_callback("victim".to_string(), 3, None, 1, record(&fired));
+        cache.put("a".to_string(), 4, None, 9);
+        cache.put("b".to_string(), 5, None, 9);
+        
+        assert_eq!(*fired.lock().unwrap(), vec!["expiring=1".to_string(), "victim=3".to_string()]);
+    }
+    
+    #[test]
+    fn test_put_refreshing() {
+        let pulls = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&pulls);
+        let cache: SmartCache<String, u64> = SmartCache::new(10);
+        
+        let refresher = move |_: &String| Some(counter.fetch_add(1, Ordering::SeqCst) + 1);
+        assert!(cache.put_refreshing("config".to_string(), refresher, Duration::from_millis(20), 5));
+        assert_eq!(cache.get("config"), Some(1));
+        
+        // Refreshed in the background without any reads
+        thread::sleep(Duration::from_millis(110));
+        assert!(cache.get("config").unwrap() >= 3);
+        
+        // Overwriting the entry stops its refresh task
+        cache.put("config".to_string(), 0, None, 5);
+        thread::sleep(Duration::from_millis(30));
+        let stopped_at = pulls.load(Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(pulls.load(Ordering::SeqCst), stopped_at);
+        assert_eq!(cache.get("config"), Some(0));
+        
+        assert!(!cache.put_refreshing("missing".to_string(), |_: &String| None, Duration::from_millis(20), 5));
+        assert!(!cache.contains_key("missing"));
+    }
+    
+    fn throttled_cache(limit: AdmissionLimit, overflow: OverflowPolicy, per_namespace: bool) -> SmartCache<String, i32> {
+        let admission = AdmissionConfig {
+            global: (!per_namespace).then_some(limit),
+            per_namespace: per_namespace.then_some(limit),
+            overflow,
+        };
+        let config = CacheConfig {
+            admission: Some(admission),
+            ..Default::default()
+        };
+        SmartCache::with_namespaces(config, |key: &String| key.split(':').next().map(str::to_string))
+    }
+    
+    #[test]
+    fn test_admission_throttling() {
+        let slow = AdmissionLimit { rate_per_sec: 0.001, burst: 2 };
+        
+        let cache = throttled_cache(slow, OverflowPolicy::Reject, false);
+        assert!(cache.put("a:1".to_string(), 1, None, 5));
+        assert!(cache.put("b:1".to_string(), 2, None, 5));
+        assert!(!cache.put("b:2".to_string(), 3, None, 5));
+        assert_eq!(cache.get_stats().throttled, 1);
+        
+        // Each namespace has its own bucket
+        let cache = throttled_cache(slow, OverflowPolicy::Reject, true);
+        assert!(cache.put("a:1".to_string(), 1, None, 5));
+        assert!(cache.put("a:2".to_string(), 2, None, 5));
+        assert!(!cache.put("a:3".to_string(), 3, None, 5));
+        assert!(cache.put("b:1".to_string(), 4, None, 5));
+        
+        let cache = throttled_cache(slow, OverflowPolicy::DropLowestPriority, false);
+        cache.put("a:low".to_string(), 1, None, 2);
+        cache.put("a:high".to_string(), 2, None, 9);
+        assert!(!cache.put("a:lower".to_string(), 3, None, 1));
+        assert!(cache.put("a:mid".to_string(), 4, None, 5));
+        assert!(!cache.contains_key("a:low"));
+        assert!(cache.contains_key("a:high"));
+        
+        let fast = AdmissionLimit { rate_per_sec: 50.0, burst: 1 };
+        let cache = throttled_cache(fast, OverflowPolicy::Block, false);
+        let start = Instant::now();
+        for i in 0..3 {
+            assert!(cache.put(format!("a:{}", i), i, None, 5));
+        }
+        assert!(start.elapsed() >= Duration::from_millis(35));
+    }
+    
+    #[test]
+    fn test_namespace_eviction_isolation() {
+        let config = CacheConfig {
+            max_capacity: 10,
+            namespace_quotas: HashMap::from([("a".to_string(), 4), ("b".to_string(), 4)]),
+            ..Default::default()
+        };
+        let cache: SmartCache<String, i32> =
+            SmartCache::with_namespaces(config, |key: &String| key.split(':').next().map(str::to_string));
+        let count = |prefix: &str| cache.export_where(|key, _, _| key.starts_with(prefix)).len();
+        
+        // "a" borrows both elastic slots
+        for i in 0..6 {
+            cache.put(format!("a:{}", i), i, None, 5);
+        }
+        for i in 0..4 {
+            cache.put(format!("b:{}", i), i, None, 5);
+        }
+        
+        // A burst from "a" only evicts "a"
+        for i in 6..20 {
+            cache.put(format!("a:{}", i), i, None, 5);
+        }
+        assert_eq!((count("a:"), count("b:")), (6, 4));
+        
+        // "c" has no reservation, so it takes space back from the borrower
+        cache.put("c:0".to_string(), 0, None, 5);
+        assert_eq!((count("a:"), count("b:"), count("c:")), (5, 4, 1));
+        
+        // "b" at its reservation evicts its own entries
+        cache.put("b:4".to_string(), 4, None, 5);
+        assert_eq!((count("a:"), count("b:"), count("c:")), (5, 4, 1));
+        assert!(!cache.contains_key("b:0"));
+    }
+    
+    #[test]
+    fn test_encrypted_snapshot() {
+        let dir = temp_dir("snapshot_encrypted");
+        let path = dir.join("secret.enc");
+        let keys = StaticKeyProvider::new(1, [7; 32]);
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("ssn".to_string(), "123-45-6789".to_string(), None, 5);
+        
+        assert_eq!(cache.save_snapshot_encrypted(&path, &keys).unwrap(), 1);
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(11).any(|window| window == b"123-45-6789"));
+        
+        let restored: SmartCache<String, String> = SmartCache::new(10);
+        let report = restored.load_snapshot_encrypted(&path, &keys, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(report.loaded, 1);
+        assert_eq!(restored.get("ssn"), Some("123-45-6789".to_string()));
+        
+        // Wrong key, flipped byte and truncation all fail closed
+        let other = StaticKeyProvider::new(1, [8; 32]);
+        assert!(matches!(read_encrypted_snapshot::<String, String>(&path, &other), Err(SnapshotError::Encryption(_))));
+        let mut tampered = raw.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        std::fs::write(&path, &tampered).unwrap();
+        assert!(matches!(read_encrypted_snapshot::<String, String>(&path, &keys), Err(SnapshotError::Encryption(_))));
+        std::fs::write(&path, &raw[..raw.len() - 5]).unwrap();
+        assert!(matches!(read_encrypted_snapshot::<String, String>(&path, &keys), Err(SnapshotError::Encryption(_))));
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_value_checksums() {
+        let cache: SmartCache<String, String> = SmartCache::new(10).with_value_checksums(IntegrityCheck::Always);
+        cache.put("a".to_string(), "intact".to_string(), None, 5);
+        cache.put("b".to_string(), "flipped".to_string(), None, 5);
+        assert_eq!(cache.try_get("a"), Ok(Some("intact".to_string())));
+        
+        // Simulate a bit flip in the stored value
+        cache.data.write().unwrap().get_mut("b").unwrap().value = "flipper".to_string();
+        assert!(matches!(cache.try_get("b"), Err(CacheError::Corrupted { .. })));
+        assert!(!cache.contains_key("b"));
+        assert_eq!(cache.get_stats().corruptions, 1);
+        
+        // Sampled mode checks one read in n
+        let sampled: SmartCache<String, String> = SmartCache::new(10).with_value_checksums(IntegrityCheck::Sampled(2));
+        sampled.put("c".to_string(), "value".to_string(), None, 5);
+        sampled.data.write().unwrap().get_mut("c").unwrap().value = "valve".to_string();
+        assert!(sampled.try_get("c").is_err());
+        assert_eq!(sampled.get("c"), None);
+    }
+    
+    #[test]
+    fn test_stale_while_revalidate() {
+        let loads = Arc::new(AtomicU64::new(0));
+        let loads_clone = Arc::clone(&loads);
+        let config = CacheConfig {
+            stale_grace: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let cache: SmartCache<String, String> = SmartCache::with_config(config).with_loader(move |key: &String| {
+            thread::sleep(Duration::from_millis(50));
+            let n = loads_clone.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("{}-v{}", key, n))
+        });
+        cache.put("k".to_string(), "k-v0".to_string(), Some(Duration::from_millis(50)), 5);
+        
+        // Expired but within grace: the old value is served while one load runs
+        clock::advance(Duration::from_millis(80));
+        assert_eq!(cache.get("k"), Some("k-v0".to_string()));
+        assert_eq!(cache.get("k"), Some("k-v0".to_string()));
+        assert!(!cache.contains_key("k"));
+        
+        thread::sleep(Duration::from_millis(150));
+        assert_eq!(cache.get("k"), Some("k-v1".to_string()));
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get_stats().stale_hits, 2);
+    }
+    
+    #[test]
+    fn test_serve_stale_on_error() {
+        let backend_up = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let backend = Arc::clone(&backend_up);
+        let config = CacheConfig {
+            error_grace: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let cache: SmartCache<String, String> = SmartCache::with_config(config).with_loader(move |key: &String| {
+            if backend.load(Ordering::SeqCst) {
+                Ok(format!("{}-fresh", key))
+            } else {
+                Err("backend unavailable".into())
+            }
+        });
+        
+        assert_eq!(cache.get_or_load(&"k".to_string()).unwrap(), "k-fresh");
+        cache.put("k".to_string(), "k-old".to_string(), Some(Duration::from_millis(20)), 5);
+        clock::advance(Duration::from_millis(40));
+        
+        // The backend is down: the expired value is served instead of an error
+        backend_up.store(false, Ordering::SeqCst);
+        assert_eq!(cache.get_or_load(&"k".to_string()).unwrap(), "k-old");
+        assert_eq!(cache.get("k"), Some("k-old".to_string()));
+        assert_eq!(cache.get_stats().degraded_hits, 2);
+        
+        // Nothing to fall back on for a key never cached
+        assert!(cache.get_or_load(&"other".to_string()).is_err());
+    }
+    
+    #[test]
+    fn test_clear_and_size() {
+        let cache: SmartCache<i32, String> = SmartCache::new(10);
+        cache.put(1, "a".to_string(), None, 5);
+        cache.put(2, "b".to_string(), Some(Duration::from_millis(10)), 5);
+        clock::advance(Duration::from_millis(20));
+        assert_eq!(cache.size(), 2);
+        assert_eq!(cache.len(), 1);
+        
+        cache.clear();
+        assert_eq!(cache.size(), 0);
+        assert!(cache.is_empty());
+        // The cache stays usable after a reset
+        cache.put(3, "c".to_string(), None, 5);
+        assert_eq!(cache.get(&3), Some("c".to_string()));
+    }
+    
+    #[test]
+    fn test_invalidate_all() {
+        let config = CacheConfig {
+            max_capacity: 4,
+            stale_grace: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let cache: SmartCache<i32, String> = SmartCache::with_config(config).with_loader(|key: &i32| Ok(key.to_string()));
+        let expired = Arc::new(AtomicU64::new(0));
+        for i in 0..4 {
+            let expired = Arc::clone(&expired);
+            cache.put_with_callback(i, format!("old-{}", i), None, 5, move |_, _| {
+                expired.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        let fork = cache.fork();
+        cache.invalidate_all();
+        
+        // Gone at once, not even served stale, but still stored until reclaimed
+        assert_eq!(cache.get(&0), None);
+        assert!(!cache.contains_key(&1));
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.size(), 3);
+        assert_eq!(fork.len(), 4);
+        
+        // Invalidated entries make room before any live one, without a trace
+        for i in 10..13 {
+            cache.put(i, format!("new-{}", i), None, 1);
+        }
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.size(), 3);
+        assert!((10..13).all(|i| cache.contains_key(&i)));
+        let stats = cache.get_stats();
+        assert_eq!((stats.evictions, stats.ttl_expirations), (0, 0));
+        assert_eq!(expired.load(Ordering::SeqCst), 0);
+    }
+    
+    struct EventLog(Arc<Mutex<Vec<CacheEvent<i32, String>>>>);
+    
+    impl CacheCallback<i32, String> for EventLog {
+        fn on_event(&self, event: CacheEvent<i32, String>) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+    
+    #[test]
+    fn test_update_delete_and_clear_events() {
+        let cache: SmartCache<i32, String> = SmartCache::new(10);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        cache.add_callback(Box::new(EventLog(Arc::clone(&events))));
+        
+        cache.put(1, "a".to_string(), None, 5);
+        cache.put(1, "b".to_string(), None, 5);
+        cache.put(2, "c".to_string(), Some(Duration::from_millis(10)), 5);
+        clock::advance(Duration::from_millis(20));
+        // Overwriting an expired entry counts as a fresh insert
+        cache.put(2, "d".to_string(), None, 5);
+        cache.delete(&2);
+        cache.put(3, "e".to_string(), Some(Duration::from_millis(10)), 5);
+        clock::advance(Duration::from_millis(20));
+        cache.clear();
+        
+        let events = events.lock().unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [
+                CacheEvent::Insert(1),
+                CacheEvent::Update { key: 1, old_value },
+                CacheEvent::Insert(2),
+                CacheEvent::Insert(2),
+                CacheEvent::Delete(2),
+                CacheEvent::Insert(3),
+                CacheEvent::Clear { entries: 1 },
+            ] if old_value.as_str() == "a"
+        ));
+    }
+    
+    // Holds every event up until the gate opens
+    struct GatedLog(Arc<Mutex<Vec<CacheEvent<i32, String>>>>, Arc<(Mutex<bool>, std::sync::Condvar)>);
+    
+    impl CacheCallback<i32, String> for GatedLog {
+        fn on_event(&self, event: CacheEvent<i32, String>) {
+            let (open, opened) = &*self.1;
+            let _open = opened.wait_while(open.lock().unwrap(), |open| !*open).unwrap();
+            self.0.lock().unwrap().push(event);
+        }
+    }
+    
+    #[test]
+    fn test_bounded_event_queue() {
+        let config = CacheConfig {
+            async_events: Some(AsyncEvents {
+                capacity: 2,
+                overflow: EventOverflow::DropOldest,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let cache: SmartCache<i32, String> = SmartCache::with_config(config);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let gate = Arc::new((Mutex::new(false), std::sync::Condvar::new()));
+        cache.add_callback(Box::new(GatedLog(Arc::clone(&events), Arc::clone(&gate))));
+        
+        // The first insert is taken by the dispatcher, which then stalls on the gate
+        cache.put(0, "a".to_string(), None, 5);
+        thread::sleep(Duration::from_millis(50));
+        for i in 1..=5 {
+            cache.put(i, "a".to_string(), None, 5);
+        }
+        assert_eq!(cache.get_stats().events_dropped, 3);
+        
+        *gate.0.lock().unwrap() = true;
+        gate.1.notify_all();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while events.lock().unwrap().len() < 3 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        // The oldest queued events made way for the newest
+        assert!(matches!(
+            events.lock().unwrap().as_slice(),
+            [CacheEvent::Insert(0), CacheEvent::Insert(4), CacheEvent::Insert(5)]
+        ));
+    }
+    
+    type Delivered = Arc<Mutex<Vec<(String, CacheEvent<i32, String>)>>>;
+    
+    // Records which dispatcher delivered each event; panics on the insert of key 3
+    struct ThreadLog(Delivered);
+    
+    impl CacheCallback<i32, String> for ThreadLog {
+        fn on_event(&self, event: CacheEvent<i32, String>) {
+            if matches!(event, CacheEvent::Insert(3)) {
+                panic!("listener failed");
+            }
+            let name = thread::current().name().unwrap_or_default().to_string();
+            self.0.lock().unwrap().push((name, event));
+        }
+    }
+    
+    #[test]
+    fn test_event_dispatcher_pool() {
+        let config = CacheConfig {
+            async_events: Some(AsyncEvents {
+                threads: 4,
+                thread_name: "test-events",
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let cache: SmartCache<i32, String> = SmartCache::with_config(config);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        cache.add_callback(Box::new(ThreadLog(Arc::clone(&events))));
+        
+        for i in 0..20 {
+            cache.put(i, "a".to_string(), None, 5);
+            cache.delete(&i);
+        }
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while events.lock().unwrap().len() < 39 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 39);
+        assert!(events.iter().all(|(name, _)| name.starts_with("test-events-")));
+        // Each key's events come from one dispatcher, in order
+        for i in (0..20).filter(|&i| i != 3) {
+            let of_key: Vec<_> = events.iter().filter(|(_, event)| matches!(event, CacheEvent::Insert(k) | CacheEvent::Delete(k) if *k == i)).collect();
+            assert!(matches!(of_key.as_slice(), [(a, CacheEvent::Insert(_)), (b, CacheEvent::Delete(_))] if a == b));
+        }
+        // The dispatcher that hit the panic carried on
+        assert!(events.iter().any(|(_, event)| matches!(event, CacheEvent::Delete(3))));
+        assert_eq!(cache.get_stats().dispatcher_restarts, 1);
+    }
+    
+    struct SlowListener;
+    
+    impl CacheCallback<i32, String> for SlowListener {
+        fn on_event(&self, _event: CacheEvent<i32, String>) {
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+    
+    #[test]
+    fn test_shutdown() {
+        let dir = temp_dir("shutdown");
+        let config = CacheConfig {
+            cleanup_interval: Duration::from_secs(60),
+            async_events: Some(AsyncEvents::default()),
+            ..Default::default()
+        };
+        let cache: SmartCache<i32, String> = SmartCache::with_config(config);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        cache.add_callback(Box::new(EventLog(Arc::clone(&events))));
+        cache.put_refreshing(0, |_| Some("fresh".to_string()), Duration::from_secs(60), 5);
+        for i in 1..=10 {
+            cache.put(i, "a".to_string(), None, 5);
+        }
+        
+        // Sleeping threads are woken rather than waited out, and the queue is drained first
+        let started = Instant::now();
+        let report = cache.shutdown_with_snapshot(Duration::from_secs(5), dir.join("final.json"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!(report.snapshot.unwrap().unwrap(), 11);
+        assert_eq!(events.lock().unwrap().len(), 11);
+        
+        // Still usable, but nothing is dispatched any more
+        cache.put(11, "b".to_string(), None, 5);
+        assert_eq!(cache.get(&11), Some("b".to_string()));
+        assert_eq!(events.lock().unwrap().len(), 11);
+        assert!(cache.shutdown(Duration::ZERO).is_clean());
+        std::fs::remove_dir_all(dir).unwrap();
+        
+        // A listener too slow for the timeout leaves its dispatcher behind
+        let config = CacheConfig {
+            async_events: Some(AsyncEvents::default()),
+            ..Default::default()
+        };
+        let cache: SmartCache<i32, String> = SmartCache::with_config(config);
+        cache.add_callback(Box::new(SlowListener));
+        for i in 0..5 {
+            cache.put(i, "a".to_string(), None, 5);
+        }
+        let report = cache.shutdown(Duration::from_millis(20));
+        assert!(!report.is_clean());
+        assert_eq!(report.threads_running, vec!["cache-events-0".to_string()]);
+        assert!(report.events_undelivered > 0);
+        assert!(report.snapshot.is_none());
+    }
+    
+    #[test]
+    fn test_eviction_event_metadata() {
+        let config = CacheConfig {
+            max_capacity: 1,
+            include_values_in_events: true,
+            ..Default::default()
+        };
+        let cache: SmartCache<i32, String> = SmartCache::with_config(config);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        cache.add_callback(Box::new(EventLog(Arc::clone(&events))));
+        
+        cache.put(1, "first".to_string(), None, 3);
+        cache.get(&1);
+        cache.put(2, "second".to_string(), Some(Duration::from_millis(10)), 5);
+        clock::advance(Duration::from_millis(20));
+        assert_eq!(cache.get(&2), None);
+        
+        let events = events.lock().unwrap();
+        let removed: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                CacheEvent::Eviction { key, entry } => Some(("eviction", *key, entry)),
+                CacheEvent::TTLExpiry { key, entry } => Some(("expiry", *key, entry)),
+                _ => None,
+            })
+            .collect();
+        assert!(matches!(
+            removed.as_slice(),
+            [("eviction", 1, evicted), ("expiry", 2, expired)]
+                if evicted.priority == 3
+                    && evicted.access_count == 1
+                    && evicted.value.as_deref().map(String::as_str) == Some("first")
+                    && expired.priority == 5
+                    && expired.age >= Duration::from_millis(10)
+        ));
+    }
+    
+    #[test]
+    fn test_priority_decay() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            priority_decay: Some(PriorityDecay { half_life: Duration::from_millis(10), floor: 1 }),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("stale", 1, None, 10);
+        clock::advance(Duration::from_millis(50));
+        cache.put("low", 2, None, 2);
+        clock::advance(Duration::from_millis(50));
+        
+        // Fixed priorities would score "low" higher (0.05s / 2 against 0.1s / 10), but
+        // ten half-lives have brought "stale" down to about 1
+        cache.put("new", 3, None, 5);
+        assert!(!cache.contains_key("stale"));
+        assert!(cache.contains_key("low"));
+        assert!(cache.contains_key("new"));
+    }
+    
+    #[test]
+    fn test_sampled_eviction() {
+        let fill = |sample: Option<usize>| {
+            let config = CacheConfig {
+                max_capacity: 3,
+                eviction_sample_size: sample,
+                ..Default::default()
+            };
+            let cache = SmartCache::with_config(config);
+            cache.put("oldest", 1, None, 10);
+            cache.put("old", 2, None, 10);
+            clock::advance(Duration::from_millis(50));
+            cache.put("low", 3, None, 1);
+            clock::advance(Duration::from_millis(50));
+            cache.put("new", 4, None, 5);
+            cache
+        };
+        
+        // A full scan finds "low" (0.05s / 1 against 0.1s / 10)
+        let full = fill(None);
+        assert!(!full.contains_key("low"));
+        assert!(full.contains_key("oldest"));
+        
+        // The two least recently used entries are both priority 10
+        let sampled = fill(Some(2));
+        assert!(sampled.contains_key("low"));
+        assert!(!sampled.contains_key("oldest"));
+        assert!(sampled.contains_key("old"));
+    }
+    
+    #[test]
+    fn test_priority_boost() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            priority_boost: Some(PriorityBoost { min_rate: 5.0, max_boost: 4 }),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("cold", 1, None, 6);
+        cache.put("hot", 2, None, 3);
+        for _ in 0..40 {
+            cache.get(&"hot");
+        }
+        let (_, meta) = cache.get_with_metadata(&"hot").unwrap();
+        assert_eq!(meta.priority, 3);
+        assert_eq!(meta.effective_priority, 7.0, "41 reads in the first second: boosted by the max of 4");
+        cache.get(&"cold");
+        clock::advance(Duration::from_millis(100));
+        
+        // Both idle about as long; declared priorities would evict "hot" first
+        cache.put("new", 3, None, 5);
+        assert!(cache.contains_key("hot"));
+        assert!(!cache.contains_key("cold"));
+    }
+    
+    #[test]
+    fn test_next_eviction_candidates() {
+        let cache = SmartCache::new(10);
+        cache.put("a", 1, None, 5);
+        cache.put("b", 2, None, 2);
+        clock::advance(Duration::from_millis(20));
+        cache.put("c", 3, None, 5);
+        
+        // Equally idle, "b" scores higher on its lower priority; "c" has barely aged
+        let candidates = cache.next_eviction_candidates(2);
+        assert_eq!(candidates.iter().map(|(key, _)| *key).collect::<Vec<_>>(), ["b", "a"]);
+        assert!(candidates[0].1 > candidates[1].1);
+        assert_eq!(cache.next_eviction_candidates(10).len(), 3);
+        assert_eq!(cache.size(), 3);
+    }
+    
+    #[test]
+    fn test_pause_maintenance() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            cleanup_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("a", 1, Some(Duration::from_millis(20)), 5);
+        cache.put("b", 2, None, 5);
+        cache.pause_maintenance();
+        assert!(cache.is_maintenance_paused());
+        
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.size(), 2, "the cleaner leaves the expired entry alone");
+        assert!(!cache.put("c", 3, None, 5), "no room is made for a new key");
+        assert!(cache.put("b", 20, None, 5));
+        
+        assert_eq!(cache.resume_maintenance(), 0);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.size() > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.size(), 1);
+        assert!(cache.put("c", 3, None, 5));
+    }
+    
+    #[test]
+    fn test_health() {
+        let config = CacheConfig {
+            max_capacity: 10,
+            cleanup_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("a", 1, None, 5);
+        let started = SmartCache::<&str, i32>::new(10).health();
+        assert!(started.is_healthy() && started.last_cleanup.is_none());
+        
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.health().last_cleanup.is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        let health = cache.health();
+        assert!(health.is_healthy());
+        assert!(health.last_cleanup.is_some());
+        assert_eq!((health.entries, health.capacity, health.expired_unreclaimed), (1, 10, 0));
+        
+        // A paused cleaner is not overdue, but expired entries pile up
+        cache.pause_maintenance();
+        cache.put("b", 2, Some(Duration::from_millis(5)), 5);
+        clock::advance(Duration::from_millis(50));
+        let health = cache.health();
+        assert!(health.is_healthy());
+        assert_eq!((health.entries, health.expired_unreclaimed), (2, 1));
+    }
+    
+    #[test]
+    fn test_ttl_distribution() {
+        let cache = SmartCache::new(10);
+        cache.put(1, 1, Some(Duration::from_millis(1)), 5);
+        for (key, secs) in [(2, 30), (3, 300), (4, 1800), (5, 7200), (6, 7200)] {
+            cache.put(key, key, Some(Duration::from_secs(secs)), 5);
+        }
+        clock::advance(Duration::from_millis(10));
+        assert_eq!(
+            cache.ttl_distribution(),
+            TtlDistribution {
+                expired: 1,
+                under_1m: 1,
+                under_10m: 1,
+                under_1h: 1,
+                over_1h: 2,
+            }
+        );
+    }
+    
+    #[test]
+    fn test_memory_accounting() {
+        let cache = SmartCache::with_namespaces(CacheConfig::default(), |key: &String| {
+            key.split_once(':').map(|(namespace, _)| namespace.to_string())
+        });
+        let empty = cache.estimated_memory_bytes();
+        cache.put("a:1".to_string(), "x".repeat(1000), None, 5);
+        cache.put("b:1".to_string(), "y".repeat(10), None, 5);
+        
+        let stats = cache.memory_stats();
+        assert!(stats.by_namespace[&Some("a".to_string())] > 1000);
+        assert!(stats.by_namespace[&Some("b".to_string())] < 1000);
+        assert_eq!(stats.by_namespace.values().sum::<usize>(), stats.entry_bytes);
+        assert!(stats.total_bytes >= stats.entry_bytes);
+        assert!(cache.estimated_memory_bytes() > empty);
+        
+        assert_eq!(cache.get_stats().estimated_memory_bytes, None);
+        let with_memory = cache.get_stats_with_memory();
+        assert_eq!(with_memory.estimated_memory_bytes, Some(stats.total_bytes));
+        assert_eq!(with_memory.memory_by_namespace, stats.by_namespace);
+    }
+    
+    #[test]
+    fn test_bulk_load() {
+        let cache = SmartCache::new(3);
+        let report = cache.bulk_load((0..5).map(|i| (i, i * 10, None, if i == 4 { 1 } else { 5 })));
+        assert_eq!(report, WarmupReport { loaded: 5, evicted: 2 });
+        assert_eq!(cache.get_stats().insertions, 5);
+        
+        // The one capacity pass takes the low-priority entry, then the least recent
+        assert!(!cache.contains_key(&4));
+        assert!(!cache.contains_key(&0));
+        assert_eq!(cache.get(&3), Some(30));
+        assert_eq!(cache.size(), 3);
+    }
+    
+    #[test]
+    fn test_striped_stats_are_exact() {
+        let cache = SmartCache::new(100);
+        cache.put(0, 0, None, 5);
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for i in 0..1_000 {
+                        cache.get(&(i % 2));
+                    }
+                });
+            }
+        });
+        
+        let stats = cache.get_stats();
+        assert_eq!((stats.hits, stats.misses, stats.insertions), (4_000, 4_000, 1));
+    }
+    
+    #[test]
+    fn test_incremental_expiry_scan() {
+        let cache = SmartCache::new(10);
+        for key in 0..4 {
+            cache.put(key, key, Some(Duration::from_millis(5)), 5);
+        }
+        cache.put(4, 4, Some(Duration::from_millis(30)), 5);
+        cache.put(5, 5, None, 5);
+        clock::advance(Duration::from_millis(10));
+        
+        let mut scan = ExpiryScan::new(3, Duration::from_secs(1));
+        let mut step = || {
+            let data = cache.data.read().unwrap();
+            let lru_list = cache.lru_list.lock().unwrap();
+            let mut keys = scan.step(&data, &lru_list, clock::now(), Duration::ZERO, 0);
+            keys.sort();
+            (keys, scan.due.len())
+        };
+        // Three entries per step from the LRU tail, picking up where the last
+        // stopped; key 4 expires within the horizon, so it is kept as due
+        assert_eq!(step(), (vec![0, 1, 2], 0));
+        assert_eq!(step(), (vec![3], 1));
+        clock::advance(Duration::from_millis(30));
+        assert_eq!(step(), (vec![0, 1, 2, 4], 0));
+        
+        // The background pass gets through everything a budget at a time
+        let config = CacheConfig {
+            cleanup_interval: Duration::from_millis(5),
+            expiry_scan_budget: Some(2),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        for key in 0..10 {
+            cache.put(key, key, Some(Duration::from_millis(1)), 5);
+        }
+        cache.put(10, 10, None, 5);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.size() > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.size(), 1);
+        assert_eq!(cache.get_stats().ttl_expirations, 10);
+    }
+
+    #[test]
+    fn test_freeze() {
+        let cache = SmartCache::new(10);
+        cache.put(1, "one".to_string(), None, 7);
+        cache.put(2, "two".to_string(), Some(Duration::from_millis(20)), 5);
+        cache.put(3, "three".to_string(), Some(Duration::from_millis(1)), 5);
+        clock::advance(Duration::from_millis(5));
+        
+        let frozen = cache.freeze();
+        assert_eq!(frozen.len(), 2);
+        assert!(!frozen.contains_key(&3));
+        assert_eq!(frozen.priority(&1), Some(7));
+        assert!(frozen.ttl_remaining(&2).unwrap() <= Duration::from_millis(15));
+        
+        // Later writes and expiry don't reach the frozen copy, and reading it
+        // leaves the cache's stats alone
+        cache.put(1, "uno".to_string(), None, 5);
+        cache.put(4, "four".to_string(), None, 5);
+        clock::advance(Duration::from_millis(20));
+        assert_eq!(frozen.get(&1).map(String::as_str), Some("one"));
+        assert_eq!(frozen.get(&2).map(String::as_str), Some("two"));
+        assert_eq!(frozen.get(&4), None);
+        assert_eq!(cache.get_stats().hits, 0);
+    }
+    
+    #[test]
+    fn test_fork() {
+        let cache = SmartCache::new(4);
+        for key in 0..3 {
+            cache.put(key, key.to_string(), None, 5);
+        }
+        cache.put(9, "gone".to_string(), Some(Duration::from_millis(1)), 5);
+        clock::advance(Duration::from_millis(5));
+        cache.get(&0);
+        
+        // Nothing is copied until one side writes
+        let fork = cache.fork();
+        let shares_table = |fork: &SmartCache<i32, String>| Shared::ptr_eq(&cache.data.read().unwrap(), &fork.data.read().unwrap());
+        assert!(shares_table(&fork));
+        assert!(Shared::ptr_eq(&cache.lru_list.lock().unwrap(), &fork.lru_list.lock().unwrap()));
+        assert!(!fork.contains_key(&9));
+        assert_eq!(fork.get_stats().hits, 0);
+        
+        fork.put(1, "fork".to_string(), None, 5);
+        assert!(!shares_table(&fork));
+        cache.delete(&2);
+        assert_eq!(cache.get(&1), Some("1".to_string()));
+        assert_eq!(cache.size(), 3);
+        
+        // The fork kept the recency order: 0 was read after 2 before forking, so
+        // 2 is least recent and goes first
+        fork.put(3, "3".to_string(), None, 5);
+        fork.put(4, "4".to_string(), None, 5);
+        assert!(!fork.contains_key(&2));
+        assert!(fork.contains_key(&0));
+        assert_eq!(fork.get(&1), Some("fork".to_string()));
+    }
 }
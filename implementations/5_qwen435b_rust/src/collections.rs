@@ -0,0 +1,383 @@
+// Redis-style collection values. A cache of Collection<T> gets list, set and hash
+// operations that change the stored collection in place under its shard's lock,
+// instead of a get, a change and a put of the whole value, which copies large
+// collections and loses updates that race with it.
+//
+// As in Redis, writing to a missing or expired key creates the collection (with
+// the default TTL), a collection emptied by a pop or remove is deleted, and using
+// a key as the wrong kind of collection fails with CollectionError::WrongType.
+// Reads count as hits or misses like get.
+
+use crate::{clock, CacheEntry, SmartCache};
+use dashmap::mapref::entry::Entry;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+
+// Priority of a collection created by a write, as for seed records without one
+const CREATED_PRIORITY: u8 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de> + Eq + Hash"))]
+pub enum Collection<T> {
+    List(VecDeque<T>),
+    Set(HashSet<T>),
+    // Field to value
+    Hash(HashMap<T, T>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectionError {
+    // The key holds a different kind of collection
+    WrongType,
+    // Creating the collection needs a free slot while maintenance is paused
+    NoRoom,
+}
+
+impl fmt::Display for CollectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectionError::WrongType => write!(f, "key holds a different kind of collection"),
+            CollectionError::NoRoom => write!(f, "no room for a new collection while maintenance is paused"),
+        }
+    }
+}
+
+impl std::error::Error for CollectionError {}
+
+impl<T> Collection<T> {
+    pub fn len(&self) -> usize {
+        match self {
+            Collection::List(list) => list.len(),
+            Collection::Set(set) => set.len(),
+            Collection::Hash(hash) => hash.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn list(&self) -> Result<&VecDeque<T>, CollectionError> {
+        match self {
+            Collection::List(list) => Ok(list),
+            _ => Err(CollectionError::WrongType),
+        }
+    }
+
+    fn list_mut(&mut self) -> Result<&mut VecDeque<T>, CollectionError> {
+        match self {
+            Collection::List(list) => Ok(list),
+            _ => Err(CollectionError::WrongType),
+        }
+    }
+
+    fn set(&self) -> Result<&HashSet<T>, CollectionError> {
+        match self {
+            Collection::Set(set) => Ok(set),
+            _ => Err(CollectionError::WrongType),
+        }
+    }
+
+    fn set_mut(&mut self) -> Result<&mut HashSet<T>, CollectionError> {
+        match self {
+            Collection::Set(set) => Ok(set),
+            _ => Err(CollectionError::WrongType),
+        }
+    }
+
+    fn hash(&self) -> Result<&HashMap<T, T>, CollectionError> {
+        match self {
+            Collection::Hash(hash) => Ok(hash),
+            _ => Err(CollectionError::WrongType),
+        }
+    }
+
+    fn hash_mut(&mut self) -> Result<&mut HashMap<T, T>, CollectionError> {
+        match self {
+            Collection::Hash(hash) => Ok(hash),
+            _ => Err(CollectionError::WrongType),
+        }
+    }
+}
+
+// LRANGE indexes: inclusive, negative ones count from the end, out of range ones
+// are clamped
+fn list_range(len: usize, start: isize, stop: isize) -> std::ops::Range<usize> {
+    let len = len as isize;
+    let from_end = |index: isize| if index < 0 { len + index } else { index };
+    let start = from_end(start).max(0);
+    let end = (from_end(stop) + 1).min(len);
+    if start >= end {
+        return 0..0;
+    }
+    start as usize..end as usize
+}
+
+impl<K, T> SmartCache<K, Collection<T>>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    T: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    // Apply `op` to the live collection at `key`, or to `empty()` stored in its
+    // place; a collection `op` leaves empty is not stored
+    fn modify_or_create<R>(
+        &self,
+        key: K,
+        empty: fn() -> Collection<T>,
+        op: impl FnOnce(&mut Collection<T>) -> Result<R, CollectionError>,
+    ) -> Result<R, CollectionError> {
+        // As in put, len() is read before the key's shard is held
+        let paused = self.is_maintenance_paused();
+        let full = paused && self.data.len() >= self.config.max_capacity;
+
+        let now = clock::now();
+        let adaptive_ttl = self.config.adaptive_ttl.as_ref();
+        let generation = self.generation();
+        let result = match self.data.entry(key.clone()) {
+            Entry::Occupied(mut occupied) if now <= occupied.get().expires_at(adaptive_ttl, generation) => {
+                let entry = occupied.get_mut();
+                *entry.last_accessed.write() = now;
+                let result = op(&mut entry.value)?;
+                if entry.value.is_empty() {
+                    occupied.remove();
+                } else {
+                    drop(occupied);
+                    self.lru_queue.push(key);
+                }
+                return Ok(result);
+            }
+            slot => {
+                let mut collection = empty();
+                let result = op(&mut collection)?;
+                if collection.is_empty() {
+                    return Ok(result);
+                }
+                if full && matches!(slot, Entry::Vacant(_)) {
+                    return Err(CollectionError::NoRoom);
+                }
+                let entry = CacheEntry {
+                    value: collection,
+                    priority: CREATED_PRIORITY,
+                    ttl: now + self.config.default_ttl,
+                    created_at: now,
+                    last_accessed: Arc::new(RwLock::new(now)),
+                    access_count: Arc::new(AtomicU64::new(0)),
+                    generation,
+                };
+                match slot {
+                    Entry::Occupied(mut occupied) => {
+                        occupied.insert(entry);
+                    }
+                    Entry::Vacant(vacant) => {
+                        vacant.insert(entry);
+                    }
+                }
+                result
+            }
+        };
+        self.finish_insert(key, paused);
+        Ok(result)
+    }
+
+    // Apply `op` to the live collection at `key`; None if there is none
+    fn modify<Q, R>(
+        &self,
+        key: &Q,
+        op: impl FnOnce(&mut Collection<T>) -> Result<R, CollectionError>,
+    ) -> Result<Option<R>, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = clock::now();
+        let Some(mut entry) = self.data.get_mut(key) else {
+            return Ok(None);
+        };
+        if now > entry.expires_at(self.config.adaptive_ttl.as_ref(), self.generation()) {
+            return Ok(None);
+        }
+        *entry.last_accessed.write() = now;
+        let result = op(&mut entry.value)?;
+
+        if entry.value.is_empty() {
+            drop(entry);
+            // Unless a racing write has refilled it since
+            self.data.remove_if(key, |_, entry| entry.value.is_empty());
+        } else {
+            let key = entry.key().clone();
+            drop(entry);
+            self.lru_queue.push(key);
+        }
+        Ok(Some(result))
+    }
+
+    fn read<Q, R>(
+        &self,
+        key: &Q,
+        op: impl FnOnce(&Collection<T>) -> Result<R, CollectionError>,
+    ) -> Result<Option<R>, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.access(key, false, |entry, _, _| op(&entry.value)).transpose()
+    }
+
+    // Push each value onto the head of the list, so the last one ends up first;
+    // returns the list's length
+    pub fn lpush(&self, key: K, values: impl IntoIterator<Item = T>) -> Result<usize, CollectionError> {
+        self.modify_or_create(key, || Collection::List(VecDeque::new()), |collection| {
+            let list = collection.list_mut()?;
+            for value in values {
+                list.push_front(value);
+            }
+            Ok(list.len())
+        })
+    }
+
+    pub fn rpush(&self, key: K, values: impl IntoIterator<Item = T>) -> Result<usize, CollectionError> {
+        self.modify_or_create(key, || Collection::List(VecDeque::new()), |collection| {
+            let list = collection.list_mut()?;
+            list.extend(values);
+            Ok(list.len())
+        })
+    }
+
+    pub fn lpop<Q>(&self, key: &Q) -> Result<Option<T>, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Ok(self.modify(key, |collection| Ok(collection.list_mut()?.pop_front()))?.flatten())
+    }
+
+    pub fn rpop<Q>(&self, key: &Q) -> Result<Option<T>, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Ok(self.modify(key, |collection| Ok(collection.list_mut()?.pop_back()))?.flatten())
+    }
+
+    pub fn llen<Q>(&self, key: &Q) -> Result<usize, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Ok(self.read(key, |collection| Ok(collection.list()?.len()))?.unwrap_or(0))
+    }
+
+    // Elements `start` through `stop`, inclusive; -1 is the last one
+    pub fn lrange<Q>(&self, key: &Q, start: isize, stop: isize) -> Result<Vec<T>, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let range = |collection: &Collection<T>| {
+            let list = collection.list()?;
+            Ok(list.range(list_range(list.len(), start, stop)).cloned().collect())
+        };
+        Ok(self.read(key, range)?.unwrap_or_default())
+    }
+
+    // Returns how many members were not in the set yet
+    pub fn sadd(&self, key: K, members: impl IntoIterator<Item = T>) -> Result<usize, CollectionError> {
+        self.modify_or_create(key, || Collection::Set(HashSet::new()), |collection| {
+            let set = collection.set_mut()?;
+            Ok(members.into_iter().filter(|member| set.insert(member.clone())).count())
+        })
+    }
+
+    // Returns how many members were removed
+    pub fn srem<'a, Q>(&self, key: &Q, members: impl IntoIterator<Item = &'a T>) -> Result<usize, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        T: 'a,
+    {
+        let removed = self.modify(key, |collection| {
+            let set = collection.set_mut()?;
+            Ok(members.into_iter().filter(|member| set.remove(*member)).count())
+        })?;
+        Ok(removed.unwrap_or(0))
+    }
+
+    pub fn sismember<Q>(&self, key: &Q, member: &T) -> Result<bool, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Ok(self.read(key, |collection| Ok(collection.set()?.contains(member)))?.unwrap_or(false))
+    }
+
+    pub fn scard<Q>(&self, key: &Q) -> Result<usize, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Ok(self.read(key, |collection| Ok(collection.set()?.len()))?.unwrap_or(0))
+    }
+
+    pub fn smembers<Q>(&self, key: &Q) -> Result<Vec<T>, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Ok(self.read(key, |collection| Ok(collection.set()?.iter().cloned().collect()))?.unwrap_or_default())
+    }
+
+    // Set each field to its value; returns how many fields are new
+    pub fn hset(&self, key: K, fields: impl IntoIterator<Item = (T, T)>) -> Result<usize, CollectionError> {
+        self.modify_or_create(key, || Collection::Hash(HashMap::new()), |collection| {
+            let hash = collection.hash_mut()?;
+            Ok(fields.into_iter().filter(|(field, value)| hash.insert(field.clone(), value.clone()).is_none()).count())
+        })
+    }
+
+    pub fn hget<Q>(&self, key: &Q, field: &T) -> Result<Option<T>, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Ok(self.read(key, |collection| Ok(collection.hash()?.get(field).cloned()))?.flatten())
+    }
+
+    // Returns how many fields were removed
+    pub fn hdel<'a, Q>(&self, key: &Q, fields: impl IntoIterator<Item = &'a T>) -> Result<usize, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        T: 'a,
+    {
+        let removed = self.modify(key, |collection| {
+            let hash = collection.hash_mut()?;
+            Ok(fields.into_iter().filter(|field| hash.remove(*field).is_some()).count())
+        })?;
+        Ok(removed.unwrap_or(0))
+    }
+
+    pub fn hlen<Q>(&self, key: &Q) -> Result<usize, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        Ok(self.read(key, |collection| Ok(collection.hash()?.len()))?.unwrap_or(0))
+    }
+
+    pub fn hgetall<Q>(&self, key: &Q) -> Result<Vec<(T, T)>, CollectionError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pairs = |collection: &Collection<T>| {
+            Ok(collection.hash()?.iter().map(|(field, value)| (field.clone(), value.clone())).collect())
+        };
+        Ok(self.read(key, pairs)?.unwrap_or_default())
+    }
+}
@@ -1,38 +1,125 @@
 // Qwen3-435B Rust Implementation - Score: 94/100  
 // Production-grade with DashMap for sharded locking
 
-use dashmap::DashMap;
-use parking_lot::RwLock;
+use dashmap::mapref::entry::Entry;
+use dashmap::{DashMap, SharedValue};
+use parking_lot::{Mutex, RwLock};
+use std::borrow::Borrow;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use std::thread;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
 use crossbeam::queue::SegQueue;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
 
-// Lock-free statistics using atomics
+pub mod clock;
+pub mod collections;
+pub mod numa;
+pub mod statsd;
+
+pub use cache_types::{ExportedEntry, FrozenCache, HealthReport, KeyProvider, MemSize, PriorityBoost, PriorityDecay, StaticKeyProvider, TtlDistribution};
+use cache_types::{FrozenEntry, StripedCounter};
+use cache_types::snapshot;
+pub use cache_types::snapshot::{
+    diff_snapshots, list_snapshots, read_snapshot, read_snapshot_lossy, verify_snapshot, write_snapshot, EntryChange,
+    Snapshot, SnapshotDiff, SnapshotError, SnapshotEvent, SnapshotLoadReport, SnapshotSchedule, SnapshotVerification,
+    SNAPSHOT_VERSION,
+};
+
+use numa::NumaTopology;
+use statsd::{StatsdConfig, StatsdEmitter};
+
+// Lock-free statistics using striped atomics
 pub struct AtomicStats {
-    hits: AtomicU64,
-    misses: AtomicU64,
-    evictions: AtomicU64,
-    insertions: AtomicU64,
+    hits: StripedCounter,
+    misses: StripedCounter,
+    evictions: StripedCounter,
+    insertions: StripedCounter,
 }
 
 impl AtomicStats {
     fn new() -> Self {
         Self {
-            hits: AtomicU64::new(0),
-            misses: AtomicU64::new(0),
-            evictions: AtomicU64::new(0),
-            insertions: AtomicU64::new(0),
+            hits: StripedCounter::new(),
+            misses: StripedCounter::new(),
+            evictions: StripedCounter::new(),
+            insertions: StripedCounter::new(),
         }
     }
     
     pub fn hit_rate(&self) -> f64 {
-        let hits = self.hits.load(Ordering::Relaxed);
-        let misses = self.misses.load(Ordering::Relaxed);
+        let hits = self.hits.get();
+        let misses = self.misses.get();
         let total = hits + misses;
         if total == 0 { 0.0 } else { hits as f64 / total as f64 }
     }
+    
+    fn snapshot(&self, size: usize) -> CacheStats {
+        CacheStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            hit_rate: self.hit_rate(),
+            evictions: self.evictions.get(),
+            insertions: self.insertions.get(),
+            size,
+            estimated_memory_bytes: None,
+        }
+    }
+}
+
+// Point-in-time view of AtomicStats plus the entry count
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub evictions: u64,
+    pub insertions: u64,
+    pub size: usize,
+    // Only get_stats_with_memory fills this in, since it takes a pass over
+    // every entry; None from get_stats
+    pub estimated_memory_bytes: Option<usize>,
+}
+
+impl CacheStats {
+    // The old stringly-keyed shape, for callers that emit it as JSON
+    pub fn to_map(&self) -> HashMap<String, f64> {
+        let mut map = HashMap::from([
+            ("hits".to_string(), self.hits as f64),
+            ("misses".to_string(), self.misses as f64),
+            ("hit_rate".to_string(), self.hit_rate),
+            ("evictions".to_string(), self.evictions as f64),
+            ("insertions".to_string(), self.insertions as f64),
+            ("size".to_string(), self.size as f64),
+        ]);
+        if let Some(bytes) = self.estimated_memory_bytes {
+            map.insert("estimated_memory_bytes".to_string(), bytes as f64);
+        }
+        map
+    }
+}
+
+// Values that know their own lifetime (tokens, DNS records); see put_expirable
+pub trait Expirable {
+    fn ttl(&self) -> Option<Duration>;
+}
+
+// Frequency-driven expiry (see CacheConfig::adaptive_ttl). Hot entries live past
+// their TTL, cold ones expire before it; both are derived from the hit count and
+// last access time, so hits never need a write lock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveTtl {
+    // Each hit pushes expiry out by this much...
+    pub extension_per_hit: Duration,
+    // ...up to this much in total
+    pub max_extension: Duration,
+    // An entry expires once idle for this long times (1 + its hit count)
+    pub idle_timeout: Duration,
 }
 
 #[derive(Clone)]
@@ -40,10 +127,196 @@ pub struct CacheEntry<V: Clone> {
     value: V,
     priority: u8,
     ttl: Instant,
+    created_at: Instant,
     last_accessed: Arc<RwLock<Instant>>,
     access_count: Arc<AtomicU64>,
+    // The cache's generation when stored; see invalidate_all
+    generation: u64,
+}
+
+// Which writes go through, decided under the key's shard lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteIf {
+    Always,
+    // Only when the key holds no live entry
+    Absent,
+    // Only over a live entry, keeping its expiry and priority
+    Present,
+    // When the key holds no live entry or one of lower priority than the write's
+    HigherPriority,
+}
+
+impl From<ConflictPolicy> for WriteIf {
+    fn from(policy: ConflictPolicy) -> Self {
+        match policy {
+            ConflictPolicy::KeepExisting => WriteIf::Absent,
+            ConflictPolicy::Overwrite => WriteIf::Always,
+            ConflictPolicy::KeepHigherPriority => WriteIf::HigherPriority,
+        }
+    }
+}
+
+// Stored: Ok with the live value replaced. Not stored: Err with the live value
+// that stopped an Absent or HigherPriority write, if that was the reason.
+type WriteResult<V> = Result<Option<V>, Option<V>>;
+
+// Entry bookkeeping handed out alongside a value
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryMetadata {
+    pub priority: u8,
+    // What eviction scores with: `priority` after decay and boost
+    pub effective_priority: f64,
+    pub access_count: usize,
+    pub age: Duration,
+    pub idle: Duration,
+    pub ttl_remaining: Duration,
+}
+
+// What `import` does when a key is already present
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    KeepExisting,
+    Overwrite,
+    KeepHigherPriority,
+}
+
+// One row of a warm-up seed file (CSV header: key,value,ttl_secs,priority)
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedRecord<K, V> {
+    pub key: K,
+    pub value: V,
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    #[serde(default = "default_seed_priority")]
+    pub priority: u8,
+}
+
+fn default_seed_priority() -> u8 {
+    5
+}
+
+// (key, value, ttl, priority) for bulk_load, the arguments put takes
+pub type BulkEntry<K, V> = (K, V, Option<Duration>, u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmupReport {
+    pub loaded: usize,
+    pub evicted: usize,
+}
+
+#[derive(Debug)]
+pub enum WarmupError {
+    Io(std::io::Error),
+    Parse(String),
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for WarmupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarmupError::Io(err) => write!(f, "failed to read seed file: {}", err),
+            WarmupError::Parse(msg) => write!(f, "invalid seed record: {}", msg),
+            WarmupError::UnsupportedFormat(ext) => write!(f, "unsupported seed file format: {:?}", ext),
+        }
+    }
+}
+
+impl std::error::Error for WarmupError {}
+
+impl From<std::io::Error> for WarmupError {
+    fn from(err: std::io::Error) -> Self {
+        WarmupError::Io(err)
+    }
+}
+
+// Seed files are loaded in batches so readers are not locked out for the whole file
+const WARMUP_BATCH: usize = 10_000;
+
+fn read_seed_file<K, V>(path: &Path) -> Result<Vec<SeedRecord<K, V>>, WarmupError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let reader = BufReader::new(File::open(path)?);
+            serde_json::from_reader(reader).map_err(|err| WarmupError::Parse(err.to_string()))
+        }
+        Some("csv") => {
+            let mut reader = csv::Reader::from_reader(File::open(path)?);
+            reader
+                .deserialize()
+                .collect::<Result<_, _>>()
+                .map_err(|err| WarmupError::Parse(err.to_string()))
+        }
+        other => Err(WarmupError::UnsupportedFormat(other.unwrap_or_default().to_string())),
+    }
+}
+
+impl<V: Clone> CacheEntry<V> {
+    // Seconds since the last access over the effective priority; the highest goes first
+    fn eviction_score(&self, config: &CacheConfig, now: Instant) -> f64 {
+        let idle = now.saturating_duration_since(*self.last_accessed.read());
+        idle.as_secs_f64() / self.effective_priority(config, now)
+    }
+    
+    // eviction_score, except that expired entries, invalidated ones included, go
+    // before any live one
+    fn eviction_rank(&self, config: &CacheConfig, now: Instant, generation: u64) -> f64 {
+        if now > self.expires_at(config.adaptive_ttl.as_ref(), generation) {
+            f64::INFINITY
+        } else {
+            self.eviction_score(config, now)
+        }
+    }
+    
+    // The priority eviction scores with, capped at the top of the scale
+    fn effective_priority(&self, config: &CacheConfig, now: Instant) -> f64 {
+        let decayed = match &config.priority_decay {
+            Some(decay) => decay.apply(self.priority, now.saturating_duration_since(*self.last_accessed.read())),
+            None => f64::from(self.priority),
+        };
+        let boost = config.priority_boost.map_or(0.0, |boost| {
+            boost.apply(self.access_count.load(Ordering::Relaxed) as usize, now.saturating_duration_since(self.created_at))
+        });
+        (decayed + boost).min(10.0)
+    }
+    
+    fn metadata(&self, config: &CacheConfig, now: Instant, previous_access: Instant) -> EntryMetadata {
+        EntryMetadata {
+            priority: self.priority,
+            effective_priority: self.effective_priority(config, now),
+            access_count: self.access_count.load(Ordering::Relaxed) as usize,
+            age: now.saturating_duration_since(self.created_at),
+            idle: now.saturating_duration_since(previous_access),
+            ttl_remaining: self.deadline(config.adaptive_ttl.as_ref()).saturating_duration_since(now),
+        }
+    }
+    
+    // An entry stored before the last invalidate_all expired just before it was
+    // created, so it reads as expired even at that instant
+    fn expires_at(&self, adaptive: Option<&AdaptiveTtl>, generation: u64) -> Instant {
+        if self.generation == generation {
+            self.deadline(adaptive)
+        } else {
+            self.created_at.checked_sub(Duration::from_nanos(1)).unwrap_or(self.created_at)
+        }
+    }
+    
+    // When the TTL, as adaptive_ttl extends it, runs out
+    fn deadline(&self, adaptive: Option<&AdaptiveTtl>) -> Instant {
+        let Some(adaptive) = adaptive else {
+            return self.ttl;
+        };
+        let hits = u32::try_from(self.access_count.load(Ordering::Relaxed)).unwrap_or(u32::MAX);
+        let extended = self.ttl + adaptive.extension_per_hit.saturating_mul(hits).min(adaptive.max_extension);
+        let idle_deadline = *self.last_accessed.read() + adaptive.idle_timeout.saturating_mul(hits.saturating_add(1));
+        extended.min(idle_deadline)
+    }
 }
 
+type EntryMap<K, V> = Arc<DashMap<K, CacheEntry<V>>>;
+
 pub struct SmartCache<K, V> 
 where
     K: Clone + Eq + std::hash::Hash,
@@ -51,6 +324,16 @@ where
 {
     // DashMap for sharded locking - 10x better concurrency
     data: Arc<DashMap<K, CacheEntry<V>>>,
+    // Where the cleanup thread finds `data`; swapped by reshard. Held for the
+    // length of each cleanup pass.
+    cleanup_target: Arc<Mutex<EntryMap<K, V>>>,
+    // Set by pause_maintenance; the cleanup thread skips its passes while set
+    maintenance_paused: Arc<AtomicBool>,
+    // Bumped by invalidate_all; shared with the cleanup and snapshot threads
+    generation: Arc<AtomicU64>,
+    // Held by puts of new keys while they make room and insert, so racing puts
+    // cannot take the same free slot. Overwrites never take it.
+    admission: Mutex<()>,
     
     // Lock-free LRU queue
     lru_queue: Arc<SegQueue<K>>,
@@ -59,7 +342,17 @@ where
     stats: Arc<AtomicStats>,
     
     config: CacheConfig,
+    // Set with numa_placement
+    numa: Option<NumaLayout>,
+    // Detached; the cleanup loop runs for the life of the process. Kept so
+    // health() can tell whether it is still alive.
     cleanup_handle: Option<thread::JoinHandle<()>>,
+    // When the cleanup thread last finished a pass
+    last_cleanup: Arc<Mutex<Option<Instant>>>,
+    // Set by start_snapshot_scheduler. The thread keeps only a Weak of this
+    // slot, which no one else holds, to notice the cache being dropped.
+    snapshot_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    created_at: Instant,
 }
 
 #[derive(Clone)]
@@ -68,6 +361,30 @@ pub struct CacheConfig {
     pub default_ttl: Duration,
     pub cleanup_interval: Duration,
     pub shard_amount: usize,
+    // None keeps plain fixed TTLs
+    pub adaptive_ttl: Option<AdaptiveTtl>,
+    // Decay the priorities of unaccessed entries for eviction; None keeps them fixed
+    pub priority_decay: Option<PriorityDecay>,
+    // Raise the priorities of frequently read entries for eviction; None keeps them as declared
+    pub priority_boost: Option<PriorityBoost>,
+    // Split the shards between the NUMA nodes and keep each shard's table in its
+    // node's memory (see numa.rs). Tables are sized to their share of max_capacity
+    // up front, since one that has to grow later loses its placement until the next
+    // reshard.
+    pub numa_placement: bool,
+    // Table slots the background cleanup examines per pass, resuming where the
+    // last pass stopped, plus the entries an earlier pass saw falling due. Empty
+    // slots count toward the budget. None scans every entry each pass.
+    pub expiry_scan_budget: Option<usize>,
+    // Used by start_snapshot_scheduler; None disables periodic snapshots
+    pub snapshot_schedule: Option<SnapshotSchedule>,
+}
+
+// Four shards per core keeps lock collisions rare without wasting memory on
+// small machines; DashMap needs a power of two
+pub fn default_shard_amount() -> usize {
+    let cores = thread::available_parallelism().map_or(4, |cores| cores.get());
+    (cores * 4).next_power_of_two().clamp(4, 1024)
 }
 
 impl Default for CacheConfig {
@@ -76,7 +393,13 @@ impl Default for CacheConfig {
             max_capacity: 10000,
             default_ttl: Duration::from_secs(3600),
             cleanup_interval: Duration::from_secs(60),
-            shard_amount: 64, // Number of shards in DashMap
+            shard_amount: default_shard_amount(),
+            adaptive_ttl: None,
+            priority_decay: None,
+            priority_boost: None,
+            numa_placement: false,
+            expiry_scan_budget: None,
+            snapshot_schedule: None,
         }
     }
 }
@@ -96,185 +419,1922 @@ where
     
     pub fn with_config(config: CacheConfig) -> Self {
         let data = Arc::new(DashMap::with_shard_amount(config.shard_amount));
+        let numa = config.numa_placement.then(|| NumaLayout::place(&data, config.max_capacity, NumaTopology::detect()));
         let lru_queue = Arc::new(SegQueue::new());
         let stats = Arc::new(AtomicStats::new());
+        let maintenance_paused = Arc::new(AtomicBool::new(false));
+        let generation = Arc::new(AtomicU64::new(0));
+        let last_cleanup = Arc::new(Mutex::new(None));
         
         // Cleanup thread with async-style operations
-        let data_clone = Arc::clone(&data);
+        let cleanup_target = Arc::new(Mutex::new(Arc::clone(&data)));
+        let target_clone = Arc::clone(&cleanup_target);
         let stats_clone = Arc::clone(&stats);
+        let paused_clone = Arc::clone(&maintenance_paused);
+        let generation_clone = Arc::clone(&generation);
+        let last_cleanup_clone = Arc::clone(&last_cleanup);
         let cleanup_interval = config.cleanup_interval;
+        let adaptive_ttl = config.adaptive_ttl;
+        let mut scan = config.expiry_scan_budget.map(|budget| ExpiryScan::new(budget, cleanup_interval));
         
         let cleanup_handle = thread::spawn(move || {
             loop {
                 thread::sleep(cleanup_interval);
-                Self::cleanup_expired(&data_clone, &stats_clone);
+                let data = target_clone.lock();
+                if !paused_clone.load(Ordering::Acquire) {
+                    let generation = generation_clone.load(Ordering::Acquire);
+                    Self::cleanup_expired(&data, &stats_clone, adaptive_ttl.as_ref(), generation, scan.as_mut());
+                    *last_cleanup_clone.lock() = Some(clock::now());
+                }
             }
         });
         
         Self {
             data,
+            cleanup_target,
+            maintenance_paused,
+            generation,
+            admission: Mutex::new(()),
             lru_queue,
             stats,
             config,
+            numa,
             cleanup_handle: Some(cleanup_handle),
+            last_cleanup,
+            snapshot_handle: Arc::new(Mutex::new(None)),
+            created_at: clock::now(),
         }
     }
     
     pub fn put(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool {
-        let ttl = ttl.unwrap_or(self.config.default_ttl);
-        
-        // Check capacity - DashMap handles concurrency internally
-        if self.data.len() >= self.config.max_capacity && !self.data.contains_key(&key) {
-            self.evict_with_sharding();
+        self.write(key, value, ttl, priority, 0, WriteIf::Always).is_ok()
+    }
+    
+    // Store only if the key holds no live entry. Returns the live value that is
+    // kept instead, or None once stored (or refused while maintenance is paused
+    // and the cache is full).
+    pub fn put_if_absent(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> Option<V> {
+        self.write(key, value, ttl, priority, 0, WriteIf::Absent).err().flatten()
+    }
+    
+    // Swap in a new value only if the key holds a live entry, which keeps its
+    // expiry and priority. Returns the replaced value, None if nothing was stored.
+    pub fn replace(&self, key: K, value: V) -> Option<V> {
+        self.write(key, value, None, 0, 0, WriteIf::Present).ok().flatten()
+    }
+    
+    // `access_count` starts the new entry's count; only import carries one over
+    fn write(&self, key: K, value: V, ttl: Option<Duration>, priority: u8, access_count: u64, when: WriteIf) -> WriteResult<V> {
+        // A new key is admitted under the admission lock: room is made before the
+        // insert, so len() stays within max_capacity while other puts run. While
+        // maintenance is paused nothing is evicted, so it needs a free slot instead.
+        // len() locks every shard, so it is read before the key's shard is held.
+        let paused = self.is_maintenance_paused();
+        let admission = (when != WriteIf::Present && !self.data.contains_key(&key)).then(|| self.admission.lock());
+        if admission.is_some() && !paused {
+            while self.data.len() >= self.config.max_capacity && self.evict_one(None) {}
         }
+        let full = paused && self.data.len() >= self.config.max_capacity;
         
-        let entry = CacheEntry {
+        let now = clock::now();
+        let generation = self.generation();
+        let new_entry = |ttl: Duration, priority: u8| CacheEntry {
             value,
-            priority: priority.min(10).max(1),
-            ttl: Instant::now() + ttl,
-            last_accessed: Arc::new(RwLock::new(Instant::now())),
-            access_count: Arc::new(AtomicU64::new(0)),
+            priority: priority.clamp(1, 10),
+            ttl: now + ttl,
+            created_at: now,
+            last_accessed: Arc::new(RwLock::new(now)),
+            access_count: Arc::new(AtomicU64::new(access_count)),
+            generation,
+        };
+        let ttl = ttl.unwrap_or(self.config.default_ttl);
+        let replaced = match self.data.entry(key.clone()) {
+            Entry::Occupied(mut occupied) => {
+                let expires_at = occupied.get().expires_at(self.config.adaptive_ttl.as_ref(), generation);
+                let live = now <= expires_at;
+                let entry = match when {
+                    WriteIf::Absent if live => return Err(Some(occupied.get().value.clone())),
+                    WriteIf::HigherPriority if live && priority <= occupied.get().priority => {
+                        return Err(Some(occupied.get().value.clone()));
+                    }
+                    WriteIf::Present if !live => return Err(None),
+                    WriteIf::Present => new_entry(expires_at - now, occupied.get().priority),
+                    _ => new_entry(ttl, priority),
+                };
+                let old = occupied.insert(entry);
+                live.then_some(old.value)
+            }
+            Entry::Vacant(_) if full || when == WriteIf::Present => return Err(None),
+            Entry::Vacant(vacant) => {
+                vacant.insert(new_entry(ttl, priority));
+                None
+            }
         };
         
-        // DashMap insert is atomic and thread-safe
-        self.data.insert(key.clone(), entry);
+        self.finish_insert(key, paused);
+        drop(admission);
+        Ok(replaced)
+    }
+    
+    // Capacity, LRU and stats for a key just stored
+    fn finish_insert(&self, key: K, paused: bool) {
+        // Catches a key removed between write's check and its insert, which was
+        // stored without making room first
+        while !paused && self.data.len() > self.config.max_capacity && self.evict_one(Some(&key)) {}
         self.lru_queue.push(key);
         
-        self.stats.insertions.fetch_add(1, Ordering::Relaxed);
-        true
+        self.stats.insertions.add(1);
+    }
+    
+    // put() for self-expiring values: an explicit `ttl` wins, then the value's own,
+    // then the configured default
+    pub fn put_expirable(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool
+    where
+        V: Expirable,
+    {
+        let ttl = ttl.or_else(|| value.ttl());
+        self.put(key, value, ttl, priority)
+    }
+    
+    // Lookups take any borrowed form of the key, e.g. &str for String keys
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.access(key, false, |entry, _, _| entry.value.clone())
     }
     
-    pub fn get(&self, key: &K) -> Option<V> {
+    // get() for Copy values (counters, ids, scores): copied out of the entry with
+    // nothing allocated. The key is not pushed to lru_queue, whose segments are
+    // allocated as it grows; eviction ranks by last_accessed, which is still updated.
+    pub fn get_copy<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+        V: Copy,
+    {
+        self.access(key, true, |entry, _, _| entry.value)
+    }
+    
+    // Value and metadata from a single shard lookup
+    pub fn get_with_metadata<Q>(&self, key: &Q) -> Option<(V, EntryMetadata)>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.access(key, false, |entry, now, previous_access| {
+            (entry.value.clone(), entry.metadata(&self.config, now, previous_access))
+        })
+    }
+    
+    fn access<Q, R>(&self, key: &Q, skip_queue: bool, read: impl FnOnce(&CacheEntry<V>, Instant, Instant) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
         if let Some(entry) = self.data.get(key) {
             // Check TTL
-            if Instant::now() > entry.ttl {
+            let now = clock::now();
+            if now > entry.expires_at(self.config.adaptive_ttl.as_ref(), self.generation()) {
                 drop(entry); // Release the lock
                 self.data.remove(key);
-                self.stats.misses.fetch_add(1, Ordering::Relaxed);
+                self.stats.misses.add(1);
                 return None;
             }
             
             // Update access metadata with minimal locking
-            *entry.last_accessed.write() = Instant::now();
+            let previous_access = std::mem::replace(&mut *entry.last_accessed.write(), now);
             entry.access_count.fetch_add(1, Ordering::Relaxed);
             
-            let value = entry.value.clone();
-            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            let result = read(&entry, now, previous_access);
+            self.stats.hits.add(1);
             
             // Push to LRU queue (lock-free)
-            self.lru_queue.push(key.clone());
+            if !skip_queue {
+                self.lru_queue.push(entry.key().clone());
+            }
             
-            Some(value)
+            Some(result)
         } else {
-            self.stats.misses.fetch_add(1, Ordering::Relaxed);
+            self.stats.misses.add(1);
             None
         }
     }
     
-    pub fn delete(&self, key: &K) -> bool {
+    // Existence check that leaves stats, access counts and the LRU queue untouched
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.data
+            .get(key)
+            .is_some_and(|entry| clock::now() <= entry.expires_at(self.config.adaptive_ttl.as_ref(), self.generation()))
+    }
+    
+    pub fn delete<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
         self.data.remove(key).is_some()
     }
     
-    pub fn clear(&self) {
-        self.data.clear();
+    // Keep only entries the predicate accepts, one shard at a time
+    pub fn retain<F>(&self, mut keep: F) -> usize
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        let now = clock::now();
+        let mut removed = 0;
+        self.data.retain(|key, entry| {
+            let last_accessed = *entry.last_accessed.read();
+            let kept = keep(key, &entry.value, &entry.metadata(&self.config, now, last_accessed));
+            if !kept {
+                removed += 1;
+            }
+            kept
+        });
+        removed
+    }
+    
+    // Empty the cache and hand back the live values; each shard is drained
+    // under its own lock, so concurrent puts into already-drained shards survive
+    pub fn drain(&self) -> Vec<(K, V)> {
+        let now = clock::now();
+        let generation = self.generation();
+        let mut drained = Vec::with_capacity(self.data.len());
+        self.data.retain(|key, entry| {
+            if now <= entry.expires_at(self.config.adaptive_ttl.as_ref(), generation) {
+                drained.push((key.clone(), entry.value.clone()));
+            }
+            false
+        });
+        while self.lru_queue.pop().is_some() {}
+        drained
+    }
+    
+    // Copy out live entries matching the predicate, with their remaining TTLs
+    pub fn export_where<F>(&self, predicate: F) -> Vec<ExportedEntry<K, V>>
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        Self::export_entries(&self.config, &self.data, self.generation(), predicate)
+    }
+    
+    fn export_entries<F>(config: &CacheConfig, data: &DashMap<K, CacheEntry<V>>, generation: u64, mut predicate: F) -> Vec<ExportedEntry<K, V>>
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        let now = clock::now();
+        
+        data.iter()
+            .filter(|entry| now <= entry.expires_at(config.adaptive_ttl.as_ref(), generation))
+            .filter_map(|entry| {
+                let last_accessed = *entry.last_accessed.read();
+                let meta = entry.metadata(config, now, last_accessed);
+                predicate(entry.key(), &entry.value, &meta).then(|| ExportedEntry {
+                    key: entry.key().clone(),
+                    value: entry.value.clone(),
+                    priority: meta.priority,
+                    ttl_remaining: meta.ttl_remaining,
+                    access_count: meta.access_count,
+                })
+            })
+            .collect()
+    }
+    
+    // Load exported records through the normal put path (capacity and
+    // eviction rules apply), keeping their access counts. The conflict check
+    // and the write happen under the key's shard lock, so a concurrent put
+    // can't slip in between them. Returns how many records were written.
+    pub fn import(&self, entries: Vec<ExportedEntry<K, V>>, policy: ConflictPolicy) -> usize {
+        let mut imported = 0;
+        for record in entries {
+            if record.ttl_remaining.is_zero() {
+                continue;
+            }
+            let ttl = Some(record.ttl_remaining);
+            let access_count = record.access_count as u64;
+            if self.write(record.key, record.value, ttl, record.priority, access_count, policy.into()).is_ok() {
+                imported += 1;
+            }
+        }
+        imported
+    }
+    
+    // Bulk-load a CSV or JSON seed file before traffic arrives
+    pub fn warm_from_file<P: AsRef<Path>>(&self, path: P) -> Result<WarmupReport, WarmupError>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        self.warm_from_file_with_progress(path, |_, _| {})
+    }
+    
+    // Same as warm_from_file, reporting (loaded, total) after every batch.
+    // Capacity is only enforced once, after the last batch.
+    pub fn warm_from_file_with_progress<P, F>(&self, path: P, mut progress: F) -> Result<WarmupReport, WarmupError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(usize, usize),
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let records = read_seed_file(path.as_ref())?;
+        let total = records.len();
+        let entries = records
+            .into_iter()
+            .map(|record| (record.key, record.value, record.ttl_secs.map(Duration::from_secs), record.priority));
+        Ok(self.load_batches(entries, |loaded| progress(loaded, total)))
     }
     
-    pub fn get_stats(&self) -> HashMap<String, f64> {
-        let mut stats = HashMap::new();
-        stats.insert("hits".to_string(), self.stats.hits.load(Ordering::Relaxed) as f64);
-        stats.insert("misses".to_string(), self.stats.misses.load(Ordering::Relaxed) as f64);
-        stats.insert("hit_rate".to_string(), self.stats.hit_rate());
-        stats.insert("evictions".to_string(), self.stats.evictions.load(Ordering::Relaxed) as f64);
-        stats.insert("insertions".to_string(), self.stats.insertions.load(Ordering::Relaxed) as f64);
-        stats.insert("size".to_string(), self.data.len() as f64);
-        stats
+    // Insert many entries with one capacity pass and one stats update at the
+    // end instead of per-entry bookkeeping. Entries go in in batches, so lookups
+    // are not locked out for the whole load; later duplicates win.
+    pub fn bulk_load<I>(&self, entries: I) -> WarmupReport
+    where
+        I: IntoIterator<Item = BulkEntry<K, V>>,
+    {
+        self.load_batches(entries.into_iter(), |_| {})
     }
     
-    fn evict_with_sharding(&self) {
-        // Efficient eviction using sharded approach
-        let mut candidates = Vec::new();
+    fn load_batches<I>(&self, entries: I, mut progress: impl FnMut(usize)) -> WarmupReport
+    where
+        I: Iterator<Item = BulkEntry<K, V>>,
+    {
+        let mut entries = entries.peekable();
+        let mut loaded = 0;
         
-        // Sample from each shard to find eviction candidates
-        for entry in self.data.iter().take(100) {
-            let age = entry.last_accessed.read().elapsed().as_secs() as f64;
-            let score = age / entry.priority as f64;
-            candidates.push((entry.key().clone(), score));
+        while entries.peek().is_some() {
+            let batch: Vec<_> = entries.by_ref().take(WARMUP_BATCH).collect();
+            loaded += batch.len();
+            self.insert_unchecked(batch);
+            progress(loaded);
         }
         
-        // Sort by score and evict highest
-        candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        self.stats.insertions.add(loaded as u64);
+        let evicted = self.evict_to_capacity();
+        WarmupReport { loaded, evicted }
+    }
+    
+    // Insert without per-entry capacity checks; pair with evict_to_capacity
+    fn insert_unchecked(&self, batch: Vec<BulkEntry<K, V>>) {
+        let now = clock::now();
+        let generation = self.generation();
+        
+        for (key, value, ttl, priority) in batch {
+            let ttl = ttl.unwrap_or(self.config.default_ttl);
+            let entry = CacheEntry {
+                value,
+                priority: priority.clamp(1, 10),
+                ttl: now + ttl,
+                created_at: now,
+                last_accessed: Arc::new(RwLock::new(now)),
+                access_count: Arc::new(AtomicU64::new(0)),
+                generation,
+            };
+            self.data.insert(key.clone(), entry);
+            self.lru_queue.push(key);
+        }
+    }
+    
+    // One eviction pass over every shard, in eviction_order
+    fn evict_to_capacity(&self) -> usize {
+        let excess = self.data.len().saturating_sub(self.config.max_capacity);
+        if excess == 0 || self.is_maintenance_paused() {
+            return 0;
+        }
         
-        if let Some((key, _)) = candidates.first() {
-            self.data.remove(key);
-            self.stats.evictions.fetch_add(1, Ordering::Relaxed);
+        let scored = self.eviction_order();
+        let mut evicted = 0;
+        for (_, _, key) in scored.into_iter().take(excess) {
+            if self.data.remove(&key).is_some() {
+                evicted += 1;
+            }
         }
+        self.stats.evictions.add(evicted as u64);
+        evicted
+    }
+    
+    // The `n` entries a full eviction pass would take next, each with its
+    // eviction_score (highest goes first; infinite for expired entries). Nothing is evicted. A put of a new key
+    // into a full cache takes the first of these.
+    pub fn next_eviction_candidates(&self, n: usize) -> Vec<(K, f64)> {
+        let mut scored = self.eviction_order();
+        scored.truncate(n);
+        scored.into_iter().map(|(score, _, key)| (key, score)).collect()
     }
     
-    fn cleanup_expired(data: &Arc<DashMap<K, CacheEntry<V>>>, stats: &Arc<AtomicStats>) {
-        let now = Instant::now();
-        let expired: Vec<K> = data
+    // Expired entries, then highest age / priority score first, lower priority
+    // first among equal scores
+    fn eviction_order(&self) -> Vec<(f64, u8, K)> {
+        let now = clock::now();
+        let generation = self.generation();
+        let mut scored: Vec<(f64, u8, K)> = self
+            .data
             .iter()
-            .filter(|entry| now > entry.ttl)
-            .map(|entry| entry.key().clone())
+            .map(|entry| (entry.eviction_rank(&self.config, now, generation), entry.priority, entry.key().clone()))
             .collect();
-        
-        for key in expired {
-            data.remove(&key);
-            stats.evictions.fetch_add(1, Ordering::Relaxed);
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored
+    }
+    
+    pub fn clear(&self) {
+        self.data.clear();
+    }
+    
+    // clear() for large caches: bumps the generation, and every entry stored
+    // before reads as expired from then on. No shard is locked and no entry
+    // touched, so traffic doesn't wait at all. The entries are reclaimed like any
+    // expired ones: by the cleanup thread, purge_expired, a put of the same key,
+    // or eviction, which takes them first. Stats are kept.
+    pub fn invalidate_all(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+    
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+    
+    // Live entries only; expired ones awaiting cleanup are not counted. Shards are
+    // counted one after another, so new keys are held off meanwhile: a put moving
+    // a slot from one shard to another could otherwise be counted twice.
+    pub fn len(&self) -> usize {
+        let _admission = self.admission.lock();
+        let now = clock::now();
+        let adaptive = self.config.adaptive_ttl.as_ref();
+        let generation = self.generation();
+        self.data.iter().filter(|entry| now <= entry.expires_at(adaptive, generation)).count()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.config.max_capacity
+    }
+    
+    // Total weight of stored entries, expired ones included, as counted against
+    // capacity. Every entry weighs 1 until entries carry their own weights.
+    pub fn weighted_size(&self) -> usize {
+        self.data.len()
+    }
+    
+    pub fn get_stats(&self) -> CacheStats {
+        self.stats.snapshot(self.data.len())
+    }
+    
+    // A pass over every entry, so unlike get_stats this is not free; a growing
+    // `expired` count means cleanup is falling behind. TTLs are as adaptive_ttl
+    // adjusts them.
+    pub fn ttl_distribution(&self) -> TtlDistribution {
+        let now = clock::now();
+        let generation = self.generation();
+        let mut distribution = TtlDistribution::default();
+        for entry in self.data.iter() {
+            let expires_at = entry.expires_at(self.config.adaptive_ttl.as_ref(), generation);
+            distribution.record(expires_at.checked_duration_since(now));
         }
+        distribution
     }
-}
-
-use std::collections::HashMap;
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::sync::Arc;
-    use std::thread;
     
-    #[test]
-    fn test_concurrent_access() {
-        let cache = Arc::new(SmartCache::new(1000));
-        let mut handles = vec![];
+    // Copies one shard at a time under its read lock, so writes landing during
+    // the copy may show in some shards and not others. TTLs are as adaptive_ttl
+    // had extended them at the time.
+    pub fn freeze(&self) -> FrozenCache<K, V> {
+        let now = clock::now();
+        let adaptive = self.config.adaptive_ttl.as_ref();
+        let generation = self.generation();
+        let entries = self
+            .data
+            .iter()
+            .filter_map(|entry| {
+                let expires_at = entry.expires_at(adaptive, generation);
+                (now <= expires_at).then(|| {
+                    let frozen = FrozenEntry {
+                        value: entry.value.clone(),
+                        priority: entry.priority,
+                        expires_at,
+                    };
+                    (entry.key().clone(), frozen)
+                })
+            })
+            .collect();
+        FrozenCache::new(now, entries)
+    }
+    
+    // Push get_stats() to a statsd agent every `config.interval` until the
+    // returned emitter is stopped or dropped. Fails if the agent address does not
+    // resolve or no local socket can be bound.
+    pub fn start_statsd(&self, config: StatsdConfig) -> std::io::Result<StatsdEmitter> {
+        let stats = Arc::clone(&self.stats);
+        // Follows reshard to the current map
+        let target = Arc::clone(&self.cleanup_target);
+        StatsdEmitter::start(config, move || {
+            let data = Arc::clone(&target.lock());
+            stats.snapshot(data.len())
+        })
+    }
+    
+    pub fn shard_amount(&self) -> usize {
+        self.data.shards().len()
+    }
+    
+    // Index of the shard that owns `key`, in 0..shard_amount(). Stable until the
+    // next reshard.
+    pub fn shard_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.data.determine_map(key)
+    }
+    
+    // Where the shards live; None unless numa_placement is set
+    pub fn numa_layout(&self) -> Option<&NumaLayout> {
+        self.numa.as_ref()
+    }
+    
+    // The NUMA node whose memory holds `key`'s shard, as a hint for which node's
+    // threads should handle the key. None unless numa_placement is set.
+    pub fn node_of_key<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.numa.as_ref()?.node_of_shard(self.shard_of(key))
+    }
+    
+    // None past the last shard
+    pub fn shard_handle(&self, index: usize) -> Option<ShardHandle<'_, K, V>> {
+        (index < self.shard_amount()).then_some(ShardHandle { cache: self, index })
+    }
+    
+    // Move every entry into a map with `shard_amount` shards (a power of two,
+    // at least 2). Taking &mut self means no operation can race the move.
+    pub fn reshard(&mut self, shard_amount: usize) {
+        assert!(
+            shard_amount > 1 && shard_amount.is_power_of_two(),
+            "shard amount must be a power of two greater than 1"
+        );
+        let resharded = DashMap::with_shard_amount(shard_amount);
+        // Placed before the move, so the entries go straight into node-local tables
+        if let Some(numa) = self.numa.take() {
+            self.numa = Some(NumaLayout::place(&resharded, self.config.max_capacity, numa.topology));
+        }
+        for shard in self.data.shards() {
+            for (key, entry) in shard.write().drain() {
+                resharded.insert(key, entry.into_inner());
+            }
+        }
         
-        // Spawn 100 threads
-        for i in 0..100 {
-            let cache_clone = Arc::clone(&cache);
-            let handle = thread::spawn(move || {
-                for j in 0..100 {
-                    let key = format!("{}_{}", i, j);
-                    cache_clone.put(key.clone(), format!("value_{}_{}", i, j), None, 5);
-                    cache_clone.get(&key);
+        self.data = Arc::new(resharded);
+        *self.cleanup_target.lock() = Arc::clone(&self.data);
+        self.config.shard_amount = shard_amount;
+    }
+    
+    // Evict the entry eviction_order would take first, other than `keep`: the
+    // highest score across all shards, the lower priority among equal scores.
+    // Each shard is read-locked in turn for its best candidate and only the
+    // winner's shard is write-locked, so no two shard locks are held at once.
+    // Returns false once there is nothing left to evict.
+    fn evict_one(&self, keep: Option<&K>) -> bool {
+        let now = clock::now();
+        let generation = self.generation();
+        let shards = self.data.shards();
+        let mut victim: Option<(f64, u8, usize, K)> = None;
+        for (index, shard) in shards.iter().enumerate() {
+            let shard = shard.read();
+            for (key, entry) in shard.iter().filter(|(key, _)| Some(*key) != keep) {
+                let score = entry.get().eviction_rank(&self.config, now, generation);
+                let priority = entry.get().priority;
+                let better = victim
+                    .as_ref()
+                    .is_none_or(|(best, best_priority, ..)| score.total_cmp(best).then(best_priority.cmp(&priority)).is_gt());
+                if better {
+                    victim = Some((score, priority, index, key.clone()));
                 }
-            });
-            handles.push(handle);
+            }
         }
         
-        for handle in handles {
-            handle.join().unwrap();
+        let Some((_, _, index, key)) = victim else {
+            return false;
+        };
+        // Already gone if another writer removed it since the scan; the caller
+        // checks len() again either way
+        if shards[index].write().remove(&key).is_some() {
+            self.stats.evictions.add(1);
         }
-        
-        // Check stats
-        let stats = cache.get_stats();
-        assert!(stats.get("hits").unwrap() > &0.0);
-        assert!(stats.get("insertions").unwrap() > &0.0);
+        true
     }
     
-    #[test]
-    fn test_sharded_performance() {
-        let cache = SmartCache::new(100);
+    // Freeze the cache's own mutations for backups and debugging: the background
+    // cleanup pass is skipped and nothing is evicted for capacity, so puts of new
+    // keys into a full cache are rejected. Bulk loads, and puts racing the pause
+    // itself, may still overshoot capacity until resume_maintenance trims them. Returns once a
+    // cleanup pass in progress is done.
+    pub fn pause_maintenance(&self) {
+        let _target = self.cleanup_target.lock();
+        self.maintenance_paused.store(true, Ordering::Release);
+    }
+    
+    // Returns the entries evicted to get back within capacity
+    pub fn resume_maintenance(&self) -> usize {
+        self.maintenance_paused.store(false, Ordering::Release);
+        self.evict_to_capacity()
+    }
+    
+    pub fn is_maintenance_paused(&self) -> bool {
+        self.maintenance_paused.load(Ordering::Acquire)
+    }
+    
+    // Diagnostics for readiness probes. Counting expired entries visits every
+    // shard under its read lock, so probe at a modest rate.
+    pub fn health(&self) -> HealthReport {
+        let cleanup_alive = self.cleanup_handle.as_ref().is_some_and(|handle| !handle.is_finished());
+        let last_cleanup = *self.last_cleanup.lock();
+        let now = clock::now();
+        let idle = now.saturating_duration_since(last_cleanup.unwrap_or(self.created_at));
+        let cleanup_overdue = !self.is_maintenance_paused() && idle > self.config.cleanup_interval.saturating_mul(2);
         
-        // Fill cache
-        for i in 0..100 {
-            cache.put(i, format!("value_{}", i), None, (i % 10) as u8 + 1);
+        let mut lock_wait = Duration::ZERO;
+        let mut entries = 0;
+        let mut expired_unreclaimed = 0;
+        let generation = self.generation();
+        for shard in self.data.shards() {
+            let waiting = Instant::now();
+            let shard = shard.read();
+            lock_wait = lock_wait.max(waiting.elapsed());
+            entries += shard.len();
+            expired_unreclaimed += shard
+                .values()
+                .filter(|entry| now > entry.get().expires_at(self.config.adaptive_ttl.as_ref(), generation))
+                .count();
+        }
+        HealthReport {
+            cleanup_alive,
+            last_cleanup,
+            cleanup_overdue,
+            event_queue_depth: 0,
+            lock_wait,
+            entries,
+            capacity: self.config.max_capacity,
+            expired_unreclaimed,
         }
+    }
+    
+    // Run a cleanup pass now instead of waiting for the background interval
+    pub fn purge_expired(&self) -> usize {
+        Self::cleanup_expired(&self.data, &self.stats, self.config.adaptive_ttl.as_ref(), self.generation(), None)
+    }
+    
+    // With a scan only its next budget of slots and its due keys are looked at;
+    // without one, every entry is
+    fn cleanup_expired(
+        data: &Arc<DashMap<K, CacheEntry<V>>>,
+        stats: &Arc<AtomicStats>,
+        adaptive: Option<&AdaptiveTtl>,
+        generation: u64,
+        scan: Option<&mut ExpiryScan<K>>,
+    ) -> usize {
+        let now = clock::now();
+        let expired: Vec<K> = match scan {
+            Some(scan) => scan.step(data, now, adaptive, generation),
+            None => data
+                .iter()
+                .filter(|entry| now > entry.expires_at(adaptive, generation))
+                .map(|entry| entry.key().clone())
+                .collect(),
+        };
         
-        // Force eviction
-        for i in 100..200 {
-            cache.put(i, format!("value_{}", i), None, 5);
+        let mut removed = 0;
+        for key in expired {
+            // Re-check under the shard lock; the key may have been re-put since the scan
+            if data.remove_if(&key, |_, entry| now > entry.expires_at(adaptive, generation)).is_some() {
+                stats.evictions.add(1);
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + Serialize + DeserializeOwned + 'static,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    // Write all live entries to `path` (gzip-compressed when it ends in .gz).
+    // Shards are read one at a time, so this is not a point-in-time copy under
+    // concurrent writes.
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<usize, SnapshotError> {
+        let entries = self.export_where(|_, _, _| true);
+        let count = entries.len();
+        write_snapshot(path.as_ref(), &Snapshot::new(entries))?;
+        Ok(count)
+    }
+    
+    // Restore a snapshot through import(); TTLs continue from where they were saved.
+    // Records failing their checksum are skipped and listed in the report.
+    pub fn load_snapshot<P: AsRef<Path>>(&self, path: P, policy: ConflictPolicy) -> Result<SnapshotLoadReport, SnapshotError> {
+        let (snapshot, verification) = read_snapshot_lossy(path.as_ref())?;
+        Ok(SnapshotLoadReport {
+            loaded: self.import(snapshot.entries, policy),
+            verification,
+        })
+    }
+    
+    // Start the background thread described by `config.snapshot_schedule`,
+    // handing the outcome of every run to `on_event`. Returns false when no
+    // schedule is configured or the thread already runs. The thread follows
+    // reshard the way the cleanup thread does, and stops on its next tick once
+    // the cache has been dropped.
+    pub fn start_snapshot_scheduler<F>(&self, mut on_event: F) -> bool
+    where
+        F: FnMut(SnapshotEvent) + Send + 'static,
+    {
+        let Some(schedule) = self.config.snapshot_schedule.clone() else {
+            return false;
+        };
+        let mut handle = self.snapshot_handle.lock();
+        if handle.is_some() {
+            return false;
         }
         
-        assert_eq!(cache.data.len(), 100);
+        let config = self.config.clone();
+        let target = Arc::clone(&self.cleanup_target);
+        let generation = Arc::clone(&self.generation);
+        let alive = Arc::downgrade(&self.snapshot_handle);
+        let spawned = thread::Builder::new()
+            .name("cache-snapshots".to_string())
+            .spawn(move || loop {
+                thread::sleep(schedule.interval);
+                if alive.strong_count() == 0 {
+                    break;
+                }
+                let data = Arc::clone(&target.lock());
+                let entries = Self::export_entries(&config, &data, generation.load(Ordering::Acquire), |_, _, _| true);
+                on_event(snapshot::write_scheduled_snapshot(&schedule, entries));
+            })
+            .expect("failed to spawn snapshot scheduler");
+        *handle = Some(spawned);
+        true
+    }
+}
+
+// The access metadata sits behind two Arcs of its own
+impl<V: Clone + MemSize> MemSize for CacheEntry<V> {
+    fn heap_size(&self) -> usize {
+        self.value.heap_size() + std::mem::size_of::<RwLock<Instant>>() + std::mem::size_of::<AtomicU64>()
+    }
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + MemSize + 'static,
+    V: Clone + Send + Sync + MemSize + 'static,
+{
+    // Approximate RAM held by the cache: every shard's table including its empty
+    // slots, plus what each key and entry own per MemSize, plus the LRU queue.
+    // Queued keys count only their size_of, since the queue can't be walked
+    // without draining it. Shards are read one at a time, so writes to other
+    // shards may land mid-pass.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        let slot = std::mem::size_of::<(K, SharedValue<CacheEntry<V>>)>() + 1;
+        let tables: usize = self
+            .data
+            .shards()
+            .iter()
+            .map(|shard| {
+                let shard = shard.read();
+                shard.raw_table().buckets() * slot
+                    + shard.iter().map(|(key, entry)| key.heap_size() + entry.get().heap_size()).sum::<usize>()
+            })
+            .sum();
+        tables + self.lru_queue.len() * std::mem::size_of::<K>()
+    }
+    
+    // get_stats plus estimated_memory_bytes
+    pub fn get_stats_with_memory(&self) -> CacheStats {
+        CacheStats {
+            estimated_memory_bytes: Some(self.estimated_memory_bytes()),
+            ..self.get_stats()
+        }
+    }
+}
+
+// Where the incremental cleanup (CacheConfig::expiry_scan_budget) left off, as a
+// shard and a slot in its table, and the keys it saw falling due before the
+// next pass
+struct ExpiryScan<K> {
+    budget: usize,
+    horizon: Duration,
+    shard: usize,
+    slot: usize,
+    due: Vec<(Instant, K)>,
+}
+
+impl<K: Clone + Eq + std::hash::Hash> ExpiryScan<K> {
+    fn new(budget: usize, horizon: Duration) -> Self {
+        Self {
+            budget: budget.max(1),
+            horizon,
+            shard: 0,
+            slot: 0,
+            due: Vec::new(),
+        }
+    }
+    
+    // Keys now expired among the due ones and the next `budget` slots, taking
+    // each shard's read lock in turn. A reshard or a table that has grown since
+    // the last pass only shifts where the walk resumes.
+    fn step<V: Clone>(&mut self, data: &DashMap<K, CacheEntry<V>>, now: Instant, adaptive: Option<&AdaptiveTtl>, generation: u64) -> Vec<K> {
+        let mut expired = Vec::new();
+        self.due.retain(|(at, key)| {
+            if now <= *at {
+                return true;
+            }
+            expired.push(key.clone());
+            false
+        });
+        
+        let shards = data.shards();
+        let mut examined = 0;
+        while examined < self.budget {
+            if self.shard >= shards.len() {
+                self.shard = 0;
+                self.slot = 0;
+            }
+            let shard = shards[self.shard].read();
+            let table = shard.raw_table();
+            while examined < self.budget && self.slot < table.buckets() {
+                // SAFETY: the slot is within the table, which the read lock keeps
+                // from being resized or written while we look
+                if unsafe { table.is_bucket_full(self.slot) } {
+                    let (key, entry) = unsafe { table.bucket(self.slot).as_ref() };
+                    let expires_at = entry.get().expires_at(adaptive, generation);
+                    if now > expires_at {
+                        expired.push(key.clone());
+                    } else if expires_at <= now + self.horizon {
+                        self.due.push((expires_at, key.clone()));
+                    }
+                }
+                self.slot += 1;
+                examined += 1;
+            }
+            if self.slot >= table.buckets() {
+                self.shard += 1;
+                self.slot = 0;
+            }
+        }
+        expired
+    }
+}
+
+// One shard's slice of the cache, for thread-per-shard designs: route each key to
+// the thread owning shard_of(key) and let that thread work through its handle.
+// Keys of other shards are refused (a miss, or false), so misrouting shows up as
+// failed operations instead of silent cross-core traffic. A put of a new key
+// into a full cache may still evict from any shard.
+pub struct ShardHandle<'a, K, V>
+where
+    K: Clone + Eq + std::hash::Hash,
+    V: Clone,
+{
+    cache: &'a SmartCache<K, V>,
+    index: usize,
+}
+
+impl<K, V> ShardHandle<'_, K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    
+    pub fn owns<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.cache.shard_of(key) == self.index
+    }
+    
+    // Entries stored in this shard, expired ones included until cleaned up
+    pub fn len(&self) -> usize {
+        self.cache.data.shards()[self.index].read().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        if !self.owns(key) {
+            return None;
+        }
+        self.cache.get(key)
+    }
+    
+    pub fn put(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool {
+        self.owns(&key) && self.cache.put(key, value, ttl, priority)
+    }
+    
+    pub fn delete<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.owns(key) && self.cache.delete(key)
+    }
+    
+    // The NUMA node holding this shard, with numa_placement
+    pub fn node(&self) -> Option<usize> {
+        self.cache.numa.as_ref()?.node_of_shard(self.index)
+    }
+}
+
+fn shard_share(max_capacity: usize, shards: usize, shard_index: usize) -> usize {
+    max_capacity / shards + usize::from(shard_index < max_capacity % shards)
+}
+
+// Which NUMA node each shard belongs to, with numa_placement
+#[derive(Debug, Clone)]
+pub struct NumaLayout {
+    topology: NumaTopology,
+    shard_nodes: Vec<usize>,
+    placed_shards: usize,
+}
+
+impl NumaLayout {
+    // Size every shard's table to its share of `max_capacity` and bind it to its node
+    fn place<K, V>(data: &DashMap<K, V>, max_capacity: usize, topology: NumaTopology) -> Self
+    where
+        K: Eq + std::hash::Hash,
+    {
+        let shards = data.shards();
+        let shard_nodes = numa::shard_nodes(shards.len(), topology.len());
+        let mut placed_shards = 0;
+        for (index, shard) in shards.iter().enumerate() {
+            let mut shard = shard.write();
+            let missing = shard_share(max_capacity, shards.len(), index).saturating_sub(shard.len());
+            shard.reserve(missing);
+            let (ptr, layout) = shard.raw_table().allocation_info();
+            if numa::place_allocation(ptr, layout, &topology.nodes()[shard_nodes[index]]) {
+                placed_shards += 1;
+            }
+        }
+        NumaLayout { topology, shard_nodes, placed_shards }
+    }
+    
+    pub fn topology(&self) -> &NumaTopology {
+        &self.topology
+    }
+    
+    pub fn node_of_shard(&self, shard: usize) -> Option<usize> {
+        self.shard_nodes.get(shard).copied()
+    }
+    
+    pub fn shards_on_node(&self, node: usize) -> Vec<usize> {
+        (0..self.shard_nodes.len()).filter(|&shard| self.shard_nodes[shard] == node).collect()
+    }
+    
+    // Shards whose memory the kernel agreed to place; 0 without NUMA support.
+    // Tables smaller than a page are never placed (see numa::place_allocation).
+    pub fn placed_shards(&self) -> usize {
+        self.placed_shards
+    }
+}
+
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    
+    #[test]
+    fn test_concurrent_access() {
+        let cache = Arc::new(SmartCache::new(1000));
+        let mut handles = vec![];
+        
+        // Spawn 100 threads
+        for i in 0..100 {
+            let cache_clone = Arc::clone(&cache);
+            let handle = thread::spawn(move || {
+                for j in 0..100 {
+                    let key = format!("{}_{}", i, j);
+                    cache_clone.put(key.clone(), format!("value_{}_{}", i, j), None, 5);
+                    cache_clone.get(&key);
+                }
+            });
+            handles.push(handle);
+        }
+        
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        
+        // Check stats
+        let stats = cache.get_stats();
+        assert!(stats.hits > 0);
+        assert!(stats.insertions > 0);
+    }
+    
+    #[test]
+    fn test_sharded_performance() {
+        let cache = SmartCache::new(100);
+        
+        // Fill cache
+        for i in 0..100 {
+            cache.put(i, format!("value_{}", i), None, (i % 10) as u8 + 1);
+        }
+        
+        // Force eviction
+        for i in 100..200 {
+            cache.put(i, format!("value_{}", i), None, 5);
+        }
+        
+        assert_eq!(cache.data.len(), 100);
+    }
+    
+    #[test]
+    fn test_get_with_metadata() {
+        let cache = SmartCache::new(10);
+        
+        cache.put("a".to_string(), 1, Some(Duration::from_secs(60)), 9);
+        cache.get(&"a".to_string());
+        
+        let (value, meta) = cache.get_with_metadata(&"a".to_string()).unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(meta.priority, 9);
+        assert_eq!(meta.access_count, 2);
+        assert!(meta.ttl_remaining > Duration::from_secs(59));
+        assert!(cache.get_with_metadata(&"b".to_string()).is_none());
+        
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.to_map()["hits"], 2.0);
+    }
+    
+    #[test]
+    fn test_contains_key_has_no_side_effects() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, 1, None, 5);
+        cache.put(2, 2, Some(Duration::from_millis(20)), 5);
+        
+        assert!(cache.contains_key(&1));
+        assert!(!cache.contains_key(&3));
+        
+        clock::advance(Duration::from_millis(40));
+        assert!(!cache.contains_key(&2));
+        
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 0);
+        assert_eq!(cache.get_with_metadata(&1).unwrap().1.access_count, 1);
+    }
+    
+    #[test]
+    fn test_purge_expired() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, 1, Some(Duration::from_millis(20)), 5);
+        cache.put(2, 2, None, 5);
+        
+        assert_eq!(cache.purge_expired(), 0);
+        clock::advance(Duration::from_millis(40));
+        assert_eq!(cache.purge_expired(), 1);
+        assert!(cache.contains_key(&2));
+        assert_eq!(cache.data.len(), 1);
+    }
+    
+    #[test]
+    fn test_invalidate_all() {
+        let cache = SmartCache::new(4);
+        for i in 0..4 {
+            cache.put(i, i, None, 5);
+        }
+        cache.get(&0);
+        cache.invalidate_all();
+        
+        // Gone at once, still stored until reclaimed
+        assert_eq!(cache.get(&0), None);
+        assert!(!cache.contains_key(&1));
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.data.len(), 3);
+        assert_eq!(cache.get_stats().hits, 1);
+        
+        // Invalidated entries are evicted before any live one
+        for i in 10..13 {
+            cache.put(i, i, None, 1);
+        }
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.data.len(), 3);
+        assert!((10..13).all(|i| cache.contains_key(&i)));
+    }
+    
+    #[test]
+    fn test_retain() {
+        let cache = SmartCache::new(100);
+        
+        for i in 0..10 {
+            cache.put(format!("tenant_a:{}", i), i, None, 5);
+            cache.put(format!("tenant_b:{}", i), i, None, 5);
+        }
+        
+        let removed = cache.retain(|key, _, _| !key.starts_with("tenant_a:"));
+        assert_eq!(removed, 10);
+        assert_eq!(cache.data.len(), 10);
+        assert!(cache.contains_key(&"tenant_b:3".to_string()));
+    }
+    
+    #[test]
+    fn test_drain() {
+        let cache = SmartCache::new(100);
+        
+        for i in 0..20 {
+            cache.put(i, i * 2, None, 5);
+        }
+        
+        let mut drained = cache.drain();
+        drained.sort();
+        assert_eq!(drained.len(), 20);
+        assert_eq!(drained[7], (7, 14));
+        assert_eq!(cache.data.len(), 0);
+        assert!(cache.lru_queue.is_empty());
+    }
+    
+    #[test]
+    fn test_export_import() {
+        let source = SmartCache::new(100);
+        for i in 0..10 {
+            source.put(i, i, None, 5);
+        }
+        
+        source.get(&2);
+        
+        let exported = source.export_where(|key, _, _| key % 2 == 0);
+        assert_eq!(exported.len(), 5);
+        
+        let target = SmartCache::new(100);
+        target.put(4, 40, None, 9);
+        assert_eq!(target.import(exported.clone(), ConflictPolicy::KeepHigherPriority), 4);
+        assert_eq!(target.import(exported.clone(), ConflictPolicy::KeepExisting), 0);
+        assert!(target.contains_key(&4));
+        assert!(!target.contains_key(&5));
+        assert_eq!(target.get(&4), Some(40));
+        // The read before the export, plus this one
+        assert_eq!(target.get_with_metadata(&2).unwrap().1.access_count, 2);
+        
+        // Only records actually stored are counted
+        let full = SmartCache::new(1);
+        full.put(99, 0, None, 5);
+        full.pause_maintenance();
+        assert_eq!(full.import(exported, ConflictPolicy::Overwrite), 0);
+    }
+    
+    fn write_seed_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}_{}_{}", env!("CARGO_PKG_NAME"), std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+    
+    #[test]
+    fn test_warm_from_file() {
+        let csv = write_seed_file("seed.csv", "key,value,ttl_secs,priority\na,1,60,9\nb,2,,1\nc,3,,5\n");
+        let json = write_seed_file("seed.json", r#"[{"key": "d", "value": 4}, {"key": "e", "value": 5, "priority": 2}]"#);
+        
+        let cache: SmartCache<String, i32> = SmartCache::new(4);
+        let mut progress = Vec::new();
+        let report = cache.warm_from_file_with_progress(&csv, |loaded, total| progress.push((loaded, total))).unwrap();
+        assert_eq!(report, WarmupReport { loaded: 3, evicted: 0 });
+        assert_eq!(progress, vec![(3, 3)]);
+        
+        // Over capacity by one: the single eviction pass drops the lowest priority entry
+        let report = cache.warm_from_file(&json).unwrap();
+        assert_eq!(report, WarmupReport { loaded: 2, evicted: 1 });
+        assert!(!cache.contains_key(&"b".to_string()));
+        
+        let (value, meta) = cache.get_with_metadata(&"a".to_string()).unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(meta.priority, 9);
+        assert!(meta.ttl_remaining <= Duration::from_secs(60));
+        
+        assert!(matches!(cache.warm_from_file("seed.txt"), Err(WarmupError::UnsupportedFormat(_))));
+        std::fs::remove_file(csv).unwrap();
+        std::fs::remove_file(json).unwrap();
+    }
+    
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}_{}", env!("CARGO_PKG_NAME"), std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let dir = temp_dir("snapshot_roundtrip");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("a".to_string(), "1".to_string(), Some(Duration::from_secs(30)), 8);
+        cache.put("b".to_string(), "2".to_string(), None, 2);
+        
+        for name in ["plain.json", "packed.json.gz"] {
+            let path = dir.join(name);
+            assert_eq!(cache.save_snapshot(&path).unwrap(), 2);
+            
+            let restored: SmartCache<String, String> = SmartCache::new(10);
+            let report = restored.load_snapshot(&path, ConflictPolicy::Overwrite).unwrap();
+            assert_eq!(report.loaded, 2);
+            assert!(report.verification.is_valid());
+            let (value, meta) = restored.get_with_metadata(&"a".to_string()).unwrap();
+            assert_eq!(value, "1");
+            assert_eq!(meta.priority, 8);
+            assert!(meta.ttl_remaining <= Duration::from_secs(30));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_diff() {
+        let dir = temp_dir("snapshot_diff");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("same".to_string(), "1".to_string(), None, 5);
+        cache.put("moved".to_string(), "2".to_string(), Some(Duration::from_secs(60)), 5);
+        cache.save_snapshot(dir.join("before.json")).unwrap();
+        
+        // Same value, rewritten with a later expiry and a higher priority
+        cache.put("moved".to_string(), "2".to_string(), Some(Duration::from_secs(600)), 9);
+        cache.save_snapshot(dir.join("after.json")).unwrap();
+        
+        let before: Snapshot<String, String> = read_snapshot(&dir.join("before.json")).unwrap();
+        let after: Snapshot<String, String> = read_snapshot(&dir.join("after.json")).unwrap();
+        let diff = diff_snapshots(&before, &after);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.unchanged, 1);
+        let change = &diff.changed[0];
+        assert_eq!(change.key, "moved");
+        assert!(!change.value_changed);
+        assert_eq!(change.priority_delta, 4);
+        assert!(change.expiry_shift_ms > 500_000);
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_corruption_detection() {
+        let dir = temp_dir("snapshot_corruption");
+        let path = dir.join("reordered.json");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        for key in ["a", "b", "c"] {
+            cache.put(key.to_string(), key.to_uppercase(), None, 5);
+        }
+        cache.save_snapshot(&path).unwrap();
+        
+        // Every record is intact on its own; only the file checksum notices
+        let mut raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        raw["records"].as_array_mut().unwrap().swap(0, 2);
+        std::fs::write(&path, raw.to_string()).unwrap();
+        
+        let verification = verify_snapshot(&path).unwrap();
+        assert_eq!(verification.records, 3);
+        assert!(verification.corrupt_records.is_empty());
+        assert!(!verification.checksum_ok);
+        assert!(matches!(read_snapshot::<String, String>(&path), Err(SnapshotError::Corrupt(_))));
+        
+        let restored: SmartCache<String, String> = SmartCache::new(10);
+        let report = restored.load_snapshot(&path, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(report.loaded, 3);
+        assert!(!report.verification.is_valid());
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_scheduler_retention() {
+        let dir = temp_dir("snapshot_scheduler");
+        let config = CacheConfig {
+            snapshot_schedule: Some(SnapshotSchedule {
+                directory: dir.clone(),
+                interval: Duration::from_millis(20),
+                retain: 2,
+                compress: true,
+                encryption: None,
+            }),
+            ..Default::default()
+        };
+        let mut cache: SmartCache<String, String> = SmartCache::with_config(config);
+        cache.put("a".to_string(), "1".to_string(), None, 5);
+        
+        let (sender, events) = std::sync::mpsc::channel();
+        assert!(cache.start_snapshot_scheduler(move |event| {
+            let _ = sender.send(event);
+        }));
+        assert!(!cache.start_snapshot_scheduler(|_| {}));
+        // Runs after a reshard see the new table
+        cache.reshard(4);
+        cache.put("b".to_string(), "2".to_string(), None, 5);
+        while !matches!(events.recv_timeout(Duration::from_secs(5)).unwrap(), SnapshotEvent::Saved { entries: 2, .. }) {}
+        for _ in 0..2 {
+            let event = events.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(matches!(event, SnapshotEvent::Saved { entries: 2, .. }));
+        }
+        
+        // The thread exits once the cache is gone, so no run can land between
+        // its write and its prune while the directory is listed
+        let scheduler = cache.snapshot_handle.lock().take().unwrap();
+        drop(cache);
+        scheduler.join().unwrap();
+        let snapshots = list_snapshots(&dir).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        let latest: Snapshot<String, String> = read_snapshot(snapshots.last().unwrap()).unwrap();
+        assert_eq!(latest.entries.len(), 2);
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[derive(Clone)]
+    struct Token {
+        expires_in: Option<Duration>,
+    }
+    
+    impl Expirable for Token {
+        fn ttl(&self) -> Option<Duration> {
+            self.expires_in
+        }
+    }
+    
+    #[test]
+    fn test_put_expirable() {
+        let cache: SmartCache<String, Token> = SmartCache::new(10);
+        let short = Token { expires_in: Some(Duration::from_millis(50)) };
+        
+        cache.put_expirable("own".to_string(), short.clone(), None, 5);
+        cache.put_expirable("explicit".to_string(), short, Some(Duration::from_secs(60)), 5);
+        cache.put_expirable("default".to_string(), Token { expires_in: None }, None, 5);
+        
+        clock::advance(Duration::from_millis(80));
+        assert!(cache.get(&"own".to_string()).is_none());
+        assert!(cache.get(&"explicit".to_string()).is_some());
+        assert!(cache.get(&"default".to_string()).is_some());
+    }
+    
+    #[test]
+    fn test_borrowed_key_lookups() {
+        let cache: SmartCache<String, i32> = SmartCache::new(10);
+        cache.put("alpha".to_string(), 1, None, 5);
+        
+        assert_eq!(cache.get("alpha"), Some(1));
+        assert_eq!(cache.get_with_metadata("alpha").map(|(_, meta)| meta.access_count), Some(2));
+        assert!(cache.contains_key("alpha"));
+        assert_eq!(cache.get("beta"), None);
+        
+        assert!(cache.delete("alpha"));
+        assert!(!cache.contains_key("alpha"));
+    }
+    
+    #[test]
+    fn test_adaptive_ttl() {
+        let cache = SmartCache::with_config(CacheConfig {
+            default_ttl: Duration::from_millis(100),
+            adaptive_ttl: Some(AdaptiveTtl {
+                extension_per_hit: Duration::from_millis(100),
+                max_extension: Duration::from_millis(300),
+                idle_timeout: Duration::from_millis(60),
+            }),
+            ..Default::default()
+        });
+        cache.put("hot", 1, None, 5);
+        cache.put("cold", 2, None, 5);
+        
+        // Hot entry is read every 40ms and outlives its 100ms TTL
+        for _ in 0..4 {
+            clock::advance(Duration::from_millis(40));
+            assert_eq!(cache.get("hot"), Some(1));
+        }
+        // Cold entry was never read and decayed after its 60ms idle timeout
+        assert!(!cache.contains_key("cold"));
+        
+        // The extension is capped: 100ms TTL + 300ms max
+        let (_, meta) = cache.get_with_metadata("hot").unwrap();
+        assert!(meta.ttl_remaining <= Duration::from_millis(300));
+    }
+    
+    #[test]
+    fn test_striped_stats_across_threads() {
+        let cache = Arc::new(SmartCache::<String, String>::new(10));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        cache.get("missing");
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(cache.get_stats().misses, 8000);
+    }
+    
+    #[test]
+    fn test_capacity_under_concurrent_puts() {
+        let cache = Arc::new(SmartCache::with_config(CacheConfig {
+            max_capacity: 64,
+            shard_amount: 8,
+            ..Default::default()
+        }));
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let cache = Arc::clone(&cache);
+                thread::spawn(move || {
+                    for i in 0..500 {
+                        cache.put(t * 1000 + i, i, None, (i % 10) as u8 + 1);
+                        // Concurrent puts never leave the cache past capacity, not even briefly
+                        assert!(cache.len() <= 64);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        
+        assert_eq!(cache.len(), 64);
+        assert_eq!(cache.get_stats().evictions as usize, 8 * 500 - 64);
+    }
+    
+    #[test]
+    fn test_eviction_compares_priority_across_shards() {
+        let cache = SmartCache::with_config(CacheConfig {
+            max_capacity: 8,
+            shard_amount: 8,
+            ..Default::default()
+        });
+        for i in 0..4 {
+            cache.put(i, i, None, 10);
+            cache.put(100 + i, i, None, 1);
+        }
+        clock::advance(Duration::from_secs(60));
+        
+        // Whichever shards the new keys land in, the low-priority entries go first
+        for i in 0..4 {
+            cache.put(200 + i, i, None, 5);
+        }
+        assert_eq!(cache.len(), 8);
+        assert!((0..4).all(|i| cache.contains_key(&i)));
+        assert!((0..4).all(|i| !cache.contains_key(&(100 + i))));
+    }
+    
+    #[test]
+    fn test_reshard() {
+        let mut cache = SmartCache::with_config(CacheConfig {
+            shard_amount: 4,
+            ..Default::default()
+        });
+        assert!(default_shard_amount().is_power_of_two());
+        for i in 0..100 {
+            cache.put(i, i * 10, None, 5);
+        }
+        cache.get(&7);
+        
+        cache.reshard(32);
+        assert_eq!(cache.shard_amount(), 32);
+        assert_eq!(cache.data.len(), 100);
+        assert_eq!(cache.get(&42), Some(420));
+        assert_eq!(cache.get_with_metadata(&7).unwrap().1.access_count, 2);
+        assert_eq!(cache.purge_expired(), 0);
+    }
+    
+    #[test]
+    fn test_size_accessors() {
+        let cache = SmartCache::new(10);
+        assert!(cache.is_empty());
+        assert_eq!(cache.capacity(), 10);
+        
+        cache.put("a", 1, None, 5);
+        cache.put("b", 2, Some(Duration::from_millis(10)), 5);
+        clock::advance(Duration::from_millis(20));
+        
+        // The expired entry still occupies capacity until cleanup
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.weighted_size(), 2);
+        cache.purge_expired();
+        assert_eq!(cache.weighted_size(), 1);
+        assert!(!cache.is_empty());
+    }
+    
+    #[test]
+    fn test_priority_decay() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            shard_amount: 2,
+            priority_decay: Some(PriorityDecay { half_life: Duration::from_millis(10), floor: 1 }),
+            ..Default::default()
+        };
+        let mut cache = SmartCache::with_config(config);
+        cache.put("stale", 1, None, 10);
+        clock::advance(Duration::from_millis(50));
+        cache.put("low", 2, None, 2);
+        clock::advance(Duration::from_millis(50));
+        
+        // Fixed priorities would score "low" higher (0.05s / 2 against 0.1s / 10), but
+        // ten half-lives have brought "stale" down to about 1. Which shard a put
+        // evicts from depends on the hasher, so the scoring is checked on the
+        // full-map pass.
+        assert_eq!(cache.evict_to_capacity(), 0);
+        cache.config.max_capacity = 1;
+        assert_eq!(cache.evict_to_capacity(), 1);
+        assert!(!cache.contains_key("stale"));
+        assert!(cache.contains_key("low"));
+    }
+    
+    #[test]
+    fn test_priority_boost() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            shard_amount: 2,
+            priority_boost: Some(PriorityBoost { min_rate: 5.0, max_boost: 4 }),
+            ..Default::default()
+        };
+        let mut cache = SmartCache::with_config(config);
+        cache.put("cold", 1, None, 6);
+        cache.put("hot", 2, None, 3);
+        for _ in 0..40 {
+            cache.get("hot");
+        }
+        let (_, meta) = cache.get_with_metadata("hot").unwrap();
+        assert_eq!(meta.priority, 3);
+        assert_eq!(meta.effective_priority, 7.0, "41 reads in the first second: boosted by the max of 4");
+        cache.get("cold");
+        clock::advance(Duration::from_millis(100));
+        
+        // Both idle about as long; declared priorities would evict "hot" first
+        cache.config.max_capacity = 1;
+        assert_eq!(cache.evict_to_capacity(), 1);
+        assert!(cache.contains_key("hot"));
+        assert!(!cache.contains_key("cold"));
+    }
+    
+    #[test]
+    fn test_next_eviction_candidates() {
+        let cache = SmartCache::new(10);
+        cache.put("a", 1, None, 5);
+        cache.put("b", 2, None, 2);
+        clock::advance(Duration::from_millis(20));
+        cache.put("c", 3, None, 5);
+        
+        // Equally idle, "b" scores higher on its lower priority; "c" has barely aged
+        let candidates = cache.next_eviction_candidates(2);
+        assert_eq!(candidates.iter().map(|(key, _)| *key).collect::<Vec<_>>(), ["b", "a"]);
+        assert!(candidates[0].1 > candidates[1].1);
+        assert_eq!(cache.next_eviction_candidates(10).len(), 3);
+        assert_eq!(cache.get_stats().size, 3);
+    }
+    
+    #[test]
+    fn test_pause_maintenance() {
+        let config = CacheConfig {
+            max_capacity: 2,
+            cleanup_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("a", 1, Some(Duration::from_millis(20)), 5);
+        cache.put("b", 2, None, 5);
+        cache.pause_maintenance();
+        assert!(cache.is_maintenance_paused());
+        
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get_stats().size, 2, "the cleaner leaves the expired entry alone");
+        assert!(!cache.put("c", 3, None, 5), "no room is made for a new key");
+        assert!(cache.put("b", 20, None, 5));
+        
+        assert_eq!(cache.resume_maintenance(), 0);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.get_stats().size > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.get_stats().size, 1);
+        assert!(cache.put("c", 3, None, 5));
+    }
+    
+    #[test]
+    fn test_health() {
+        let config = CacheConfig {
+            max_capacity: 10,
+            cleanup_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        cache.put("a", 1, None, 5);
+        let started = SmartCache::<&str, i32>::new(10).health();
+        assert!(started.is_healthy() && started.last_cleanup.is_none());
+        
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.health().last_cleanup.is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        let health = cache.health();
+        assert!(health.is_healthy());
+        assert!(health.last_cleanup.is_some());
+        assert_eq!((health.entries, health.capacity, health.expired_unreclaimed), (1, 10, 0));
+        
+        // A paused cleaner is not overdue, but expired entries pile up
+        cache.pause_maintenance();
+        cache.put("b", 2, Some(Duration::from_millis(5)), 5);
+        clock::advance(Duration::from_millis(50));
+        let health = cache.health();
+        assert!(health.is_healthy());
+        assert_eq!((health.entries, health.expired_unreclaimed), (2, 1));
+    }
+    
+    #[test]
+    fn test_statsd_emitter() {
+        let agent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        agent.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let cache = SmartCache::new(10);
+        cache.put("a", 1, None, 5);
+        cache.get("a");
+        cache.get("b");
+        
+        let emitter = cache
+            .start_statsd(StatsdConfig {
+                agent: agent.local_addr().unwrap().to_string(),
+                interval: Duration::from_secs(60),
+                prefix: "test".to_string(),
+                tags: vec!["env:ci".to_string()],
+            })
+            .unwrap();
+        // Stopping flushes without waiting out the interval
+        emitter.stop();
+        
+        let mut buf = [0; 1500];
+        let len = agent.recv(&mut buf).unwrap();
+        let lines: Vec<_> = std::str::from_utf8(&buf[..len]).unwrap().lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "test.hits:1|c|#env:ci",
+                "test.misses:1|c|#env:ci",
+                "test.evictions:0|c|#env:ci",
+                "test.insertions:1|c|#env:ci",
+                "test.hit_rate:0.5|g|#env:ci",
+                "test.size:1|g|#env:ci",
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_ttl_distribution() {
+        let cache = SmartCache::new(10);
+        cache.put(1, 1, Some(Duration::from_millis(1)), 5);
+        for (key, secs) in [(2, 30), (3, 300), (4, 1800), (5, 7200), (6, 7200)] {
+            cache.put(key, key, Some(Duration::from_secs(secs)), 5);
+        }
+        clock::advance(Duration::from_millis(10));
+        assert_eq!(
+            cache.ttl_distribution(),
+            TtlDistribution {
+                expired: 1,
+                under_1m: 1,
+                under_10m: 1,
+                under_1h: 1,
+                over_1h: 2,
+            }
+        );
+    }
+    
+    #[test]
+    fn test_bulk_load() {
+        let cache = SmartCache::new(3);
+        let report = cache.bulk_load((0..5).map(|i| (i, i * 10, None, if i == 4 { 1 } else { 5 })));
+        assert_eq!(report, WarmupReport { loaded: 5, evicted: 2 });
+        assert_eq!(cache.get_stats().insertions, 5);
+        
+        // The one capacity pass takes the low-priority entry first
+        assert!(!cache.contains_key(&4));
+        assert_eq!(cache.get_stats().size, 3);
+    }
+    
+    #[test]
+    fn test_shard_handles() {
+        let config = CacheConfig { shard_amount: 4, ..Default::default() };
+        let cache = SmartCache::with_config(config);
+        assert!(cache.shard_handle(4).is_none());
+        
+        // One thread per shard, each putting only the keys routed to it
+        thread::scope(|scope| {
+            for index in 0..cache.shard_amount() {
+                let cache = &cache;
+                scope.spawn(move || {
+                    let shard = cache.shard_handle(index).unwrap();
+                    for key in (0..200).filter(|key| cache.shard_of(key) == index) {
+                        assert!(shard.put(key, key * 2, None, 5));
+                    }
+                });
+            }
+        });
+        assert_eq!(cache.len(), 200);
+        assert_eq!((0..4).map(|index| cache.shard_handle(index).unwrap().len()).sum::<usize>(), 200);
+        
+        let owner = cache.shard_handle(cache.shard_of(&7)).unwrap();
+        let other = cache.shard_handle((owner.index() + 1) % 4).unwrap();
+        assert_eq!(owner.get(&7), Some(14));
+        assert_eq!(other.get(&7), None, "misrouted keys are refused");
+        assert!(!other.put(7, 0, None, 5));
+        assert!(!other.delete(&7));
+        assert!(owner.delete(&7));
+    }
+    
+    #[test]
+    fn test_numa_placement() {
+        assert_eq!(numa::parse_cpu_list("0-3,8,10-11\n"), Some(vec![0, 1, 2, 3, 8, 10, 11]));
+        assert_eq!(numa::parse_cpu_list("0-x"), None);
+        assert_eq!(numa::shard_nodes(8, 2), vec![0, 0, 0, 0, 1, 1, 1, 1]);
+        // Only whole pages are placed, never ones shared with a neighbour
+        assert_eq!(numa::inner_pages(100, 10_000, 4096), Some((4096, 8192)));
+        assert_eq!(numa::inner_pages(4096, 8192, 4096), Some((4096, 12_288)));
+        assert_eq!(numa::inner_pages(100, 5000, 4096), None);
+        // CPUs past cpu_set_t's 1024 are not a panic, just not there
+        assert!(!numa::bind_current_thread(&numa::NumaNode { id: 0, cpus: vec![4096] }));
+        
+        // Two nodes, whatever this machine has; placement itself is best effort
+        let topology = NumaTopology::from_nodes(vec![
+            numa::NumaNode { id: 0, cpus: vec![0] },
+            numa::NumaNode { id: 1, cpus: vec![1] },
+        ]);
+        let data: DashMap<u64, u64> = DashMap::with_shard_amount(4);
+        let layout = NumaLayout::place(&data, 100, topology);
+        assert_eq!(layout.shards_on_node(1), vec![2, 3]);
+        assert!(data.shards().iter().all(|shard| shard.read().capacity() >= 25), "tables are sized to their share");
+        
+        let config = CacheConfig { shard_amount: 8, max_capacity: 1000, numa_placement: true, ..Default::default() };
+        let mut cache = SmartCache::with_config(config);
+        let nodes = cache.numa_layout().unwrap().topology().len();
+        for key in 0..100 {
+            cache.put(key, key, None, 5);
+            assert!(cache.node_of_key(&key).unwrap() < nodes);
+        }
+        cache.reshard(16);
+        assert_eq!((0..nodes).map(|node| cache.numa_layout().unwrap().shards_on_node(node).len()).sum::<usize>(), 16);
+        assert_eq!(cache.get(&42), Some(42));
+        assert_eq!(SmartCache::<u64, u64>::new(10).node_of_key(&1), None);
+    }
+    
+    #[test]
+    fn test_collection_values() {
+        use collections::{Collection, CollectionError};
+        
+        let cache: Arc<SmartCache<String, Collection<String>>> = Arc::new(SmartCache::new(10));
+        let items = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>();
+        
+        assert_eq!(cache.rpush("list".to_string(), items(&["b", "c"])), Ok(2));
+        assert_eq!(cache.lpush("list".to_string(), items(&["a"])), Ok(3));
+        assert_eq!(cache.lrange("list", 0, -1), Ok(items(&["a", "b", "c"])));
+        assert_eq!(cache.lrange("list", -2, 10), Ok(items(&["b", "c"])));
+        assert_eq!(cache.lpop("list"), Ok(Some("a".to_string())));
+        assert_eq!(cache.llen("list"), Ok(2));
+        assert_eq!(cache.llen("missing"), Ok(0));
+        
+        assert_eq!(cache.sadd("set".to_string(), items(&["x", "y", "x"])), Ok(2));
+        assert_eq!(cache.sismember("set", &"x".to_string()), Ok(true));
+        assert_eq!(cache.srem("set", &items(&["x", "z"])), Ok(1));
+        assert_eq!(cache.scard("set"), Ok(1));
+        
+        let fields = vec![("name".to_string(), "ada".to_string()), ("lang".to_string(), "rust".to_string())];
+        assert_eq!(cache.hset("hash".to_string(), fields), Ok(2));
+        assert_eq!(cache.hset("hash".to_string(), vec![("name".to_string(), "grace".to_string())]), Ok(0));
+        assert_eq!(cache.hget("hash", &"name".to_string()), Ok(Some("grace".to_string())));
+        assert_eq!(cache.hdel("hash", &items(&["lang"])), Ok(1));
+        assert_eq!(cache.hlen("hash"), Ok(1));
+        
+        assert_eq!(cache.sadd("list".to_string(), items(&["x"])), Err(CollectionError::WrongType));
+        assert_eq!(cache.llen("hash"), Err(CollectionError::WrongType));
+        
+        // An emptied collection is deleted
+        cache.rpop("list").unwrap();
+        cache.rpop("list").unwrap();
+        assert!(!cache.contains_key("list"));
+        
+        // Pushes from many threads all land
+        let handles: Vec<_> = (0..8).map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for i in 0..500 {
+                    cache.rpush("shared".to_string(), [format!("{}-{}", t, i)]).unwrap();
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(cache.llen("shared"), Ok(4000));
+    }
+    
+    #[test]
+    fn test_incremental_expiry_scan() {
+        let cache = SmartCache::new(10);
+        for key in 0..4 {
+            cache.put(key, key, Some(Duration::from_millis(5)), 5);
+        }
+        cache.put(4, 4, Some(Duration::from_millis(30)), 5);
+        cache.put(5, 5, None, 5);
+        clock::advance(Duration::from_millis(10));
+        
+        // Three slots per step, picking up where the last stopped, so one walk over
+        // every table finds each expired key; key 4 expires within the horizon, so
+        // it is kept as due (twice if the last step wrapped around to it)
+        let slots: usize = cache.data.shards().iter().map(|shard| shard.read().raw_table().buckets()).sum();
+        let mut scan = ExpiryScan::new(3, Duration::from_secs(1));
+        let mut found = Vec::new();
+        for _ in 0..slots.div_ceil(3) {
+            let keys = scan.step(&cache.data, clock::now(), None, 0);
+            assert!(keys.len() <= 3);
+            found.extend(keys);
+        }
+        found.sort();
+        found.dedup();
+        assert_eq!(found, vec![0, 1, 2, 3]);
+        assert!(!scan.due.is_empty() && scan.due.iter().all(|(_, key)| *key == 4));
+        
+        clock::advance(Duration::from_millis(30));
+        assert!(scan.step(&cache.data, clock::now(), None, 0).contains(&4));
+        assert!(scan.due.is_empty());
+        
+        // The background pass gets through everything a budget at a time
+        let config = CacheConfig {
+            cleanup_interval: Duration::from_millis(5),
+            shard_amount: 4,
+            expiry_scan_budget: Some(8),
+            ..Default::default()
+        };
+        let cache = SmartCache::with_config(config);
+        for key in 0..10 {
+            cache.put(key, key, Some(Duration::from_millis(1)), 5);
+        }
+        cache.put(10, 10, None, 5);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.data.len() > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.data.len(), 1);
+        assert_eq!(cache.get_stats().evictions, 10);
+    }
+
+    #[test]
+    fn test_freeze() {
+        let cache = SmartCache::new(10);
+        cache.put(1, "one".to_string(), None, 7);
+        cache.put(2, "two".to_string(), Some(Duration::from_millis(20)), 5);
+        cache.put(3, "three".to_string(), Some(Duration::from_millis(1)), 5);
+        clock::advance(Duration::from_millis(5));
+        
+        let frozen = cache.freeze();
+        assert_eq!(frozen.len(), 2);
+        assert!(!frozen.contains_key(&3));
+        assert_eq!(frozen.priority(&1), Some(7));
+        assert!(frozen.ttl_remaining(&2).unwrap() <= Duration::from_millis(15));
+        
+        // Later writes and expiry don't reach the frozen copy, and reading it
+        // leaves the cache's stats alone
+        cache.put(1, "uno".to_string(), None, 5);
+        cache.put(4, "four".to_string(), None, 5);
+        clock::advance(Duration::from_millis(20));
+        assert_eq!(frozen.get(&1).map(String::as_str), Some("one"));
+        assert_eq!(frozen.get(&2).map(String::as_str), Some("two"));
+        assert_eq!(frozen.get(&4), None);
+        assert_eq!(cache.get_stats().hits, 0);
+    }
+    
+    #[test]
+    fn test_estimated_memory_bytes() {
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        let empty = cache.estimated_memory_bytes();
+        cache.put("big".to_string(), "x".repeat(1000), None, 5);
+        cache.put("small".to_string(), "y".repeat(10), None, 5);
+        let full = cache.estimated_memory_bytes();
+        assert!(full > empty + 1010);
+        assert_eq!(cache.get_stats().estimated_memory_bytes, None);
+        assert_eq!(cache.get_stats_with_memory().estimated_memory_bytes, Some(full));
+        
+        cache.delete("big");
+        assert!(cache.estimated_memory_bytes() + 1000 <= full);
     }
 }
@@ -0,0 +1,168 @@
+// NUMA topology and memory placement for CacheConfig::numa_placement. On a
+// multi-socket machine each node (socket) has its own memory, and reaching another
+// node's costs a trip over the interconnect. With numa_placement the shards are
+// split into one contiguous block per node, and each shard's table is bound to its
+// node, so its pages land there whichever thread touches them first. Threads that
+// mostly work on one node's keys (see SmartCache::node_of_key) then stay local.
+//
+// Topology comes from /sys/devices/system/node. Anywhere that is missing (other
+// platforms, kernels without NUMA) the machine counts as one node and placement
+// does nothing.
+
+use std::alloc::Layout;
+use std::ptr::NonNull;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumaNode {
+    // The kernel's node number
+    pub id: usize,
+    pub cpus: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumaTopology {
+    nodes: Vec<NumaNode>,
+}
+
+impl NumaTopology {
+    pub fn detect() -> Self {
+        Self::from_sysfs().filter(|topology| !topology.nodes.is_empty()).unwrap_or_else(Self::single_node)
+    }
+
+    // Every CPU on node 0
+    pub fn single_node() -> Self {
+        let cpus = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+        NumaTopology { nodes: vec![NumaNode { id: 0, cpus: (0..cpus).collect() }] }
+    }
+
+    pub fn from_nodes(nodes: Vec<NumaNode>) -> Self {
+        assert!(!nodes.is_empty(), "a topology has at least one node");
+        NumaTopology { nodes }
+    }
+
+    fn from_sysfs() -> Option<Self> {
+        let online = std::fs::read_to_string("/sys/devices/system/node/online").ok()?;
+        let nodes = parse_cpu_list(&online)?
+            .into_iter()
+            .map(|id| {
+                let cpulist = std::fs::read_to_string(format!("/sys/devices/system/node/node{}/cpulist", id)).ok()?;
+                Some(NumaNode { id, cpus: parse_cpu_list(&cpulist)? })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(NumaTopology { nodes })
+    }
+
+    // Nodes are referred to by their position here, 0..len()
+    pub fn nodes(&self) -> &[NumaNode] {
+        &self.nodes
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node_of_cpu(&self, cpu: usize) -> Option<usize> {
+        self.nodes.iter().position(|node| node.cpus.contains(&cpu))
+    }
+
+    // The node the calling thread is running on right now
+    pub fn current_node(&self) -> Option<usize> {
+        current_cpu().and_then(|cpu| self.node_of_cpu(cpu))
+    }
+}
+
+// "0-3,8,10-11" as used in sysfs; None if malformed
+pub(crate) fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => cpus.extend(first.parse::<usize>().ok()?..=last.parse::<usize>().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+// Which node each of `shards` shards belongs to: contiguous blocks of (nearly)
+// equal size, so shard i goes to node i * nodes / shards
+pub(crate) fn shard_nodes(shards: usize, nodes: usize) -> Vec<usize> {
+    (0..shards).map(|shard| shard * nodes / shards).collect()
+}
+
+#[cfg(target_os = "linux")]
+fn current_cpu() -> Option<usize> {
+    let cpu = unsafe { libc::sched_getcpu() };
+    usize::try_from(cpu).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_cpu() -> Option<usize> {
+    None
+}
+
+// Restrict the calling thread to the node's CPUs; false where that is not supported.
+// The mask is sized to the node's highest CPU: a cpu_set_t only holds CPU_SETSIZE
+// (1024) of them, and larger machines number past that.
+#[cfg(target_os = "linux")]
+pub fn bind_current_thread(node: &NumaNode) -> bool {
+    let Some(&highest) = node.cpus.iter().max() else {
+        return false;
+    };
+    let mut mask = vec![0u64; highest / 64 + 1];
+    for &cpu in &node.cpus {
+        mask[cpu / 64] |= 1 << (cpu % 64);
+    }
+    let size = std::mem::size_of_val(mask.as_slice());
+    unsafe { libc::syscall(libc::SYS_sched_setaffinity, 0, size, mask.as_ptr()) == 0 }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn bind_current_thread(_node: &NumaNode) -> bool {
+    false
+}
+
+// The pages lying wholly inside `size` bytes at `addr`, as a page-aligned
+// (start, end); None when the allocation doesn't cover a whole page
+#[cfg(any(target_os = "linux", test))]
+pub(crate) fn inner_pages(addr: usize, size: usize, page: usize) -> Option<(usize, usize)> {
+    let start = addr.div_ceil(page) * page;
+    let end = (addr + size) / page * page;
+    (start < end).then_some((start, end))
+}
+
+// Prefer `node` for the pages of an allocation, moving the ones already touched.
+// Preferred rather than bound, so a full node spills over instead of failing.
+// The allocation comes from the global allocator and may share its first and
+// last pages with other allocations, so only the pages wholly inside it are
+// placed; a policy on the shared ones would move their neighbours too. Tables
+// smaller than a page are left where they are, and report false.
+#[cfg(target_os = "linux")]
+pub(crate) fn place_allocation(ptr: NonNull<u8>, layout: Layout, node: &NumaNode) -> bool {
+    const MPOL_PREFERRED: libc::c_int = 1;
+    const MPOL_MF_MOVE: libc::c_uint = 1 << 1;
+    if layout.size() == 0 {
+        return true;
+    }
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(4096) as usize;
+    let Some((start, end)) = inner_pages(ptr.as_ptr() as usize, layout.size(), page) else {
+        return false;
+    };
+
+    let mut mask = vec![0u64; node.id / 64 + 1];
+    mask[node.id / 64] |= 1 << (node.id % 64);
+    // The kernel reads maxnode - 1 bits of the mask
+    let max_node = mask.len() * 64 + 1;
+    let status = unsafe {
+        libc::syscall(libc::SYS_mbind, start, end - start, MPOL_PREFERRED, mask.as_ptr(), max_node, MPOL_MF_MOVE)
+    };
+    status == 0
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn place_allocation(_ptr: NonNull<u8>, _layout: Layout, _node: &NumaNode) -> bool {
+    false
+}
@@ -0,0 +1,150 @@
+// Push-based metrics for SmartCache::start_statsd: a background thread sends the
+// cache's counters and gauges to a statsd or Datadog agent over UDP every
+// interval. Counters go out as the change since the previous flush, so the agent
+// can sum them across instances; hit rate and size are gauges. Lines are packed
+// into datagrams small enough to pass without fragmentation, and send errors are
+// ignored, as is usual for statsd.
+
+use crate::CacheStats;
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// Leaves room for IP and UDP headers within a 1500-byte MTU
+const MAX_DATAGRAM: usize = 1432;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsdConfig {
+    // host:port of the agent
+    pub agent: String,
+    pub interval: Duration,
+    // Prepended to each metric name with a dot
+    pub prefix: String,
+    // Datadog-style tags ("env:prod"); leave empty for plain statsd
+    pub tags: Vec<String>,
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            agent: "127.0.0.1:8125".to_string(),
+            interval: Duration::from_secs(10),
+            prefix: "cache".to_string(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+// Keeps the emitter running; stop() or dropping it sends a final flush and
+// ends the thread
+pub struct StatsdEmitter {
+    stopping: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl StatsdEmitter {
+    pub(crate) fn start<F>(config: StatsdConfig, sample: F) -> io::Result<Self>
+    where
+        F: Fn() -> CacheStats + Send + 'static,
+    {
+        let agent = config
+            .agent
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "statsd agent address did not resolve"))?;
+        let local = if agent.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(agent)?;
+
+        let stopping = Arc::new(AtomicBool::new(false));
+        let stopping_clone = Arc::clone(&stopping);
+        let handle = thread::Builder::new().name("cache-statsd".to_string()).spawn(move || {
+            let mut previous = CacheStats::default();
+            loop {
+                let stopped = !wait(&stopping_clone, config.interval);
+                let current = sample();
+                for datagram in pack(&metric_lines(&config, &previous, &current)) {
+                    let _ = socket.send(datagram.as_bytes());
+                }
+                previous = current;
+                if stopped {
+                    break;
+                }
+            }
+        })?;
+        Ok(Self {
+            stopping,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn stop(mut self) {
+        self.shut_down();
+    }
+
+    fn shut_down(&mut self) {
+        self.stopping.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for StatsdEmitter {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+// Sleep for `interval` unless stopped first; false once stopped
+fn wait(stopping: &AtomicBool, interval: Duration) -> bool {
+    let deadline = Instant::now() + interval;
+    while !stopping.load(Ordering::Acquire) {
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        thread::park_timeout(deadline - now);
+    }
+    false
+}
+
+fn metric_lines(config: &StatsdConfig, previous: &CacheStats, current: &CacheStats) -> Vec<String> {
+    let tags = if config.tags.is_empty() {
+        String::new()
+    } else {
+        format!("|#{}", config.tags.join(","))
+    };
+    let counters = [
+        ("hits", current.hits.saturating_sub(previous.hits)),
+        ("misses", current.misses.saturating_sub(previous.misses)),
+        ("evictions", current.evictions.saturating_sub(previous.evictions)),
+        ("insertions", current.insertions.saturating_sub(previous.insertions)),
+    ];
+    let mut lines: Vec<String> = counters
+        .iter()
+        .map(|(name, delta)| format!("{}.{}:{}|c{}", config.prefix, name, delta, tags))
+        .collect();
+    lines.push(format!("{}.hit_rate:{}|g{}", config.prefix, current.hit_rate, tags));
+    lines.push(format!("{}.size:{}|g{}", config.prefix, current.size, tags));
+    lines
+}
+
+// Newline-separated lines, as many per datagram as fit
+fn pack(lines: &[String]) -> Vec<String> {
+    let mut datagrams: Vec<String> = Vec::new();
+    for line in lines {
+        match datagrams.last_mut() {
+            Some(datagram) if datagram.len() + 1 + line.len() <= MAX_DATAGRAM => {
+                datagram.push('\n');
+                datagram.push_str(line);
+            }
+            _ => datagrams.push(line.clone()),
+        }
+    }
+    datagrams
+}
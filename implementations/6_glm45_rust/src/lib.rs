@@ -1,12 +1,40 @@
 // GLM-4.5 Rust Implementation - Score: 89/100
 // Focus on observability, debugging, and SQL-like queries
 
-use std::collections::{HashMap, VecDeque};
+use std::borrow::Borrow;
+use std::collections::binary_heap::PeekMut;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
 use std::sync::{Arc, RwLock, Mutex};
-use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::thread;
+use std::time::{Duration, Instant};
 use arc_swap::ArcSwap;
 use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+pub mod clock;
+#[cfg(feature = "log")]
+mod logging;
+mod query;
+
+#[cfg(feature = "log")]
+pub use logging::{LogConfig, LogEvent};
+#[cfg(feature = "log")]
+use logging::OperationLogger;
+use query::{parse_columns, parse_where, Column, Field, Row};
+
+pub use cache_types::{ExportedEntry, FrozenCache, KeyProvider, MemSize, PriorityBoost, PriorityDecay, StaticKeyProvider, TtlDistribution};
+use cache_types::{FrozenEntry, Shared, StripedCounter};
+use cache_types::snapshot;
+pub use cache_types::snapshot::{
+    diff_snapshots, list_snapshots, read_snapshot, read_snapshot_lossy, verify_snapshot, write_snapshot, EntryChange,
+    Snapshot, SnapshotDiff, SnapshotError, SnapshotEvent, SnapshotLoadReport, SnapshotSchedule, SnapshotVerification,
+    SNAPSHOT_VERSION,
+};
 
 // ===== Configuration with Hot Reload =====
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +44,20 @@ pub struct CacheConfig {
     pub cleanup_interval: Duration,
     pub enable_trace_log: bool,
     pub trace_log_capacity: usize,
+    // None disables capacity tuning and its bookkeeping. Only locked lookups are
+    // observed, not hits through the read index.
+    pub capacity_tuning: Option<CapacityTuning>,
+    // Serve get() hits from the lock-free read index (see ReadIndex)
+    pub read_fast_path: bool,
+    // Decay the priorities of unaccessed entries for eviction; None keeps them fixed
+    pub priority_decay: Option<PriorityDecay>,
+    // Raise the priorities of frequently read entries for eviction; None keeps them as declared.
+    // Reads served by the read index count once the next write folds them in.
+    pub priority_boost: Option<PriorityBoost>,
+    // Used by start_snapshot_scheduler; None disables periodic snapshots. Read
+    // once when the scheduler starts, so it is not part of hot reload.
+    #[serde(skip)]
+    pub snapshot_schedule: Option<SnapshotSchedule>,
 }
 
 impl Default for CacheConfig {
@@ -26,8 +68,375 @@ impl Default for CacheConfig {
             cleanup_interval: Duration::from_secs(60),
             enable_trace_log: true,
             trace_log_capacity: 10000,
+            capacity_tuning: None,
+            read_fast_path: false,
+            priority_decay: None,
+            priority_boost: None,
+            snapshot_schedule: None,
+        }
+    }
+}
+
+impl CacheConfig {
+    // Checked by reload_config before anything is applied
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: &str| Err(ConfigError::Invalid(msg.to_string()));
+        if self.max_capacity == 0 {
+            return invalid("max_capacity must be at least 1");
+        }
+        if self.trace_log_capacity == 0 {
+            return invalid("trace_log_capacity must be at least 1");
+        }
+        if let Some(tuning) = &self.capacity_tuning {
+            if tuning.step == 0 || tuning.window == 0 {
+                return invalid("capacity_tuning step and window must be at least 1");
+            }
+            if tuning.min_capacity == 0 || tuning.min_capacity > tuning.max_capacity {
+                return invalid("capacity_tuning needs 1 <= min_capacity <= max_capacity");
+            }
+            let fraction = 0.0..=1.0;
+            if !fraction.contains(&tuning.grow_threshold) || !fraction.contains(&tuning.shrink_threshold) {
+                return invalid("capacity_tuning thresholds must be fractions in 0..=1");
+            }
+        }
+        if self.priority_decay.is_some_and(|decay| decay.half_life.is_zero()) {
+            return invalid("priority_decay half_life must be greater than zero");
+        }
+        if self.priority_boost.is_some_and(|boost| !(boost.min_rate > 0.0 && boost.min_rate.is_finite())) {
+            return invalid("priority_boost min_rate must be a positive number");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    Invalid(String),
+    // rollback_config with no earlier config recorded
+    NoHistory,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Invalid(msg) => write!(f, "invalid cache config: {}", msg),
+            ConfigError::NoHistory => write!(f, "no earlier config to roll back to"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Earlier configs kept for rollback_config
+const CONFIG_HISTORY_LIMIT: usize = 16;
+
+// One field that differed between two configs, values rendered as JSON
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigFieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+fn config_changes(old: &CacheConfig, new: &CacheConfig) -> Vec<ConfigFieldChange> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(field, value)| old.get(*field) != Some(value))
+        .map(|(field, value)| ConfigFieldChange {
+            field: field.clone(),
+            old: old.get(field).map(|old| old.to_string()).unwrap_or_default(),
+            new: value.to_string(),
+        })
+        .collect()
+}
+
+// ===== Capacity Auto-Tuning =====
+// Each window of lookups measures the marginal value of `step` slots: misses on the
+// last `step` evicted keys (a ghost list) would have hit with `step` more, and hits
+// on the `step` least recently used entries would have missed with `step` fewer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityTuning {
+    pub min_capacity: usize,
+    pub max_capacity: usize,
+    pub step: usize,
+    // Lookups per evaluation
+    pub window: u64,
+    // Grow when ghost hits reach this fraction of lookups
+    pub grow_threshold: f64,
+    // Shrink when tail hits stay below this fraction of lookups
+    pub shrink_threshold: f64,
+    // Apply recommendations through hot reload instead of only reporting them
+    pub auto_apply: bool,
+}
+
+impl Default for CapacityTuning {
+    fn default() -> Self {
+        Self {
+            min_capacity: 100,
+            max_capacity: 100_000,
+            step: 100,
+            window: 10_000,
+            grow_threshold: 0.01,
+            shrink_threshold: 0.001,
+            auto_apply: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapacityRecommendation {
+    pub current_capacity: usize,
+    pub recommended_capacity: usize,
+    pub lookups: u64,
+    // Fraction of lookups that would have hit with `step` more slots
+    pub marginal_gain: f64,
+    // Fraction of lookups that would have missed with `step` fewer slots
+    pub marginal_loss: f64,
+}
+
+struct CapacityTuner<K> {
+    ghost: VecDeque<K>,
+    ghost_keys: HashSet<K>,
+    lookups: u64,
+    ghost_hits: u64,
+    tail_hits: u64,
+    last: Option<CapacityRecommendation>,
+}
+
+impl<K: Clone + Eq + std::hash::Hash> CapacityTuner<K> {
+    fn new() -> Self {
+        Self {
+            ghost: VecDeque::new(),
+            ghost_keys: HashSet::new(),
+            lookups: 0,
+            ghost_hits: 0,
+            tail_hits: 0,
+            last: None,
+        }
+    }
+    
+    fn record_eviction(&mut self, key: K, step: usize) {
+        if self.ghost_keys.insert(key.clone()) {
+            self.ghost.push_back(key);
+        }
+        while self.ghost.len() > step {
+            if let Some(oldest) = self.ghost.pop_front() {
+                self.ghost_keys.remove(&oldest);
+            }
+        }
+    }
+    
+    // `position` counts from the least recently used end
+    fn record_hit(&mut self, position: usize, step: usize) {
+        self.lookups += 1;
+        if position < step {
+            self.tail_hits += 1;
+        }
+    }
+    
+    fn record_miss<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.lookups += 1;
+        if self.ghost_keys.remove(key) {
+            self.ghost_hits += 1;
+            self.ghost.retain(|k| k.borrow() != key);
+        }
+    }
+    
+    // A key put back in the cache is no longer a ghost
+    fn forget(&mut self, key: &K) {
+        if self.ghost_keys.remove(key) {
+            self.ghost.retain(|k| k != key);
+        }
+    }
+    
+    // Closes the window once it holds `tuning.window` lookups
+    fn evaluate(&mut self, tuning: &CapacityTuning, current: usize) -> Option<CapacityRecommendation> {
+        if self.lookups < tuning.window.max(1) {
+            return None;
         }
+        let marginal_gain = self.ghost_hits as f64 / self.lookups as f64;
+        let marginal_loss = self.tail_hits as f64 / self.lookups as f64;
+        let recommended = if marginal_gain >= tuning.grow_threshold {
+            current.saturating_add(tuning.step)
+        } else if marginal_loss < tuning.shrink_threshold {
+            current.saturating_sub(tuning.step)
+        } else {
+            current
+        };
+        
+        let recommendation = CapacityRecommendation {
+            current_capacity: current,
+            recommended_capacity: recommended.clamp(tuning.min_capacity, tuning.max_capacity.max(tuning.min_capacity)),
+            lookups: self.lookups,
+            marginal_gain,
+            marginal_loss,
+        };
+        self.lookups = 0;
+        self.ghost_hits = 0;
+        self.tail_hits = 0;
+        self.last = Some(recommendation.clone());
+        Some(recommendation)
+    }
+}
+
+// ===== Lock-free Read Index =====
+// Immutable copy of the live entries, republished by every write while
+// read_fast_path is on, so get() hits take no cache lock. Hits only bump atomics
+// here; the next writer folds them back into access counts, recency and LRU order.
+// Writes pay an O(n) rebuild, which suits read-mostly workloads only.
+struct IndexedEntry<V> {
+    value: V,
+    ttl: Instant,
+    generation: u64,
+    hits: AtomicU64,
+    // Nanoseconds since SmartCache::index_epoch
+    last_hit: AtomicU64,
+}
+
+type ReadIndex<K, V> = HashMap<K, Arc<IndexedEntry<V>>>;
+
+// ===== Eviction Heap =====
+// Capacity eviction candidates: a heap per priority, oldest access on top. Without
+// decay or boost an entry's score only depends on its priority and idle time, so
+// each heap's top is its priority's best candidate and a victim is picked by
+// scoring ten tops rather than the whole LRU queue. Items are never updated on
+// access; eviction re-pushes a top whose entry was touched since and drops one
+// whose entry is gone or changed priority, and put() rebuilds the heaps once stale
+// items outnumber the entries.
+#[derive(Clone)]
+struct HeapItem<K> {
+    last_accessed: Instant,
+    key: K,
+}
+
+impl<K> PartialEq for HeapItem<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.last_accessed == other.last_accessed
+    }
+}
+
+impl<K> Eq for HeapItem<K> {}
+
+impl<K> PartialOrd for HeapItem<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Reversed, so BinaryHeap keeps the least recently accessed on top
+impl<K> Ord for HeapItem<K> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.last_accessed.cmp(&self.last_accessed)
+    }
+}
+
+#[derive(Clone)]
+struct EvictionHeap<K> {
+    // Indexed by priority; 0 stays empty
+    by_priority: Vec<BinaryHeap<HeapItem<K>>>,
+    // Items across all heaps, stale ones included
+    len: usize,
+}
+
+impl<K: Clone + Eq + std::hash::Hash> EvictionHeap<K> {
+    fn new() -> Self {
+        EvictionHeap {
+            by_priority: (0..=10).map(|_| BinaryHeap::new()).collect(),
+            len: 0,
+        }
+    }
+    
+    fn push<V: Clone>(&mut self, key: K, entry: &mut CacheEntry<V>) {
+        entry.heap_stamp = entry.last_accessed;
+        self.by_priority[usize::from(entry.priority)].push(HeapItem { last_accessed: entry.last_accessed, key });
+        self.len += 1;
+    }
+    
+    fn clear(&mut self) {
+        self.by_priority.iter_mut().for_each(BinaryHeap::clear);
+        self.len = 0;
     }
+    
+    fn rebuild<V: Clone>(&mut self, data: &mut HashMap<K, CacheEntry<V>>) {
+        self.clear();
+        for (key, entry) in data.iter_mut() {
+            self.push(key.clone(), entry);
+        }
+    }
+    
+    fn rebuild_if_sparse<V: Clone>(&mut self, data: &mut HashMap<K, CacheEntry<V>>) {
+        if self.len > 2 * data.len() + 64 {
+            self.rebuild(data);
+        }
+    }
+    
+    // Bring one priority's top up to date; its access time if any entry is left
+    fn settle<V: Clone>(&mut self, priority: usize, data: &mut HashMap<K, CacheEntry<V>>) -> Option<Instant> {
+        let heap = &mut self.by_priority[priority];
+        while let Some(mut top) = heap.peek_mut() {
+            match data.get_mut(&top.key) {
+                Some(entry) if usize::from(entry.priority) == priority && entry.heap_stamp == top.last_accessed => {
+                    if entry.last_accessed == top.last_accessed {
+                        return Some(top.last_accessed);
+                    }
+                    // Touched since it was pushed: sinks to its place when `top` drops
+                    top.last_accessed = entry.last_accessed;
+                    entry.heap_stamp = entry.last_accessed;
+                }
+                _ => {
+                    PeekMut::pop(top);
+                    self.len -= 1;
+                }
+            }
+        }
+        None
+    }
+    
+    // Pop the entry the LRU scan would evict: the highest score, the least recently
+    // accessed among equal scores. Invalidated entries were last accessed before
+    // any live one, so they come up first.
+    fn pop_victim<V: Clone>(
+        &mut self,
+        config: &CacheConfig,
+        data: &mut HashMap<K, CacheEntry<V>>,
+        now: Instant,
+        generation: u64,
+    ) -> Option<K> {
+        let mut best: Option<(f64, Instant, usize)> = None;
+        for priority in 1..=10 {
+            let Some(last_accessed) = self.settle(priority, data) else {
+                continue;
+            };
+            let top = self.by_priority[priority].peek()?;
+            let score = data[&top.key].eviction_score(config, now, generation);
+            let better = match best {
+                None => true,
+                Some((best_score, best_accessed, _)) => {
+                    score > best_score || (score == best_score && last_accessed < best_accessed)
+                }
+            };
+            if better {
+                best = Some((score, last_accessed, priority));
+            }
+        }
+        let (_, _, priority) = best?;
+        self.len -= 1;
+        self.by_priority[priority].pop().map(|item| item.key)
+    }
+}
+
+// ===== Self-Expiring Values =====
+// Values that know their own lifetime (tokens, DNS records); see put_expirable
+pub trait Expirable {
+    fn ttl(&self) -> Option<Duration>;
 }
 
 // ===== Cache Entry =====
@@ -39,15 +448,269 @@ pub struct CacheEntry<V: Clone> {
     created_at: Instant,
     last_accessed: Instant,
     access_count: usize,
+    // last_accessed when this entry's eviction heap item was pushed
+    heap_stamp: Instant,
+    // The cache's generation when stored; see invalidate_all
+    generation: u64,
+}
+
+// Which writes go through, decided under the data lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteIf {
+    Always,
+    // Only when the key holds no live entry
+    Absent,
+    // Only over a live entry, keeping its expiry and priority
+    Present,
+    // When the key holds no live entry or one of lower priority than the write's
+    HigherPriority,
+}
+
+impl From<ConflictPolicy> for WriteIf {
+    fn from(policy: ConflictPolicy) -> Self {
+        match policy {
+            ConflictPolicy::KeepExisting => WriteIf::Absent,
+            ConflictPolicy::Overwrite => WriteIf::Always,
+            ConflictPolicy::KeepHigherPriority => WriteIf::HigherPriority,
+        }
+    }
+}
+
+// Stored: Ok with the live value replaced. Not stored: Err with the live value
+// that stopped an Absent or HigherPriority write, if that was the reason.
+type WriteResult<V> = Result<Option<V>, Option<V>>;
+
+// ===== Entry Metadata =====
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntryMetadata {
+    pub priority: u8,
+    // What eviction scores with: `priority` after decay and boost
+    pub effective_priority: f64,
+    pub access_count: usize,
+    pub age: Duration,
+    pub idle: Duration,
+    pub ttl_remaining: Duration,
+}
+
+impl<V: Clone> CacheEntry<V> {
+    // Why this entry is being removed to make room: an already-expired victim is
+    // reported as such rather than as a capacity eviction
+    fn eviction_reason(&self, now: Instant, generation: u64) -> EvictionReason {
+        if now > self.expires_at(generation) {
+            EvictionReason::TTLExpired
+        } else {
+            EvictionReason::CapacityExceeded { victim_priority: self.priority }
+        }
+    }
+    
+    // Whole seconds since the last access over the effective priority; the highest
+    // goes first, and an invalidated entry before any other
+    fn eviction_score(&self, config: &CacheConfig, now: Instant, generation: u64) -> f64 {
+        if self.generation != generation {
+            return f64::INFINITY;
+        }
+        let idle = now.saturating_duration_since(self.last_accessed);
+        idle.as_secs() as f64 / self.effective_priority(config, now)
+    }
+    
+    // An entry stored before the last invalidate_all expired just before it was
+    // created, so it reads as expired even at that instant
+    fn expires_at(&self, generation: u64) -> Instant {
+        if self.generation == generation {
+            self.ttl
+        } else {
+            self.created_at.checked_sub(Duration::from_nanos(1)).unwrap_or(self.created_at)
+        }
+    }
+    
+    // The priority eviction scores with, capped at the top of the scale
+    fn effective_priority(&self, config: &CacheConfig, now: Instant) -> f64 {
+        let decayed = match &config.priority_decay {
+            Some(decay) => decay.apply(self.priority, now.saturating_duration_since(self.last_accessed)),
+            None => f64::from(self.priority),
+        };
+        let boost = config.priority_boost.map_or(0.0, |boost| {
+            boost.apply(self.access_count, now.saturating_duration_since(self.created_at))
+        });
+        (decayed + boost).min(10.0)
+    }
+    
+    // `idle` is measured against the access before the current one
+    fn metadata(&self, config: &CacheConfig, now: Instant, previous_access: Instant) -> EntryMetadata {
+        EntryMetadata {
+            priority: self.priority,
+            effective_priority: self.effective_priority(config, now),
+            access_count: self.access_count,
+            age: now.saturating_duration_since(self.created_at),
+            idle: now.saturating_duration_since(previous_access),
+            ttl_remaining: self.ttl.saturating_duration_since(now),
+        }
+    }
+}
+
+// ===== Export / Import =====
+// What `import` does when a key is already present
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    KeepExisting,
+    Overwrite,
+    KeepHigherPriority,
+}
+
+// ===== Warm-up Loading =====
+// One row of a warm-up seed file (CSV header: key,value,ttl_secs,priority)
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedRecord<K, V> {
+    pub key: K,
+    pub value: V,
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    #[serde(default = "default_seed_priority")]
+    pub priority: u8,
+}
+
+fn default_seed_priority() -> u8 {
+    5
+}
+
+// (key, value, ttl, priority) for bulk_load, the arguments put takes
+pub type BulkEntry<K, V> = (K, V, Option<Duration>, u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmupReport {
+    pub loaded: usize,
+    pub evicted: usize,
+}
+
+#[derive(Debug)]
+pub enum WarmupError {
+    Io(std::io::Error),
+    Parse(String),
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for WarmupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarmupError::Io(err) => write!(f, "failed to read seed file: {}", err),
+            WarmupError::Parse(msg) => write!(f, "invalid seed record: {}", msg),
+            WarmupError::UnsupportedFormat(ext) => write!(f, "unsupported seed file format: {:?}", ext),
+        }
+    }
+}
+
+impl std::error::Error for WarmupError {}
+
+impl From<std::io::Error> for WarmupError {
+    fn from(err: std::io::Error) -> Self {
+        WarmupError::Io(err)
+    }
+}
+
+// Seed files are loaded in batches so readers are not locked out for the whole file
+const WARMUP_BATCH: usize = 10_000;
+
+fn read_seed_file<K, V>(path: &Path) -> Result<Vec<SeedRecord<K, V>>, WarmupError>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let reader = BufReader::new(File::open(path)?);
+            serde_json::from_reader(reader).map_err(|err| WarmupError::Parse(err.to_string()))
+        }
+        Some("csv") => {
+            let mut reader = csv::Reader::from_reader(File::open(path)?);
+            reader
+                .deserialize()
+                .collect::<Result<_, _>>()
+                .map_err(|err| WarmupError::Parse(err.to_string()))
+        }
+        other => Err(WarmupError::UnsupportedFormat(other.unwrap_or_default().to_string())),
+    }
 }
 
 // ===== Operation Tracing for Debugging =====
+// Renders keys for trace and explanation output, so keys need not implement ToString
+pub type KeyFormatter<K> = Arc<dyn Fn(&K) -> String + Send + Sync>;
+
 #[derive(Debug, Clone, Serialize)]
 pub enum CacheOperation {
     Put { key: String, priority: u8, ttl_secs: u64 },
     Get { key: String, hit: bool },
     Delete { key: String },
     Eviction { key: String, reason: EvictionReason },
+    Resize { from: usize, to: usize },
+    ConfigChanged { changes: Vec<ConfigFieldChange> },
+    // invalidate_all hid `entries` stored entries at once
+    InvalidateAll { entries: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationType {
+    Put,
+    Get,
+    Delete,
+    Eviction,
+    Resize,
+    ConfigChanged,
+    InvalidateAll,
+}
+
+impl CacheOperation {
+    pub fn op_type(&self) -> OperationType {
+        match self {
+            CacheOperation::Put { .. } => OperationType::Put,
+            CacheOperation::Get { .. } => OperationType::Get,
+            CacheOperation::Delete { .. } => OperationType::Delete,
+            CacheOperation::Eviction { .. } => OperationType::Eviction,
+            CacheOperation::Resize { .. } => OperationType::Resize,
+            CacheOperation::ConfigChanged { .. } => OperationType::ConfigChanged,
+            CacheOperation::InvalidateAll { .. } => OperationType::InvalidateAll,
+        }
+    }
+    
+    // None for operations on the cache as a whole
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            CacheOperation::Put { key, .. }
+            | CacheOperation::Get { key, .. }
+            | CacheOperation::Delete { key }
+            | CacheOperation::Eviction { key, .. } => Some(key),
+            CacheOperation::Resize { .. }
+            | CacheOperation::ConfigChanged { .. }
+            | CacheOperation::InvalidateAll { .. } => None,
+        }
+    }
+}
+
+// Selects trace records for get_trace_filtered; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct TraceFilter {
+    // Empty matches every type
+    pub op_types: Vec<OperationType>,
+    // Operations without a key never match a prefix
+    pub key_prefix: Option<String>,
+    pub since: Option<Instant>,
+    // Keep only the most recent matches
+    pub limit: Option<usize>,
+}
+
+impl TraceFilter {
+    fn matches(&self, record: &TraceRecord) -> bool {
+        (self.op_types.is_empty() || self.op_types.contains(&record.op.op_type()))
+            && self.key_prefix.as_deref().is_none_or(|prefix| {
+                record.op.key().is_some_and(|key| key.starts_with(prefix))
+            })
+            && self.since.is_none_or(|since| record.at >= since)
+    }
+}
+
+#[derive(Clone)]
+struct TraceRecord {
+    at: Instant,
+    op: CacheOperation,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -72,19 +735,91 @@ pub struct EvictionExplanation {
 #[derive(Debug, Serialize)]
 pub enum QueryResult {
     Entries(Vec<QueryEntry>),
+    // SELECT with a column list: one value per column in each row
+    Rows { columns: Vec<String>, rows: Vec<Vec<QueryValue>> },
     Count(usize),
     Stats(HashMap<String, f64>),
+    // The WHERE clause could not be parsed
+    Error(String),
 }
 
 #[derive(Debug, Serialize)]
 pub struct QueryEntry {
     pub key: String,
     pub priority: u8,
+    pub effective_priority: f64,
     pub access_count: usize,
     pub age_secs: u64,
     pub ttl_remaining_secs: i64,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum QueryValue {
+    Text(String),
+    Int(i64),
+    Float(f64),
+}
+
+// An entry as the query engine reads it; the key is formatted on first use
+struct EntryRow<'a, K, V: Clone> {
+    key: &'a K,
+    entry: &'a CacheEntry<V>,
+    config: &'a CacheConfig,
+    now: Instant,
+    generation: u64,
+    key_formatter: &'a KeyFormatter<K>,
+    formatted: std::cell::OnceCell<String>,
+}
+
+impl<K, V: Clone> EntryRow<'_, K, V> {
+    fn into_entry(self) -> QueryEntry {
+        QueryEntry {
+            priority: self.entry.priority,
+            effective_priority: self.number(Field::EffectivePriority),
+            access_count: self.entry.access_count,
+            age_secs: self.number(Field::AgeSecs) as u64,
+            ttl_remaining_secs: self.number(Field::TtlRemaining) as i64,
+            key: self.formatted.into_inner().unwrap_or_else(|| (self.key_formatter)(self.key)),
+        }
+    }
+}
+
+impl<K, V: Clone> Row for EntryRow<'_, K, V> {
+    fn number(&self, field: Field) -> f64 {
+        match field {
+            Field::Priority => f64::from(self.entry.priority),
+            Field::EffectivePriority => self.entry.effective_priority(self.config, self.now),
+            Field::AccessCount => self.entry.access_count as f64,
+            Field::AgeSecs => self.now.saturating_duration_since(self.entry.created_at).as_secs() as f64,
+            Field::TtlRemaining => self.entry.expires_at(self.generation).checked_duration_since(self.now)
+                .map_or(-1.0, |d| d.as_secs() as f64),
+        }
+    }
+    
+    fn key(&self) -> &str {
+        self.formatted.get_or_init(|| (self.key_formatter)(self.key))
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &text[prefix.len()..])
+}
+
+// `SELECT <columns> FROM cache <clause>` split into the column list and the clause
+fn split_select(sql: &str) -> Option<(&str, &str)> {
+    let rest = strip_prefix_ignore_case(sql, "SELECT ")?;
+    let at = rest.to_ascii_lowercase().find(" from cache")?;
+    Some((&rest[..at], &rest[at + " from cache".len()..]))
+}
+
+enum Projection {
+    All,
+    Count,
+    Columns(Vec<Column>),
+}
+
 // ===== Circular Buffer for Trace Log =====
 pub struct CircularBuffer<T> {
     buffer: Vec<Option<T>>,
@@ -112,43 +847,109 @@ impl<T: Clone> CircularBuffer<T> {
     }
     
     fn to_vec(&self) -> Vec<T> {
-        let mut result = Vec::new();
-        let mut idx = self.head;
-        while idx != self.tail {
-            if let Some(ref item) = self.buffer[idx] {
-                result.push(item.clone());
-            }
-            idx = (idx + 1) % self.capacity;
+        self.iter().cloned().collect()
+    }
+    
+    // Oldest first
+    fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        let len = (self.tail + self.capacity - self.head) % self.capacity;
+        (0..len).filter_map(move |offset| self.buffer[(self.head + offset) % self.capacity].as_ref())
+    }
+    
+    // Change the capacity, keeping the most recent items that still fit
+    fn resize(&mut self, capacity: usize) {
+        let items = self.to_vec();
+        *self = Self::new(capacity);
+        let skip = items.len().saturating_sub(capacity.saturating_sub(1));
+        for item in items.into_iter().skip(skip) {
+            self.push(item);
         }
-        result
+    }
+}
+
+impl CircularBuffer<TraceRecord> {
+    fn record(&mut self, op: CacheOperation) {
+        self.push(TraceRecord { at: clock::now(), op });
     }
 }
 
 // ===== Main Cache Implementation =====
+// The entry table, shared with a fork() until either side writes (see Shared)
+type Entries<K, V> = RwLock<Shared<HashMap<K, CacheEntry<V>>>>;
+
 pub struct SmartCache<K, V> 
 where
-    K: Clone + Eq + std::hash::Hash + ToString,
+    K: Clone + Eq + std::hash::Hash,
     V: Clone,
 {
-    data: Arc<RwLock<HashMap<K, CacheEntry<V>>>>,
-    lru_queue: Arc<Mutex<VecDeque<K>>>,
+    data: Arc<Entries<K, V>>,
+    lru_queue: Arc<Mutex<Shared<VecDeque<K>>>>,
+    // Locked after data and lru_queue
+    eviction_heap: Arc<Mutex<Shared<EvictionHeap<K>>>>,
     
     // Configuration with hot reload
     config: Arc<ArcSwap<CacheConfig>>,
+    config_history: Arc<Mutex<Vec<Arc<CacheConfig>>>>,
     
     // Advanced debugging features
-    trace_log: Arc<Mutex<CircularBuffer<CacheOperation>>>,
+    trace_log: Arc<Mutex<CircularBuffer<TraceRecord>>>,
+    key_formatter: KeyFormatter<K>,
+    tuner: Arc<Mutex<CapacityTuner<K>>>,
+    
+    // Lock-free read path
+    index: Arc<ArcSwap<ReadIndex<K, V>>>,
+    index_epoch: Instant,
     
     // Statistics
-    stats: Arc<RwLock<CacheStats>>,
+    stats: Arc<StatCounters>,
+    // Set by pause_maintenance
+    maintenance_paused: Arc<AtomicBool>,
+    // Bumped by invalidate_all
+    generation: Arc<AtomicU64>,
+    // Set by start_snapshot_scheduler
+    snapshot_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    #[cfg(feature = "log")]
+    logger: Option<Arc<OperationLogger>>,
 }
 
+// ===== Statistics =====
 #[derive(Debug, Clone, Default)]
 pub struct CacheStats {
     pub hits: u64,
     pub misses: u64,
     pub evictions: u64,
     pub insertions: u64,
+    // Only get_stats_with_memory fills this in, since it takes a pass over
+    // every entry; None from get_stats
+    pub estimated_memory_bytes: Option<usize>,
+}
+
+struct StatCounters {
+    hits: StripedCounter,
+    misses: StripedCounter,
+    evictions: StripedCounter,
+    insertions: StripedCounter,
+}
+
+impl StatCounters {
+    fn new() -> Self {
+        Self {
+            hits: StripedCounter::new(),
+            misses: StripedCounter::new(),
+            evictions: StripedCounter::new(),
+            insertions: StripedCounter::new(),
+        }
+    }
+    
+    fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            evictions: self.evictions.get(),
+            insertions: self.insertions.get(),
+            estimated_memory_bytes: None,
+        }
+    }
 }
 
 impl<K, V> SmartCache<K, V>
@@ -157,150 +958,900 @@ where
     V: Clone + Send + Sync + 'static,
 {
     pub fn new(max_capacity: usize) -> Self {
-        let config = CacheConfig {
+        Self::with_config(CacheConfig {
             max_capacity,
             ..Default::default()
-        };
-        
+        })
+    }
+    
+    pub fn with_config(config: CacheConfig) -> Self {
+        Self::build(config, |key: &K| key.to_string())
+    }
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    // For keys without ToString (tuples, structs): `formatter` renders them in traces
+    pub fn with_key_formatter<F>(max_capacity: usize, formatter: F) -> Self
+    where
+        F: Fn(&K) -> String + Send + Sync + 'static,
+    {
+        Self::build(
+            CacheConfig {
+                max_capacity,
+                ..Default::default()
+            },
+            formatter,
+        )
+    }
+    
+    fn build<F>(config: CacheConfig, formatter: F) -> Self
+    where
+        F: Fn(&K) -> String + Send + Sync + 'static,
+    {
         let trace_log = Arc::new(Mutex::new(CircularBuffer::new(config.trace_log_capacity)));
         let config = Arc::new(ArcSwap::from_pointee(config));
         
         Self {
-            data: Arc::new(RwLock::new(HashMap::new())),
-            lru_queue: Arc::new(Mutex::new(VecDeque::new())),
+            data: Arc::new(RwLock::new(Shared::default())),
+            lru_queue: Arc::new(Mutex::new(Shared::default())),
+            eviction_heap: Arc::new(Mutex::new(Shared::new(EvictionHeap::new()))),
             config,
+            config_history: Arc::new(Mutex::new(Vec::new())),
             trace_log,
-            stats: Arc::new(RwLock::new(CacheStats::default())),
+            key_formatter: Arc::new(formatter),
+            tuner: Arc::new(Mutex::new(CapacityTuner::new())),
+            index: Arc::new(ArcSwap::from_pointee(HashMap::new())),
+            index_epoch: clock::now(),
+            stats: Arc::new(StatCounters::new()),
+            maintenance_paused: Arc::new(AtomicBool::new(false)),
+            generation: Arc::new(AtomicU64::new(0)),
+            snapshot_handle: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "log")]
+            logger: None,
         }
     }
     
-    pub fn put(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool {
-        let config = self.config.load();
-        let ttl = ttl.unwrap_or(config.default_ttl);
-        
-        // Log operation
+    // Write evictions, expirations, config reloads and errors to the `log` crate,
+    // independently of the trace log
+    #[cfg(feature = "log")]
+    pub fn with_logging(mut self, config: LogConfig) -> Self {
+        self.logger = Some(Arc::new(OperationLogger::new(config)));
+        self
+    }
+    
+    fn format_key(&self, key: &K) -> String {
+        (self.key_formatter)(key)
+    }
+    
+    // Every removal path reports through here
+    fn record_eviction(&self, config: &CacheConfig, key: &K, reason: EvictionReason) {
+        #[cfg(feature = "log")]
+        if let Some(logger) = &self.logger {
+            logger.eviction(&reason, || self.format_key(key));
+        }
         if config.enable_trace_log {
-            self.trace_log.lock().unwrap().push(CacheOperation::Put {
-                key: key.to_string(),
-                priority,
-                ttl_secs: ttl.as_secs(),
+            self.trace_log.lock().unwrap().record(CacheOperation::Eviction {
+                key: self.format_key(key),
+                reason,
             });
         }
-        
+    }
+    
+    fn log_error(&self, operation: &'static str, error: &dyn fmt::Display) {
+        #[cfg(feature = "log")]
+        if let Some(logger) = &self.logger {
+            logger.error(operation, error);
+        }
+        #[cfg(not(feature = "log"))]
+        let _ = (operation, error);
+    }
+    
+    // A second handle onto the same state, for work moved to a background thread
+    fn share(&self) -> Self {
+        Self {
+            data: Arc::clone(&self.data),
+            lru_queue: Arc::clone(&self.lru_queue),
+            eviction_heap: Arc::clone(&self.eviction_heap),
+            config: Arc::clone(&self.config),
+            config_history: Arc::clone(&self.config_history),
+            trace_log: Arc::clone(&self.trace_log),
+            key_formatter: Arc::clone(&self.key_formatter),
+            tuner: Arc::clone(&self.tuner),
+            index: Arc::clone(&self.index),
+            index_epoch: self.index_epoch,
+            stats: Arc::clone(&self.stats),
+            maintenance_paused: Arc::clone(&self.maintenance_paused),
+            generation: Arc::clone(&self.generation),
+            snapshot_handle: Arc::clone(&self.snapshot_handle),
+            #[cfg(feature = "log")]
+            logger: self.logger.clone(),
+        }
+    }
+    
+    // A new cache holding this one's entries in the same recency order, with the
+    // same config, and independent of it from then on. Nothing is copied here: the
+    // two share the entry table, LRU queue and eviction heaps, and the first write
+    // on either side copies them for that side (see Shared), values included. A
+    // locked hit counts as a write, since it updates access metadata. The fork
+    // starts with zeroed stats, an empty trace log and tuner history, and an
+    // empty read index, so recency from fast-path hits the parent has not folded
+    // in yet is not carried over. Entries the parent has invalidated stay so.
+    pub fn fork(&self) -> Self {
+        let data = self.data.read().unwrap();
+        let lru_queue = self.lru_queue.lock().unwrap();
+        let eviction_heap = self.eviction_heap.lock().unwrap();
+        let config = self.config.load_full();
+        Self {
+            data: Arc::new(RwLock::new(Shared::clone(&data))),
+            lru_queue: Arc::new(Mutex::new(Shared::clone(&lru_queue))),
+            eviction_heap: Arc::new(Mutex::new(Shared::clone(&eviction_heap))),
+            config: Arc::new(ArcSwap::new(Arc::clone(&config))),
+            config_history: Arc::new(Mutex::new(Vec::new())),
+            trace_log: Arc::new(Mutex::new(CircularBuffer::new(config.trace_log_capacity))),
+            key_formatter: Arc::clone(&self.key_formatter),
+            tuner: Arc::new(Mutex::new(CapacityTuner::new())),
+            index: Arc::new(ArcSwap::from_pointee(HashMap::new())),
+            index_epoch: clock::now(),
+            stats: Arc::new(StatCounters::new()),
+            maintenance_paused: Arc::new(AtomicBool::new(false)),
+            generation: Arc::new(AtomicU64::new(self.generation())),
+            snapshot_handle: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "log")]
+            logger: self.logger.clone(),
+        }
+    }
+    
+    pub fn put(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool {
+        self.write(key, value, ttl, priority, 0, WriteIf::Always).is_ok()
+    }
+    
+    // Store only if the key holds no live entry. Returns the live value that is
+    // kept instead, or None once stored (or refused while maintenance is paused
+    // and the cache is full).
+    pub fn put_if_absent(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> Option<V> {
+        self.write(key, value, ttl, priority, 0, WriteIf::Absent).err().flatten()
+    }
+    
+    // Swap in a new value only if the key holds a live entry, which keeps its
+    // expiry and priority. Returns the replaced value, None if nothing was stored.
+    pub fn replace(&self, key: K, value: V) -> Option<V> {
+        self.write(key, value, None, 0, 0, WriteIf::Present).ok().flatten()
+    }
+    
+    // `access_count` starts the new entry's count; only import carries one over
+    fn write(&self, key: K, value: V, ttl: Option<Duration>, priority: u8, access_count: usize, when: WriteIf) -> WriteResult<V> {
+        let config = self.config.load();
         let mut data = self.data.write().unwrap();
         let mut lru_queue = self.lru_queue.lock().unwrap();
         
-        // Check capacity
+        let now = clock::now();
+        let generation = self.generation();
+        let current = data.get(&key).filter(|entry| now <= entry.expires_at(generation));
+        let (ttl, priority) = match (when, current) {
+            (WriteIf::Absent, Some(entry)) => return Err(Some(entry.value.clone())),
+            (WriteIf::HigherPriority, Some(entry)) if priority <= entry.priority => return Err(Some(entry.value.clone())),
+            (WriteIf::Present, None) => return Err(None),
+            (WriteIf::Present, Some(entry)) => (entry.ttl - now, entry.priority),
+            _ => (ttl.unwrap_or(config.default_ttl), priority),
+        };
+        let live = current.is_some();
+        
+        // Log operation
+        if config.enable_trace_log {
+            self.trace_log.lock().unwrap().record(CacheOperation::Put {
+                key: self.format_key(&key),
+                priority,
+                ttl_secs: ttl.as_secs(),
+            });
+        }
+        
+        // Check capacity; while maintenance is paused no room is made
         if !data.contains_key(&key) && data.len() >= config.max_capacity {
+            if self.is_maintenance_paused() {
+                return Err(None);
+            }
             self.evict_with_explanation(&mut data, &mut lru_queue);
         }
         
-        let entry = CacheEntry {
+        let mut entry = CacheEntry {
             value,
-            priority: priority.min(10).max(1),
-            ttl: Instant::now() + ttl,
-            created_at: Instant::now(),
-            last_accessed: Instant::now(),
-            access_count: 0,
+            priority: priority.clamp(1, 10),
+            ttl: now + ttl,
+            created_at: now,
+            last_accessed: now,
+            access_count,
+            heap_stamp: now,
+            generation,
         };
         
-        data.insert(key.clone(), entry);
+        if config.capacity_tuning.is_some() {
+            self.tuner.lock().unwrap().forget(&key);
+        }
+        let mut eviction_heap = self.eviction_heap.lock().unwrap();
+        match data.get(&key) {
+            // The queued item stays valid and is re-pushed lazily
+            Some(previous) if previous.priority == entry.priority => entry.heap_stamp = previous.heap_stamp,
+            _ => eviction_heap.push(key.clone(), &mut entry),
+        }
+        let replaced = data.insert(key.clone(), entry).filter(|_| live).map(|old| old.value);
+        eviction_heap.rebuild_if_sparse(&mut data);
+        drop(eviction_heap);
         lru_queue.retain(|k| k != &key);
         lru_queue.push_back(key);
+        self.publish_index(&mut data, &mut lru_queue);
         
-        self.stats.write().unwrap().insertions += 1;
-        true
+        self.stats.insertions.add(1);
+        Ok(replaced)
+    }
+    
+    // put() for self-expiring values: an explicit `ttl` wins, then the value's own,
+    // then the configured default
+    pub fn put_expirable(&self, key: K, value: V, ttl: Option<Duration>, priority: u8) -> bool
+    where
+        V: Expirable,
+    {
+        let ttl = ttl.or_else(|| value.ttl());
+        self.put(key, value, ttl, priority)
+    }
+    
+    // Lookups take any borrowed form of the key, e.g. &str for String keys.
+    // An owned key is only built when the trace log needs to render it.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        if let Some(value) = self.get_indexed(key) {
+            return Some(value);
+        }
+        self.access(key, |entry, _| entry.value.clone())
+    }
+    
+    // get() for Copy values (counters, ids, scores): copied out of the entry or the
+    // read index, with no allocation on a hit unless the trace log or capacity tuning
+    // is on
+    pub fn get_copy<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+        V: Copy,
+    {
+        if let Some(value) = self.get_indexed(key) {
+            return Some(value);
+        }
+        self.access(key, |entry, _| entry.value)
+    }
+    
+    // Hit through the read index; misses and expired entries take the locked path
+    fn get_indexed<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        let config = self.config.load();
+        if !config.read_fast_path {
+            return None;
+        }
+        let index = self.index.load();
+        let indexed = index.get(key)?;
+        let now = clock::now();
+        if now > indexed.ttl || indexed.generation != self.generation() {
+            return None;
+        }
+        
+        indexed.hits.fetch_add(1, Ordering::Relaxed);
+        let since_epoch = now.saturating_duration_since(self.index_epoch).as_nanos() as u64;
+        indexed.last_hit.fetch_max(since_epoch, Ordering::Relaxed);
+        self.stats.hits.add(1);
+        
+        if config.enable_trace_log {
+            self.trace_log.lock().unwrap().record(CacheOperation::Get {
+                key: self.format_key(&key.to_owned()),
+                hit: true,
+            });
+        }
+        Some(indexed.value.clone())
+    }
+    
+    // Fold hits taken through the current index back into the entries, then
+    // publish a fresh index. Writers call this with both locks held.
+    fn publish_index(&self, data: &mut HashMap<K, CacheEntry<V>>, lru_queue: &mut VecDeque<K>) {
+        let previous = self.index.load();
+        let mut touched: Vec<(u64, K)> = Vec::new();
+        for (key, indexed) in previous.iter() {
+            let hits = indexed.hits.swap(0, Ordering::Relaxed);
+            if hits == 0 {
+                continue;
+            }
+            let last_hit = indexed.last_hit.load(Ordering::Relaxed);
+            if let Some(entry) = data.get_mut(key) {
+                entry.access_count += hits as usize;
+                entry.last_accessed = entry.last_accessed.max(self.index_epoch + Duration::from_nanos(last_hit));
+                touched.push((last_hit, key.clone()));
+            }
+        }
+        touched.sort_by_key(|(last_hit, _)| *last_hit);
+        for (_, key) in touched {
+            if let Some(position) = lru_queue.iter().position(|k| k == &key) {
+                let queued = lru_queue.remove(position).unwrap();
+                lru_queue.push_back(queued);
+            }
+        }
+        
+        if !self.config.load().read_fast_path {
+            if !previous.is_empty() {
+                self.index.store(Arc::new(HashMap::new()));
+            }
+            return;
+        }
+        let now = clock::now();
+        let generation = self.generation();
+        let next: ReadIndex<K, V> = data
+            .iter()
+            .filter(|(_, entry)| now <= entry.expires_at(generation))
+            .map(|(key, entry)| {
+                let indexed = IndexedEntry {
+                    value: entry.value.clone(),
+                    ttl: entry.ttl,
+                    generation,
+                    hits: AtomicU64::new(0),
+                    last_hit: AtomicU64::new(0),
+                };
+                (key.clone(), Arc::new(indexed))
+            })
+            .collect();
+        self.index.store(Arc::new(next));
+    }
+    
+    // Value and metadata from a single lookup (one LRU update, one trace record)
+    pub fn get_with_metadata<Q>(&self, key: &Q) -> Option<(V, EntryMetadata)>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        self.access(key, |entry, previous_access| {
+            (entry.value.clone(), entry.metadata(&self.config.load(), entry.last_accessed, previous_access))
+        })
     }
     
-    pub fn get(&self, key: &K) -> Option<V> {
+    fn access<Q, R>(&self, key: &Q, read: impl FnOnce(&CacheEntry<V>, Instant) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        let result = self.lookup(key, read);
+        self.tune_capacity();
+        result
+    }
+    
+    fn lookup<Q, R>(&self, key: &Q, read: impl FnOnce(&CacheEntry<V>, Instant) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
         let mut data = self.data.write().unwrap();
         
         if let Some(entry) = data.get_mut(key) {
-            if Instant::now() > entry.ttl {
+            let now = clock::now();
+            if now > entry.expires_at(self.generation()) {
                 // Log operation
                 let config = self.config.load();
                 if config.enable_trace_log {
-                    self.trace_log.lock().unwrap().push(CacheOperation::Get {
-                        key: key.to_string(),
+                    self.trace_log.lock().unwrap().record(CacheOperation::Get {
+                        key: self.format_key(&key.to_owned()),
                         hit: false,
                     });
                 }
                 
-                data.remove(key);
-                self.lru_queue.lock().unwrap().retain(|k| k != key);
-                self.stats.write().unwrap().misses += 1;
+                if let Some((owned, _)) = data.remove_entry(key) {
+                    self.record_eviction(&config, &owned, EvictionReason::TTLExpired);
+                }
+                self.lru_queue.lock().unwrap().retain(|k| k.borrow() != key);
+                if config.capacity_tuning.is_some() {
+                    self.tuner.lock().unwrap().record_miss(key);
+                }
+                self.stats.misses.add(1);
                 return None;
             }
             
-            entry.last_accessed = Instant::now();
+            let previous_access = entry.last_accessed;
+            entry.last_accessed = now;
             entry.access_count += 1;
-            let value = entry.value.clone();
+            let result = read(entry, previous_access);
             
-            // Update LRU
+            // Update LRU by moving the queued key, so a hit never clones it
             let mut lru_queue = self.lru_queue.lock().unwrap();
-            lru_queue.retain(|k| k != key);
-            lru_queue.push_back(key.clone());
+            let position = lru_queue.iter().position(|k| k.borrow() == key);
+            if let Some(position) = position {
+                let queued = lru_queue.remove(position).unwrap();
+                lru_queue.push_back(queued);
+            }
             
-            // Log operation
             let config = self.config.load();
+            if let (Some(tuning), Some(position)) = (&config.capacity_tuning, position) {
+                self.tuner.lock().unwrap().record_hit(position, tuning.step);
+            }
+            
+            // Log operation
             if config.enable_trace_log {
-                self.trace_log.lock().unwrap().push(CacheOperation::Get {
-                    key: key.to_string(),
+                self.trace_log.lock().unwrap().record(CacheOperation::Get {
+                    key: self.format_key(&key.to_owned()),
                     hit: true,
                 });
             }
             
-            self.stats.write().unwrap().hits += 1;
-            Some(value)
+            self.stats.hits.add(1);
+            Some(result)
         } else {
             // Log operation
             let config = self.config.load();
             if config.enable_trace_log {
-                self.trace_log.lock().unwrap().push(CacheOperation::Get {
-                    key: key.to_string(),
+                self.trace_log.lock().unwrap().record(CacheOperation::Get {
+                    key: self.format_key(&key.to_owned()),
                     hit: false,
                 });
             }
+            if config.capacity_tuning.is_some() {
+                self.tuner.lock().unwrap().record_miss(key);
+            }
             
-            self.stats.write().unwrap().misses += 1;
+            self.stats.misses.add(1);
             None
         }
     }
     
-    // ===== SQL-like Query Interface =====
-    pub fn query(&self, sql: &str) -> QueryResult {
+    // Existence check that leaves stats, trace log and LRU order untouched
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        self.data
+            .read()
+            .unwrap()
+            .get(key)
+            .is_some_and(|entry| clock::now() <= entry.expires_at(self.generation()))
+    }
+    
+    pub fn delete<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: std::hash::Hash + Eq + ?Sized,
+    {
+        let mut data = self.data.write().unwrap();
+        let mut lru_queue = self.lru_queue.lock().unwrap();
+        
+        let Some((key, _)) = data.remove_entry(key) else {
+            return false;
+        };
+        lru_queue.retain(|k| k != &key);
+        self.publish_index(&mut data, &mut lru_queue);
+        
+        if self.config.load().enable_trace_log {
+            self.trace_log.lock().unwrap().record(CacheOperation::Delete {
+                key: self.format_key(&key),
+            });
+        }
+        true
+    }
+    
+    // Drop every entry; stats, trace log and the tuner's history are kept
+    pub fn clear(&self) {
+        let mut data = self.data.write().unwrap();
+        let mut lru_queue = self.lru_queue.lock().unwrap();
+        
+        let config = self.config.load();
+        if config.enable_trace_log {
+            let mut trace_log = self.trace_log.lock().unwrap();
+            for key in data.keys() {
+                trace_log.record(CacheOperation::Delete { key: self.format_key(key) });
+            }
+        }
+        // Swapped rather than cleared, so tables still shared with a fork
+        // aren't copied just to be emptied
+        *data = Shared::default();
+        *lru_queue = Shared::default();
+        *self.eviction_heap.lock().unwrap() = Shared::new(EvictionHeap::new());
+        self.publish_index(&mut data, &mut lru_queue);
+    }
+    
+    // clear() for large caches: bumps the generation, and every entry stored
+    // before reads as expired from then on, read index included. Only the read
+    // lock is taken, and no entry or index is touched, so traffic doesn't wait
+    // on a rebuild. The entries are reclaimed like any expired ones: by
+    // purge_expired, a lookup or put of the same key, or capacity eviction, which
+    // takes them first. Traced as one InvalidateAll rather than a Delete per key;
+    // stats and the tuner's history are kept.
+    pub fn invalidate_all(&self) {
         let data = self.data.read().unwrap();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        if self.config.load().enable_trace_log {
+            self.trace_log.lock().unwrap().record(CacheOperation::InvalidateAll { entries: data.len() });
+        }
+    }
+    
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+    
+    // Stored entries, including expired ones not yet purged
+    pub fn size(&self) -> usize {
+        self.data.read().unwrap().len()
+    }
+    
+    // Freeze capacity eviction for backups and debugging: puts of new keys into a
+    // full cache are rejected, and shrinking max_capacity (by reload_config or
+    // capacity tuning) or a bulk load leaves the cache over capacity until
+    // resume_maintenance trims it. Returns once an eviction in progress is done.
+    pub fn pause_maintenance(&self) {
+        let _data = self.data.write().unwrap();
+        self.maintenance_paused.store(true, Ordering::Release);
+    }
+    
+    // Returns the entries evicted to get back within capacity
+    pub fn resume_maintenance(&self) -> usize {
+        self.maintenance_paused.store(false, Ordering::Release);
+        self.evict_to_capacity()
+    }
+    
+    pub fn is_maintenance_paused(&self) -> bool {
+        self.maintenance_paused.load(Ordering::Acquire)
+    }
+    
+    // Remove every expired entry now; there is no background cleaner here
+    pub fn purge_expired(&self) -> usize {
+        let mut data = self.data.write().unwrap();
+        let mut lru_queue = self.lru_queue.lock().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
+        
+        let expired_keys: Vec<K> = data
+            .iter()
+            .filter(|(_, entry)| now > entry.expires_at(generation))
+            .map(|(key, _)| key.clone())
+            .collect();
+        
+        let config = self.config.load();
+        for key in &expired_keys {
+            data.remove(key);
+            lru_queue.retain(|k| k != key);
+            self.record_eviction(&config, key, EvictionReason::TTLExpired);
+        }
+        
+        expired_keys.len()
+    }
+    
+    // Keep only entries the predicate accepts; removals are traced as deletes
+    pub fn retain<F>(&self, mut keep: F) -> usize
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        let mut data = self.data.write().unwrap();
+        let mut lru_queue = self.lru_queue.lock().unwrap();
+        let config = self.config.load();
+        let now = clock::now();
         
-        if sql.starts_with("SELECT * FROM cache WHERE priority >") {
-            let priority_threshold: u8 = sql
-                .split('>')
-                .last()
-                .and_then(|s| s.trim().parse().ok())
-                .unwrap_or(5);
+        let removed_keys: Vec<K> = data
+            .iter()
+            .filter(|(key, entry)| !keep(key, &entry.value, &entry.metadata(&config, now, entry.last_accessed)))
+            .map(|(key, _)| key.clone())
+            .collect();
+        
+        let config = self.config.load();
+        for key in &removed_keys {
+            data.remove(key);
             
-            let entries: Vec<QueryEntry> = data
-                .iter()
-                .filter(|(_, entry)| entry.priority > priority_threshold)
-                .map(|(key, entry)| QueryEntry {
-                    key: key.to_string(),
-                    priority: entry.priority,
-                    access_count: entry.access_count,
-                    age_secs: entry.created_at.elapsed().as_secs(),
-                    ttl_remaining_secs: entry.ttl.duration_since(Instant::now())
-                        .map(|d| d.as_secs() as i64)
-                        .unwrap_or(-1),
+            if config.enable_trace_log {
+                self.trace_log.lock().unwrap().record(CacheOperation::Delete {
+                    key: self.format_key(key),
+                });
+            }
+        }
+        lru_queue.retain(|k| data.contains_key(k));
+        self.publish_index(&mut data, &mut lru_queue);
+        
+        removed_keys.len()
+    }
+    
+    // Empty the cache and hand back the live values, least recently used first
+    pub fn drain(&self) -> Vec<(K, V)> {
+        let mut data = self.data.write().unwrap();
+        let mut lru_queue = self.lru_queue.lock().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
+        
+        let drained: Vec<(K, V)> = lru_queue
+            .drain(..)
+            .filter_map(|key| data.remove(&key).map(|entry| (key, entry)))
+            .filter(|(_, entry)| now <= entry.expires_at(generation))
+            .map(|(key, entry)| (key, entry.value))
+            .collect();
+        data.clear();
+        self.eviction_heap.lock().unwrap().clear();
+        self.publish_index(&mut data, &mut lru_queue);
+        
+        let config = self.config.load();
+        if config.enable_trace_log {
+            let mut trace_log = self.trace_log.lock().unwrap();
+            for (key, _) in &drained {
+                trace_log.record(CacheOperation::Delete { key: self.format_key(key) });
+            }
+        }
+        
+        drained
+    }
+    
+    // Copy out live entries matching the predicate, with their remaining TTLs
+    pub fn export_where<F>(&self, predicate: F) -> Vec<ExportedEntry<K, V>>
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        Self::export_entries(&self.config.load(), &self.data.read().unwrap(), self.generation(), predicate)
+    }
+    
+    fn export_entries<F>(config: &CacheConfig, data: &HashMap<K, CacheEntry<V>>, generation: u64, mut predicate: F) -> Vec<ExportedEntry<K, V>>
+    where
+        F: FnMut(&K, &V, &EntryMetadata) -> bool,
+    {
+        let now = clock::now();
+        
+        data.iter()
+            .filter(|(_, entry)| now <= entry.expires_at(generation))
+            .filter_map(|(key, entry)| {
+                let meta = entry.metadata(config, now, entry.last_accessed);
+                predicate(key, &entry.value, &meta).then(|| ExportedEntry {
+                    key: key.clone(),
+                    value: entry.value.clone(),
+                    priority: meta.priority,
+                    ttl_remaining: meta.ttl_remaining,
+                    access_count: meta.access_count,
                 })
-                .collect();
+            })
+            .collect()
+    }
+    
+    // Load exported records through the normal put path (capacity and
+    // eviction rules apply), keeping their access counts. The conflict check
+    // and the write happen under one lock, so a concurrent put can't slip in
+    // between them. Returns how many records were written.
+    pub fn import(&self, entries: Vec<ExportedEntry<K, V>>, policy: ConflictPolicy) -> usize {
+        let mut imported = 0;
+        for record in entries {
+            if record.ttl_remaining.is_zero() {
+                continue;
+            }
+            let ttl = Some(record.ttl_remaining);
+            if self.write(record.key, record.value, ttl, record.priority, record.access_count, policy.into()).is_ok() {
+                imported += 1;
+            }
+        }
+        imported
+    }
+    
+    // Bulk-load a CSV or JSON seed file before traffic arrives
+    pub fn warm_from_file<P: AsRef<Path>>(&self, path: P) -> Result<WarmupReport, WarmupError>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        self.warm_from_file_with_progress(path, |_, _| {})
+    }
+    
+    // Same as warm_from_file, reporting (loaded, total) after every batch.
+    // Capacity is only enforced once, after the last batch.
+    pub fn warm_from_file_with_progress<P, F>(&self, path: P, mut progress: F) -> Result<WarmupReport, WarmupError>
+    where
+        P: AsRef<Path>,
+        F: FnMut(usize, usize),
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let records = read_seed_file(path.as_ref()).inspect_err(|err| self.log_error("warm_from_file", err))?;
+        let total = records.len();
+        let entries = records
+            .into_iter()
+            .map(|record| (record.key, record.value, record.ttl_secs.map(Duration::from_secs), record.priority));
+        Ok(self.load_batches(entries, |loaded| progress(loaded, total)))
+    }
+    
+    // Insert many entries with one capacity pass and one stats update at the
+    // end instead of per-entry bookkeeping. Entries go in in batches, so lookups
+    // are not locked out for the whole load; later duplicates win.
+    pub fn bulk_load<I>(&self, entries: I) -> WarmupReport
+    where
+        I: IntoIterator<Item = BulkEntry<K, V>>,
+    {
+        self.load_batches(entries.into_iter(), |_| {})
+    }
+    
+    fn load_batches<I>(&self, entries: I, mut progress: impl FnMut(usize)) -> WarmupReport
+    where
+        I: Iterator<Item = BulkEntry<K, V>>,
+    {
+        let mut entries = entries.peekable();
+        let mut loaded = 0;
+        
+        while entries.peek().is_some() {
+            let batch: Vec<_> = entries.by_ref().take(WARMUP_BATCH).collect();
+            loaded += batch.len();
+            self.insert_unchecked(batch);
+            progress(loaded);
+        }
+        
+        self.stats.insertions.add(loaded as u64);
+        let evicted = self.evict_to_capacity();
+        WarmupReport { loaded, evicted }
+    }
+    
+    // Insert without per-entry capacity checks; pair with evict_to_capacity
+    fn insert_unchecked(&self, batch: Vec<BulkEntry<K, V>>) {
+        let config = self.config.load();
+        let mut data = self.data.write().unwrap();
+        let mut lru_queue = self.lru_queue.lock().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
+        let mut eviction_heap = self.eviction_heap.lock().unwrap();
+        let mut replaced_existing = false;
+        
+        for (key, value, ttl, priority) in batch {
+            let ttl = ttl.unwrap_or(config.default_ttl);
+            if config.enable_trace_log {
+                self.trace_log.lock().unwrap().record(CacheOperation::Put {
+                    key: self.format_key(&key),
+                    priority,
+                    ttl_secs: ttl.as_secs(),
+                });
+            }
             
-            QueryResult::Entries(entries)
-        } else if sql.starts_with("SELECT COUNT(*) FROM cache") {
-            QueryResult::Count(data.len())
+            let mut entry = CacheEntry {
+                value,
+                priority: priority.clamp(1, 10),
+                ttl: now + ttl,
+                created_at: now,
+                last_accessed: now,
+                access_count: 0,
+                heap_stamp: now,
+                generation,
+            };
+            eviction_heap.push(key.clone(), &mut entry);
+            replaced_existing |= data.insert(key.clone(), entry).is_some();
+            lru_queue.push_back(key);
+        }
+        
+        // Keep only the most recent position of keys that were loaded twice
+        if replaced_existing {
+            let mut seen = HashSet::new();
+            let mut deduped: VecDeque<K> = lru_queue.drain(..).rev().filter(|k| seen.insert(k.clone())).collect();
+            deduped.make_contiguous().reverse();
+            *lru_queue = Shared::new(deduped);
+        }
+        eviction_heap.rebuild_if_sparse(&mut data);
+        drop(eviction_heap);
+        self.publish_index(&mut data, &mut lru_queue);
+    }
+    
+    // One eviction pass in eviction_order
+    fn evict_to_capacity(&self) -> usize {
+        let config = self.config.load();
+        let mut data = self.data.write().unwrap();
+        let mut lru_queue = self.lru_queue.lock().unwrap();
+        let excess = data.len().saturating_sub(config.max_capacity);
+        if excess == 0 || self.is_maintenance_paused() {
+            return 0;
+        }
+        
+        let now = clock::now();
+        let generation = self.generation();
+        let mut scored = Self::eviction_order(&config, &data, &lru_queue, now, generation);
+        scored.truncate(excess);
+        
+        for (_, _, _, key) in &scored {
+            let Some(entry) = data.remove(key) else {
+                continue;
+            };
+            if let Some(tuning) = &config.capacity_tuning {
+                self.tuner.lock().unwrap().record_eviction(key.clone(), tuning.step);
+            }
+            self.record_eviction(&config, key, entry.eviction_reason(now, generation));
+        }
+        lru_queue.retain(|k| data.contains_key(k));
+        self.publish_index(&mut data, &mut lru_queue);
+        
+        self.stats.evictions.add(scored.len() as u64);
+        scored.len()
+    }
+    
+    // The `n` entries capacity eviction would take next, each with the score
+    // explain_eviction reports (highest goes first). Nothing is evicted.
+    pub fn next_eviction_candidates(&self, n: usize) -> Vec<(K, f64)> {
+        let config = self.config.load();
+        let data = self.data.read().unwrap();
+        let lru_queue = self.lru_queue.lock().unwrap();
+        
+        let mut scored = Self::eviction_order(&config, &data, &lru_queue, clock::now(), self.generation());
+        scored.truncate(n);
+        scored.into_iter().map(|(score, _, _, key)| (key, score)).collect()
+    }
+    
+    // The evict_with_explanation score (age / priority), highest first; lower
+    // priority and then older entries first among equal scores
+    fn eviction_order(
+        config: &CacheConfig,
+        data: &HashMap<K, CacheEntry<V>>,
+        lru_queue: &VecDeque<K>,
+        now: Instant,
+        generation: u64,
+    ) -> Vec<(f64, u8, usize, K)> {
+        let mut scored: Vec<(f64, u8, usize, K)> = lru_queue
+            .iter()
+            .enumerate()
+            .filter_map(|(position, key)| {
+                let entry = data.get(key)?;
+                Some((entry.eviction_score(config, now, generation), entry.priority, position, key.clone()))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+        scored
+    }
+    
+    // ===== SQL-like Query Interface =====
+    // SELECT *, SELECT COUNT(*) or SELECT with a column list (e.g. `key,
+    // access_count`) FROM cache, optionally with a WHERE clause (see query.rs);
+    // anything else returns hit/miss stats
+    pub fn query(&self, sql: &str) -> QueryResult {
+        let sql = sql.trim();
+        let (projection, clause) = if let Some(clause) = strip_prefix_ignore_case(sql, "SELECT * FROM cache") {
+            (Projection::All, clause)
+        } else if let Some(clause) = strip_prefix_ignore_case(sql, "SELECT COUNT(*) FROM cache") {
+            (Projection::Count, clause)
+        } else if let Some((columns, clause)) = split_select(sql) {
+            match parse_columns(columns) {
+                Ok(columns) => (Projection::Columns(columns), clause),
+                Err(msg) => return QueryResult::Error(msg),
+            }
         } else {
-            let stats = self.stats.read().unwrap();
+            let stats = self.get_stats();
             let mut stats_map = HashMap::new();
             stats_map.insert("hits".to_string(), stats.hits as f64);
             stats_map.insert("misses".to_string(), stats.misses as f64);
-            QueryResult::Stats(stats_map)
+            return QueryResult::Stats(stats_map);
+        };
+        let predicate = match parse_where(clause) {
+            Ok(predicate) => predicate,
+            Err(msg) => return QueryResult::Error(msg),
+        };
+        
+        let data = self.data.read().unwrap();
+        if matches!(projection, Projection::Count) && predicate.is_none() {
+            return QueryResult::Count(data.len());
+        }
+        let config = self.config.load();
+        let now = clock::now();
+        let generation = self.generation();
+        let rows = data
+            .iter()
+            .map(|(key, entry)| EntryRow {
+                key,
+                entry,
+                config: &config,
+                now,
+                generation,
+                key_formatter: &self.key_formatter,
+                formatted: std::cell::OnceCell::new(),
+            })
+            .filter(|row| predicate.as_ref().is_none_or(|predicate| predicate.matches(row)));
+        
+        match projection {
+            Projection::All => QueryResult::Entries(rows.map(EntryRow::into_entry).collect()),
+            Projection::Count => QueryResult::Count(rows.count()),
+            Projection::Columns(columns) => QueryResult::Rows {
+                rows: rows.map(|row| columns.iter().map(|column| column.value(&row)).collect()).collect(),
+                columns: columns.iter().map(|column| column.name().to_string()).collect(),
+            },
         }
     }
     
@@ -310,7 +1861,7 @@ where
         let lru_queue = self.lru_queue.lock().unwrap();
         
         let mut explanation = EvictionExplanation {
-            key: key.to_string(),
+            key: self.format_key(key),
             would_be_evicted: false,
             reason: String::new(),
             priority_score: 0.0,
@@ -319,16 +1870,16 @@ where
         };
         
         if let Some(entry) = data.get(key) {
-            let age_secs = entry.last_accessed.elapsed().as_secs() as f64;
-            explanation.priority_score = age_secs / entry.priority as f64;
+            let generation = self.generation();
+            explanation.priority_score = entry.eviction_score(&self.config.load(), clock::now(), generation);
             
             explanation.lru_position = lru_queue
                 .iter()
                 .position(|k| k == key)
                 .unwrap_or(usize::MAX);
             
-            explanation.ttl_remaining_secs = entry.ttl
-                .duration_since(Instant::now())
+            explanation.ttl_remaining_secs = entry.expires_at(generation)
+                .checked_duration_since(clock::now())
                 .map(|d| d.as_secs() as i64)
                 .unwrap_or(-1);
             
@@ -351,67 +1902,344 @@ where
     
     // ===== Operation Replay for Debugging =====
     pub fn get_trace_log(&self) -> Vec<CacheOperation> {
-        self.trace_log.lock().unwrap().to_vec()
+        self.trace_log.lock().unwrap().iter().map(|record| record.op.clone()).collect()
+    }
+    
+    // Matching operations oldest first, selected under the lock so only the
+    // matches are copied
+    pub fn get_trace_filtered(&self, filter: &TraceFilter) -> Vec<CacheOperation> {
+        let trace_log = self.trace_log.lock().unwrap();
+        let mut matches: Vec<CacheOperation> = trace_log
+            .iter()
+            .rev()
+            .filter(|record| filter.matches(record))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .map(|record| record.op.clone())
+            .collect();
+        matches.reverse();
+        matches
     }
     
     // ===== Hot Configuration Reload =====
-    pub fn reload_config(&self, new_config: CacheConfig) {
-        self.config.store(Arc::new(new_config));
+    // All or nothing: an invalid config is rejected before any of it takes effect.
+    // The replaced config is kept for rollback_config.
+    pub fn reload_config(&self, new_config: CacheConfig) -> Result<(), ConfigError> {
+        new_config.validate().inspect_err(|err| self.log_error("reload_config", err))?;
+        let mut history = self.config_history.lock().unwrap();
+        let previous = self.apply_config(new_config);
+        if history.len() == CONFIG_HISTORY_LIMIT {
+            history.remove(0);
+        }
+        history.push(previous);
+        Ok(())
     }
     
-    pub fn get_stats(&self) -> CacheStats {
-        self.stats.read().unwrap().clone()
+    // Configs replaced by reload_config, oldest first
+    pub fn config_history(&self) -> Vec<CacheConfig> {
+        self.config_history
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|config| CacheConfig::clone(config))
+            .collect()
     }
     
-    fn evict_with_explanation(
-        &self,
-        data: &mut HashMap<K, CacheEntry<V>>,
-        lru_queue: &mut VecDeque<K>,
-    ) {
-        let mut eviction_candidate: Option<(K, f64)> = None;
+    // Reinstate the config in force before the latest reload
+    pub fn rollback_config(&self) -> Result<(), ConfigError> {
+        let mut history = self.config_history.lock().unwrap();
+        let previous = history
+            .pop()
+            .ok_or(ConfigError::NoHistory)
+            .inspect_err(|err| self.log_error("rollback_config", err))?;
+        self.apply_config(CacheConfig::clone(&previous));
+        Ok(())
+    }
+    
+    // Swap in a validated config and bring the cache in line with it. A lower
+    // max_capacity is enforced right away by a background eviction pass rather
+    // than waiting for the next put. Returns the replaced config.
+    fn apply_config(&self, new_config: CacheConfig) -> Arc<CacheConfig> {
+        let previous = self.config.swap(Arc::new(new_config));
+        let config = self.config.load();
         
-        for key in lru_queue.iter() {
-            if let Some(entry) = data.get(key) {
-                let age = entry.last_accessed.elapsed().as_secs() as f64;
-                let score = age / entry.priority as f64;
-                
-                match &eviction_candidate {
-                    None => eviction_candidate = Some((key.clone(), score)),
-                    Some((_, current_score)) if score > *current_score => {
-                        eviction_candidate = Some((key.clone(), score));
-                    }
-                    _ => {}
-                }
-            }
+        if config.trace_log_capacity != previous.trace_log_capacity {
+            self.trace_log.lock().unwrap().resize(config.trace_log_capacity);
         }
-        
-        if let Some((key, score)) = eviction_candidate {
-            data.remove(&key);
-            lru_queue.retain(|k| k != &key);
-            
-            let config = self.config.load();
+        #[cfg(feature = "log")]
+        if let Some(logger) = &self.logger {
+            logger.config_reload(&config_changes(&previous, &config));
+        }
+        if previous.enable_trace_log || config.enable_trace_log {
+            self.trace_log.lock().unwrap().record(CacheOperation::ConfigChanged {
+                changes: config_changes(&previous, &config),
+            });
+        }
+        if config.max_capacity < previous.max_capacity {
             if config.enable_trace_log {
-                self.trace_log.lock().unwrap().push(CacheOperation::Eviction {
-                    key: key.to_string(),
-                    reason: EvictionReason::LowPriority { score },
+                self.trace_log.lock().unwrap().record(CacheOperation::Resize {
+                    from: previous.max_capacity,
+                    to: config.max_capacity,
                 });
             }
-            
-            self.stats.write().unwrap().evictions += 1;
+            if self.size() > config.max_capacity {
+                let cache = self.share();
+                thread::spawn(move || cache.evict_to_capacity());
+            }
         }
+        previous
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
     
-    #[test]
-    fn test_sql_query() {
-        let cache = SmartCache::new(10);
+    // Latest capacity-tuning evaluation; None until a full window has been observed
+    pub fn capacity_recommendation(&self) -> Option<CapacityRecommendation> {
+        self.tuner.lock().unwrap().last.clone()
+    }
+    
+    // Runs after each lookup with no cache locks held, since a shrink evicts
+    fn tune_capacity(&self) {
+        let config = self.config.load();
+        let Some(tuning) = &config.capacity_tuning else {
+            return;
+        };
+        let Some(recommendation) = self.tuner.lock().unwrap().evaluate(tuning, config.max_capacity) else {
+            return;
+        };
+        if !tuning.auto_apply || recommendation.recommended_capacity == recommendation.current_capacity {
+            return;
+        }
         
-        cache.put(1, "high", None, 8);
-        cache.put(2, "medium", None, 5);
+        self.config.rcu(|current| CacheConfig {
+            max_capacity: recommendation.recommended_capacity,
+            ..(**current).clone()
+        });
+        if config.enable_trace_log {
+            self.trace_log.lock().unwrap().record(CacheOperation::Resize {
+                from: recommendation.current_capacity,
+                to: recommendation.recommended_capacity,
+            });
+        }
+        self.evict_to_capacity();
+    }
+    
+    pub fn get_stats(&self) -> CacheStats {
+        self.stats.snapshot()
+    }
+    
+    // A pass over every entry, so unlike get_stats this is not free; a growing
+    // `expired` count means cleanup is falling behind
+    pub fn ttl_distribution(&self) -> TtlDistribution {
+        let now = clock::now();
+        let generation = self.generation();
+        let mut distribution = TtlDistribution::default();
+        for entry in self.data.read().unwrap().values() {
+            distribution.record(entry.expires_at(generation).checked_duration_since(now));
+        }
+        distribution
+    }
+    
+    // Takes the read lock for the copy, so the view is consistent
+    pub fn freeze(&self) -> FrozenCache<K, V> {
+        let data = self.data.read().unwrap();
+        let now = clock::now();
+        let generation = self.generation();
+        let entries = data
+            .iter()
+            .filter(|(_, entry)| now <= entry.expires_at(generation))
+            .map(|(key, entry)| {
+                let frozen = FrozenEntry {
+                    value: entry.value.clone(),
+                    priority: entry.priority,
+                    expires_at: entry.ttl,
+                };
+                (key.clone(), frozen)
+            })
+            .collect();
+        FrozenCache::new(now, entries)
+    }
+    
+    // Decay and boost make scores depend on more than idle time and priority, so
+    // with either configured the victim is found by scanning the LRU queue
+    fn evict_with_explanation(
+        &self,
+        data: &mut HashMap<K, CacheEntry<V>>,
+        lru_queue: &mut VecDeque<K>,
+    ) {
+        let now = clock::now();
+        let generation = self.generation();
+        let config = self.config.load();
+        let eviction_candidate = if config.priority_decay.is_none() && config.priority_boost.is_none() {
+            self.eviction_heap.lock().unwrap().pop_victim(&config, data, now, generation)
+        } else {
+            Self::scan_victim(&config, data, lru_queue, now, generation)
+        };
+        
+        if let Some(key) = eviction_candidate {
+            let Some(entry) = data.remove(&key) else {
+                return;
+            };
+            // Victims mostly sit near the front
+            if let Some(position) = lru_queue.iter().position(|k| k == &key) {
+                lru_queue.remove(position);
+            }
+            
+            let config = self.config.load();
+            if let Some(tuning) = &config.capacity_tuning {
+                self.tuner.lock().unwrap().record_eviction(key.clone(), tuning.step);
+            }
+            self.record_eviction(&config, &key, entry.eviction_reason(clock::now(), generation));
+            
+            self.stats.evictions.add(1);
+        }
+    }
+    
+    // The highest score, the first in LRU order among equal scores
+    fn scan_victim(
+        config: &CacheConfig,
+        data: &HashMap<K, CacheEntry<V>>,
+        lru_queue: &VecDeque<K>,
+        now: Instant,
+        generation: u64,
+    ) -> Option<K> {
+        let mut eviction_candidate: Option<(&K, f64)> = None;
+        for key in lru_queue.iter() {
+            if let Some(entry) = data.get(key) {
+                let score = entry.eviction_score(config, now, generation);
+                match eviction_candidate {
+                    Some((_, current_score)) if score <= current_score => {}
+                    _ => eviction_candidate = Some((key, score)),
+                }
+            }
+        }
+        eviction_candidate.map(|(key, _)| key.clone())
+    }
+}
+
+// ===== Snapshots =====
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + Serialize + DeserializeOwned + 'static,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    // Write all live entries to `path` (gzip-compressed when it ends in .gz)
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<usize, SnapshotError> {
+        let entries = self.export_where(|_, _, _| true);
+        let count = entries.len();
+        write_snapshot(path.as_ref(), &Snapshot::new(entries))?;
+        Ok(count)
+    }
+    
+    // Restore a snapshot through import(); TTLs continue from where they were saved.
+    // Records failing their checksum are skipped and listed in the report.
+    pub fn load_snapshot<P: AsRef<Path>>(&self, path: P, policy: ConflictPolicy) -> Result<SnapshotLoadReport, SnapshotError> {
+        let (snapshot, verification) = read_snapshot_lossy(path.as_ref())?;
+        Ok(SnapshotLoadReport {
+            loaded: self.import(snapshot.entries, policy),
+            verification,
+        })
+    }
+    
+    // Start the background thread described by `config.snapshot_schedule`,
+    // handing the outcome of every run to `on_event`. Returns false when no
+    // schedule is configured or the thread already runs. Each run uses the config
+    // current at the time, and the thread stops on its next tick once the cache
+    // has been dropped.
+    pub fn start_snapshot_scheduler<F>(&self, mut on_event: F) -> bool
+    where
+        F: FnMut(SnapshotEvent) + Send + 'static,
+    {
+        let Some(schedule) = self.config.load().snapshot_schedule.clone() else {
+            return false;
+        };
+        let mut handle = self.snapshot_handle.lock().unwrap();
+        if handle.is_some() {
+            return false;
+        }
+        
+        let config = Arc::clone(&self.config);
+        let data = Arc::downgrade(&self.data);
+        let generation = Arc::clone(&self.generation);
+        let spawned = thread::Builder::new()
+            .name("cache-snapshots".to_string())
+            .spawn(move || loop {
+                thread::sleep(schedule.interval);
+                let Some(data) = data.upgrade() else {
+                    break;
+                };
+                let generation = generation.load(Ordering::Acquire);
+                let entries = Self::export_entries(&config.load(), &data.read().unwrap(), generation, |_, _, _| true);
+                drop(data);
+                on_event(snapshot::write_scheduled_snapshot(&schedule, entries));
+            })
+            .expect("failed to spawn snapshot scheduler");
+        *handle = Some(spawned);
+        true
+    }
+}
+
+// ===== Memory Accounting =====
+impl<V: Clone + MemSize> MemSize for CacheEntry<V> {
+    fn heap_size(&self) -> usize {
+        self.value.heap_size()
+    }
+}
+
+impl<V: MemSize> MemSize for IndexedEntry<V> {
+    fn heap_size(&self) -> usize {
+        self.value.heap_size()
+    }
+}
+
+impl<K: MemSize> MemSize for EvictionHeap<K> {
+    fn heap_size(&self) -> usize {
+        self.by_priority.capacity() * std::mem::size_of::<BinaryHeap<HeapItem<K>>>()
+            + self
+                .by_priority
+                .iter()
+                .map(|heap| {
+                    heap.capacity() * std::mem::size_of::<HeapItem<K>>() + heap.iter().map(|item| item.key.heap_size()).sum::<usize>()
+                })
+                .sum::<usize>()
+    }
+}
+
+impl<K, V> SmartCache<K, V>
+where
+    K: Clone + Eq + std::hash::Hash + Send + Sync + MemSize + 'static,
+    V: Clone + Send + Sync + MemSize + 'static,
+{
+    // Approximate RAM held by the cache: the entry table, LRU queue and eviction
+    // heaps including their unused capacity, plus what each key and value own per
+    // MemSize. The read index holds its own copies of the values, so with
+    // read_fast_path on they count twice. A pass over everything under the locks;
+    // tables still shared with a fork are counted by both caches.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        let data = self.data.read().unwrap();
+        let lru_queue = self.lru_queue.lock().unwrap();
+        let eviction_heap = self.eviction_heap.lock().unwrap();
+        HashMap::heap_size(&data)
+            + VecDeque::heap_size(&lru_queue)
+            + EvictionHeap::heap_size(&eviction_heap)
+            + HashMap::heap_size(&self.index.load())
+    }
+    
+    // get_stats plus estimated_memory_bytes
+    pub fn get_stats_with_memory(&self) -> CacheStats {
+        CacheStats {
+            estimated_memory_bytes: Some(self.estimated_memory_bytes()),
+            ..self.get_stats()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_sql_query() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "high", None, 8);
+        cache.put(2, "medium", None, 5);
         cache.put(3, "low", None, 2);
         
         match cache.query("SELECT * FROM cache WHERE priority > 4") {
@@ -434,13 +2262,989 @@ mod tests {
     }
     
     #[test]
-    fn test_hot_reload() {
+    fn test_eviction_heap_matches_scan() {
         let cache = SmartCache::new(100);
+        for key in 0..40 {
+            cache.put(key, key, None, (key % 10 + 1) as u8);
+        }
+        // Back-date accesses so scores differ by whole seconds
+        {
+            let mut data = cache.data.write().unwrap();
+            for (key, entry) in data.iter_mut() {
+                entry.last_accessed -= Duration::from_secs((key * 7 % 30) as u64);
+            }
+            cache.eviction_heap.lock().unwrap().rebuild(&mut data);
+        }
+        for key in (0..40).step_by(3) {
+            cache.get(&key);
+        }
+        for key in (0..40).step_by(5) {
+            cache.delete(&key);
+        }
+        for key in (1..40).step_by(4) {
+            cache.put(key, key, None, 2);
+        }
         
-        let mut new_config = CacheConfig::default();
-        new_config.max_capacity = 500;
-        cache.reload_config(new_config);
+        let config = cache.config.load();
+        let mut data = cache.data.write().unwrap();
+        let mut lru_queue = cache.lru_queue.lock().unwrap();
+        let mut eviction_heap = cache.eviction_heap.lock().unwrap();
+        let now = Instant::now();
+        while let Some(expected) = SmartCache::scan_victim(&config, &data, &lru_queue, now, 0) {
+            assert_eq!(eviction_heap.pop_victim(&config, &mut data, now, 0), Some(expected));
+            data.remove(&expected);
+            lru_queue.retain(|k| k != &expected);
+        }
+        assert_eq!(eviction_heap.pop_victim(&config, &mut data, now, 0), None);
+    }
+    
+    #[test]
+    fn test_hot_reload() {
+        let cache: SmartCache<i32, &str> = SmartCache::new(100);
+        
+        let new_config = CacheConfig {
+            max_capacity: 500,
+            ..Default::default()
+        };
+        cache.reload_config(new_config).unwrap();
         
         assert_eq!(cache.config.load().max_capacity, 500);
     }
+    
+    #[test]
+    fn test_get_with_metadata() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "value1", Some(Duration::from_secs(60)), 4);
+        
+        let (value, meta) = cache.get_with_metadata(&1).unwrap();
+        assert_eq!(value, "value1");
+        assert_eq!(meta.priority, 4);
+        assert_eq!(meta.access_count, 1);
+        assert!(meta.ttl_remaining > Duration::from_secs(59));
+        assert!(cache.get_with_metadata(&2).is_none());
+        
+        let trace = cache.get_trace_log();
+        assert_eq!(trace.len(), 3); // Put, hit, miss
+    }
+    
+    #[test]
+    fn test_contains_key_has_no_side_effects() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "value1", None, 5);
+        
+        assert!(cache.contains_key(&1));
+        assert!(!cache.contains_key(&2));
+        
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 0);
+        assert_eq!(cache.get_trace_log().len(), 1); // Only the put
+    }
+    
+    #[test]
+    fn test_purge_expired() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "short", Some(Duration::from_millis(20)), 5);
+        cache.put(2, "long", None, 5);
+        
+        assert_eq!(cache.purge_expired(), 0);
+        clock::advance(Duration::from_millis(40));
+        assert_eq!(cache.purge_expired(), 1);
+        assert!(cache.contains_key(&2));
+        assert!(matches!(
+            cache.get_trace_log().last(),
+            Some(CacheOperation::Eviction { reason: EvictionReason::TTLExpired, .. })
+        ));
+    }
+    
+    #[test]
+    fn test_retain() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "keep", None, 8);
+        cache.put(2, "drop", None, 2);
+        cache.put(3, "drop", None, 3);
+        
+        assert_eq!(cache.retain(|_, value, _| *value == "keep"), 2);
+        assert!(cache.contains_key(&1));
+        assert_eq!(cache.lru_queue.lock().unwrap().len(), 1);
+        
+        let deletes = cache
+            .get_trace_log()
+            .into_iter()
+            .filter(|op| matches!(op, CacheOperation::Delete { .. }))
+            .count();
+        assert_eq!(deletes, 2);
+    }
+    
+    #[test]
+    fn test_drain() {
+        let cache = SmartCache::new(10);
+        
+        cache.put(1, "one", None, 5);
+        cache.put(2, "two", None, 5);
+        cache.get(&1);
+        
+        assert_eq!(cache.drain(), vec![(2, "two"), (1, "one")]);
+        assert!(!cache.contains_key(&1));
+        assert!(cache.drain().is_empty());
+    }
+    
+    #[test]
+    fn test_export_import() {
+        let source = SmartCache::new(10);
+        source.put("a".to_string(), 1, None, 5);
+        source.put("b".to_string(), 2, None, 5);
+        source.get(&"b".to_string());
+        
+        let json = serde_json::to_string(&source.export_where(|_, value, _| *value > 1)).unwrap();
+        let records: Vec<ExportedEntry<String, i32>> = serde_json::from_str(&json).unwrap();
+        
+        let target = SmartCache::new(10);
+        target.put("b".to_string(), 20, None, 5);
+        assert_eq!(target.import(records.clone(), ConflictPolicy::KeepHigherPriority), 0);
+        assert_eq!(target.import(records.clone(), ConflictPolicy::Overwrite), 1);
+        // The read before the export, plus this one
+        let (value, meta) = target.get_with_metadata(&"b".to_string()).unwrap();
+        assert_eq!((value, meta.access_count), (2, 2));
+        
+        // Only records actually stored are counted
+        let full = SmartCache::new(1);
+        full.put("other".to_string(), 0, None, 5);
+        full.pause_maintenance();
+        assert_eq!(full.import(records, ConflictPolicy::Overwrite), 0);
+    }
+    
+    fn write_seed_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}_{}_{}", env!("CARGO_PKG_NAME"), std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+    
+    #[test]
+    fn test_warm_from_file() {
+        let csv = write_seed_file("seed.csv", "key,value,ttl_secs,priority\na,1,60,9\nb,2,,1\nc,3,,5\n");
+        let json = write_seed_file("seed.json", r#"[{"key": "d", "value": 4}, {"key": "e", "value": 5, "priority": 2}]"#);
+        
+        let cache: SmartCache<String, i32> = SmartCache::new(4);
+        let mut progress = Vec::new();
+        let report = cache.warm_from_file_with_progress(&csv, |loaded, total| progress.push((loaded, total))).unwrap();
+        assert_eq!(report, WarmupReport { loaded: 3, evicted: 0 });
+        assert_eq!(progress, vec![(3, 3)]);
+        
+        // Over capacity by one: the single eviction pass drops the lowest priority entry
+        let report = cache.warm_from_file(&json).unwrap();
+        assert_eq!(report, WarmupReport { loaded: 2, evicted: 1 });
+        assert!(!cache.contains_key(&"b".to_string()));
+        
+        let (value, meta) = cache.get_with_metadata(&"a".to_string()).unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(meta.priority, 9);
+        assert!(meta.ttl_remaining <= Duration::from_secs(60));
+        
+        assert!(matches!(cache.warm_from_file("seed.txt"), Err(WarmupError::UnsupportedFormat(_))));
+        std::fs::remove_file(csv).unwrap();
+        std::fs::remove_file(json).unwrap();
+    }
+    
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}_{}", env!("CARGO_PKG_NAME"), std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let dir = temp_dir("snapshot_roundtrip");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("a".to_string(), "1".to_string(), Some(Duration::from_secs(30)), 8);
+        cache.put("b".to_string(), "2".to_string(), None, 2);
+        
+        for name in ["plain.json", "packed.json.gz"] {
+            let path = dir.join(name);
+            assert_eq!(cache.save_snapshot(&path).unwrap(), 2);
+            
+            let restored: SmartCache<String, String> = SmartCache::new(10);
+            let report = restored.load_snapshot(&path, ConflictPolicy::Overwrite).unwrap();
+            assert_eq!(report.loaded, 2);
+            assert!(report.verification.is_valid());
+            let (value, meta) = restored.get_with_metadata("a").unwrap();
+            assert_eq!(value, "1");
+            assert_eq!(meta.priority, 8);
+            assert!(meta.ttl_remaining <= Duration::from_secs(30));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_diff() {
+        let dir = temp_dir("snapshot_diff");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("a".to_string(), "1".to_string(), None, 5);
+        cache.put("b".to_string(), "2".to_string(), None, 5);
+        cache.save_snapshot(dir.join("first.json")).unwrap();
+        cache.save_snapshot(dir.join("second.json")).unwrap();
+        cache.put("b".to_string(), "20".to_string(), None, 5);
+        cache.save_snapshot(dir.join("third.json")).unwrap();
+        
+        let load = |name: &str| -> Snapshot<String, String> { read_snapshot(&dir.join(name)).unwrap() };
+        assert!(diff_snapshots(&load("first.json"), &load("second.json")).is_empty());
+        let diff = diff_snapshots(&load("second.json"), &load("third.json"));
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].key, "b");
+        assert!(diff.changed[0].value_changed);
+        assert_eq!(diff.changed[0].after.value, "20");
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_corruption_detection() {
+        let dir = temp_dir("snapshot_corruption");
+        let path = dir.join("damaged.json.gz");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.put("a".to_string(), "A".to_string(), None, 5);
+        cache.save_snapshot(&path).unwrap();
+        assert!(verify_snapshot(&path).unwrap().is_valid());
+        
+        // A truncated file is reported, not loaded
+        let raw = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &raw[..raw.len() / 2]).unwrap();
+        assert!(verify_snapshot(&path).is_err());
+        let restored: SmartCache<String, String> = SmartCache::new(10);
+        assert!(restored.load_snapshot(&path, ConflictPolicy::Overwrite).is_err());
+        assert_eq!(restored.size(), 0);
+        
+        // So is a file from an unknown format version
+        let plain = dir.join("future.json");
+        cache.save_snapshot(&plain).unwrap();
+        let mut raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&plain).unwrap()).unwrap();
+        raw["version"] = serde_json::json!(SNAPSHOT_VERSION + 1);
+        std::fs::write(&plain, raw.to_string()).unwrap();
+        assert!(matches!(
+            restored.load_snapshot(&plain, ConflictPolicy::Overwrite),
+            Err(SnapshotError::UnsupportedVersion(version)) if version == SNAPSHOT_VERSION + 1
+        ));
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[test]
+    fn test_snapshot_scheduler_retention() {
+        let dir = temp_dir("snapshot_scheduler");
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        cache.reload_config(CacheConfig {
+            snapshot_schedule: Some(SnapshotSchedule {
+                directory: dir.clone(),
+                interval: Duration::from_millis(20),
+                retain: 2,
+                compress: true,
+                encryption: None,
+            }),
+            ..Default::default()
+        }).unwrap();
+        cache.put("a".to_string(), "1".to_string(), None, 5);
+        
+        let (sender, events) = std::sync::mpsc::channel();
+        assert!(cache.start_snapshot_scheduler(move |event| {
+            let _ = sender.send(event);
+        }));
+        assert!(!cache.start_snapshot_scheduler(|_| {}));
+        for _ in 0..3 {
+            let event = events.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(matches!(event, SnapshotEvent::Saved { entries: 1, .. }));
+        }
+        
+        // The thread exits once the cache is gone, so no run can land between
+        // its write and its prune while the directory is listed
+        let scheduler = cache.snapshot_handle.lock().unwrap().take().unwrap();
+        drop(cache);
+        scheduler.join().unwrap();
+        let snapshots = list_snapshots(&dir).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        let latest: Snapshot<String, String> = read_snapshot(snapshots.last().unwrap()).unwrap();
+        assert_eq!(latest.entries.len(), 1);
+        
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    #[derive(Clone)]
+    struct Token {
+        expires_in: Option<Duration>,
+    }
+    
+    impl Expirable for Token {
+        fn ttl(&self) -> Option<Duration> {
+            self.expires_in
+        }
+    }
+    
+    #[test]
+    fn test_put_expirable() {
+        let cache: SmartCache<String, Token> = SmartCache::new(10);
+        let short = Token { expires_in: Some(Duration::from_millis(50)) };
+        
+        cache.put_expirable("own".to_string(), short.clone(), None, 5);
+        cache.put_expirable("explicit".to_string(), short, Some(Duration::from_secs(60)), 5);
+        cache.put_expirable("default".to_string(), Token { expires_in: None }, None, 5);
+        
+        clock::advance(Duration::from_millis(80));
+        assert!(cache.get(&"own".to_string()).is_none());
+        assert!(cache.get(&"explicit".to_string()).is_some());
+        assert!(cache.get(&"default".to_string()).is_some());
+    }
+    
+    #[test]
+    fn test_key_formatter_for_tuple_keys() {
+        let cache: SmartCache<(u32, &str), i32> =
+            SmartCache::with_key_formatter(10, |(id, region): &(u32, &str)| format!("{}@{}", id, region));
+        
+        cache.put((7, "eu"), 1, None, 5);
+        assert_eq!(cache.get(&(7, "eu")), Some(1));
+        assert_eq!(cache.explain_eviction(&(7, "eu")).key, "7@eu");
+        
+        let trace = cache.get_trace_log();
+        assert!(matches!(&trace[0], CacheOperation::Put { key, .. } if key == "7@eu"));
+    }
+    
+    #[test]
+    fn test_borrowed_key_lookups() {
+        let cache: SmartCache<String, i32> = SmartCache::new(10);
+        cache.put("alpha".to_string(), 1, None, 5);
+        
+        assert_eq!(cache.get("alpha"), Some(1));
+        assert_eq!(cache.get_with_metadata("alpha").map(|(_, meta)| meta.access_count), Some(2));
+        assert!(cache.contains_key("alpha"));
+        assert_eq!(cache.get("beta"), None);
+    }
+    
+    #[test]
+    fn test_capacity_tuning() {
+        let cache = SmartCache::new(4);
+        cache.reload_config(CacheConfig {
+            max_capacity: 4,
+            capacity_tuning: Some(CapacityTuning {
+                min_capacity: 2,
+                max_capacity: 8,
+                step: 2,
+                window: 6,
+                grow_threshold: 0.25,
+                shrink_threshold: 0.1,
+                auto_apply: true,
+            }),
+            ..Default::default()
+        }).unwrap();
+        
+        // A cyclic working set of 6 keys keeps missing on just-evicted keys
+        for key in 0..6 {
+            cache.put(key, key, None, 5);
+        }
+        for key in 0..6 {
+            if cache.get(&key).is_none() {
+                cache.put(key, key, None, 5);
+            }
+        }
+        let recommendation = cache.capacity_recommendation().unwrap();
+        assert!(recommendation.marginal_gain >= 0.25);
+        assert_eq!(recommendation.recommended_capacity, 6);
+        assert_eq!(cache.config.load().max_capacity, 6);
+        
+        // Hits only on the most recent entry: the tail is dead weight
+        for _ in 0..6 {
+            cache.get(&5);
+        }
+        assert_eq!(cache.capacity_recommendation().unwrap().recommended_capacity, 4);
+        assert_eq!(cache.config.load().max_capacity, 4);
+        assert!(cache.data.read().unwrap().len() <= 4);
+    }
+    
+    #[test]
+    fn test_read_fast_path() {
+        let cache = SmartCache::new(10);
+        cache.reload_config(CacheConfig {
+            max_capacity: 10,
+            read_fast_path: true,
+            ..Default::default()
+        }).unwrap();
+        cache.put("a".to_string(), 1, None, 5);
+        
+        // Hits are served while a writer holds the data lock
+        let guard = cache.data.write().unwrap();
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("a"), Some(1));
+        drop(guard);
+        assert_eq!(cache.get_stats().hits, 2);
+        
+        // The next write folds those hits back into the entry
+        cache.put("b".to_string(), 2, None, 5);
+        assert_eq!(cache.data.read().unwrap()["a"].access_count, 2);
+        assert_eq!(cache.lru_queue.lock().unwrap().back(), Some(&"a".to_string()));
+        
+        // Removals are published before the write returns
+        cache.retain(|key, _, _| key != "a");
+        assert_eq!(cache.get("a"), None);
+    }
+    
+    #[test]
+    fn test_striped_stats_across_threads() {
+        let cache = Arc::new(SmartCache::<String, String>::new(10));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        cache.get("missing");
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(cache.get_stats().misses, 8000);
+    }
+    
+    #[test]
+    fn test_delete_clear_and_size() {
+        let cache = SmartCache::new(10);
+        for i in 0..4 {
+            cache.put(i, "value", None, 5);
+        }
+        assert_eq!(cache.size(), 4);
+        
+        assert!(cache.delete(&1));
+        assert!(!cache.delete(&1));
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.size(), 3);
+        
+        cache.clear();
+        assert_eq!(cache.size(), 0);
+        assert_eq!(cache.get(&0), None);
+        
+        let deletes = cache
+            .get_trace_log()
+            .iter()
+            .filter(|op| matches!(op, CacheOperation::Delete { .. }))
+            .count();
+        assert_eq!(deletes, 4);
+    }
+    
+    #[test]
+    fn test_eviction_reasons() {
+        let cache = SmartCache::new(2);
+        cache.put(1, "expiring", Some(Duration::from_millis(10)), 5);
+        cache.put(2, "low", None, 1);
+        clock::advance(Duration::from_millis(20));
+        
+        // An expired entry read back is traced as a TTL expiration, not just a miss
+        assert_eq!(cache.get(&1), None);
+        assert!(matches!(
+            cache.get_trace_log().last(),
+            Some(CacheOperation::Eviction { reason: EvictionReason::TTLExpired, .. })
+        ));
+        
+        cache.put(3, "high", None, 9);
+        cache.put(4, "high", None, 9);
+        let evictions: Vec<_> = cache
+            .get_trace_log()
+            .into_iter()
+            .filter_map(|op| match op {
+                CacheOperation::Eviction { key, reason } => Some((key, reason)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(evictions.len(), 2);
+        assert!(matches!(
+            &evictions[1],
+            (key, EvictionReason::CapacityExceeded { victim_priority: 1 }) if key == "2"
+        ));
+    }
+    
+    #[test]
+    fn test_reload_config_shrinks() {
+        let cache = SmartCache::new(10);
+        for i in 0..10 {
+            cache.put(i, "value", None, 5);
+        }
+        
+        cache.reload_config(CacheConfig {
+            max_capacity: 4,
+            trace_log_capacity: 8,
+            ..Default::default()
+        }).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.size() > 4 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.size(), 4);
+        assert_eq!(cache.get_stats().evictions, 6);
+        
+        // The trace buffer now holds only the latest records (one slot stays free)
+        let trace = cache.get_trace_log();
+        assert_eq!(trace.len(), 7);
+        assert!(trace.iter().any(|op| matches!(op, CacheOperation::Resize { from: 10, to: 4 })));
+        assert!(matches!(
+            trace.last(),
+            Some(CacheOperation::Eviction { reason: EvictionReason::CapacityExceeded { .. }, .. })
+        ));
+    }
+    
+    #[test]
+    fn test_config_rollback() {
+        let cache: SmartCache<i32, &str> = SmartCache::new(100);
+        
+        // A rejected reload leaves the config and history untouched
+        let invalid = CacheConfig {
+            max_capacity: 0,
+            ..Default::default()
+        };
+        assert!(matches!(cache.reload_config(invalid), Err(ConfigError::Invalid(_))));
+        assert_eq!(cache.config.load().max_capacity, 100);
+        assert!(cache.config_history().is_empty());
+        
+        cache
+            .reload_config(CacheConfig {
+                max_capacity: 200,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(matches!(
+            cache.get_trace_log().last(),
+            Some(CacheOperation::ConfigChanged { changes })
+                if changes == &[ConfigFieldChange {
+                    field: "max_capacity".to_string(),
+                    old: "100".to_string(),
+                    new: "200".to_string(),
+                }]
+        ));
+        assert_eq!(cache.config_history().len(), 1);
+        
+        cache.rollback_config().unwrap();
+        assert_eq!(cache.config.load().max_capacity, 100);
+        assert!(cache.config_history().is_empty());
+        assert_eq!(cache.rollback_config(), Err(ConfigError::NoHistory));
+    }
+    
+    #[cfg(feature = "log")]
+    static LOGGED: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+    
+    #[cfg(feature = "log")]
+    struct CaptureLog;
+    
+    #[cfg(feature = "log")]
+    impl log::Log for CaptureLog {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "test_cache"
+        }
+        
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                assert!(record.key_values().get(log::kv::Key::from_str("event")).is_some());
+                LOGGED.lock().unwrap().push((record.level(), record.args().to_string()));
+            }
+        }
+        
+        fn flush(&self) {}
+    }
+    
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_operational_logging() {
+        log::set_logger(&CaptureLog).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+        let config = LogConfig {
+            expirations: None,
+            max_per_second: 3,
+            target: "test_cache",
+            ..Default::default()
+        };
+        let cache: SmartCache<i32, &str> = SmartCache::new(2).with_logging(config);
+        
+        // Four capacity evictions, one over the rate limit
+        for key in 0..6 {
+            cache.put(key, "v", None, 5);
+        }
+        cache.put(10, "v", Some(Duration::from_millis(1)), 5);
+        clock::advance(Duration::from_millis(5));
+        cache.purge_expired();
+        assert!(cache.reload_config(CacheConfig { max_capacity: 0, ..Default::default() }).is_err());
+        cache.reload_config(CacheConfig { max_capacity: 5, ..Default::default() }).unwrap();
+        
+        let logged = LOGGED.lock().unwrap();
+        let evictions: Vec<_> = logged.iter().filter(|(_, msg)| msg.contains(r#""event":"eviction""#)).collect();
+        assert_eq!(evictions.len(), 3);
+        assert!(evictions.iter().all(|(level, _)| *level == log::Level::Debug));
+        assert_eq!(
+            evictions[0].1,
+            r#"{"event":"eviction","key":"0","priority":5,"reason":"capacity_exceeded"}"#
+        );
+        assert!(!logged.iter().any(|(_, msg)| msg.contains("expiration")));
+        assert!(logged.iter().any(|(level, msg)| *level == log::Level::Warn
+            && msg.contains(r#""operation":"reload_config""#)));
+        assert!(logged.iter().any(|(level, msg)| *level == log::Level::Info
+            && msg.contains(r#""changed":"max_capacity""#)));
+    }
+    
+    #[test]
+    fn test_ttl_distribution() {
+        let cache = SmartCache::new(10);
+        cache.put(1, 1, Some(Duration::from_millis(1)), 5);
+        for (key, secs) in [(2, 30), (3, 300), (4, 1800), (5, 7200), (6, 7200)] {
+            cache.put(key, key, Some(Duration::from_secs(secs)), 5);
+        }
+        clock::advance(Duration::from_millis(10));
+        assert_eq!(
+            cache.ttl_distribution(),
+            TtlDistribution {
+                expired: 1,
+                under_1m: 1,
+                under_10m: 1,
+                under_1h: 1,
+                over_1h: 2,
+            }
+        );
+    }
+    
+    #[test]
+    fn test_query_predicates() {
+        let cache = SmartCache::new(10);
+        cache.put("user:1".to_string(), "a", None, 5);
+        cache.put("user:2".to_string(), "b", None, 5);
+        cache.put("session:1".to_string(), "c", Some(Duration::from_millis(10)), 2);
+        cache.get("user:1");
+        clock::advance(Duration::from_millis(20));
+        
+        let keys = |sql: &str| match cache.query(sql) {
+            QueryResult::Entries(entries) => {
+                let mut keys: Vec<String> = entries.into_iter().map(|entry| entry.key).collect();
+                keys.sort();
+                keys
+            }
+            other => panic!("expected entries, got {:?}", other),
+        };
+        assert_eq!(keys("SELECT * FROM cache WHERE access_count = 0"), ["session:1", "user:2"]);
+        assert_eq!(keys("SELECT * FROM cache WHERE key LIKE 'user:%'"), ["user:1", "user:2"]);
+        assert_eq!(keys("select * from cache where ttl_remaining < 0"), ["session:1"]);
+        assert_eq!(keys("SELECT * FROM cache WHERE age_secs >= 0").len(), 3);
+        assert!(matches!(
+            cache.query("SELECT COUNT(*) FROM cache WHERE priority <= 2"),
+            QueryResult::Count(1)
+        ));
+        assert!(matches!(
+            cache.query("SELECT * FROM cache WHERE colour = 3"),
+            QueryResult::Error(_)
+        ));
+    }
+    
+    #[test]
+    fn test_compound_query_predicates() {
+        let cache = SmartCache::new(10);
+        for i in 0..6 {
+            cache.put(i, "value", None, (i + 1) as u8);
+        }
+        cache.get(&0);
+        cache.get(&5);
+        
+        let count = |sql: &str| match cache.query(sql) {
+            QueryResult::Count(count) => count,
+            other => panic!("expected a count, got {:?}", other),
+        };
+        assert_eq!(count("SELECT COUNT(*) FROM cache WHERE priority < 3 AND access_count = 0"), 1);
+        // AND binds tighter than OR
+        assert_eq!(count("SELECT COUNT(*) FROM cache WHERE priority = 1 OR priority > 4 AND access_count = 0"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM cache WHERE (priority = 1 OR priority > 4) AND access_count = 0"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM cache WHERE NOT access_count = 0 AND NOT key LIKE '5'"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM cache WHERE NOT (priority > 2 OR key LIKE '0')"), 1);
+        assert!(matches!(
+            cache.query("SELECT * FROM cache WHERE (priority > 2"),
+            QueryResult::Error(_)
+        ));
+    }
+    
+    #[test]
+    fn test_query_column_projection() {
+        let cache = SmartCache::new(10);
+        cache.put("user:1".to_string(), "a", None, 5);
+        cache.put("user:2".to_string(), "b", None, 5);
+        cache.put("session:1".to_string(), "c", None, 2);
+        cache.get("user:1");
+        cache.get("user:1");
+        
+        match cache.query("SELECT key, access_count FROM cache WHERE key LIKE 'user:%'") {
+            QueryResult::Rows { columns, mut rows } => {
+                assert_eq!(columns, ["key", "access_count"]);
+                rows.sort_by_key(|row| format!("{:?}", row[0]));
+                assert_eq!(rows, [
+                    vec![QueryValue::Text("user:1".to_string()), QueryValue::Int(2)],
+                    vec![QueryValue::Text("user:2".to_string()), QueryValue::Int(0)],
+                ]);
+            }
+            other => panic!("expected rows, got {:?}", other),
+        }
+        match cache.query("select Priority, effective_priority from cache where priority < 3") {
+            QueryResult::Rows { columns, rows } => {
+                assert_eq!(columns, ["priority", "effective_priority"]);
+                assert_eq!(rows, [vec![QueryValue::Int(2), QueryValue::Float(2.0)]]);
+            }
+            other => panic!("expected rows, got {:?}", other),
+        }
+        assert!(matches!(cache.query("SELECT key, colour FROM cache"), QueryResult::Error(_)));
+        assert!(matches!(cache.query("SELECT key, FROM cache"), QueryResult::Error(_)));
+    }
+    
+    #[test]
+    fn test_trace_filtering() {
+        let cache = SmartCache::new(2);
+        cache.put("user:1".to_string(), "a", None, 5);
+        cache.put("user:2".to_string(), "b", None, 5);
+        clock::advance(Duration::from_millis(20));
+        let later = clock::now();
+        cache.put("session:1".to_string(), "c", None, 5);
+        cache.get("user:2");
+        
+        let evictions = cache.get_trace_filtered(&TraceFilter {
+            op_types: vec![OperationType::Eviction],
+            ..Default::default()
+        });
+        assert!(matches!(evictions.as_slice(), [CacheOperation::Eviction { key, .. }] if key == "user:1"));
+        
+        let users = cache.get_trace_filtered(&TraceFilter {
+            key_prefix: Some("user:".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(users.len(), 4);
+        
+        let recent = cache.get_trace_filtered(&TraceFilter {
+            since: Some(later),
+            ..Default::default()
+        });
+        assert_eq!(recent.len(), 3);
+        
+        let last = cache.get_trace_filtered(&TraceFilter {
+            op_types: vec![OperationType::Put, OperationType::Get],
+            limit: Some(2),
+            ..Default::default()
+        });
+        assert!(matches!(
+            last.as_slice(),
+            [CacheOperation::Put { key: put, .. }, CacheOperation::Get { key: get, hit: true }]
+                if put == "session:1" && get == "user:2"
+        ));
+    }
+    
+    #[test]
+    fn test_priority_decay() {
+        let decay = PriorityDecay { half_life: Duration::from_secs(60), floor: 2 };
+        assert_eq!(decay.apply(10, Duration::ZERO), 10.0);
+        assert_eq!(decay.apply(10, Duration::from_secs(60)), 6.0);
+        assert_eq!(decay.apply(10, Duration::from_secs(120)), 4.0);
+        assert_eq!(decay.apply(1, Duration::from_secs(600)), 1.0, "below the floor stays put");
+        
+        let cache: SmartCache<String, i32> = SmartCache::new(10);
+        let zero_half_life = CacheConfig {
+            priority_decay: Some(PriorityDecay { half_life: Duration::ZERO, floor: 1 }),
+            ..Default::default()
+        };
+        assert!(matches!(cache.reload_config(zero_half_life), Err(ConfigError::Invalid(_))));
+    }
+    
+    #[test]
+    fn test_priority_boost() {
+        let boost = PriorityBoost { min_rate: 5.0, max_boost: 4 };
+        assert_eq!(boost.apply(4, Duration::from_millis(100)), 0.0, "below min_rate");
+        assert_eq!(boost.apply(5, Duration::from_millis(100)), 1.0, "young entries count as a second old");
+        assert_eq!(boost.apply(20, Duration::from_secs(1)), 3.0);
+        assert_eq!(boost.apply(1000, Duration::from_secs(1)), 4.0, "capped at max_boost");
+        
+        let cache = SmartCache::new(10);
+        let config = CacheConfig { priority_boost: Some(boost), ..Default::default() };
+        assert!(cache.reload_config(config).is_ok());
+        cache.put("hot".to_string(), 1, None, 3);
+        cache.put("cold".to_string(), 2, None, 3);
+        for _ in 0..40 {
+            cache.get("hot");
+        }
+        let (_, meta) = cache.get_with_metadata("hot").unwrap();
+        assert_eq!(meta.priority, 3);
+        assert_eq!(meta.effective_priority, 7.0);
+        
+        match cache.query("SELECT * FROM cache WHERE effective_priority > 3") {
+            QueryResult::Entries(entries) => {
+                assert_eq!(entries.len(), 1);
+                assert_eq!((entries[0].key.as_str(), entries[0].priority, entries[0].effective_priority), ("hot", 3, 7.0));
+            }
+            other => panic!("expected entries, got {:?}", other),
+        }
+        
+        let zero_rate = CacheConfig {
+            priority_boost: Some(PriorityBoost { min_rate: 0.0, max_boost: 4 }),
+            ..Default::default()
+        };
+        assert!(matches!(cache.reload_config(zero_rate), Err(ConfigError::Invalid(_))));
+    }
+    
+    #[test]
+    fn test_next_eviction_candidates() {
+        let cache = SmartCache::new(10);
+        cache.put("a".to_string(), 1, None, 5);
+        cache.put("b".to_string(), 2, None, 2);
+        cache.put("c".to_string(), 3, None, 5);
+        
+        // Nothing has idled a whole second, so every score is 0 and ties fall back
+        // to priority, then LRU order
+        assert_eq!(
+            cache.next_eviction_candidates(2),
+            [("b".to_string(), 0.0), ("a".to_string(), 0.0)]
+        );
+        assert_eq!(cache.next_eviction_candidates(10).len(), 3);
+        assert_eq!(cache.size(), 3);
+    }
+    
+    #[test]
+    fn test_pause_maintenance() {
+        let cache = SmartCache::new(2);
+        cache.put("a".to_string(), 1, None, 5);
+        cache.put("b".to_string(), 2, None, 5);
+        cache.pause_maintenance();
+        assert!(cache.is_maintenance_paused());
+        assert!(!cache.put("c".to_string(), 3, None, 5), "no room is made for a new key");
+        assert!(cache.put("b".to_string(), 20, None, 5));
+        
+        // A shrink waits for resume instead of evicting in the background
+        let config = CacheConfig { max_capacity: 1, ..(*cache.config.load_full()).clone() };
+        assert!(cache.reload_config(config).is_ok());
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(cache.size(), 2);
+        
+        assert_eq!(cache.resume_maintenance(), 1);
+        assert_eq!(cache.size(), 1);
+    }
+    
+    #[test]
+    fn test_bulk_load() {
+        let cache = SmartCache::new(3);
+        let report = cache.bulk_load((0..5).map(|i| (i.to_string(), i * 10, None, if i == 4 { 1 } else { 5 })));
+        assert_eq!(report, WarmupReport { loaded: 5, evicted: 2 });
+        assert_eq!(cache.get_stats().insertions, 5);
+        
+        // The one capacity pass takes the low-priority entry, then the least recent
+        assert!(!cache.contains_key("4"));
+        assert!(!cache.contains_key("0"));
+        assert_eq!(cache.get("3"), Some(30));
+        assert_eq!(cache.size(), 3);
+    }
+
+    #[test]
+    fn test_freeze() {
+        let cache = SmartCache::new(10);
+        cache.put(1, "one".to_string(), None, 7);
+        cache.put(2, "two".to_string(), Some(Duration::from_millis(20)), 5);
+        cache.put(3, "three".to_string(), Some(Duration::from_millis(1)), 5);
+        clock::advance(Duration::from_millis(5));
+        
+        let frozen = cache.freeze();
+        assert_eq!(frozen.len(), 2);
+        assert!(!frozen.contains_key(&3));
+        assert_eq!(frozen.priority(&1), Some(7));
+        assert!(frozen.ttl_remaining(&2).unwrap() <= Duration::from_millis(15));
+        
+        // Later writes and expiry don't reach the frozen copy, and reading it
+        // leaves the cache's stats alone
+        cache.put(1, "uno".to_string(), None, 5);
+        cache.put(4, "four".to_string(), None, 5);
+        clock::advance(Duration::from_millis(20));
+        assert_eq!(frozen.get(&1).map(String::as_str), Some("one"));
+        assert_eq!(frozen.get(&2).map(String::as_str), Some("two"));
+        assert_eq!(frozen.get(&4), None);
+        assert_eq!(cache.get_stats().hits, 0);
+    }
+    
+    #[test]
+    fn test_fork() {
+        let cache = SmartCache::new(4);
+        for key in 0..3 {
+            cache.put(key, key.to_string(), None, 5);
+        }
+        cache.put(9, "gone".to_string(), Some(Duration::from_millis(1)), 5);
+        clock::advance(Duration::from_millis(5));
+        cache.get(&0);
+        
+        // Nothing is copied until one side writes
+        let fork = cache.fork();
+        let shares_table = |fork: &SmartCache<i32, String>| Shared::ptr_eq(&cache.data.read().unwrap(), &fork.data.read().unwrap());
+        assert!(shares_table(&fork));
+        assert!(Shared::ptr_eq(&cache.lru_queue.lock().unwrap(), &fork.lru_queue.lock().unwrap()));
+        assert!(!fork.contains_key(&9));
+        assert_eq!(fork.get_stats().hits, 0);
+        
+        fork.put(1, "fork".to_string(), None, 5);
+        assert!(!shares_table(&fork));
+        cache.delete(&2);
+        assert_eq!(cache.get(&1), Some("1".to_string()));
+        assert_eq!(cache.size(), 3);
+        
+        // The fork kept the access times: 0 was read after 2 before forking, so
+        // 2 goes first
+        fork.put(3, "3".to_string(), None, 5);
+        fork.put(4, "4".to_string(), None, 5);
+        assert!(!fork.contains_key(&2));
+        assert!(fork.contains_key(&0));
+        assert_eq!(fork.get(&1), Some("fork".to_string()));
+    }
+    
+    #[test]
+    fn test_invalidate_all() {
+        let cache = SmartCache::new(4);
+        cache.reload_config(CacheConfig {
+            max_capacity: 4,
+            read_fast_path: true,
+            ..Default::default()
+        }).unwrap();
+        for key in 0..4 {
+            cache.put(key, key.to_string(), None, 5);
+        }
+        let fork = cache.fork();
+        
+        // Gone at once, though the read index is left as it was
+        cache.invalidate_all();
+        assert_eq!(cache.index.load().len(), 4);
+        assert_eq!(cache.get(&0), None);
+        assert!(!cache.contains_key(&1));
+        assert_eq!(cache.size(), 3);
+        assert_eq!(fork.get(&1), Some("1".to_string()));
+        
+        // Invalidated entries make room before any live one
+        for key in 10..13 {
+            cache.put(key, key.to_string(), None, 1);
+        }
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.size(), 3);
+        assert!((10..13).all(|key| cache.get(&key).is_some()));
+    }
+    
+    #[test]
+    fn test_estimated_memory_bytes() {
+        let cache: SmartCache<String, String> = SmartCache::new(10);
+        let empty = cache.estimated_memory_bytes();
+        cache.put("big".to_string(), "x".repeat(1000), None, 5);
+        cache.put("small".to_string(), "y".repeat(10), None, 5);
+        let full = cache.estimated_memory_bytes();
+        assert!(full > empty + 1010);
+        assert_eq!(cache.get_stats().estimated_memory_bytes, None);
+        assert_eq!(cache.get_stats_with_memory().estimated_memory_bytes, Some(full));
+        
+        // The read index, republished by the next write, keeps a second copy of every value
+        cache.reload_config(CacheConfig {
+            max_capacity: 10,
+            read_fast_path: true,
+            ..Default::default()
+        }).unwrap();
+        cache.put("small".to_string(), "y".repeat(10), None, 5);
+        assert!(cache.estimated_memory_bytes() > full + 1000);
+    }
 }
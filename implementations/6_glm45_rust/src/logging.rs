@@ -0,0 +1,213 @@
+// Operational logging through the `log` crate (the log feature), for deployments
+// that want evictions, expirations, config reloads and errors in their logs
+// without running the trace log. Each record carries its fields as structured
+// key-values, and its message is the same fields rendered as one JSON object so
+// plain-text loggers still write something machine-readable.
+
+use crate::{ConfigFieldChange, EvictionReason};
+use log::kv::{self, Key, Source, Value, VisitSource};
+use log::{Level, Metadata, Record};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogEvent {
+    Eviction,
+    Expiration,
+    ConfigReload,
+    Error,
+}
+
+const EVENT_TYPES: usize = 4;
+
+impl LogEvent {
+    fn name(self) -> &'static str {
+        match self {
+            LogEvent::Eviction => "eviction",
+            LogEvent::Expiration => "expiration",
+            LogEvent::ConfigReload => "config_reload",
+            LogEvent::Error => "error",
+        }
+    }
+}
+
+// Level per event type; None leaves that type out of the log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogConfig {
+    pub evictions: Option<Level>,
+    pub expirations: Option<Level>,
+    pub config_reloads: Option<Level>,
+    pub errors: Option<Level>,
+    // Records per second for each event type. The excess is dropped and counted
+    // in a `suppressed` field on the type's next record.
+    pub max_per_second: u32,
+    pub target: &'static str,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            evictions: Some(Level::Debug),
+            expirations: Some(Level::Debug),
+            config_reloads: Some(Level::Info),
+            errors: Some(Level::Warn),
+            max_per_second: 100,
+            target: "glm45_cache",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldValue {
+    Text(String),
+    Int(u64),
+    Float(f64),
+}
+
+impl FieldValue {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            FieldValue::Text(text) => serde_json::Value::from(text.as_str()),
+            FieldValue::Int(n) => serde_json::Value::from(*n),
+            FieldValue::Float(x) => serde_json::Value::from(*x),
+        }
+    }
+
+    fn to_value(&self) -> Value<'_> {
+        match self {
+            FieldValue::Text(text) => Value::from(text.as_str()),
+            FieldValue::Int(n) => Value::from(*n),
+            FieldValue::Float(x) => Value::from(*x),
+        }
+    }
+}
+
+struct Fields(Vec<(&'static str, FieldValue)>);
+
+impl Source for Fields {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn VisitSource<'kvs>) -> Result<(), kv::Error> {
+        for (name, value) in &self.0 {
+            visitor.visit_pair(Key::from_str(name), value.to_value())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Fields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let object: serde_json::Map<_, _> = self.0.iter().map(|(name, value)| (name.to_string(), value.to_json())).collect();
+        write!(f, "{}", serde_json::Value::Object(object))
+    }
+}
+
+// One-second window of a type's records
+#[derive(Default)]
+struct Window {
+    started: Option<Instant>,
+    emitted: u32,
+    suppressed: u64,
+}
+
+pub(crate) struct OperationLogger {
+    config: LogConfig,
+    windows: [Mutex<Window>; EVENT_TYPES],
+}
+
+impl OperationLogger {
+    pub(crate) fn new(config: LogConfig) -> Self {
+        Self {
+            config,
+            windows: std::array::from_fn(|_| Mutex::new(Window::default())),
+        }
+    }
+
+    pub(crate) fn eviction(&self, reason: &EvictionReason, key: impl FnOnce() -> String) {
+        let event = match reason {
+            EvictionReason::TTLExpired => LogEvent::Expiration,
+            _ => LogEvent::Eviction,
+        };
+        self.log_with(event, || {
+            let mut fields = vec![("key", FieldValue::Text(key()))];
+            match reason {
+                EvictionReason::CapacityExceeded { victim_priority } => {
+                    fields.push(("reason", FieldValue::Text("capacity_exceeded".to_string())));
+                    fields.push(("priority", FieldValue::Int(u64::from(*victim_priority))));
+                }
+                EvictionReason::LowPriority { score } => {
+                    fields.push(("reason", FieldValue::Text("low_priority".to_string())));
+                    fields.push(("score", FieldValue::Float(*score)));
+                }
+                EvictionReason::TTLExpired => {}
+            }
+            fields
+        });
+    }
+
+    pub(crate) fn config_reload(&self, changes: &[ConfigFieldChange]) {
+        self.log_with(LogEvent::ConfigReload, || {
+            let fields: Vec<_> = changes.iter().map(|change| change.field.as_str()).collect();
+            vec![("changed", FieldValue::Text(fields.join(",")))]
+        });
+    }
+
+    pub(crate) fn error(&self, operation: &'static str, error: &dyn fmt::Display) {
+        self.log_with(LogEvent::Error, || {
+            vec![
+                ("operation", FieldValue::Text(operation.to_string())),
+                ("error", FieldValue::Text(error.to_string())),
+            ]
+        });
+    }
+
+    // The fields are only built for records that get written
+    fn log_with(&self, event: LogEvent, fields: impl FnOnce() -> Vec<(&'static str, FieldValue)>) {
+        let level = match event {
+            LogEvent::Eviction => self.config.evictions,
+            LogEvent::Expiration => self.config.expirations,
+            LogEvent::ConfigReload => self.config.config_reloads,
+            LogEvent::Error => self.config.errors,
+        };
+        let Some(level) = level else {
+            return;
+        };
+        let metadata = Metadata::builder().level(level).target(self.config.target).build();
+        if level > log::max_level() || !log::logger().enabled(&metadata) {
+            return;
+        }
+        let Some(suppressed) = self.admit(event) else {
+            return;
+        };
+
+        let mut fields = Fields(fields());
+        fields.0.insert(0, ("event", FieldValue::Text(event.name().to_string())));
+        if suppressed > 0 {
+            fields.0.push(("suppressed", FieldValue::Int(suppressed)));
+        }
+        log::logger().log(
+            &Record::builder()
+                .metadata(metadata)
+                .module_path_static(Some(module_path!()))
+                .args(format_args!("{}", fields))
+                .key_values(&fields)
+                .build(),
+        );
+    }
+
+    // Returns the records suppressed since the last one written, or None when
+    // this one is over the limit
+    fn admit(&self, event: LogEvent) -> Option<u64> {
+        let mut window = self.windows[event as usize].lock().unwrap();
+        let now = Instant::now();
+        if window.started.is_none_or(|started| now.duration_since(started) >= Duration::from_secs(1)) {
+            window.started = Some(now);
+            window.emitted = 0;
+        }
+        if window.emitted >= self.config.max_per_second {
+            window.suppressed += 1;
+            return None;
+        }
+        window.emitted += 1;
+        Some(std::mem::take(&mut window.suppressed))
+    }
+}
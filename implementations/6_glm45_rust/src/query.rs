@@ -0,0 +1,318 @@
+// WHERE clauses and column lists for SmartCache::query. A condition is either
+// `field op number` on priority, effective_priority, access_count, age_secs or ttl_remaining, or `key LIKE 'pattern'` where
+// % matches any run of characters and _ matches exactly one. Conditions combine
+// with NOT, AND and OR (binding in that order) and parentheses. A column list
+// names any of key and the fields, separated by commas.
+
+use crate::QueryValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Field {
+    Priority,
+    // After decay and boost; compared as a fraction
+    EffectivePriority,
+    AccessCount,
+    AgeSecs,
+    // Seconds left, -1 once expired
+    TtlRemaining,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "priority" => Some(Field::Priority),
+            "effective_priority" => Some(Field::EffectivePriority),
+            "access_count" => Some(Field::AccessCount),
+            "age_secs" => Some(Field::AgeSecs),
+            "ttl_remaining" | "ttl_remaining_secs" => Some(Field::TtlRemaining),
+            _ => None,
+        }
+    }
+}
+
+// What conditions and columns read from an entry, so a query only works out (and
+// formats the key for) what it uses
+pub(crate) trait Row {
+    fn number(&self, field: Field) -> f64;
+    fn key(&self) -> &str;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Column {
+    Key,
+    Field(Field),
+}
+
+impl Column {
+    // As in QueryEntry
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Column::Key => "key",
+            Column::Field(Field::Priority) => "priority",
+            Column::Field(Field::EffectivePriority) => "effective_priority",
+            Column::Field(Field::AccessCount) => "access_count",
+            Column::Field(Field::AgeSecs) => "age_secs",
+            Column::Field(Field::TtlRemaining) => "ttl_remaining_secs",
+        }
+    }
+
+    pub(crate) fn value(self, row: &impl Row) -> QueryValue {
+        match self {
+            Column::Key => QueryValue::Text(row.key().to_string()),
+            Column::Field(Field::EffectivePriority) => QueryValue::Float(row.number(Field::EffectivePriority)),
+            Column::Field(field) => QueryValue::Int(row.number(field) as i64),
+        }
+    }
+}
+
+// The list between SELECT and FROM, e.g. `key, access_count`
+pub(crate) fn parse_columns(list: &str) -> Result<Vec<Column>, String> {
+    list.split(',')
+        .map(|name| {
+            let name = name.trim().to_ascii_lowercase();
+            match name.as_str() {
+                "" => Err("expected a column name".to_string()),
+                "key" => Ok(Column::Key),
+                _ => Field::parse(&name).map(Column::Field).ok_or_else(|| format!("unknown column {:?}", name)),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Condition {
+    Compare { field: Field, op: CmpOp, value: i64 },
+    KeyLike(String),
+}
+
+impl Condition {
+    pub(crate) fn matches(&self, row: &impl Row) -> bool {
+        match self {
+            Condition::Compare { field, op, value } => {
+                let actual = row.number(*field);
+                let value = *value as f64;
+                match op {
+                    CmpOp::Eq => actual == value,
+                    CmpOp::Ne => actual != value,
+                    CmpOp::Lt => actual < value,
+                    CmpOp::Le => actual <= value,
+                    CmpOp::Gt => actual > value,
+                    CmpOp::Ge => actual >= value,
+                }
+            }
+            Condition::KeyLike(pattern) => like(pattern, row.key()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Condition(Condition),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub(crate) fn matches(&self, row: &impl Row) -> bool {
+        match self {
+            Expr::Condition(condition) => condition.matches(row),
+            Expr::Not(inner) => !inner.matches(row),
+            Expr::And(left, right) => left.matches(row) && right.matches(row),
+            Expr::Or(left, right) => left.matches(row) || right.matches(row),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(i64),
+    Str(String),
+    Op(CmpOp),
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' { Token::LParen } else { Token::RParen });
+        } else if c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    // '' inside a string is an escaped quote
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        text.push('\'');
+                    }
+                    Some('\'') => break,
+                    Some(c) => text.push(c),
+                    None => return Err("unterminated string".to_string()),
+                }
+            }
+            tokens.push(Token::Str(text));
+        } else if c.is_ascii_digit() || c == '-' {
+            let mut text = String::new();
+            text.push(c);
+            chars.next();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                text.push(c);
+                chars.next();
+            }
+            let number = text.parse().map_err(|_| format!("invalid number {:?}", text))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word.to_ascii_lowercase()));
+        } else if "=!<>".contains(c) {
+            chars.next();
+            let op = match (c, chars.peek()) {
+                ('!', Some('=')) | ('<', Some('>')) => CmpOp::Ne,
+                ('<', Some('=')) => CmpOp::Le,
+                ('>', Some('=')) => CmpOp::Ge,
+                ('=', _) => CmpOp::Eq,
+                ('<', _) => CmpOp::Lt,
+                ('>', _) => CmpOp::Gt,
+                _ => return Err(format!("unexpected {:?}", c)),
+            };
+            if matches!(op, CmpOp::Ne | CmpOp::Le | CmpOp::Ge) {
+                chars.next();
+            }
+            tokens.push(Token::Op(op));
+        } else {
+            return Err(format!("unexpected {:?}", c));
+        }
+    }
+    Ok(tokens)
+}
+
+// Parse what follows `FROM cache`: nothing, or a WHERE clause
+pub(crate) fn parse_where(clause: &str) -> Result<Option<Expr>, String> {
+    let tokens = tokenize(clause)?;
+    let Some((first, rest)) = tokens.split_first() else {
+        return Ok(None);
+    };
+    if !is_keyword(first, "where") {
+        return Err(format!("expected WHERE, found {:?}", first));
+    }
+    let mut parser = Parser { tokens: rest, pos: 0 };
+    let expr = parser.or_expr()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(Some(expr)),
+        Some(token) => Err(format!("unexpected {:?}", token)),
+    }
+}
+
+fn is_keyword(token: &Token, keyword: &str) -> bool {
+    matches!(token, Token::Word(word) if word == keyword)
+}
+
+// Recursive descent, one method per precedence level
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn next_if_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.tokens.get(self.pos).is_some_and(|token| is_keyword(token, keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and_expr()?;
+        while self.next_if_keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not_expr()?;
+        while self.next_if_keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, String> {
+        if self.next_if_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not_expr()?)));
+        }
+        if self.tokens.get(self.pos) == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.or_expr()?;
+            if self.tokens.get(self.pos) != Some(&Token::RParen) {
+                return Err("expected )".to_string());
+            }
+            self.pos += 1;
+            return Ok(expr);
+        }
+        self.condition().map(Expr::Condition)
+    }
+
+    fn condition(&mut self) -> Result<Condition, String> {
+        let condition = match self.tokens.get(self.pos..self.pos + 3) {
+            Some([Token::Word(key), Token::Word(like), Token::Str(pattern)]) if key == "key" && like == "like" => {
+                Condition::KeyLike(pattern.clone())
+            }
+            Some([Token::Word(field), Token::Op(op), Token::Number(value)]) => {
+                let field = Field::parse(field).ok_or_else(|| format!("unknown field {:?}", field))?;
+                Condition::Compare { field, op: *op, value: *value }
+            }
+            _ => return Err("expected `field op number` or `key LIKE 'pattern'`".to_string()),
+        };
+        self.pos += 3;
+        Ok(condition)
+    }
+}
+
+// SQL LIKE: % matches any run of characters, _ exactly one
+fn like(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Last % seen and the text position it is currently matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '_' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '%' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '%')
+}
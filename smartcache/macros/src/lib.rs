@@ -0,0 +1,133 @@
+// #[cached] for smartcache: memoizes a function in a static smartcache::memo::Memo
+// keyed by its arguments.
+//
+//     #[cached(ttl = "60s", priority = 7, capacity = 10_000)]
+//     fn lookup(user: &str, region: u32) -> Profile { ... }
+//
+// All settings are optional: ttl takes ms, s, m or h and defaults to the backend's
+// default TTL, priority defaults to 5 and capacity to 1000. Reference arguments are
+// keyed by their owned form (&str as String), others by a clone; the return type must
+// be Clone. Generic, async and method functions are not supported.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse::Parser;
+use syn::spanned::Spanned;
+use syn::{Error, FnArg, Ident, ItemFn, LitInt, LitStr, Pat, ReturnType, Type};
+
+struct Settings {
+    ttl_ms: Option<u64>,
+    priority: u8,
+    capacity: usize,
+}
+
+fn parse_ttl(literal: &LitStr) -> syn::Result<u64> {
+    let text = literal.value();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let millis_per_unit = match unit {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => return Err(Error::new(literal.span(), "ttl needs a unit: ms, s, m or h, e.g. \"60s\"")),
+    };
+    let number: u64 = number.parse().map_err(|_| Error::new(literal.span(), "ttl must start with a whole number, e.g. \"60s\""))?;
+    match number.checked_mul(millis_per_unit) {
+        Some(0) => Err(Error::new(literal.span(), "ttl must be greater than zero")),
+        Some(millis) => Ok(millis),
+        None => Err(Error::new(literal.span(), "ttl is too long")),
+    }
+}
+
+fn parse_settings(attr: TokenStream) -> syn::Result<Settings> {
+    let mut settings = Settings { ttl_ms: None, priority: 5, capacity: 1000 };
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("ttl") {
+            settings.ttl_ms = Some(parse_ttl(&meta.value()?.parse()?)?);
+        } else if meta.path.is_ident("priority") {
+            settings.priority = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+        } else if meta.path.is_ident("capacity") {
+            let literal: LitInt = meta.value()?.parse()?;
+            settings.capacity = literal.base10_parse()?;
+            if settings.capacity == 0 {
+                return Err(Error::new(literal.span(), "capacity must be at least 1"));
+            }
+        } else {
+            return Err(meta.error("unknown setting; expected ttl, priority or capacity"));
+        }
+        Ok(())
+    });
+    parser.parse(attr)?;
+    Ok(settings)
+}
+
+fn expand(settings: Settings, function: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let signature = &function.sig;
+    if !signature.generics.params.is_empty() {
+        return Err(Error::new(signature.generics.span(), "#[cached] functions cannot be generic: the cache is one static"));
+    }
+    if let Some(asyncness) = signature.asyncness {
+        return Err(Error::new(asyncness.span(), "#[cached] does not support async functions"));
+    }
+    let output = match &signature.output {
+        ReturnType::Type(_, output) => output.clone(),
+        ReturnType::Default => return Err(Error::new(signature.span(), "#[cached] functions must return a value")),
+    };
+
+    let mut key_types = Vec::new();
+    let mut key_values = Vec::new();
+    for input in &signature.inputs {
+        let argument = match input {
+            FnArg::Typed(argument) => argument,
+            FnArg::Receiver(receiver) => return Err(Error::new(receiver.span(), "#[cached] does not support methods")),
+        };
+        let Pat::Ident(pattern) = &*argument.pat else {
+            return Err(Error::new(argument.pat.span(), "#[cached] arguments must be plain names"));
+        };
+        let name = &pattern.ident;
+        match &*argument.ty {
+            Type::Reference(reference) => {
+                let referent = &reference.elem;
+                key_types.push(quote!(<#referent as ::std::borrow::ToOwned>::Owned));
+                key_values.push(quote!(::std::borrow::ToOwned::to_owned(#name)));
+            }
+            owned => {
+                key_types.push(quote!(#owned));
+                key_values.push(quote!(::std::clone::Clone::clone(&#name)));
+            }
+        }
+    }
+
+    let ttl = match settings.ttl_ms {
+        Some(millis) => quote!(::std::option::Option::Some(::std::time::Duration::from_millis(#millis))),
+        None => quote!(::std::option::Option::None),
+    };
+    let priority = settings.priority;
+    let capacity = settings.capacity;
+    let ItemFn { attrs, vis, sig, block } = function;
+    // Mixed-site names cannot shadow the function's own arguments in the body
+    let memo = Ident::new("memo", Span::mixed_site());
+    let key = Ident::new("key", Span::mixed_site());
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            static MEMO: ::std::sync::OnceLock<::smartcache::memo::Memo<(#(#key_types,)*), #output>> =
+                ::std::sync::OnceLock::new();
+            let #memo = MEMO.get_or_init(|| ::smartcache::memo::Memo::new(#capacity, #ttl, #priority));
+            let #key = (#(#key_values,)*);
+            #memo.get_or_compute(#key, move || -> #output #block)
+        }
+    })
+}
+
+#[proc_macro_attribute]
+pub fn cached(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let function = syn::parse_macro_input!(item as ItemFn);
+    parse_settings(attr)
+        .and_then(|settings| expand(settings, function))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
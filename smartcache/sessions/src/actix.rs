@@ -0,0 +1,89 @@
+// actix-session store. Session keys are 64 random alphanumerics, like the stores
+// actix-session ships.
+
+use crate::SessionCache;
+use actix_session::storage::{LoadError, SaveError, SessionKey, SessionStore, UpdateError};
+use actix_web::cookie::time::Duration as CookieDuration;
+use rand::distributions::{Alphanumeric, DistString};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+type SessionState = HashMap<String, String>;
+
+#[derive(Clone)]
+pub struct ActixSessionStore {
+    sessions: Arc<SessionCache<SessionState>>,
+}
+
+impl ActixSessionStore {
+    pub fn new(sessions: SessionCache<SessionState>) -> Self {
+        ActixSessionStore { sessions: Arc::new(sessions) }
+    }
+
+    fn new_key(&self) -> SessionKey {
+        loop {
+            let key = Alphanumeric.sample_string(&mut rand::thread_rng(), 64);
+            if !self.sessions.contains(&key) {
+                return key.try_into().expect("64 characters is a valid session key");
+            }
+        }
+    }
+}
+
+fn std_ttl(ttl: &CookieDuration) -> Duration {
+    Duration::try_from(*ttl).unwrap_or(Duration::ZERO)
+}
+
+impl SessionStore for ActixSessionStore {
+    async fn load(&self, key: &SessionKey) -> Result<Option<SessionState>, LoadError> {
+        Ok(self.sessions.get(key.as_ref()))
+    }
+
+    async fn save(&self, state: SessionState, ttl: &CookieDuration) -> Result<SessionKey, SaveError> {
+        let key = self.new_key();
+        self.sessions.insert(key.as_ref(), state, Some(std_ttl(ttl)));
+        Ok(key)
+    }
+
+    async fn update(&self, key: SessionKey, state: SessionState, ttl: &CookieDuration) -> Result<SessionKey, UpdateError> {
+        // A session that expired in the meantime starts over under a new key
+        if !self.sessions.contains(key.as_ref()) {
+            return self.save(state, ttl).await.map_err(|err| UpdateError::Other(err.into()));
+        }
+        self.sessions.insert(key.as_ref(), state, Some(std_ttl(ttl)));
+        Ok(key)
+    }
+
+    async fn update_ttl(&self, key: &SessionKey, ttl: &CookieDuration) -> Result<(), anyhow::Error> {
+        if let Some(state) = self.sessions.get(key.as_ref()) {
+            self.sessions.insert(key.as_ref(), state, Some(std_ttl(ttl)));
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, key: &SessionKey) -> Result<(), anyhow::Error> {
+        self.sessions.remove(key.as_ref());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let store = ActixSessionStore::new(SessionCache::new(10, Duration::from_secs(60)));
+        let state = HashMap::from([("user".to_string(), "alice".to_string())]);
+        let key = store.save(state.clone(), &CookieDuration::minutes(5)).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), Some(state));
+
+        let updated = HashMap::from([("user".to_string(), "bob".to_string())]);
+        let key = store.update(key, updated.clone(), &CookieDuration::minutes(5)).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), Some(updated));
+
+        store.delete(&key).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), None);
+    }
+}
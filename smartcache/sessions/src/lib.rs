@@ -0,0 +1,151 @@
+// Session storage on top of SmartCache. SessionCache keeps session data for a
+// lifetime and evicts by tier under memory pressure: priority_by maps a session's
+// data to its eviction priority, so e.g. paying users' sessions outlive anonymous
+// ones. The framework stores build on it:
+//
+// - tower-sessions (feature tower-sessions): TowerSessionStore
+// - actix-session (feature actix-session): ActixSessionStore
+//
+//     let sessions = SessionCache::new(100_000, Duration::from_secs(30 * 60))
+//         .sliding()
+//         .priority_by(|record: &Record| if record.data.contains_key("premium") { 8 } else { 4 });
+//     let layer = SessionManagerLayer::new(TowerSessionStore::new(sessions));
+//
+// Sliding expiration restarts the lifetime on every load. The backends have no
+// time-to-idle yet, so until the proposed TTI lands a sliding load writes the
+// session back with a fresh TTL.
+
+use smartcache::SmartCache;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "actix-session")]
+mod actix;
+#[cfg(feature = "tower-sessions")]
+mod tower;
+
+#[cfg(feature = "actix-session")]
+pub use actix::ActixSessionStore;
+#[cfg(feature = "tower-sessions")]
+pub use tower::TowerSessionStore;
+
+const DEFAULT_PRIORITY: u8 = 5;
+
+type Tier<D> = Arc<dyn Fn(&D) -> u8 + Send + Sync>;
+
+pub struct SessionCache<D: Clone> {
+    cache: SmartCache<String, D>,
+    lifetime: Duration,
+    sliding: bool,
+    tier: Tier<D>,
+}
+
+impl<D: Clone + Send + Sync + 'static> SessionCache<D> {
+    pub fn new(capacity: usize, lifetime: Duration) -> Self {
+        SessionCache {
+            cache: SmartCache::new(capacity),
+            lifetime,
+            sliding: false,
+            tier: Arc::new(|_| DEFAULT_PRIORITY),
+        }
+    }
+
+    // Every load restarts the session's lifetime
+    pub fn sliding(mut self) -> Self {
+        self.sliding = true;
+        self
+    }
+
+    // Eviction priority from the session data; 5 for every session by default
+    pub fn priority_by(mut self, tier: impl Fn(&D) -> u8 + Send + Sync + 'static) -> Self {
+        self.tier = Arc::new(tier);
+        self
+    }
+
+    pub fn lifetime(&self) -> Duration {
+        self.lifetime
+    }
+
+    // ttl None is the configured lifetime; a zero ttl ends the session
+    pub fn insert(&self, id: &str, data: D, ttl: Option<Duration>) {
+        let ttl = ttl.unwrap_or(self.lifetime);
+        if ttl.is_zero() {
+            self.cache.delete(id);
+            return;
+        }
+        let priority = (self.tier)(&data);
+        self.cache.put(id.to_string(), data, Some(ttl), priority);
+    }
+
+    pub fn get(&self, id: &str) -> Option<D> {
+        let data = self.cache.get(id)?;
+        if self.sliding {
+            self.insert(id, data.clone(), None);
+        }
+        Some(data)
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.cache.contains_key(id)
+    }
+
+    pub fn remove(&self, id: &str) -> bool {
+        self.cache.delete(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smartcache::backend::clock;
+    use std::sync::Mutex;
+
+    // The mock clock is shared by every test in the process; tests that move it
+    // take turns so one's jump can't expire another's sessions. The whole suite
+    // advances it by well under the five minutes the store tests rely on.
+    static CLOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_session_lifetime() {
+        let _clock = CLOCK.lock().unwrap();
+        let sessions = SessionCache::new(10, Duration::from_secs(10));
+        sessions.insert("a", "alice".to_string(), None);
+        sessions.insert("b", "bob".to_string(), Some(Duration::from_secs(60)));
+        sessions.insert("c", "carol".to_string(), Some(Duration::ZERO));
+        assert_eq!(sessions.get("a").as_deref(), Some("alice"));
+        assert!(!sessions.contains("c"), "a zero ttl ends the session");
+
+        clock::advance(Duration::from_secs(20));
+        assert_eq!(sessions.get("a"), None);
+        assert!(sessions.contains("b"));
+        assert!(sessions.remove("b"));
+    }
+
+    #[test]
+    fn test_sliding_expiration() {
+        let _clock = CLOCK.lock().unwrap();
+        let sessions = SessionCache::new(10, Duration::from_secs(10)).sliding();
+        sessions.insert("a", 1, None);
+        for _ in 0..4 {
+            clock::advance(Duration::from_secs(6));
+            assert_eq!(sessions.get("a"), Some(1), "each load restarts the lifetime");
+        }
+        clock::advance(Duration::from_secs(11));
+        assert_eq!(sessions.get("a"), None);
+    }
+
+    #[test]
+    fn test_low_tier_sessions_are_evicted_first() {
+        let _clock = CLOCK.lock().unwrap();
+        let sessions = SessionCache::new(2, Duration::from_secs(60)).priority_by(|tier: &&str| if *tier == "premium" { 9 } else { 1 });
+        sessions.insert("p", "premium", None);
+        sessions.insert("f1", "free", None);
+        // Equally idle, the free session scores higher for eviction; the default
+        // (dashmap) backend compares scores across shards
+        clock::advance(Duration::from_secs(5));
+        sessions.insert("f2", "free", None);
+        assert!(sessions.contains("p"));
+        assert!(sessions.contains("f2"));
+        assert!(!sessions.contains("f1"));
+    }
+}
@@ -0,0 +1,83 @@
+// tower-sessions store. Records are kept until their expiry date, so the cookie and
+// the cache agree on when a session ends.
+
+use crate::SessionCache;
+use async_trait::async_trait;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tower_sessions_core::session::{Id, Record};
+use tower_sessions_core::session_store::{Result, SessionStore};
+
+#[derive(Clone)]
+pub struct TowerSessionStore {
+    sessions: Arc<SessionCache<Record>>,
+}
+
+impl TowerSessionStore {
+    pub fn new(sessions: SessionCache<Record>) -> Self {
+        TowerSessionStore { sessions: Arc::new(sessions) }
+    }
+}
+
+impl fmt::Debug for TowerSessionStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TowerSessionStore").field("lifetime", &self.sessions.lifetime()).finish()
+    }
+}
+
+#[async_trait]
+impl SessionStore for TowerSessionStore {
+    async fn create(&self, record: &mut Record) -> Result<()> {
+        // A new session must not take over a live one
+        while self.sessions.contains(&record.id.to_string()) {
+            record.id = Id::default();
+        }
+        self.save(record).await
+    }
+
+    async fn save(&self, record: &Record) -> Result<()> {
+        let remaining = Duration::try_from(record.expiry_date - OffsetDateTime::now_utc()).unwrap_or(Duration::ZERO);
+        self.sessions.insert(&record.id.to_string(), record.clone(), Some(remaining));
+        Ok(())
+    }
+
+    async fn load(&self, id: &Id) -> Result<Option<Record>> {
+        Ok(self.sessions.get(&id.to_string()))
+    }
+
+    async fn delete(&self, id: &Id) -> Result<()> {
+        self.sessions.remove(&id.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn record(expires_in: time::Duration) -> Record {
+        Record {
+            id: Id::default(),
+            data: HashMap::new(),
+            expiry_date: OffsetDateTime::now_utc() + expires_in,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let store = TowerSessionStore::new(SessionCache::new(10, Duration::from_secs(60)));
+        let mut live = record(time::Duration::minutes(5));
+        store.create(&mut live).await.unwrap();
+        assert_eq!(store.load(&live.id).await.unwrap(), Some(live.clone()));
+
+        let expired = record(time::Duration::seconds(-1));
+        store.save(&expired).await.unwrap();
+        assert_eq!(store.load(&expired.id).await.unwrap(), None, "an expired record is not stored");
+
+        store.delete(&live.id).await.unwrap();
+        assert_eq!(store.load(&live.id).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,68 @@
+// One crate name for downstream users: SmartCache is whichever implementation the
+// backend feature selects, so switching backends is a Cargo.toml change.
+//
+//     smartcache = { path = "smartcache" }                    # backend-dashmap
+//     smartcache = { path = "smartcache", default-features = false, features = ["backend-lru"] }
+//
+// backend-dashmap  Qwen435B, DashMap with sharded locking (default)
+// backend-lru      Qwen235B, custom linked list for O(1) LRU
+// backend-traced   GLM-4.5, trace log and debugging queries
+//
+// Code that sticks to new, put, get, delete, contains_key, clear and get_stats builds
+// against every backend. Anything beyond that is backend specific and reached through
+// `smartcache::backend`.
+//
+// #[cached] memoizes a function on top of whichever backend is selected (memo.rs).
+
+#[cfg(not(any(feature = "backend-dashmap", feature = "backend-lru", feature = "backend-traced")))]
+compile_error!("smartcache needs one backend feature: backend-dashmap, backend-lru or backend-traced");
+
+#[cfg(any(
+    all(feature = "backend-dashmap", feature = "backend-lru"),
+    all(feature = "backend-dashmap", feature = "backend-traced"),
+    all(feature = "backend-lru", feature = "backend-traced"),
+))]
+compile_error!("smartcache backend features are exclusive; use default-features = false when picking one");
+
+#[cfg(feature = "backend-dashmap")]
+pub use qwen435b_cache as backend;
+
+#[cfg(feature = "backend-lru")]
+pub use qwen235b_cache as backend;
+
+#[cfg(feature = "backend-traced")]
+pub use glm45_cache as backend;
+
+pub use backend::{CacheConfig, CacheStats, SmartCache};
+pub use smartcache_macros::cached;
+
+pub mod memo;
+
+// The selected backend, for logs and reports
+#[cfg(feature = "backend-dashmap")]
+pub const BACKEND: &str = "Qwen435B";
+
+#[cfg(feature = "backend-lru")]
+pub const BACKEND: &str = "Qwen235B";
+
+#[cfg(feature = "backend-traced")]
+pub const BACKEND: &str = "GLM45";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_surface() {
+        let cache: SmartCache<String, String> = SmartCache::new(2);
+        assert!(cache.put("a".to_string(), "1".to_string(), None, 5));
+        assert_eq!(cache.get("a").as_deref(), Some("1"));
+        assert!(cache.contains_key("a"));
+        assert_eq!(cache.get_stats().hits, 1);
+
+        assert!(cache.delete("a"));
+        cache.put("b".to_string(), "2".to_string(), None, 5);
+        cache.clear();
+        assert!(!cache.contains_key("b"));
+    }
+}
@@ -0,0 +1,109 @@
+// Function memoization, the runtime half of #[cached]. A Memo is a SmartCache plus
+// single flight: concurrent misses on one key run the computation once, and the
+// other callers wait for its value instead of computing it again.
+//
+// Keys must be Debug because backend-traced renders them in its trace log.
+
+use crate::SmartCache;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+pub struct Memo<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+{
+    cache: SmartCache<K, V>,
+    ttl: Option<Duration>,
+    priority: u8,
+    in_flight: Mutex<HashMap<K, Arc<Mutex<()>>>>,
+}
+
+impl<K, V> Memo<K, V>
+where
+    K: Clone + Eq + Hash + Debug + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    // ttl None falls back to the backend's default TTL
+    pub fn new(capacity: usize, ttl: Option<Duration>, priority: u8) -> Self {
+        Memo {
+            cache: new_cache(capacity),
+            ttl,
+            priority,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // The underlying cache, for stats or invalidation
+    pub fn cache(&self) -> &SmartCache<K, V> {
+        &self.cache
+    }
+
+    pub fn get_or_compute(&self, key: K, compute: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value;
+        }
+
+        let flight = Arc::clone(self.lock_in_flight().entry(key.clone()).or_default());
+        // A poisoned flight means its computation panicked; this caller computes instead
+        let _turn = flight.lock().unwrap_or_else(PoisonError::into_inner);
+        let _cleanup = FlightCleanup { memo: self, key: &key, flight: &flight };
+        // The flight ahead of us may have stored the value while we waited
+        if let Some(value) = self.cache.get(&key) {
+            return value;
+        }
+        let value = compute();
+        self.cache.put(key.clone(), value.clone(), self.ttl, self.priority);
+        value
+    }
+
+    fn lock_in_flight(&self) -> std::sync::MutexGuard<'_, HashMap<K, Arc<Mutex<()>>>> {
+        self.in_flight.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Drops the key's flight once nobody else waits on it, panics included
+struct FlightCleanup<'a, K, V>
+where
+    K: Clone + Eq + Hash + Debug + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    memo: &'a Memo<K, V>,
+    key: &'a K,
+    flight: &'a Arc<Mutex<()>>,
+}
+
+impl<K, V> Drop for FlightCleanup<'_, K, V>
+where
+    K: Clone + Eq + Hash + Debug + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        let mut in_flight = self.memo.lock_in_flight();
+        // The map and this caller hold the only references
+        if in_flight.get(self.key).is_some_and(|flight| Arc::ptr_eq(flight, self.flight) && Arc::strong_count(flight) == 2) {
+            in_flight.remove(self.key);
+        }
+    }
+}
+
+#[cfg(not(feature = "backend-traced"))]
+fn new_cache<K, V>(capacity: usize) -> SmartCache<K, V>
+where
+    K: Clone + Eq + Hash + Debug + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    SmartCache::new(capacity)
+}
+
+#[cfg(feature = "backend-traced")]
+fn new_cache<K, V>(capacity: usize) -> SmartCache<K, V>
+where
+    K: Clone + Eq + Hash + Debug + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    SmartCache::with_key_formatter(capacity, |key: &K| format!("{:?}", key))
+}
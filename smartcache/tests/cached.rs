@@ -0,0 +1,69 @@
+use smartcache::cached;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+static SQUARE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[cached(capacity = 100)]
+fn square(n: u64) -> u64 {
+    SQUARE_CALLS.fetch_add(1, Ordering::SeqCst);
+    n * n
+}
+
+#[test]
+fn test_repeated_calls_are_memoized() {
+    assert_eq!(square(4), 16);
+    assert_eq!(square(4), 16);
+    assert_eq!(square(5), 25);
+    assert_eq!(SQUARE_CALLS.load(Ordering::SeqCst), 2);
+}
+
+static GREETING_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[cached(ttl = "50ms", priority = 7)]
+fn greeting(name: &str, excited: bool) -> String {
+    GREETING_CALLS.fetch_add(1, Ordering::SeqCst);
+    format!("hello {}{}", name, if excited { "!" } else { "" })
+}
+
+#[test]
+fn test_reference_arguments_and_ttl() {
+    assert_eq!(greeting("ada", true), "hello ada!");
+    assert_eq!(greeting("ada", false), "hello ada");
+    assert_eq!(greeting("ada", true), "hello ada!");
+    assert_eq!(GREETING_CALLS.load(Ordering::SeqCst), 2);
+
+    thread::sleep(Duration::from_millis(120));
+    greeting("ada", true);
+    assert_eq!(GREETING_CALLS.load(Ordering::SeqCst), 3, "an expired result is computed again");
+}
+
+static SLOW_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[cached]
+fn slow(key: u32) -> Result<u32, String> {
+    SLOW_CALLS.fetch_add(1, Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(50));
+    let doubled = key.checked_mul(2).ok_or("overflow")?;
+    Ok(doubled)
+}
+
+#[test]
+fn test_concurrent_misses_compute_once() {
+    let barrier = Arc::new(Barrier::new(8));
+    let callers: Vec<_> = (0..8)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                slow(21)
+            })
+        })
+        .collect();
+    for caller in callers {
+        assert_eq!(caller.join().unwrap(), Ok(42));
+    }
+    assert_eq!(SLOW_CALLS.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,414 @@
+// HTTP response caching as tower middleware, so it also fits axum routers.
+//
+//     let cache = CacheLayer::new(10_000)
+//         .vary_by(header::ACCEPT_LANGUAGE)
+//         .route_priority("/static", 9);
+//     let invalidator = cache.invalidator();
+//     let app = Router::new().route(...).layer(cache);
+//
+// GET and HEAD responses are cached under method, path, query and the vary_by
+// headers, in the SmartCache the smartcache backend feature selects. A response is
+// stored when its status is heuristically cacheable (200, 203, 204, 301, 404, 410),
+// it sets no cookie and its Cache-Control allows it: s-maxage or max-age is the TTL,
+// no-store, no-cache and private keep it out, and without a max-age the default_ttl
+// applies (none by default, so nothing is cached). A response that varies on a
+// header the layer does not key on is not cached either.
+//
+// Requests sent with Cache-Control: no-cache skip the lookup, no-store also skips
+// storing. Every response from the cache carries x-cache: HIT, stored ones MISS.
+//
+// Invalidation: a successful POST, PUT, PATCH or DELETE drops everything cached for
+// its path (all queries, methods and variants), and Invalidator does the same on
+// demand, e.g. from a message queue listener.
+
+use bytes::Bytes;
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use http::{Method, Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Either, Full};
+use smartcache::SmartCache;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::{Layer, Service};
+
+// Cached and buffered responses are Full, the rest stream through untouched
+pub type CacheBody<B> = Either<Full<Bytes>, B>;
+
+const X_CACHE: HeaderName = HeaderName::from_static("x-cache");
+const DEFAULT_PRIORITY: u8 = 5;
+
+#[derive(Clone)]
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    generation: u64,
+}
+
+struct Store {
+    cache: SmartCache<String, CachedResponse>,
+    // Bumped per path on invalidation; responses stored under an older generation are stale
+    generations: Mutex<HashMap<String, u64>>,
+}
+
+impl Store {
+    fn generation(&self, path: &str) -> u64 {
+        let generations = self.generations.lock().unwrap_or_else(PoisonError::into_inner);
+        generations.get(path).copied().unwrap_or(0)
+    }
+
+    fn invalidate(&self, path: &str) {
+        let mut generations = self.generations.lock().unwrap_or_else(PoisonError::into_inner);
+        *generations.entry(path.to_string()).or_insert(0) += 1;
+    }
+
+    fn lookup(&self, key: &str, generation: u64) -> Option<CachedResponse> {
+        let cached = self.cache.get(key)?;
+        if cached.generation < generation {
+            self.cache.delete(key);
+            return None;
+        }
+        Some(cached)
+    }
+}
+
+// Drops cached responses from outside the request path
+#[derive(Clone)]
+pub struct Invalidator {
+    store: Arc<Store>,
+}
+
+impl Invalidator {
+    // Everything cached for the path, whatever the query, method or varied headers
+    pub fn invalidate(&self, path: &str) {
+        self.store.invalidate(path);
+    }
+
+    pub fn clear(&self) {
+        self.store.cache.clear();
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Settings {
+    vary: Vec<HeaderName>,
+    default_ttl: Option<Duration>,
+    // Path prefix and priority; the longest matching prefix wins
+    routes: Vec<(String, u8)>,
+}
+
+impl Settings {
+    fn key<B>(&self, request: &Request<B>) -> String {
+        let target = request.uri().path_and_query().map_or("/", |target| target.as_str());
+        let mut key = format!("{} {}", request.method(), target);
+        for name in &self.vary {
+            let values: Vec<_> = request.headers().get_all(name).iter().map(|value| String::from_utf8_lossy(value.as_bytes())).collect();
+            key.push_str(&format!("\n{}: {}", name, values.join(",")));
+        }
+        key
+    }
+
+    fn priority(&self, path: &str) -> u8 {
+        self.routes
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(DEFAULT_PRIORITY, |(_, priority)| *priority)
+    }
+
+    // How long the response may be cached, None when it may not
+    fn ttl<B>(&self, response: &Response<B>) -> Option<Duration> {
+        let cacheable_status = matches!(response.status().as_u16(), 200 | 203 | 204 | 301 | 404 | 410);
+        if !cacheable_status || response.headers().contains_key(header::SET_COOKIE) {
+            return None;
+        }
+        let varies_on_unkeyed = response
+            .headers()
+            .get_all(header::VARY)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .any(|name| name == "*" || !self.vary.iter().any(|keyed| keyed.as_str().eq_ignore_ascii_case(name)));
+        if varies_on_unkeyed {
+            return None;
+        }
+
+        let directives = CacheControl::parse(response.headers());
+        if directives.no_store || directives.no_cache || directives.private {
+            return None;
+        }
+        match directives.s_maxage.or(directives.max_age) {
+            Some(0) => None,
+            Some(seconds) => Some(Duration::from_secs(seconds)),
+            None => self.default_ttl,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    max_age: Option<u64>,
+    s_maxage: Option<u64>,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut directives = CacheControl::default();
+        let values = headers.get_all(header::CACHE_CONTROL).iter().filter_map(|value| value.to_str().ok());
+        for directive in values.flat_map(|value| value.split(',')) {
+            let (name, argument) = match directive.split_once('=') {
+                Some((name, argument)) => (name.trim(), Some(argument.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            match name.to_ascii_lowercase().as_str() {
+                "no-store" => directives.no_store = true,
+                "no-cache" => directives.no_cache = true,
+                "private" => directives.private = true,
+                "max-age" => directives.max_age = argument.and_then(|seconds| seconds.parse().ok()),
+                "s-maxage" => directives.s_maxage = argument.and_then(|seconds| seconds.parse().ok()),
+                _ => {}
+            }
+        }
+        directives
+    }
+}
+
+#[derive(Clone)]
+pub struct CacheLayer {
+    store: Arc<Store>,
+    settings: Settings,
+}
+
+impl CacheLayer {
+    pub fn new(capacity: usize) -> Self {
+        CacheLayer {
+            store: Arc::new(Store {
+                cache: SmartCache::new(capacity),
+                generations: Mutex::new(HashMap::new()),
+            }),
+            settings: Settings::default(),
+        }
+    }
+
+    // Key on this request header too; include HOST when serving several hosts
+    pub fn vary_by(mut self, name: HeaderName) -> Self {
+        self.settings.vary.push(name);
+        self
+    }
+
+    // TTL for responses without max-age or s-maxage
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.settings.default_ttl = Some(ttl);
+        self
+    }
+
+    // Eviction priority for paths under the prefix; others get 5
+    pub fn route_priority(mut self, prefix: impl Into<String>, priority: u8) -> Self {
+        self.settings.routes.push((prefix.into(), priority));
+        self
+    }
+
+    pub fn invalidator(&self) -> Invalidator {
+        Invalidator { store: Arc::clone(&self.store) }
+    }
+}
+
+impl<S> Layer<S> for CacheLayer {
+    type Service = CacheService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CacheService {
+            inner,
+            store: Arc::clone(&self.store),
+            settings: Arc::new(self.settings.clone()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CacheService<S> {
+    inner: S,
+    store: Arc<Store>,
+    settings: Arc<Settings>,
+}
+
+fn cached_response<B>(cached: CachedResponse, x_cache: &'static str) -> Response<CacheBody<B>> {
+    let mut response = Response::new(Either::Left(Full::new(cached.body)));
+    *response.status_mut() = cached.status;
+    *response.headers_mut() = cached.headers;
+    response.headers_mut().insert(X_CACHE, HeaderValue::from_static(x_cache));
+    response
+}
+
+impl<S, ReqB, ResB> Service<Request<ReqB>> for CacheService<S>
+where
+    S: Service<Request<ReqB>, Response = Response<ResB>> + Clone + Send + 'static,
+    S::Future: Send,
+    ReqB: Send + 'static,
+    ResB: Body<Data = Bytes> + Send + 'static,
+{
+    type Response = Response<CacheBody<ResB>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqB>) -> Self::Future {
+        // The clone is not ready; the instance poll_ready was called on serves this request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let store = Arc::clone(&self.store);
+        let settings = Arc::clone(&self.settings);
+
+        Box::pin(async move {
+            let method = request.method().clone();
+            let path = request.uri().path().to_string();
+            if method != Method::GET && method != Method::HEAD {
+                let response = inner.call(request).await?;
+                let writes = !matches!(method, Method::OPTIONS | Method::TRACE | Method::CONNECT);
+                if writes && response.status().is_success() {
+                    store.invalidate(&path);
+                }
+                return Ok(response.map(Either::Right));
+            }
+
+            let key = settings.key(&request);
+            let generation = store.generation(&path);
+            let directives = CacheControl::parse(request.headers());
+            if !directives.no_cache && !directives.no_store {
+                if let Some(cached) = store.lookup(&key, generation) {
+                    return Ok(cached_response(cached, "HIT"));
+                }
+            }
+
+            let response = inner.call(request).await?;
+            let ttl = match settings.ttl(&response) {
+                Some(ttl) if !directives.no_store => ttl,
+                _ => return Ok(response.map(Either::Right)),
+            };
+            let (parts, body) = response.into_parts();
+            let Ok(collected) = body.collect().await else {
+                // The body is gone by now, so there is nothing left to pass on
+                let mut failed = Response::new(Either::Left(Full::new(Bytes::new())));
+                *failed.status_mut() = StatusCode::BAD_GATEWAY;
+                return Ok(failed);
+            };
+            let cached = CachedResponse {
+                status: parts.status,
+                headers: parts.headers,
+                body: collected.to_bytes(),
+                generation,
+            };
+            store.cache.put(key, cached.clone(), Some(ttl), settings.priority(&path));
+            Ok(cached_response(cached, "MISS"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::{service_fn, ServiceExt};
+
+    // Counts calls and answers with the call number and the given Cache-Control
+    fn counting_service(
+        calls: Arc<AtomicUsize>,
+        cache_control: &'static str,
+    ) -> impl Service<
+        Request<()>,
+        Response = Response<Full<Bytes>>,
+        Error = Infallible,
+        Future = impl Future<Output = Result<Response<Full<Bytes>>, Infallible>> + Send,
+    > + Clone
+       + Send
+       + 'static {
+        service_fn(move |_request: Request<()>| {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                let mut response = Response::new(Full::new(Bytes::from(call.to_string())));
+                response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+                Ok(response)
+            }
+        })
+    }
+
+    async fn send<S>(service: &S, request: Request<()>) -> (String, Option<String>)
+    where
+        S: Service<Request<()>, Response = Response<CacheBody<Full<Bytes>>>, Error = Infallible> + Clone,
+    {
+        let response = service.clone().oneshot(request).await.unwrap();
+        let x_cache = response.headers().get(X_CACHE).map(|value| value.to_str().unwrap().to_string());
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (String::from_utf8(body.to_vec()).unwrap(), x_cache)
+    }
+
+    fn get(uri: &str) -> Request<()> {
+        Request::get(uri).body(()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_max_age_response_is_served_from_cache() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = CacheLayer::new(100).layer(counting_service(Arc::clone(&calls), "public, max-age=60"));
+
+        assert_eq!(send(&service, get("/items?page=1")).await, ("1".to_string(), Some("MISS".to_string())));
+        assert_eq!(send(&service, get("/items?page=1")).await, ("1".to_string(), Some("HIT".to_string())));
+        assert_eq!(send(&service, get("/items?page=2")).await.0, "2", "the query is part of the key");
+
+        let no_cache = Request::get("/items?page=1").header(header::CACHE_CONTROL, "no-cache").body(()).unwrap();
+        assert_eq!(send(&service, no_cache).await.0, "3");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_uncacheable_responses_pass_through() {
+        for cache_control in ["no-store", "private, max-age=60", "max-age=0"] {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let service = CacheLayer::new(100).layer(counting_service(Arc::clone(&calls), cache_control));
+            send(&service, get("/")).await;
+            assert_eq!(send(&service, get("/")).await, ("2".to_string(), None), "{}", cache_control);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vary_by_header() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = CacheLayer::new(100)
+            .vary_by(header::ACCEPT_LANGUAGE)
+            .layer(counting_service(Arc::clone(&calls), "max-age=60"));
+        let in_language = |language| Request::get("/").header(header::ACCEPT_LANGUAGE, language).body(()).unwrap();
+
+        assert_eq!(send(&service, in_language("de")).await.0, "1");
+        assert_eq!(send(&service, in_language("en")).await.0, "2");
+        assert_eq!(send(&service, in_language("de")).await.0, "1");
+    }
+
+    #[tokio::test]
+    async fn test_writes_and_invalidator_drop_the_path() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let layer = CacheLayer::new(100);
+        let invalidator = layer.invalidator();
+        let service = layer.layer(counting_service(Arc::clone(&calls), "max-age=60"));
+
+        send(&service, get("/items/7")).await;
+        send(&service, get("/items/8")).await;
+        send(&service, Request::post("/items/7").body(()).unwrap()).await;
+        assert_eq!(send(&service, get("/items/7")).await.0, "4", "the POST dropped /items/7");
+        assert_eq!(send(&service, get("/items/8")).await.0, "2", "other paths stay cached");
+
+        invalidator.invalidate("/items/8");
+        assert_eq!(send(&service, get("/items/8")).await.0, "5");
+    }
+}
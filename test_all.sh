@@ -82,6 +82,26 @@ else
 fi
 cd ../..
 
+# DynCache over every Rust implementation
+echo "Testing Rust cache_core..."
+cd cache_core
+if cargo test --release 2>&1 | grep -E "(test result|running)" | tail -1; then
+    echo -e "${GREEN}✓ cache_core tests passed${NC}"
+else
+    echo -e "${RED}✗ cache_core tests failed${NC}"
+fi
+cd ..
+
+# Conformance suite (same checks against every Rust implementation)
+echo "Testing Rust conformance suite..."
+cd conformance
+if cargo test --release 2>&1 | grep -E "(test result|running)" | tail -1; then
+    echo -e "${GREEN}✓ Conformance tests passed${NC}"
+else
+    echo -e "${RED}✗ Conformance tests failed${NC}"
+fi
+cd ..
+
 echo -e "\n=================================================="
 echo -e "${GREEN}Testing complete!${NC}"
 echo "=================================================="